@@ -1,6 +1,7 @@
 #ifndef NEWSBOAT_CACHE_H_
 #define NEWSBOAT_CACHE_H_
 
+#include <cstdint>
 #include <memory>
 #include <mutex>
 #include <sqlite3.h>
@@ -8,6 +9,7 @@
 
 #include "configcontainer.h"
 #include "filepath.h"
+#include "libnewsboat-ffi/src/readstate.rs.h" // IWYU pragma: export
 
 namespace newsboat {
 
@@ -40,13 +42,44 @@ const SchemaVersion unknown_version = {0, 0};
 
 using schema_patches = std::map<SchemaVersion, std::vector<std::string>>;
 
+/// What `Cache::cleanup_cache()` would remove.
+struct CleanupSummary {
+	/// Feeds that are no longer in the urls file.
+	unsigned int feeds;
+	/// Articles of these feeds.
+	unsigned int articles;
+	/// Read articles of the remaining feeds, which are removed if
+	/// `delete-read-articles-on-quit` is enabled.
+	unsigned int read_articles;
+};
+
+/// How `Cache` treats the file it opens.
+enum class CacheMode {
+	/// Changes are written to the file.
+	ReadWrite,
+	/// None of the changes (including the ones made while opening the cache)
+	/// are written to the file: they're rolled back when the cache is closed.
+	DryRun,
+	/// The file is opened read-only, and changes are dropped. Used while
+	/// another instance of Newsboat writes to the file.
+	ReadOnly,
+};
+
 class Cache {
 public:
-	Cache(const Filepath& cachefile, ConfigContainer& c);
+	Cache(const Filepath& cachefile, ConfigContainer& c,
+		CacheMode mode = CacheMode::ReadWrite);
 	~Cache();
 
 	static std::unique_ptr<Cache> in_memory(ConfigContainer& c);
 
+	/// Whether the cache was opened with `CacheMode::ReadOnly`, i.e. changes
+	/// to it are dropped.
+	bool is_read_only() const
+	{
+		return mode == CacheMode::ReadOnly;
+	}
+
 	void externalize_rssfeed(RssFeed& feed, bool reset_unread);
 	std::shared_ptr<RssFeed> internalize_rssfeed(std::string rssurl,
 		RssIgnores* ign);
@@ -57,6 +90,18 @@ public:
 	std::vector<std::string> cleanup_cache(std::vector<std::shared_ptr<RssFeed>> feeds,
 		bool always_clean = false);
 	void do_vacuum();
+	/// Returns what `cleanup_cache(feeds, true)` would remove.
+	CleanupSummary summarize_cleanup(
+		const std::vector<std::shared_ptr<RssFeed>>& feeds);
+	/// Returns the size of unused pages in the cache file, in bytes. That's
+	/// the least amount of space that `do_vacuum()` would reclaim.
+	std::uint64_t reclaimable_space();
+	/// Number of articles that were deleted because they were older than
+	/// `keep-articles-days`.
+	unsigned int expired_articles_count() const;
+	/// Number of articles that were deleted because their feeds had more than
+	/// `max-items` articles.
+	unsigned int excess_articles_count() const;
 	std::vector<std::shared_ptr<RssItem>> search_for_items(
 			const std::string& querystr,
 			const std::string& feedurl,
@@ -77,12 +122,26 @@ public:
 	void remove_old_deleted_items(RssFeed* feed);
 	void mark_items_read_by_guid(const std::vector<std::string>& guids);
 	std::vector<std::string> get_read_item_guids();
+
+	/// Read state and flags of all articles in the cache.
+	std::vector<readstate::bridged::ArticleState> get_article_states();
+
+	/// Stores read state and flags of the given articles, including the
+	/// times when they last changed.
+	void update_article_states(
+		const rust::Vec<readstate::bridged::ArticleState>& articles);
 	void fetch_descriptions(RssFeed* feed);
 	std::string fetch_description(const RssItem& item);
 
 private:
 	SchemaVersion get_schema_version();
 	void populate_tables();
+	/// Throws if the schema isn't the one this version of Newsboat creates.
+	void check_schema_is_current();
+	/// Returns `true` (and logs that `caller` dropped its changes) if the
+	/// cache is read-only. Methods that write to the cache call this first,
+	/// so that SQLite never gets to reject the queries.
+	bool drops_changes(const std::string& caller) const;
 	void set_pragmas();
 	void delete_item_unlocked(const RssItem& item);
 	void clean_old_articles();
@@ -111,9 +170,15 @@ private:
 		bool do_throw);
 
 	void close_database();
+	unsigned int run_count_query(const std::string& query);
+	std::string feed_url_list(
+		const std::vector<std::shared_ptr<RssFeed>>& feeds);
 
 	sqlite3* db = nullptr;
 	ConfigContainer& cfg;
+	const CacheMode mode;
+	unsigned int expired_articles = 0;
+	unsigned int excess_articles = 0;
 	std::recursive_mutex mtx;
 };
 
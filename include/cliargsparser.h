@@ -3,8 +3,10 @@
 
 #include "libnewsboat-ffi/src/cliargsparser.rs.h" // IWYU pragma: export
 
+#include <cstdint>
 #include <optional>
 #include <string>
+#include <utility>
 #include <vector>
 
 #include "logger.h"
@@ -25,6 +27,26 @@ public:
 
 	bool do_cleanup() const;
 
+	/// If `true`, pending migrations of Newsboat's files should be applied,
+	/// and Newsboat should quit.
+	bool do_migrate() const;
+
+	/// If `true`, `-x`, `--vacuum`, `--cleanup` and `--migrate` should only
+	/// report what they would do, without modifying the cache or other files.
+	bool dry_run() const;
+
+	/// If `true`, the lock file should be removed before locking, unless
+	/// it's held by a process that's running on this host.
+	bool force_unlock() const;
+
+	/// If set, and another instance holds the lock, Newsboat should wait up
+	/// to this many seconds for it to be released.
+	std::optional<std::uint64_t> wait_lock() const;
+
+	/// If `true`, the requested actions only read the cache, so they can run
+	/// while another instance holds the lock.
+	bool can_run_read_only() const;
+
 	Filepath importfile() const;
 
 	/// If non-null, Newsboat should import read articles info from this
@@ -63,6 +85,33 @@ public:
 
 	bool refresh_on_start() const;
 
+	/// If `true`, Newsboat should check key bindings from the config for
+	/// conflicts and mistakes, print the problems, and exit.
+	bool lint_keymap() const;
+
+	/// If set, Newsboat should print a reference of the key bindings in this
+	/// format ("markdown", "roff" or "json"), and exit.
+	std::optional<std::string> export_keymap() const;
+
+	/// If set, Newsboat should run this subcommand (e.g. `feeds list`)
+	/// instead of starting the TUI.
+	std::optional<cliargsparser::bridged::Subcommand> subcommand() const;
+
+	/// If set, Newsboat should print this shell completion script, and exit.
+	std::optional<std::string> completions() const;
+
+	/// If set, Newsboat should send this command to the running instance
+	/// through its control socket, print the reply, and exit.
+	std::optional<std::string> remote_command() const;
+
+	/// If `true`, Newsboat should create the directories of the profile
+	/// given to --create-profile, and exit.
+	bool create_profile() const;
+
+	/// If `true`, Newsboat should print the names of existing profiles, and
+	/// exit.
+	bool list_profiles() const;
+
 	std::optional<Filepath> url_file() const;
 
 	std::optional<Filepath> lock_file() const;
@@ -82,10 +131,19 @@ public:
 	/// \note The parser does not check if the passed commands are valid.
 	std::vector<std::string> cmds_to_execute() const;
 
+	/// Settings given with `-o name=value`, as (name, value) pairs, in the
+	/// order they appeared on the command line.
+	std::vector<std::pair<std::string, std::string>> config_overrides() const;
+
 	std::optional<Filepath> log_file() const;
 
 	std::optional<Level> log_level() const;
 
+	/// Returns the list of options for `--help`, one option per line, with
+	/// aligned descriptions. It's generated from the same table the parser
+	/// uses, so it lists exactly the options that are accepted.
+	static std::string options_usage();
+
 	/// Returns the reference to the Rust object.
 	///
 	/// This is only meant to be used in situations when one wants to pass
@@ -4,6 +4,7 @@
 #include <map>
 #include <mutex>
 #include <string>
+#include <utility>
 #include <vector>
 
 #include "3rd-party/expected.hpp"
@@ -72,6 +73,9 @@ public:
 	int get_configvalue_as_int(const std::string& key) const;
 	Filepath get_configvalue_as_filepath(const std::string& key) const;
 	std::string get_configvalue(const std::string& key) const;
+	/// Problems with the value of setting `key` if it's a format string; see
+	/// `ConfigData::format_errors`.
+	std::vector<std::string> get_format_errors(const std::string& key) const;
 	nonstd::expected<void, std::string> set_configvalue(const std::string& key,
 		const std::string& value);
 	void reset_to_default(const std::string& key);
@@ -80,6 +84,19 @@ public:
 	FeedSortStrategy get_feed_sort_strategy() const;
 	ArticleSortStrategy get_article_sort_strategy() const;
 
+	/// Applies settings from NEWSBOAT_<SETTING> environment variables, then
+	/// `assignments`, which are (name, value) pairs from `-o name=value`
+	/// options. Call this after the config files are parsed, so that the
+	/// overrides take precedence. Returns an error message if some override
+	/// names an unknown setting or has an invalid value.
+	nonstd::expected<void, std::string> apply_overrides(
+		const std::vector<std::pair<std::string, std::string>>& assignments);
+
+	/// Describes the overrides that `apply_overrides()` would apply, one
+	/// line per override, e.g. "browser = lynx (from NEWSBOAT_BROWSER)".
+	std::vector<std::string> describe_overrides(
+		const std::vector<std::pair<std::string, std::string>>& assignments) const;
+
 	static const std::string PARTIAL_FILE_SUFFIX;
 
 private:
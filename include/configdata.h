@@ -3,6 +3,7 @@
 
 #include <string>
 #include <unordered_set>
+#include <vector>
 
 #include "3rd-party/expected.hpp"
 
@@ -23,6 +24,11 @@ public:
 	/// equal to `v` (which *must* be one of `values`).
 	ConfigData(const std::string& v, const std::unordered_set<std::string>& values);
 
+	/// Construct a format string (see `FmtStrFormatter`), currently equal to
+	/// `v`, in which `format_keys` are the only valid keys.
+	static ConfigData format_string(const std::string& v,
+		const std::string& format_keys);
+
 	/// Current value of the setting.
 	const std::string& value() const
 	{
@@ -34,6 +40,11 @@ public:
 	/// On error, returns internationalized error message.
 	nonstd::expected<void, std::string> set_value(std::string new_value);
 
+	/// Problems with the current value if this setting is a format string
+	/// (see `FmtStrFormatter::validate`). These aren't errors: the value is
+	/// still used, and renders as best it can.
+	std::vector<std::string> format_errors() const;
+
 	/// Default value of the setting.
 	const std::string& default_value() const
 	{
@@ -71,6 +82,8 @@ private:
 	ConfigDataType type_;
 	std::unordered_set<std::string> enum_values_;
 	bool multi_option_;
+	/// If not empty, the value is a format string with these keys.
+	std::string format_keys_;
 };
 
 } // namespace newsboat
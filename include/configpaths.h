@@ -3,8 +3,11 @@
 
 #include "libnewsboat-ffi/src/configpaths.rs.h" // IWYU pragma: export
 
+#include <optional>
 #include <string>
+#include <vector>
 
+#include "3rd-party/expected.hpp"
 #include "cliargsparser.h"
 
 namespace newsboat {
@@ -34,11 +37,34 @@ public:
 	/// true if copied something, false otherwise.
 	bool try_migrate_from_newsbeuter();
 
+	/// Human-readable description of the migrations that would bring
+	/// Newsboat's files up to date, one line per element.
+	nonstd::expected<std::vector<std::string>, std::string> migration_report()
+	const;
+
+	/// Applies pending migrations of Newsboat's files.
+	nonstd::expected<void, std::string> migrate() const;
+
+	/// Applies pending migrations, unless paths were changed on the command
+	/// line.
+	nonstd::expected<void, std::string> try_migrate() const;
+
 	/// Creates Newsboat's dotdir or XDG config & data dirs (depending on what
 	/// was configured during initialization, when processing CLI args, and if
 	/// migration found anything).
 	bool create_dirs() const;
 
+	/// Name of the profile chosen with --profile or --create-profile, if
+	/// any.
+	std::optional<std::string> profile() const;
+
+	/// Returns `true` if the chosen profile exists, or if no profile was
+	/// chosen.
+	bool profile_exists() const;
+
+	/// Names of all existing profiles, in alphabetical order.
+	std::vector<std::string> list_profiles() const;
+
 	/// Path to the URLs file.
 	Filepath url_file() const;
 
@@ -59,6 +85,15 @@ public:
 	/// \note This changes when path to config file changes.
 	Filepath lock_file() const;
 
+	/// Lock file that versions which kept the lock next to the cache would
+	/// use, if it's not `lock_file()`. Such a version doesn't notice the
+	/// current lock, so it shouldn't run alongside this one.
+	std::optional<Filepath> legacy_lock_file() const;
+
+	/// Directory with the lock file, unless the user chose a different lock
+	/// file.
+	Filepath lock_dir() const;
+
 	/// \brief Path to the queue file.
 	///
 	/// Queue file stores enqueued podcasts. It's written by Newsboat, and
@@ -70,6 +105,24 @@ public:
 
 	/// Path to the file with command-line history.
 	Filepath cmdline_history_file() const;
+
+	/// Directory for log files whose path wasn't specified by the user. No
+	/// value means that Newsboat uses the dotdir, and logs go into the current
+	/// directory.
+	std::optional<Filepath> log_dir() const;
+
+	/// The directory that holds all the files if Newsboat runs in portable
+	/// mode, i.e. if NEWSBOAT_HOME is set or there is a `newsboat.portable`
+	/// file next to the executable.
+	std::optional<Filepath> portable_dir() const;
+
+	/// The config file, followed by the `*.conf` files from the `config.d`
+	/// directory next to it, in lexical order.
+	std::vector<Filepath> config_files() const;
+
+	/// The URLs file, followed by the files from the `urls.d` directory next
+	/// to it, in lexical order.
+	std::vector<Filepath> url_files() const;
 private:
 	rust::Box<configpaths::bridged::ConfigPaths> rs_object;
 };
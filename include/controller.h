@@ -5,10 +5,13 @@
 
 #include <libxml/tree.h>
 
+#include "3rd-party/expected.hpp"
+
 #include "cache.h"
 #include "colormanager.h"
 #include "configcontainer.h"
 #include "configparser.h"
+#include "controlsocket.h"
 #include "feedcontainer.h"
 #include "filtercontainer.h"
 #include "fslock.h"
@@ -53,12 +56,19 @@ public:
 	{
 		return refresh_on_start;
 	}
+	/// If `true`, another instance holds the lock, so this one only reads
+	/// the cache: reloading and other operations that change it are disabled.
+	bool is_read_only() const
+	{
+		return read_only;
+	}
 	EnqueueResult enqueue_url(RssItem& item, RssFeed& feed);
 
 	void reload_urls_file();
 	void edit_urls_file();
 	void edit_urls_file(const std::string& cmdline);
 	Filepath get_urls_file();
+	Filepath get_config_file();
 
 	FeedContainer* get_feedcontainer()
 	{
@@ -115,18 +125,35 @@ public:
 	}
 
 private:
-	int import_opml(const Filepath& opmlFile, const Filepath& urlFile);
+	int import_opml(const Filepath& opmlFile,
+		const std::vector<Filepath>& urlFiles);
+	/// Prints pending migrations of Newsboat's files, and applies them
+	/// unless `dry_run` is `true`. Returns the exit code.
+	int run_migrations(bool dry_run);
 	void export_opml(bool version2);
 	void rec_find_rss_outlines(xmlNode* node, std::string tag);
-	int execute_commands(const std::vector<std::string>& cmds);
-
-	void import_read_information(const Filepath& readinfofile);
-	void export_read_information(const Filepath& readinfofile);
+	int execute_commands(const std::vector<std::string>& cmds, bool dry_run);
+	/// Prints the feeds that `reload` would fetch.
+	void report_reload_plan();
+	/// Prints how many articles were deleted by the limits on the cache
+	/// size, i.e. `keep-articles-days` and `max-items`.
+	void report_cache_limits();
+	int run_subcommand(const CliArgsParser& args);
+	/// Runs a command received through the control socket. Called from the
+	/// View's main loop, on the UI thread.
+	nonstd::expected<std::string, std::string> handle_remote_command(
+		const RemoteCommand& command);
+
+	nonstd::expected<void, std::string> import_read_information(
+		const Filepath& readinfofile);
+	void export_read_information(const Filepath& readinfofile,
+		const CliArgsParser& args);
 
 	View* v;
 	std::unique_ptr<UrlReader> urlcfg;
 	std::unique_ptr<Cache> rsscache;
 	bool refresh_on_start;
+	bool read_only;
 	ConfigContainer cfg;
 	RssIgnores ign;
 	FeedContainer feedcontainer;
@@ -144,6 +171,10 @@ private:
 	std::unique_ptr<Reloader> reloader;
 
 	QueueManager queueManager;
+
+	// Declared last, so that it's stopped before the objects it refers to are
+	// destroyed.
+	ControlSocket control_socket;
 };
 
 } // namespace newsboat
@@ -0,0 +1,87 @@
+#ifndef NEWSBOAT_CONTROLSOCKET_H_
+#define NEWSBOAT_CONTROLSOCKET_H_
+
+#include "libnewsboat-ffi/src/controlsocket.rs.h" // IWYU pragma: export
+
+#include <atomic>
+#include <deque>
+#include <functional>
+#include <future>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <thread>
+
+#include "3rd-party/expected.hpp"
+#include "filepath.h"
+
+namespace newsboat {
+
+/// A command received through the control socket.
+struct RemoteCommand {
+	controlsocket::bridged::CommandKind kind;
+	/// Feed URL, or the GUID of an article for `Open`. If it's not set, the
+	/// command applies to all feeds.
+	std::optional<std::string> argument;
+};
+
+/// Unix-domain socket through which other programs control this instance.
+/// Commands are received on a separate thread, which queues them until the
+/// owner of the socket passes them to a handler with `handle_requests()`;
+/// what the handler returns is sent back to the client.
+class ControlSocket {
+public:
+	using Reply = nonstd::expected<std::string, std::string>;
+	using Handler = std::function<Reply(const RemoteCommand&)>;
+
+	ControlSocket();
+	~ControlSocket();
+
+	/// Starts listening on `path`, on a thread that runs until `stop()` is
+	/// called. Received commands are passed to `handler` by
+	/// `handle_requests()`.
+	nonstd::expected<void, std::string> start(const Filepath& path,
+		Handler handler);
+
+	/// Stops the thread started by `start()`, and removes the socket.
+	void stop();
+
+	/// File descriptor that becomes readable when commands are waiting for
+	/// `handle_requests()`, so that the owner can wait for them with
+	/// `poll()`. It's -1 if the socket isn't started.
+	int wakeup_fd() const;
+
+	/// Passes the waiting commands to the handler, and sends its replies back.
+	/// Returns immediately if there are no commands.
+	void handle_requests();
+
+	/// Path to the socket of the instance that holds the lock at `lock_file`.
+	static Filepath socket_path(const Filepath& lock_file);
+
+	/// Sends `command` to the instance listening on `path`, and returns its
+	/// reply.
+	static nonstd::expected<std::string, std::string> send(
+		const Filepath& path, const std::string& command);
+
+private:
+	struct Request {
+		RemoteCommand command;
+		std::promise<Reply> reply;
+	};
+
+	void run();
+
+	rust::Box<controlsocket::bridged::ControlSocket> rs_object;
+	Handler handler;
+	std::thread thread;
+	std::atomic<bool> stopping;
+
+	std::mutex requests_mtx;
+	std::deque<Request> requests;
+	/// Self-pipe through which the thread wakes up the owner of the socket.
+	int wakeup_pipe[2];
+};
+
+} // namespace newsboat
+
+#endif /* NEWSBOAT_CONTROLSOCKET_H_ */
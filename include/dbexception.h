@@ -12,6 +12,10 @@ public:
 		: msg(sqlite3_errmsg(h))
 	{
 	}
+	explicit DbException(const std::string& message)
+		: msg(message)
+	{
+	}
 	~DbException() throw() override {}
 	const char* what() const throw() override
 	{
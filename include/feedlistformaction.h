@@ -36,6 +36,7 @@ public:
 		return Dialog::FeedList;
 	}
 	std::string title() override;
+	std::map<std::string, std::string> get_variables() override;
 
 	bool jump_to_next_unread_feed(unsigned int& feedpos);
 	bool jump_to_previous_unread_feed(unsigned int& feedpos);
@@ -3,10 +3,14 @@
 
 #include <optional>
 
+#include "filepath.h"
+
 namespace newsboat {
 
 struct FileOrigin {
 	std::size_t line_number;
+	/// The urls file, or one of its fragments, which contains the feed.
+	Filepath file;
 };
 
 struct FeedOrigin {
@@ -2,6 +2,7 @@
 #define NEWSBOAT_FILEURLREADER_H_
 
 #include <string>
+#include <vector>
 
 #include "urlreader.h"
 
@@ -9,9 +10,14 @@ namespace newsboat {
 
 class FileUrlReader : public UrlReader {
 public:
-	explicit FileUrlReader(const Filepath& file = {});
+	/// \brief Creates a reader for the urls `file` and its `fragments`.
+	///
+	/// Fragments are read after the urls file, in the order given, but are
+	/// never written to.
+	explicit FileUrlReader(const Filepath& file = {},
+		const std::vector<Filepath>& fragments = {});
 
-	/// \brief Load URLs from the urls file.
+	/// \brief Load URLs from the urls file and its fragments.
 	///
 	/// \return A non-value on success, a structure with error info otherwise.
 	std::optional<utils::ReadTextFileError> reload() override;
@@ -22,11 +28,18 @@ public:
 
 	/// \brief Write URLs back to the urls file.
 	///
+	/// URLs that were read from fragments are left out, as they're still in
+	/// their own files.
+	///
 	/// \return A non-value on success, an error message otherwise.
 	std::optional<std::string> write_config();
 
 private:
+	std::optional<utils::ReadTextFileError> load_file(const Filepath& file);
+	bool is_from_fragment(const FeedOrigin& origin) const;
+
 	const Filepath filename;
+	const std::vector<Filepath> fragments;
 };
 
 }
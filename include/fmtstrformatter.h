@@ -4,6 +4,7 @@
 #include "libnewsboat-ffi/src/fmtstrformatter.rs.h" // IWYU pragma: export
 
 #include <string>
+#include <vector>
 
 namespace newsboat {
 
@@ -14,8 +15,32 @@ public:
 	FmtStrFormatter& operator=(FmtStrFormatter&&) = default;
 	~FmtStrFormatter() = default;
 	void register_fmt(char f, const std::string& value);
+
+	/// Sets the text that replaces the end of values that don't fit into
+	/// their width. Empty by default, i.e. values are simply cut.
+	void set_ellipsis(const std::string& ellipsis);
+
 	std::string do_format(const std::string& fmt, unsigned int width = 0);
 
+	/// Formats each of `rows` with `fmt`, like `do_format` does, but pads
+	/// "auto width" specifiers (`%*x` and `%-*x`, optionally with a limit
+	/// like `%-*30x`) to the widest value of that key among all the rows.
+	///
+	/// Returns formatted rows, in the same order as `rows`.
+	static std::vector<std::string> do_format_rows(
+		std::vector<FmtStrFormatter> rows,
+		const std::string& fmt,
+		unsigned int width = 0);
+
+	/// Checks `fmt` for unknown keys, malformed conditionals, unterminated
+	/// format specifiers and invalid widths. `valid_keys` lists all the keys
+	/// that are valid in the context where `fmt` is used.
+	///
+	/// Returns human-readable descriptions of the problems; an empty vector
+	/// means the format string is valid.
+	static std::vector<std::string> validate(const std::string& fmt,
+		const std::string& valid_keys);
+
 private:
 	rust::Box<fmtstrformatter::bridged::FmtStrFormatter> rs_object;
 };
@@ -1,6 +1,7 @@
 #ifndef NEWSBOAT_FORMACTION_H_
 #define NEWSBOAT_FORMACTION_H_
 
+#include <map>
 #include <memory>
 #include <string>
 #include <vector>
@@ -16,6 +17,7 @@ namespace newsboat {
 
 class ConfigContainer;
 class RssFeed;
+class RssItem;
 class TextviewWidget;
 class View;
 
@@ -29,6 +31,7 @@ enum class CommandType {
 	SET,
 	SOURCE,
 	DUMPCONFIG,
+	SAVEMACRO,
 	EXEC,
 	UNKNOWN,	/// Unknown/non-existing command. Tokenized input is stored in Command.args
 	INVALID, 	/// differs from UNKNOWN in that no input was parsed
@@ -114,6 +117,10 @@ public:
 
 	virtual std::string title() = 0;
 
+	/// Values of the variables that operation sequences with conditions can
+	/// use in this dialog, e.g. `unread` or `link` of the selected article.
+	virtual std::map<std::string, std::string> get_variables();
+
 	virtual std::vector<std::string> get_suggestions(
 		const std::string& fragment);
 
@@ -137,6 +144,8 @@ protected:
 
 	void edit_urls(const std::vector<std::string>& args, std::shared_ptr<RssFeed> feed);
 
+	static std::map<std::string, std::string> item_variables(const RssItem& item);
+
 	/// The name of the "main" STFL widget, i.e. the one that should be focused
 	/// by default.
 	virtual std::string main_widget() const = 0;
@@ -174,6 +183,9 @@ private:
 	void handle_quit();
 	void handle_source(const std::vector<std::string>& args);
 	void handle_dumpconfig(const std::vector<std::string>& args);
+	void handle_savemacro(const std::vector<std::string>& args);
+	bool handle_record_macro(const std::vector<std::string>& args);
+	bool handle_replay_macro(const std::vector<std::string>& args);
 	void handle_exec(const std::vector<std::string>& args);
 
 	LineView head_line;
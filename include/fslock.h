@@ -3,9 +3,12 @@
 
 #include "libnewsboat-ffi/src/fslock.rs.h" // IWYU pragma: export
 
+#include <chrono>
+#include <optional>
 #include <string>
 #include <sys/types.h>
 
+#include "3rd-party/expected.hpp"
 #include "filepath.h"
 
 namespace newsboat {
@@ -18,6 +21,30 @@ public:
 	bool try_lock(const Filepath& lock_file, pid_t& pid,
 		std::string& error_message);
 
+	/// Like `try_lock()`, but if another process on this host holds the
+	/// lock, waits up to `timeout` for it to be released.
+	bool lock_with_timeout(const Filepath& lock_file, pid_t& pid,
+		std::chrono::seconds timeout, std::string& error_message);
+
+	/// Like `try_lock()`, but if another process on this host holds the
+	/// lock, succeeds without taking any lock and sets `unlocked` to `true`.
+	/// That process's PID is stored into `pid`. This process must not modify
+	/// the files that the lock protects, and nothing stops the other process
+	/// from modifying them in the meantime.
+	bool try_lock_or_go_unlocked(const Filepath& lock_file, pid_t& pid,
+		bool& unlocked, std::string& error_message);
+
+	/// PID of the process that holds the lock at `lock_file`, if another
+	/// process holds it. Neither creates the file nor takes the lock.
+	static std::optional<pid_t> lock_holder(const Filepath& lock_file);
+
+	/// Removes the lock file if the process that holds it isn't running on
+	/// this host. Returns `true` if the file was removed, `false` if there
+	/// was nothing to remove, and an error if the lock is held by a running
+	/// process or couldn't be removed.
+	static nonstd::expected<bool, std::string> force_unlock(
+		const Filepath& lock_file);
+
 private:
 	rust::Box<fslock::bridged::FsLock> rs_object;
 };
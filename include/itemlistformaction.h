@@ -39,6 +39,7 @@ public:
 		return Dialog::ArticleList;
 	}
 	std::string title() override;
+	std::map<std::string, std::string> get_variables() override;
 
 	std::shared_ptr<RssFeed> get_feed()
 	{
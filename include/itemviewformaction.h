@@ -42,6 +42,7 @@ public:
 		return Dialog::Article;
 	}
 	std::string title() override;
+	std::map<std::string, std::string> get_variables() override;
 
 	void finished_qna(QnaFinishAction op) override;
 
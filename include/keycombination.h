@@ -1,6 +1,8 @@
 #ifndef NEWSBOAT_KEYCOMBINATION_H_
 #define NEWSBOAT_KEYCOMBINATION_H_
 
+#include "libnewsboat-ffi/src/keycombination.rs.h" // IWYU pragma: export
+
 #include <string>
 #include <vector>
 
@@ -48,6 +50,22 @@ private:
 	AltState alt;
 };
 
+// Turns the key events reported by STFL into key combinations. Escape
+// sequences that curses doesn't know about (e.g. the ones for <C-ENTER>) are
+// reported byte by byte, and are put back together here.
+class KeyDecoder {
+public:
+	KeyDecoder();
+	std::vector<KeyCombination> push_event(const std::string& event);
+	// Returns the keys left over from an unfinished escape sequence; call this
+	// if no more input arrived for a while after has_pending() became true
+	std::vector<KeyCombination> flush();
+	bool has_pending() const;
+
+private:
+	rust::Box<keycombination::bridged::KeyDecoder> rs_object;
+};
+
 } // namespace newsboat
 
 #endif /* NEWSBOAT_KEYCOMBINATION_H_ */
@@ -95,6 +95,8 @@ enum Operation {
 	OP_NEXTFEED,
 	OP_PREVFEED,
 	OP_MACROPREFIX,
+	OP_RECORDMACRO,
+	OP_REPLAYMACRO,
 	OP_DELETE,
 	OP_DELETE_ALL,
 	OP_PURGE_DELETED,
@@ -111,6 +113,7 @@ enum Operation {
 	OP_PREVSEARCHRESULTS,
 	OP_ARTICLEFEED,
 	OP_SET,
+	OP_RUNPROGRAM,
 
 	// podboat-specific operations:
 	OP_PB_MIN = 1000,
@@ -235,6 +238,16 @@ public:
 	std::vector<MacroCmd> get_operation(const std::vector<KeyCombination>& key_sequence,
 		Dialog context, MultiKeyBindingState& state, BindingType& type);
 	std::vector<MacroCmd> get_macro(const KeyCombination& key_combination);
+
+	/// Returns the commands to run when the key sequence bound to `cmds`
+	/// is preceded by a numeric prefix `count`, like `5j`. Usually that's
+	/// `cmds` repeated `count` times, but operations that accept a number
+	/// (e.g. `goto-url`) get `count` as their argument instead.
+	static std::vector<MacroCmd> apply_count(const std::vector<MacroCmd>& cmds,
+		unsigned int count);
+
+	/// The largest numeric prefix that can be typed before a key sequence.
+	static unsigned int max_count();
 	char get_key(const std::string& keycode);
 	std::vector<KeyCombination> get_keys(Operation op, Dialog context);
 	void handle_action(std::string_view action,
@@ -247,6 +260,17 @@ public:
 		std::string_view command_name, bool allow_description = true);
 	std::vector<MacroCmd> get_startup_operation_sequence();
 
+	/// Checks `bind` commands processed so far for bindings that replace
+	/// each other, and for unknown contexts and operations. Returns
+	/// human-readable descriptions of the problems; an empty vector means
+	/// there were none.
+	std::vector<std::string> lint_bindings() const;
+
+	/// Renders the default key bindings, with `bind` commands processed so
+	/// far applied on top, as a per-context reference. `format` is one of
+	/// "markdown", "roff" and "json".
+	std::string render_reference(const std::string& format) const;
+
 	StflRichText prepare_keymap_hint(const std::vector<KeyMapHintEntry>& hints,
 		Dialog context);
 
@@ -268,6 +292,7 @@ private:
 	std::map<Dialog, Mapping> context_keymaps;
 	std::map<KeyCombination, MacroBinding> macros_;
 	std::vector<MacroCmd> startup_operations_sequence;
+	std::vector<std::string> bind_commands;
 };
 
 } // namespace newsboat
@@ -0,0 +1,49 @@
+#ifndef NEWSBOAT_MACRORECORDER_H_
+#define NEWSBOAT_MACRORECORDER_H_
+
+#include "libnewsboat-ffi/src/macrorecorder.rs.h" // IWYU pragma: export
+
+#include <string>
+#include <vector>
+
+#include "3rd-party/expected.hpp"
+#include "filepath.h"
+#include "keymap.h"
+
+namespace newsboat {
+
+/// Records operations executed in the UI into registers named by a letter or
+/// a digit, so that they can be replayed or saved as a `macro` command.
+class MacroRecorder {
+public:
+	MacroRecorder();
+	~MacroRecorder() = default;
+
+	nonstd::expected<void, std::string> start_recording(char reg);
+	/// Returns the register that the recording was stored into.
+	nonstd::expected<char, std::string> stop_recording();
+	bool is_recording() const;
+
+	/// Adds `cmd` to the recording, if there is one in progress.
+	void record(const MacroCmd& cmd);
+
+	/// Returns the operations stored in `reg`.
+	nonstd::expected<std::vector<MacroCmd>, std::string> replay(char reg,
+		KeyMap& keymap) const;
+
+	/// Returns a `macro` command that binds the contents of `reg` to `key`.
+	nonstd::expected<std::string, std::string> macro_command(char reg,
+		const std::string& key, const std::string& description) const;
+
+	/// Appends the `macro` command for `reg` (see `macro_command()`) to the
+	/// config file at `path`.
+	nonstd::expected<void, std::string> save_macro(char reg, const std::string& key,
+		const std::string& description, const Filepath& path) const;
+
+private:
+	rust::Box<macrorecorder::bridged::MacroRecorder> rs_object;
+};
+
+} // namespace newsboat
+
+#endif /* NEWSBOAT_MACRORECORDER_H_ */
@@ -2,6 +2,7 @@
 #define PODBOAT_CONTROLLER_H_
 
 #include <memory>
+#include <optional>
 #include <string>
 #include <vector>
 
@@ -73,9 +74,13 @@ private:
 	std::unique_ptr<QueueLoader> ql;
 
 	newsboat::Filepath lock_file;
+	/// Lock file that versions which kept the lock in the data directory
+	/// would use, if it's not `lock_file`.
+	std::optional<newsboat::Filepath> legacy_lock_file;
 	std::unique_ptr<newsboat::FsLock> fslock;
 
 	bool automatic_dl = false;
+	bool force_unlock = false;
 	newsboat::ColorManager colorman;
 	newsboat::KeyMap keys;
 };
@@ -0,0 +1,37 @@
+#ifndef NEWSBOAT_PROGRAMRUNNER_H_
+#define NEWSBOAT_PROGRAMRUNNER_H_
+
+#include "libnewsboat-ffi/src/keymap.rs.h" // IWYU pragma: export
+
+#include <map>
+#include <optional>
+#include <string>
+
+#include "3rd-party/expected.hpp"
+#include "keymap.h"
+
+namespace newsboat {
+
+/// Runs an operation sequence with conditions and variables (the argument of
+/// OP_RUNPROGRAM) one operation at a time, so that each condition sees the
+/// effects of the operations that ran before it.
+class ProgramRunner {
+public:
+	explicit ProgramRunner(const std::string& program);
+	~ProgramRunner() = default;
+
+	/// Returns the next operation to run, or std::nullopt if the program is
+	/// finished. `variables` provide the values for variables that the
+	/// program doesn't set itself, like `unread` or `link`.
+	nonstd::expected<std::optional<MacroCmd>, std::string> next_operation(
+		const std::map<std::string, std::string>& variables);
+
+private:
+	// Declared first, so that it's set while `rs_object` is initialized
+	bool parsing_failed = false;
+	rust::Box<keymap::bridged::ProgramRunner> rs_object;
+};
+
+} // namespace newsboat
+
+#endif /* NEWSBOAT_PROGRAMRUNNER_H_ */
@@ -113,6 +113,7 @@ public:
 	}
 
 	std::shared_ptr<RssItem> get_item_by_guid(const std::string& guid);
+	bool has_item(const std::string& guid);
 	std::shared_ptr<RssItem> get_item_by_guid_unlocked(
 		const std::string& guid);
 
@@ -46,6 +46,9 @@ public:
 	};
 
 	static void reset();
+	/// Asks the terminal to report mouse clicks and wheel movements as SGR
+	/// mouse sequences, or to stop doing that. `reset()` turns reporting off.
+	static void set_mouse_reporting(bool enabled);
 	static std::string quote(const std::string& text);
 };
 
@@ -8,7 +8,9 @@
 #include <vector>
 
 #include "formaction.h"
+#include "keycombination.h"
 #include "links.h"
+#include "macrorecorder.h"
 #include "statusline.h"
 #include "filepath.h"
 
@@ -18,6 +20,7 @@ class Cache;
 class ColorManager;
 class Controller;
 class ConfigContainer;
+class ControlSocket;
 class FeedListFormAction;
 class FilterContainer;
 struct FilterNameExprPair;
@@ -116,6 +119,13 @@ public:
 
 	void force_redraw();
 
+	/// Makes the main loop wait for commands from `socket` as well as for
+	/// user input, and handle them as they arrive.
+	void enable_remote_requests(ControlSocket& socket);
+	/// Opens the article with `guid` from `feed` on top of the feed list, like
+	/// the user would.
+	void open_article(std::shared_ptr<RssFeed> feed, const std::string& guid);
+
 	void notify_itemlist_change(std::shared_ptr<RssFeed> feed);
 
 	void feedlist_mark_pos_if_visible(unsigned int pos);
@@ -141,9 +151,20 @@ public:
 
 	static void ctrl_c_action(int sig);
 
-protected:
 	bool run_commands(const std::vector<MacroCmd>& commands, BindingType binding_type);
+	/// Runs the program that OP_RUNPROGRAM got as its argument. Variables
+	/// are looked up in whichever dialog is current when they're needed.
+	bool run_program(const std::vector<std::string>& args, BindingType binding_type);
+
+	MacroRecorder& get_macro_recorder()
+	{
+		return macro_recorder;
+	}
+	/// Makes the main loop pass the next key press to `op` as its argument,
+	/// instead of looking up its binding. `prompt` is shown in the meantime.
+	void read_register(Operation op, const std::string& prompt);
 
+protected:
 	void apply_colors(std::shared_ptr<FormAction> fa);
 
 	bool handle_qna_event(const std::string& event, std::shared_ptr<FormAction> fa);
@@ -176,6 +197,24 @@ protected:
 
 private:
 	bool try_prepare_query_feed(std::shared_ptr<RssFeed> feed);
+	/// Draws `fa` and returns the next event. If remote requests are enabled,
+	/// handles the ones that arrive in the meantime, and returns an empty
+	/// event after that.
+	std::string wait_for_event(std::shared_ptr<FormAction> fa);
+	/// Returns the keys that `event` completes; a "TIMEOUT" event flushes
+	/// the unfinished escape sequence.
+	std::vector<KeyCombination> decode_event(const std::string& event);
+	/// Turns mouse reporting on if `mouse-support` is enabled, except while
+	/// a question is asked: the reports would end up in the input field.
+	void update_mouse_reporting();
+
+	ControlSocket* control_socket;
+
+	KeyDecoder key_decoder;
+	MacroRecorder macro_recorder;
+	/// Operation waiting for the next key press, set by `read_register()`;
+	/// OP_NIL if there is none.
+	Operation register_op;
 };
 
 } // namespace newsboat
@@ -38,113 +38,24 @@ void print_usage(const std::string& argv0, const ConfigPaths& configpaths)
 			argv0);
 	std::cout << msg;
 
-	struct arg {
-		const char name;
-		const std::string longname;
-		const std::string params;
-		const std::string desc;
-	};
+	std::cout << CliArgsParser::options_usage();
+	std::cout << std::endl;
 
-	static const std::vector<arg> args = {
-		{'e', "export-to-opml", "", _s("export OPML feed to stdout")},
-		{'-', "export-to-opml2", "", _s("export OPML 2.0 feed including tags to stdout")},
-		{'r', "refresh-on-start", "", _s("refresh feeds on start")},
-		{'i', "import-from-opml", _s("<file>"), _s("import OPML file")},
-		{
-			'u',
-			"url-file",
-			_s("<file>"),
-			_s("read RSS feed URLs from <file>")
-		},
-		{
-			'c',
-			"cache-file",
-			_s("<file>"),
-			_s("use <file> as cache file")
-		},
-		{
-			'C',
-			"config-file",
-			_s("<file>"),
-			_s("read configuration from <file>")
-		},
-		{
-			'-',
-			"queue-file",
-			_s("<file>"),
-			_s("use <file> as podcast queue file")
-		},
-		{
-			'-',
-			"search-history-file",
-			_s("<file>"),
-			_s("save the input history of the search to <file>")
-		},
-		{
-			'-',
-			"cmdline-history-file",
-			_s("<file>"),
-			_s("save the input history of the command line to <file>")
-		},
-		{'X', "vacuum", "", _s("compact the cache")},
-		{
-			'x',
-			"execute",
-			_s("<command>..."),
-			_s("execute list of commands")
-		},
-		{'q', "quiet", "", _s("quiet startup")},
-		{'v', "version", "", _s("get version information")},
-		{
-			'l',
-			"log-level",
-			_s("<loglevel>"),
-			_s("write a log with a certain log level (valid values: 1 to 6,"
-				" for user error, critical, error, warning, info, and debug respectively)")
-		},
-		{
-			'd',
-			"log-file",
-			_s("<file>"),
-			_s("use <file> as output log file")
-		},
-		{
-			'E',
-			"export-to-file",
-			_s("<file>"),
-			_s("export list of read articles to <file>")
-		},
-		{
-			'I',
-			"import-from-file",
-			_s("<file>"),
-			_s("import list of read articles from <file>")
-		},
-		{'h', "help", "", _s("this help")},
-		{'-', "cleanup", "", _s("remove unreferenced items from cache")}
+	std::cout << _("Subcommands (print results to stdout; --format=<tsv|json>, default: tsv):")
+		<< '\n';
+	const std::vector<std::pair<std::string, std::string>> subcommands = {
+		{"feeds list", _("list feeds with their unread and total item counts")},
+		{"items list [--feed=<url>] [--unread]", _("list items")},
+		{"items mark-read <guid>...", _("mark items as read")},
 	};
-
-	std::vector<std::pair<std::string, std::string>> helpLines;
-	std::size_t maxLength = 0;
-	for (const auto& a : args) {
-		std::string longcolumn;
-		if (a.name != '-') {
-			longcolumn += "-";
-			longcolumn += a.name;
-			longcolumn += ", ";
-		} else {
-			longcolumn += "    ";
-		}
-		longcolumn += "--" + a.longname;
-		longcolumn += a.params.size() > 0 ? "=" + a.params : "";
-
-		maxLength = std::max(maxLength, longcolumn.length());
-		helpLines.push_back({longcolumn, a.desc});
+	std::size_t subcommandLength = 0;
+	for (const auto& subcommand : subcommands) {
+		subcommandLength = std::max(subcommandLength, subcommand.first.length());
 	}
-	for (const auto& helpLine : helpLines) {
-		std::cout << std::string(8, ' ') << helpLine.first;
-		const auto padding = maxLength - helpLine.first.length();
-		std::cout << std::string(2 + padding, ' ') << helpLine.second;
+	for (const auto& subcommand : subcommands) {
+		std::cout << std::string(8, ' ') << subcommand.first;
+		const auto padding = subcommandLength - subcommand.first.length();
+		std::cout << std::string(2 + padding, ' ') << subcommand.second;
 		std::cout << std::endl;
 	}
 	std::cout << std::endl;
@@ -186,7 +97,8 @@ void print_usage(const std::string& argv0, const ConfigPaths& configpaths)
 		<< std::endl;
 }
 
-void print_version(const std::string& argv0, unsigned int level)
+void print_version(const std::string& argv0, unsigned int level,
+	const std::vector<std::string>& overrides)
 {
 	if (level <= 1) {
 		std::stringstream ss;
@@ -241,6 +153,14 @@ void print_version(const std::string& argv0, unsigned int level)
 	} else {
 		std::cout << LICENSE_str << std::endl;
 	}
+
+	if (!overrides.empty()) {
+		std::cout << _("Overridden settings:") << std::endl;
+		for (const auto& config_override : overrides) {
+			std::cout << "  " << config_override << std::endl;
+		}
+		std::cout << std::endl;
+	}
 }
 
 int main(int argc, char* argv[])
@@ -278,7 +198,11 @@ int main(int argc, char* argv[])
 			return args.return_code().value();
 		}
 	} else if (args.show_version()) {
-		print_version(args.program_name(), args.show_version());
+		print_version(args.program_name(), args.show_version(),
+			c.get_config()->describe_overrides(args.config_overrides()));
+		return EXIT_SUCCESS;
+	} else if (const auto script = args.completions()) {
+		std::cout << script.value();
 		return EXIT_SUCCESS;
 	}
 
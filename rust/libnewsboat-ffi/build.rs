@@ -14,15 +14,20 @@ fn main() {
     add_cxxbridge("charencoding");
     add_cxxbridge("filepath");
     add_cxxbridge("cliargsparser");
+    add_cxxbridge("configoverrides");
     add_cxxbridge("configpaths");
+    add_cxxbridge("controlsocket");
     add_cxxbridge("fmtstrformatter");
     add_cxxbridge("fslock");
     add_cxxbridge("history");
     add_cxxbridge("keycombination");
     add_cxxbridge("keymap");
     add_cxxbridge("logger");
+    add_cxxbridge("macrorecorder");
     add_cxxbridge("matchererror");
+    add_cxxbridge("readstate");
     add_cxxbridge("scopemeasure");
+    add_cxxbridge("scriptoutput");
     add_cxxbridge("stflrichtext");
     add_cxxbridge("utils");
 }
@@ -1,7 +1,7 @@
 use cxx::{ExternType, type_id};
 
 use crate::filepath::PathBuf;
-use libnewsboat::cliargsparser;
+use libnewsboat::{cliargsparser, completions};
 use std::ffi::OsString;
 use std::os::unix::ffi::OsStringExt;
 use std::pin::Pin;
@@ -30,6 +30,32 @@ mod bridged {
         pub data: Vec<u8>,
     }
 
+    enum SubcommandKind {
+        ListFeeds,
+        ListItems,
+        MarkItemsRead,
+    }
+
+    /// Flattened `cliargsparser::Subcommand`, since cxx doesn't support enums with data.
+    struct Subcommand {
+        kind: SubcommandKind,
+        /// Name of the output format: "tsv" or "json".
+        format: String,
+        /// `ListItems`: if `has_feed` is `true`, only the items of this feed should be listed.
+        has_feed: bool,
+        feed: String,
+        /// `ListItems`: only unread items should be listed.
+        unread_only: bool,
+        /// `MarkItemsRead`: GUIDs of the items to mark.
+        guids: Vec<String>,
+    }
+
+    /// Argument of a `-o name=value` option.
+    struct ConfigAssignment {
+        name: String,
+        value: String,
+    }
+
     extern "Rust" {
         type CliArgsParser;
 
@@ -40,11 +66,23 @@ mod bridged {
         fn export_as_opml2(cliargsparser: &CliArgsParser) -> bool;
         fn do_vacuum(cliargsparser: &CliArgsParser) -> bool;
         fn do_cleanup(cliargsparser: &CliArgsParser) -> bool;
+        fn do_migrate(cliargsparser: &CliArgsParser) -> bool;
+        fn dry_run(cliargsparser: &CliArgsParser) -> bool;
+        fn force_unlock(cliargsparser: &CliArgsParser) -> bool;
+        fn wait_lock(cliargsparser: &CliArgsParser, seconds: &mut u64) -> bool;
+        fn can_run_read_only(cliargsparser: &CliArgsParser) -> bool;
         fn do_show_version(cliargsparser: &CliArgsParser) -> u64;
         fn silent(cliargsparser: &CliArgsParser) -> bool;
         fn using_nonstandard_configs(cliargsparser: &CliArgsParser) -> bool;
         fn should_print_usage(cliargsparser: &CliArgsParser) -> bool;
         fn refresh_on_start(cliargsparser: &CliArgsParser) -> bool;
+        fn lint_keymap(cliargsparser: &CliArgsParser) -> bool;
+        fn export_keymap(cliargsparser: &CliArgsParser, format: &mut String) -> bool;
+        fn subcommand(cliargsparser: &CliArgsParser, subcommand: &mut Subcommand) -> bool;
+        fn completions(cliargsparser: &CliArgsParser, script: &mut String) -> bool;
+        fn remote_command(cliargsparser: &CliArgsParser, command: &mut String) -> bool;
+        fn create_profile(cliargsparser: &CliArgsParser) -> bool;
+        fn list_profiles(cliargsparser: &CliArgsParser) -> bool;
 
         fn importfile(cliargsparser: &CliArgsParser, mut file: Pin<&mut PathBuf>);
         fn program_name(cliargsparser: &CliArgsParser) -> String;
@@ -68,7 +106,11 @@ mod bridged {
 
         fn cmds_to_execute(cliargsparser: &CliArgsParser) -> Vec<String>;
 
+        fn config_overrides(cliargsparser: &CliArgsParser) -> Vec<ConfigAssignment>;
+
         fn log_level(cliargsparser: &CliArgsParser, level: &mut i8) -> bool;
+
+        fn options_usage() -> String;
     }
 }
 
@@ -102,6 +144,32 @@ fn do_cleanup(cliargsparser: &CliArgsParser) -> bool {
     cliargsparser.0.do_cleanup
 }
 
+fn do_migrate(cliargsparser: &CliArgsParser) -> bool {
+    cliargsparser.0.do_migrate
+}
+
+fn dry_run(cliargsparser: &CliArgsParser) -> bool {
+    cliargsparser.0.dry_run
+}
+
+fn force_unlock(cliargsparser: &CliArgsParser) -> bool {
+    cliargsparser.0.force_unlock
+}
+
+fn wait_lock(cliargsparser: &CliArgsParser, seconds: &mut u64) -> bool {
+    match cliargsparser.0.wait_lock {
+        Some(s) => {
+            *seconds = s;
+            true
+        }
+        None => false,
+    }
+}
+
+fn can_run_read_only(cliargsparser: &CliArgsParser) -> bool {
+    cliargsparser.0.can_run_read_only()
+}
+
 fn do_show_version(cliargsparser: &CliArgsParser) -> u64 {
     cliargsparser.0.show_version as u64
 }
@@ -122,6 +190,83 @@ fn refresh_on_start(cliargsparser: &CliArgsParser) -> bool {
     cliargsparser.0.refresh_on_start
 }
 
+fn lint_keymap(cliargsparser: &CliArgsParser) -> bool {
+    cliargsparser.0.lint_keymap
+}
+
+fn export_keymap(cliargsparser: &CliArgsParser, format: &mut String) -> bool {
+    match cliargsparser.0.export_keymap {
+        Some(f) => {
+            *format = f.name().to_owned();
+            true
+        }
+        None => false,
+    }
+}
+
+fn create_profile(cliargsparser: &CliArgsParser) -> bool {
+    cliargsparser.0.create_profile
+}
+
+fn list_profiles(cliargsparser: &CliArgsParser) -> bool {
+    cliargsparser.0.list_profiles
+}
+
+fn completions(cliargsparser: &CliArgsParser, script: &mut String) -> bool {
+    match cliargsparser.0.completions {
+        Some(shell) => {
+            *script = completions::generate(shell);
+            true
+        }
+        None => false,
+    }
+}
+
+fn options_usage() -> String {
+    cliargsparser::options_usage()
+}
+
+fn remote_command(cliargsparser: &CliArgsParser, command: &mut String) -> bool {
+    match &cliargsparser.0.remote_command {
+        Some(c) => {
+            *command = c.to_string();
+            true
+        }
+        None => false,
+    }
+}
+
+fn subcommand(cliargsparser: &CliArgsParser, output: &mut bridged::Subcommand) -> bool {
+    use cliargsparser::Subcommand;
+
+    let Some(subcommand) = &cliargsparser.0.subcommand else {
+        return false;
+    };
+    match subcommand {
+        Subcommand::ListFeeds { format } => {
+            output.kind = bridged::SubcommandKind::ListFeeds;
+            output.format = format.name().to_owned();
+        }
+        Subcommand::ListItems {
+            feed,
+            unread_only,
+            format,
+        } => {
+            output.kind = bridged::SubcommandKind::ListItems;
+            output.format = format.name().to_owned();
+            output.has_feed = feed.is_some();
+            output.feed = feed.clone().unwrap_or_default();
+            output.unread_only = *unread_only;
+        }
+        Subcommand::MarkItemsRead { guids, format } => {
+            output.kind = bridged::SubcommandKind::MarkItemsRead;
+            output.format = format.name().to_owned();
+            output.guids = guids.clone();
+        }
+    }
+    true
+}
+
 fn importfile(cliargsparser: &CliArgsParser, mut output: Pin<&mut PathBuf>) {
     match &cliargsparser.0.importfile {
         Some(path) => output.0 = path.to_owned(),
@@ -251,6 +396,18 @@ fn cmds_to_execute(cliargsparser: &CliArgsParser) -> Vec<String> {
     cliargsparser.0.cmds_to_execute.to_owned()
 }
 
+fn config_overrides(cliargsparser: &CliArgsParser) -> Vec<bridged::ConfigAssignment> {
+    cliargsparser
+        .0
+        .config_overrides
+        .iter()
+        .map(|(name, value)| bridged::ConfigAssignment {
+            name: name.clone(),
+            value: value.clone(),
+        })
+        .collect()
+}
+
 fn log_level(cliargsparser: &CliArgsParser, level: &mut i8) -> bool {
     match cliargsparser.0.log_level {
         Some(l) => {
@@ -0,0 +1,41 @@
+use libnewsboat::configoverrides;
+
+#[cxx::bridge(namespace = "newsboat::configoverrides::bridged")]
+mod bridged {
+    /// A `name=value` pair, e.g. from a `-o` option.
+    struct Assignment {
+        name: String,
+        value: String,
+    }
+
+    struct ConfigOverride {
+        name: String,
+        value: String,
+        /// Human-readable description, saying where the override came from.
+        description: String,
+    }
+
+    extern "Rust" {
+        // `settings` are the names of all known settings. Overrides come from the process's
+        // environment and from `assignments`.
+        fn collect_overrides(settings: &[&str], assignments: &[Assignment]) -> Vec<ConfigOverride>;
+    }
+}
+
+fn collect_overrides(
+    settings: &[&str],
+    assignments: &[bridged::Assignment],
+) -> Vec<bridged::ConfigOverride> {
+    let assignments: Vec<(String, String)> = assignments
+        .iter()
+        .map(|assignment| (assignment.name.clone(), assignment.value.clone()))
+        .collect();
+    configoverrides::collect(settings, std::env::vars_os(), &assignments)
+        .into_iter()
+        .map(|config_override| bridged::ConfigOverride {
+            description: config_override.describe(),
+            name: config_override.name,
+            value: config_override.value,
+        })
+        .collect()
+}
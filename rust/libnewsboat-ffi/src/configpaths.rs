@@ -34,14 +34,37 @@ mod bridged {
 
         fn try_migrate_from_newsbeuter(configpaths: &mut ConfigPaths) -> bool;
 
+        fn migration_report(
+            configpaths: &ConfigPaths,
+            report: &mut Vec<String>,
+            error_message: &mut String,
+        ) -> bool;
+        fn migrate(configpaths: &ConfigPaths, error_message: &mut String) -> bool;
+        fn try_migrate(configpaths: &ConfigPaths, error_message: &mut String) -> bool;
+
+        fn profile(configpaths: &ConfigPaths, name: &mut String) -> bool;
+        fn profile_exists(configpaths: &ConfigPaths) -> bool;
+        fn list_profiles(configpaths: &ConfigPaths) -> Vec<String>;
+
         fn url_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>);
         fn cache_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>);
         fn set_cache_file(configpaths: &mut ConfigPaths, path: &PathBuf);
         fn config_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>);
         fn lock_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>);
+        fn legacy_lock_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) -> bool;
+        fn lock_dir(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>);
         fn queue_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>);
         fn search_history_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>);
         fn cmdline_history_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>);
+        fn log_dir(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) -> bool;
+        fn portable_dir(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) -> bool;
+
+        fn config_files(
+            configpaths: &ConfigPaths,
+            index: usize,
+            mut path: Pin<&mut PathBuf>,
+        ) -> bool;
+        fn url_files(configpaths: &ConfigPaths, index: usize, mut path: Pin<&mut PathBuf>) -> bool;
     }
 }
 
@@ -69,6 +92,61 @@ fn try_migrate_from_newsbeuter(configpaths: &mut ConfigPaths) -> bool {
     configpaths.0.try_migrate_from_newsbeuter()
 }
 
+fn migration_report(
+    configpaths: &ConfigPaths,
+    report: &mut Vec<String>,
+    error_message: &mut String,
+) -> bool {
+    match configpaths.0.migration_plan() {
+        Ok(plan) => {
+            *report = plan.describe();
+            true
+        }
+        Err(e) => {
+            *error_message = e.to_string();
+            false
+        }
+    }
+}
+
+fn migrate(configpaths: &ConfigPaths, error_message: &mut String) -> bool {
+    match configpaths.0.migration_plan().and_then(|plan| plan.apply()) {
+        Ok(()) => true,
+        Err(e) => {
+            *error_message = e.to_string();
+            false
+        }
+    }
+}
+
+fn try_migrate(configpaths: &ConfigPaths, error_message: &mut String) -> bool {
+    match configpaths.0.try_migrate() {
+        Ok(_) => true,
+        Err(e) => {
+            *error_message = e.to_string();
+            false
+        }
+    }
+}
+
+fn profile(configpaths: &ConfigPaths, name: &mut String) -> bool {
+    match configpaths.0.profile() {
+        Some(profile) => {
+            *name = profile.to_owned();
+            true
+        }
+        None => false,
+    }
+}
+
+fn profile_exists(configpaths: &ConfigPaths) -> bool {
+    configpaths.0.profile_exists()
+}
+
+fn list_profiles(configpaths: &ConfigPaths) -> Vec<String> {
+    configpaths.0.list_profiles()
+}
+
 fn url_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) {
     path.0 = configpaths.0.url_file().to_owned();
 }
@@ -89,6 +167,20 @@ fn lock_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) {
     path.0 = configpaths.0.lock_file().to_owned();
 }
 
+fn legacy_lock_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) -> bool {
+    match configpaths.0.legacy_lock_file() {
+        Some(file) => {
+            path.0 = file.to_owned();
+            true
+        }
+        None => false,
+    }
+}
+
+fn lock_dir(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) {
+    path.0 = configpaths.0.lock_dir().to_owned();
+}
+
 fn queue_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) {
     path.0 = configpaths.0.queue_file().to_owned();
 }
@@ -100,3 +192,42 @@ fn search_history_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) {
 fn cmdline_history_file(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) {
     path.0 = configpaths.0.cmdline_history_file().to_owned();
 }
+
+fn log_dir(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) -> bool {
+    match configpaths.0.log_dir() {
+        Some(dir) => {
+            path.0 = dir.to_owned();
+            true
+        }
+        None => false,
+    }
+}
+
+fn portable_dir(configpaths: &ConfigPaths, mut path: Pin<&mut PathBuf>) -> bool {
+    match configpaths.0.portable_dir() {
+        Some(dir) => {
+            path.0 = dir.to_owned();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Puts the `index`-th element of `files` into `path`. Returns `false` if there is no such element.
+fn nth_file(files: Vec<std::path::PathBuf>, index: usize, mut path: Pin<&mut PathBuf>) -> bool {
+    match files.into_iter().nth(index) {
+        Some(file) => {
+            path.0 = file;
+            true
+        }
+        None => false,
+    }
+}
+
+fn config_files(configpaths: &ConfigPaths, index: usize, path: Pin<&mut PathBuf>) -> bool {
+    nth_file(configpaths.0.config_files(), index, path)
+}
+
+fn url_files(configpaths: &ConfigPaths, index: usize, path: Pin<&mut PathBuf>) -> bool {
+    nth_file(configpaths.0.url_files(), index, path)
+}
@@ -0,0 +1,116 @@
+use libnewsboat::controlsocket;
+use std::pin::Pin;
+use std::time::Duration;
+
+use crate::filepath::PathBuf;
+
+// cxx doesn't allow to share types from other crates, so we have to wrap it
+// cf. https://github.com/dtolnay/cxx/issues/496
+struct ControlSocket(Option<controlsocket::ControlSocket>);
+
+#[cxx::bridge(namespace = "newsboat::controlsocket::bridged")]
+mod bridged {
+    #[namespace = "newsboat::filepath::bridged"]
+    extern "C++" {
+        include!("libnewsboat-ffi/src/filepath.rs.h");
+        type PathBuf = crate::filepath::PathBuf;
+    }
+
+    enum CommandKind {
+        Reload,
+        MarkRead,
+        UnreadCount,
+        Open,
+    }
+
+    /// Flattened `controlsocket::Command`, since cxx doesn't support enums with data.
+    struct Command {
+        kind: CommandKind,
+        /// If `false`, the command applies to all feeds.
+        has_argument: bool,
+        /// Feed URL, or the GUID of an article for `Open`.
+        argument: String,
+    }
+
+    extern "Rust" {
+        type ControlSocket;
+
+        fn create() -> Box<ControlSocket>;
+        fn bind(socket: &mut ControlSocket, path: &PathBuf, error_message: &mut String) -> bool;
+        fn next_command(socket: &mut ControlSocket, timeout_ms: u64, command: &mut Command)
+        -> bool;
+        fn reply(socket: &mut ControlSocket, success: bool, text: &str);
+
+        fn socket_path(lock_path: &PathBuf, mut output: Pin<&mut PathBuf>);
+        fn send_command(socket_path: &PathBuf, command: &str, reply: &mut String) -> bool;
+    }
+}
+
+fn create() -> Box<ControlSocket> {
+    Box::new(ControlSocket(None))
+}
+
+fn bind(socket: &mut ControlSocket, path: &PathBuf, error_message: &mut String) -> bool {
+    match controlsocket::ControlSocket::bind(&path.0) {
+        Ok(s) => {
+            socket.0 = Some(s);
+            true
+        }
+        Err(message) => {
+            *error_message = message;
+            false
+        }
+    }
+}
+
+fn next_command(
+    socket: &mut ControlSocket,
+    timeout_ms: u64,
+    output: &mut bridged::Command,
+) -> bool {
+    use controlsocket::Command;
+
+    let Some(socket) = &mut socket.0 else {
+        return false;
+    };
+    let Some(command) = socket.next_command(Duration::from_millis(timeout_ms)) else {
+        return false;
+    };
+
+    let (kind, argument) = match command {
+        Command::Reload(feed_url) => (bridged::CommandKind::Reload, feed_url),
+        Command::MarkRead(feed_url) => (bridged::CommandKind::MarkRead, feed_url),
+        Command::UnreadCount(feed_url) => (bridged::CommandKind::UnreadCount, feed_url),
+        Command::Open(guid) => (bridged::CommandKind::Open, Some(guid)),
+    };
+    output.kind = kind;
+    output.has_argument = argument.is_some();
+    output.argument = argument.unwrap_or_default();
+    true
+}
+
+fn reply(socket: &mut ControlSocket, success: bool, text: &str) {
+    if let Some(socket) = &mut socket.0 {
+        let text = text.to_owned();
+        socket.reply(if success { Ok(text) } else { Err(text) });
+    }
+}
+
+fn socket_path(lock_path: &PathBuf, mut output: Pin<&mut PathBuf>) {
+    output.0 = controlsocket::socket_path(&lock_path.0);
+}
+
+fn send_command(socket_path: &PathBuf, command: &str, reply: &mut String) -> bool {
+    let result = controlsocket::Command::parse(command)
+        .and_then(|command| controlsocket::send_command(&socket_path.0, &command));
+    match result {
+        Ok(text) => {
+            *reply = text;
+            true
+        }
+        Err(message) => {
+            *reply = message;
+            false
+        }
+    }
+}
@@ -4,15 +4,27 @@ use libnewsboat::fmtstrformatter;
 // cf. https://github.com/dtolnay/cxx/issues/496
 struct FmtStrFormatter(fmtstrformatter::FmtStrFormatter);
 
+// Rows for `do_format_rows`; cxx can't pass a slice of opaque Rust types, so the formatters are
+// collected on the Rust side first.
+struct FmtStrFormatterRows(Vec<fmtstrformatter::FmtStrFormatter>);
+
 #[cxx::bridge(namespace = "newsboat::fmtstrformatter::bridged")]
 mod bridged {
     extern "Rust" {
         type FmtStrFormatter;
+        type FmtStrFormatterRows;
 
         fn create() -> Box<FmtStrFormatter>;
 
         fn register_fmt(fmt: &mut FmtStrFormatter, key: u8, value: &str);
+        fn set_ellipsis(fmt: &mut FmtStrFormatter, ellipsis: &str);
         fn do_format(fmt: &mut FmtStrFormatter, format: &str, width: u32) -> String;
+
+        fn create_rows() -> Box<FmtStrFormatterRows>;
+        fn push_row(rows: &mut FmtStrFormatterRows, row: Box<FmtStrFormatter>);
+        fn do_format_rows(rows: &FmtStrFormatterRows, format: &str, width: u32) -> Vec<String>;
+
+        fn validate(format: &str, valid_keys: &str) -> Vec<String>;
     }
 }
 
@@ -24,6 +36,31 @@ fn register_fmt(fmt: &mut FmtStrFormatter, key: u8, value: &str) {
     fmt.0.register_fmt(key as char, value.to_string());
 }
 
+fn set_ellipsis(fmt: &mut FmtStrFormatter, ellipsis: &str) {
+    fmt.0.set_ellipsis(ellipsis);
+}
+
 fn do_format(fmt: &mut FmtStrFormatter, format: &str, width: u32) -> String {
     fmt.0.do_format(format, width)
 }
+
+fn create_rows() -> Box<FmtStrFormatterRows> {
+    Box::new(FmtStrFormatterRows(Vec::new()))
+}
+
+#[allow(clippy::boxed_local)] // cxx can only pass opaque Rust types by Box
+fn push_row(rows: &mut FmtStrFormatterRows, row: Box<FmtStrFormatter>) {
+    rows.0.push(row.0);
+}
+
+fn do_format_rows(rows: &FmtStrFormatterRows, format: &str, width: u32) -> Vec<String> {
+    fmtstrformatter::do_format_rows(&rows.0, format, width)
+}
+
+fn validate(format: &str, valid_keys: &str) -> Vec<String> {
+    let valid_keys = valid_keys.chars().collect();
+    fmtstrformatter::validate(format, &valid_keys)
+        .into_iter()
+        .map(|error| error.to_string())
+        .collect()
+}
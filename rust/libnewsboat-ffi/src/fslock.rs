@@ -1,4 +1,5 @@
 use libnewsboat::fslock;
+use std::time::Duration;
 
 use crate::filepath::PathBuf;
 
@@ -24,6 +25,26 @@ mod bridged {
             pid: &mut i64,
             error_message: &mut String,
         ) -> bool;
+        fn lock_with_timeout(
+            fslock: &mut FsLock,
+            new_lock_path: &PathBuf,
+            pid: &mut i64,
+            timeout_seconds: u64,
+            error_message: &mut String,
+        ) -> bool;
+        fn try_lock_or_go_unlocked(
+            fslock: &mut FsLock,
+            new_lock_path: &PathBuf,
+            pid: &mut i64,
+            unlocked: &mut bool,
+            error_message: &mut String,
+        ) -> bool;
+        fn lock_holder(lock_path: &PathBuf, pid: &mut i64) -> bool;
+        fn force_unlock(
+            lock_path: &PathBuf,
+            removed: &mut bool,
+            error_message: &mut String,
+        ) -> bool;
     }
 }
 
@@ -46,3 +67,68 @@ fn try_lock(
     }
     false
 }
+
+fn lock_with_timeout(
+    fslock: &mut FsLock,
+    new_lock_path: &PathBuf,
+    pid: &mut i64,
+    timeout_seconds: u64,
+    error_message: &mut String,
+) -> bool {
+    let p: &mut libc::pid_t = &mut 0;
+    let timeout = Duration::from_secs(timeout_seconds);
+    let result = fslock.0.lock_with_timeout(&new_lock_path.0, p, timeout);
+    *pid = i64::from(*p);
+    match result {
+        Ok(_) => true,
+        Err(message) => {
+            *error_message = message;
+            false
+        }
+    }
+}
+
+fn try_lock_or_go_unlocked(
+    fslock: &mut FsLock,
+    new_lock_path: &PathBuf,
+    pid: &mut i64,
+    unlocked: &mut bool,
+    error_message: &mut String,
+) -> bool {
+    let p: &mut libc::pid_t = &mut 0;
+    let result = fslock.0.try_lock_or_go_unlocked(&new_lock_path.0, p);
+    *pid = i64::from(*p);
+    match result {
+        Ok(mode) => {
+            *unlocked = mode == fslock::LockMode::Unlocked;
+            true
+        }
+        Err(message) => {
+            *error_message = message;
+            false
+        }
+    }
+}
+
+fn lock_holder(lock_path: &PathBuf, pid: &mut i64) -> bool {
+    match fslock::lock_holder(&lock_path.0) {
+        Some(owner) => {
+            *pid = i64::from(owner.pid);
+            true
+        }
+        None => false,
+    }
+}
+
+fn force_unlock(lock_path: &PathBuf, removed: &mut bool, error_message: &mut String) -> bool {
+    match fslock::force_unlock(&lock_path.0) {
+        Ok(result) => {
+            *removed = result;
+            true
+        }
+        Err(message) => {
+            *error_message = message;
+            false
+        }
+    }
+}
@@ -3,6 +3,7 @@ use libnewsboat::keycombination;
 // cxx doesn't allow to share types from other crates, so we have to wrap it
 // cf. https://github.com/dtolnay/cxx/issues/496
 struct KeyCombination(keycombination::KeyCombination);
+struct KeyDecoder(keycombination::KeyDecoder);
 
 #[cxx::bridge(namespace = "newsboat::keycombination::bridged")]
 mod ffi {
@@ -16,6 +17,13 @@ mod ffi {
         fn has_shift(key_combination: &KeyCombination) -> bool;
         fn has_control(key_combination: &KeyCombination) -> bool;
         fn has_alt(key_combination: &KeyCombination) -> bool;
+
+        type KeyDecoder;
+
+        fn create_decoder() -> Box<KeyDecoder>;
+        fn push_event(decoder: &mut KeyDecoder, event: &str) -> Vec<KeyCombination>;
+        fn flush(decoder: &mut KeyDecoder) -> Vec<KeyCombination>;
+        fn has_pending(decoder: &KeyDecoder) -> bool;
     }
 }
 
@@ -45,3 +53,24 @@ fn has_control(key_combination: &KeyCombination) -> bool {
 fn has_alt(key_combination: &KeyCombination) -> bool {
     key_combination.0.has_alt()
 }
+
+fn create_decoder() -> Box<KeyDecoder> {
+    Box::new(KeyDecoder(keycombination::KeyDecoder::new()))
+}
+
+fn push_event(decoder: &mut KeyDecoder, event: &str) -> Vec<KeyCombination> {
+    decoder
+        .0
+        .push_event(event)
+        .into_iter()
+        .map(KeyCombination)
+        .collect()
+}
+
+fn flush(decoder: &mut KeyDecoder) -> Vec<KeyCombination> {
+    decoder.0.flush().into_iter().map(KeyCombination).collect()
+}
+
+fn has_pending(decoder: &KeyDecoder) -> bool {
+    decoder.0.has_pending()
+}
@@ -1,3 +1,9 @@
+use libnewsboat::keymap::Interpreter;
+
+// cxx doesn't allow to share types from other crates, so we have to wrap it
+// cf. https://github.com/dtolnay/cxx/issues/496
+struct ProgramRunner(Interpreter);
+
 #[cxx::bridge(namespace = "newsboat::keymap::bridged")]
 mod ffi {
     #[derive(Default)]
@@ -13,16 +19,22 @@ mod ffi {
         description: String,
     }
 
+    struct KeymapIssue {
+        /// Index of the binding (in the list passed to `lint_bindings()`) that the issue is about.
+        binding: usize,
+        message: String,
+    }
+
     extern "Rust" {
-        // `tokenize_operation_sequence()` returns `Option<Vec<Vec<String>>>`, but cxx doesn't
+        // `parse_operation_sequence()` returns `Option<Vec<Vec<String>>>`, but cxx doesn't
         // support `Option` and doesn't allow `Vec<Vec<_>>`. Here's how we work around that:
         //
         // 1. C++ doesn't care if the parse failed or just returned no operations, so we drop the
         //    `Option` and represent both `None` and `Some([])` with an empty vector (see
-        //    `tokenize_operation_sequence()` code further down below);
+        //    `parse_operation_sequence()` code further down below);
         //
         // 2. we put `Vec<String>` into a shared struct type called `Operation`.
-        fn tokenize_operation_sequence(
+        fn parse_operation_sequence(
             input: &str,
             description: &mut String,
             allow_description: bool,
@@ -30,16 +42,58 @@ mod ffi {
         ) -> Vec<Operation>;
 
         fn tokenize_binding(input: &str, parsing_failed: &mut bool) -> Binding;
+
+        type ProgramRunner;
+
+        // Prepares to run the program that a `run-program` operation got as its argument.
+        fn create_program_runner(program: &str, parsing_failed: &mut bool) -> Box<ProgramRunner>;
+
+        // Runs the program up to its next operation, and puts that into `operation`. Variables
+        // that aren't set by the program itself are looked up in `names` and the corresponding
+        // `values`. Returns `false` when the program is finished, or if it failed, in which case
+        // `error_message` is set.
+        fn next_program_operation(
+            runner: &mut ProgramRunner,
+            names: &[&str],
+            values: &[&str],
+            operation: &mut Operation,
+            error_message: &mut String,
+        ) -> bool;
+
+        // Returns the operations that a binding should run when its key sequence is preceded by
+        // a numeric prefix `count`, as in `Binding::operations_with_count()`.
+        fn operations_with_count(operations: &[Operation], count: u32) -> Vec<Operation>;
+
+        // The largest numeric prefix that can be typed before a key sequence.
+        fn max_count() -> u32;
+
+        // Bindings are passed as the arguments of `bind` commands; the ones that can't be
+        // parsed are skipped.
+        fn lint_bindings(
+            bindings: &[&str],
+            contexts: &[&str],
+            operations: &[&str],
+        ) -> Vec<KeymapIssue>;
+
+        // `defaults` are applied first, then `bindings` (arguments of `bind` commands, as in
+        // `lint_bindings()`). `format` is a name accepted by `ReferenceFormat::try_from()`; an
+        // unknown format results in an empty string.
+        fn render_keymap_reference(
+            defaults: &[Binding],
+            bindings: &[&str],
+            contexts: &[&str],
+            format: &str,
+        ) -> String;
     }
 }
 
-fn tokenize_operation_sequence(
+fn parse_operation_sequence(
     input: &str,
     description: &mut String,
     allow_description: bool,
     parsing_failed: &mut bool,
 ) -> Vec<ffi::Operation> {
-    match libnewsboat::keymap::tokenize_operation_sequence(input, allow_description) {
+    match libnewsboat::keymap::parse_operation_sequence(input, allow_description) {
         Some((operations, opt_description)) => {
             *parsing_failed = false;
             *description = opt_description.unwrap_or_default();
@@ -76,3 +130,125 @@ fn tokenize_binding(input: &str, parsing_failed: &mut bool) -> ffi::Binding {
         }
     }
 }
+
+fn create_program_runner(program: &str, parsing_failed: &mut bool) -> Box<ProgramRunner> {
+    let statements = match libnewsboat::keymap::parse_program(program, false) {
+        Some((statements, _description)) => statements,
+        None => {
+            *parsing_failed = true;
+            vec![]
+        }
+    };
+    Box::new(ProgramRunner(Interpreter::new(statements)))
+}
+
+fn next_program_operation(
+    runner: &mut ProgramRunner,
+    names: &[&str],
+    values: &[&str],
+    operation: &mut ffi::Operation,
+    error_message: &mut String,
+) -> bool {
+    let lookup = |name: &str| {
+        names
+            .iter()
+            .position(|n| *n == name)
+            .and_then(|i| values.get(i))
+            .map(|value| value.to_string())
+    };
+    match runner.0.next_operation(&lookup) {
+        Ok(Some(tokens)) => {
+            operation.tokens = tokens;
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            *error_message = e.to_string();
+            false
+        }
+    }
+}
+
+fn operations_with_count(operations: &[ffi::Operation], count: u32) -> Vec<ffi::Operation> {
+    use libnewsboat::keymap::{Binding, CountUsage};
+
+    let operations: Vec<Vec<String>> = operations
+        .iter()
+        .map(|operation| operation.tokens.clone())
+        .collect();
+    let binding = Binding {
+        key_sequence: String::new(),
+        contexts: Vec::new(),
+        count: CountUsage::for_operations(&operations),
+        operations,
+        description: None,
+    };
+
+    binding
+        .operations_with_count(Some(count))
+        .into_iter()
+        .map(|tokens| ffi::Operation { tokens })
+        .collect()
+}
+
+fn max_count() -> u32 {
+    libnewsboat::keymap::MAX_COUNT
+}
+
+fn lint_bindings(
+    bindings: &[&str],
+    contexts: &[&str],
+    operations: &[&str],
+) -> Vec<ffi::KeymapIssue> {
+    // Remember the original position of each binding, so that issues point at the right one even
+    // if some bindings before it failed to parse.
+    let (positions, parsed): (Vec<usize>, Vec<_>) = bindings
+        .iter()
+        .enumerate()
+        .filter_map(|(i, input)| libnewsboat::keymap::tokenize_binding(input).map(|b| (i, b)))
+        .unzip();
+    let contexts = contexts.iter().map(|name| name.to_string()).collect();
+    let operations = operations.iter().map(|name| name.to_string()).collect();
+
+    libnewsboat::keymap::lint(&parsed, &contexts, &operations)
+        .into_iter()
+        .map(|issue| ffi::KeymapIssue {
+            binding: positions[issue.binding()],
+            message: issue.to_string(),
+        })
+        .collect()
+}
+
+fn render_keymap_reference(
+    defaults: &[ffi::Binding],
+    bindings: &[&str],
+    contexts: &[&str],
+    format: &str,
+) -> String {
+    use libnewsboat::keymap::{Binding, CountUsage, ReferenceFormat, render_reference};
+
+    let Ok(format) = ReferenceFormat::try_from(format) else {
+        return String::new();
+    };
+
+    let defaults = defaults.iter().map(|binding| {
+        let operations: Vec<Vec<String>> = binding
+            .operations
+            .iter()
+            .map(|operation| operation.tokens.clone())
+            .collect();
+        Binding {
+            key_sequence: binding.key_sequence.clone(),
+            contexts: binding.contexts.clone(),
+            count: CountUsage::for_operations(&operations),
+            operations,
+            description: Some(binding.description.clone()).filter(|d| !d.is_empty()),
+        }
+    });
+    let bindings = bindings
+        .iter()
+        .filter_map(|input| libnewsboat::keymap::tokenize_binding(input));
+    let all: Vec<Binding> = defaults.chain(bindings).collect();
+
+    render_reference(&all, contexts, format)
+}
@@ -10,7 +10,9 @@ use std::process::abort;
 
 pub mod charencoding;
 pub mod cliargsparser;
+pub mod configoverrides;
 pub mod configpaths;
+pub mod controlsocket;
 pub mod filepath;
 pub mod fmtstrformatter;
 pub mod fslock;
@@ -19,8 +21,11 @@ pub mod human_panic;
 pub mod keycombination;
 pub mod keymap;
 pub mod logger;
+pub mod macrorecorder;
 pub mod matchererror;
+pub mod readstate;
 pub mod scopemeasure;
+pub mod scriptoutput;
 pub mod stflrichtext;
 pub mod utils;
 
@@ -0,0 +1,144 @@
+use crate::filepath::PathBuf;
+use libnewsboat::keymap::{self, MacroError};
+
+// cxx doesn't allow to share types from other crates, so we have to wrap it
+// cf. https://github.com/dtolnay/cxx/issues/496
+struct MacroRecorder(keymap::MacroRecorder);
+
+#[cxx::bridge(namespace = "newsboat::macrorecorder::bridged")]
+mod bridged {
+    #[namespace = "newsboat::filepath::bridged"]
+    extern "C++" {
+        include!("libnewsboat-ffi/src/filepath.rs.h");
+
+        type PathBuf = crate::filepath::PathBuf;
+    }
+
+    extern "Rust" {
+        type MacroRecorder;
+
+        fn create() -> Box<MacroRecorder>;
+
+        // Registers are passed as ASCII characters. Functions that can fail return `false` and
+        // put a human-readable message into `error_message`.
+        fn start_recording(
+            recorder: &mut MacroRecorder,
+            reg: u8,
+            error_message: &mut String,
+        ) -> bool;
+        fn stop_recording(
+            recorder: &mut MacroRecorder,
+            reg: &mut u8,
+            error_message: &mut String,
+        ) -> bool;
+        fn is_recording(recorder: &MacroRecorder) -> bool;
+        fn record(recorder: &mut MacroRecorder, operation: &[String]);
+
+        // The operations are returned as an operation sequence, which can be parsed the same way
+        // as the one in a `macro` command.
+        fn replay(
+            recorder: &MacroRecorder,
+            reg: u8,
+            operations: &mut String,
+            error_message: &mut String,
+        ) -> bool;
+        fn macro_command(
+            recorder: &MacroRecorder,
+            reg: u8,
+            key: &str,
+            description: &str,
+            command: &mut String,
+            error_message: &mut String,
+        ) -> bool;
+        fn save_macro(
+            recorder: &MacroRecorder,
+            reg: u8,
+            key: &str,
+            description: &str,
+            path: &PathBuf,
+            error_message: &mut String,
+        ) -> bool;
+    }
+}
+
+/// Stores the result into `output`, or the error into `error_message`, and returns `true` if
+/// there was no error.
+fn report<T>(result: Result<T, MacroError>, output: &mut T, error_message: &mut String) -> bool {
+    match result {
+        Ok(value) => {
+            *output = value;
+            true
+        }
+        Err(e) => {
+            *error_message = e.to_string();
+            false
+        }
+    }
+}
+
+fn create() -> Box<MacroRecorder> {
+    Box::new(MacroRecorder(keymap::MacroRecorder::new()))
+}
+
+fn start_recording(recorder: &mut MacroRecorder, reg: u8, error_message: &mut String) -> bool {
+    let result = recorder.0.start_recording(char::from(reg));
+    report(result, &mut (), error_message)
+}
+
+fn stop_recording(recorder: &mut MacroRecorder, reg: &mut u8, error_message: &mut String) -> bool {
+    // Registers are ASCII, so the conversion always succeeds
+    let result = recorder
+        .0
+        .stop_recording()
+        .map(|register| u8::try_from(register).unwrap_or(b'?'));
+    report(result, reg, error_message)
+}
+
+fn is_recording(recorder: &MacroRecorder) -> bool {
+    recorder.0.recording_register().is_some()
+}
+
+fn record(recorder: &mut MacroRecorder, operation: &[String]) {
+    recorder.0.record(operation);
+}
+
+fn replay(
+    recorder: &MacroRecorder,
+    reg: u8,
+    operations: &mut String,
+    error_message: &mut String,
+) -> bool {
+    let result = recorder
+        .0
+        .replay(char::from(reg), None)
+        .map(|operations| keymap::format_operation_sequence(&operations, None));
+    report(result, operations, error_message)
+}
+
+fn macro_command(
+    recorder: &MacroRecorder,
+    reg: u8,
+    key: &str,
+    description: &str,
+    command: &mut String,
+    error_message: &mut String,
+) -> bool {
+    let description = Some(description).filter(|d| !d.is_empty());
+    let result = recorder.0.macro_command(char::from(reg), key, description);
+    report(result, command, error_message)
+}
+
+fn save_macro(
+    recorder: &MacroRecorder,
+    reg: u8,
+    key: &str,
+    description: &str,
+    path: &PathBuf,
+    error_message: &mut String,
+) -> bool {
+    let description = Some(description).filter(|d| !d.is_empty());
+    let result = recorder
+        .0
+        .save_macro(char::from(reg), key, description, &path.0);
+    report(result, &mut (), error_message)
+}
@@ -0,0 +1,91 @@
+use crate::cliargsparser::CliArgsParser;
+use libnewsboat::readstate;
+
+#[cxx::bridge(namespace = "newsboat::readstate::bridged")]
+mod bridged {
+    #[namespace = "newsboat::cliargsparser::bridged"]
+    extern "C++" {
+        include!("libnewsboat-ffi/src/cliargsparser.rs.h");
+        type CliArgsParser = crate::cliargsparser::CliArgsParser;
+    }
+
+    struct ArticleState {
+        guid: String,
+        feed_url: String,
+        unread: bool,
+        read_changed: i64,
+        flags: String,
+        flags_changed: i64,
+    }
+
+    extern "Rust" {
+        // Writes `articles` in the format requested with `--read-state-format`.
+        fn export_read_state(articles: &[ArticleState], args: &CliArgsParser) -> String;
+
+        // Merges read state from `input` (in any supported format) into `local`, and puts the
+        // articles whose state changed into `changed`.
+        fn merge_read_state(
+            local: &[ArticleState],
+            input: &str,
+            now: i64,
+            changed: &mut Vec<ArticleState>,
+            error_message: &mut String,
+        ) -> bool;
+    }
+}
+
+impl From<&bridged::ArticleState> for readstate::ArticleState {
+    fn from(article: &bridged::ArticleState) -> Self {
+        readstate::ArticleState {
+            guid: article.guid.clone(),
+            feed_url: article.feed_url.clone(),
+            unread: article.unread,
+            read_changed: article.read_changed,
+            flags: article.flags.clone(),
+            flags_changed: article.flags_changed,
+        }
+    }
+}
+
+impl From<readstate::ArticleState> for bridged::ArticleState {
+    fn from(article: readstate::ArticleState) -> Self {
+        bridged::ArticleState {
+            guid: article.guid,
+            feed_url: article.feed_url,
+            unread: article.unread,
+            read_changed: article.read_changed,
+            flags: article.flags,
+            flags_changed: article.flags_changed,
+        }
+    }
+}
+
+fn to_local(articles: &[bridged::ArticleState]) -> Vec<readstate::ArticleState> {
+    articles.iter().map(readstate::ArticleState::from).collect()
+}
+
+fn export_read_state(articles: &[bridged::ArticleState], args: &CliArgsParser) -> String {
+    readstate::export(&to_local(articles), args.0.readinfo_export_format)
+}
+
+fn merge_read_state(
+    local: &[bridged::ArticleState],
+    input: &str,
+    now: i64,
+    changed: &mut Vec<bridged::ArticleState>,
+    error_message: &mut String,
+) -> bool {
+    match readstate::import(input, now) {
+        Ok(imported) => {
+            *changed = readstate::merge(&to_local(local), &imported)
+                .into_iter()
+                .map(bridged::ArticleState::from)
+                .collect();
+            true
+        }
+        Err(error) => {
+            *error_message = error.to_string();
+            false
+        }
+    }
+}
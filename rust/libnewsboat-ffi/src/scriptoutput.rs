@@ -0,0 +1,80 @@
+use libnewsboat::cliargsparser::OutputFormat;
+use libnewsboat::scriptoutput;
+use std::ffi::OsStr;
+
+#[cxx::bridge(namespace = "newsboat::scriptoutput::bridged")]
+mod bridged {
+    struct FeedRecord {
+        url: String,
+        title: String,
+        unread_count: u32,
+        total_count: u32,
+    }
+
+    struct ItemRecord {
+        guid: String,
+        feed_url: String,
+        title: String,
+        link: String,
+        author: String,
+        pub_date: i64,
+        unread: bool,
+    }
+
+    struct MarkReadRecord {
+        guid: String,
+        found: bool,
+    }
+
+    extern "Rust" {
+        // `format` is the name of an output format, as returned by the `subcommand()` getter of
+        // `CliArgsParser`.
+        fn format_feeds(records: &[FeedRecord], format: &str) -> String;
+        fn format_items(records: &[ItemRecord], format: &str) -> String;
+        fn format_mark_read(records: &[MarkReadRecord], format: &str) -> String;
+    }
+}
+
+fn output_format(format: &str) -> OutputFormat {
+    OutputFormat::try_from(OsStr::new(format)).unwrap_or_default()
+}
+
+fn format_feeds(records: &[bridged::FeedRecord], format: &str) -> String {
+    let records: Vec<scriptoutput::FeedRecord> = records
+        .iter()
+        .map(|record| scriptoutput::FeedRecord {
+            url: record.url.clone(),
+            title: record.title.clone(),
+            unread_count: record.unread_count,
+            total_count: record.total_count,
+        })
+        .collect();
+    scriptoutput::format_records(&records, output_format(format))
+}
+
+fn format_items(records: &[bridged::ItemRecord], format: &str) -> String {
+    let records: Vec<scriptoutput::ItemRecord> = records
+        .iter()
+        .map(|record| scriptoutput::ItemRecord {
+            guid: record.guid.clone(),
+            feed_url: record.feed_url.clone(),
+            title: record.title.clone(),
+            link: record.link.clone(),
+            author: record.author.clone(),
+            pub_date: record.pub_date,
+            unread: record.unread,
+        })
+        .collect();
+    scriptoutput::format_records(&records, output_format(format))
+}
+
+fn format_mark_read(records: &[bridged::MarkReadRecord], format: &str) -> String {
+    let records: Vec<scriptoutput::MarkReadRecord> = records
+        .iter()
+        .map(|record| scriptoutput::MarkReadRecord {
+            guid: record.guid.clone(),
+            found: record.found,
+        })
+        .collect();
+    scriptoutput::format_records(&records, output_format(format))
+}
@@ -2,19 +2,509 @@ use gettextrs::gettext;
 use lexopt::{Parser, ValueExt};
 use libc::{EXIT_FAILURE, EXIT_SUCCESS};
 use std::ffi::{OsStr, OsString};
+use std::fmt::Write;
 
 use std::path::{Path, PathBuf};
 
+use crate::completions::Shell;
+use crate::configoverrides::parse_assignment;
+use crate::configpaths::is_valid_profile_name;
+use crate::controlsocket;
+use crate::keymap::ReferenceFormat;
 use crate::logger::Level;
+use crate::readstate::ReadStateFormat;
 use crate::utils;
 use strprintf::fmt;
 
+/// Output format of subcommands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Tab-separated values, one record per line, without a header.
+    #[default]
+    Tsv,
+    /// An array of objects.
+    Json,
+}
+
+impl OutputFormat {
+    /// Name of the format, as accepted by `--format`.
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Json => "json",
+        }
+    }
+}
+
+impl TryFrom<&OsStr> for OutputFormat {
+    type Error = ();
+
+    fn try_from(value: &OsStr) -> Result<Self, Self::Error> {
+        match value.to_str() {
+            Some("tsv") => Ok(OutputFormat::Tsv),
+            Some("json") => Ok(OutputFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A command that works on the cache and prints its results, without starting the TUI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Subcommand {
+    /// `feeds list`
+    ListFeeds { format: OutputFormat },
+
+    /// `items list [--feed URL] [--unread]`
+    ListItems {
+        feed: Option<String>,
+        unread_only: bool,
+        format: OutputFormat,
+    },
+
+    /// `items mark-read GUID...`
+    MarkItemsRead {
+        guids: Vec<String>,
+        format: OutputFormat,
+    },
+}
+
+impl Subcommand {
+    /// Name of the subcommand, as it's written on the command line (e.g. `items list`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Subcommand::ListFeeds { .. } => "feeds list",
+            Subcommand::ListItems { .. } => "items list",
+            Subcommand::MarkItemsRead { .. } => "items mark-read",
+        }
+    }
+
+    fn format_mut(&mut self) -> &mut OutputFormat {
+        match self {
+            Subcommand::ListFeeds { format }
+            | Subcommand::ListItems { format, .. }
+            | Subcommand::MarkItemsRead { format, .. } => format,
+        }
+    }
+}
+
+/// Names of all subcommands.
+pub const SUBCOMMANDS: &[&str] = &["feeds list", "items list", "items mark-read"];
+
+/// Commands that can be passed to `-x`.
+pub const EXECUTE_COMMANDS: &[&str] = &["reload", "print-unread"];
+
+/// What an option takes as its value. Used to complete the value in the shell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionValue {
+    /// The option is a flag.
+    None,
+    /// A path to a file.
+    Path,
+    /// One of the listed words.
+    Choice(&'static [&'static str]),
+    /// One or more of the listed words, as separate arguments.
+    Choices(&'static [&'static str]),
+    /// Anything else, e.g. a URL.
+    Text,
+}
+
+/// Description of a command-line option.
+#[derive(Debug)]
+pub struct OptionSpec {
+    pub long: &'static str,
+    pub short: &'static [char],
+    /// Other long names the option is accepted under. They're kept for compatibility, and aren't
+    /// advertised.
+    pub aliases: &'static [&'static str],
+    pub value: OptionValue,
+    /// How the value is shown in `--help`, e.g. `<name>`. If empty, it's derived from `value`
+    /// (see `OptionSpec::value_placeholder()`); options that take `OptionValue::Text` must set it.
+    pub placeholder: &'static str,
+    /// Subcommands that accept this option. Empty for options that are accepted everywhere.
+    pub subcommands: &'static [&'static str],
+    pub description: &'static str,
+}
+
+/// Marks `msgid` for extraction into the translation template without translating it (xtr looks
+/// for this name by default). The string has to be passed through `gettext()` when shown.
+const fn gettext_noop(msgid: &'static str) -> &'static str {
+    msgid
+}
+
+const fn option(
+    long: &'static str,
+    short: &'static [char],
+    value: OptionValue,
+    description: &'static str,
+) -> OptionSpec {
+    OptionSpec {
+        long,
+        short,
+        aliases: &[],
+        value,
+        placeholder: "",
+        subcommands: &[],
+        description,
+    }
+}
+
+impl OptionSpec {
+    /// How the option's value is shown in `--help`, e.g. `<file>` or `<tsv|json>`. Empty for
+    /// flags.
+    pub fn value_placeholder(&self) -> String {
+        if !self.placeholder.is_empty() {
+            return self.placeholder.to_owned();
+        }
+        match self.value {
+            OptionValue::None | OptionValue::Text => String::new(),
+            OptionValue::Path => gettext_noop("<file>").to_owned(),
+            OptionValue::Choice(choices) => format!("<{}>", choices.join("|")),
+            OptionValue::Choices(choices) => format!("<{}>...", choices.join("|")),
+        }
+    }
+}
+
+/// All the options that `parse_cliargs()` accepts. Options that aren't listed here are rejected,
+/// and shell completions are generated from this list, so the two can't disagree.
+pub const OPTIONS: &[OptionSpec] = &[
+    option(
+        "import-from-opml",
+        &['i'],
+        OptionValue::Path,
+        gettext_noop("import OPML file"),
+    ),
+    option(
+        "export-to-opml",
+        &['e'],
+        OptionValue::None,
+        gettext_noop("export OPML feed to stdout"),
+    ),
+    option(
+        "export-to-opml2",
+        &[],
+        OptionValue::None,
+        gettext_noop("export OPML 2.0 feed including tags to stdout"),
+    ),
+    option(
+        "refresh-on-start",
+        &['r'],
+        OptionValue::None,
+        gettext_noop("refresh feeds on start"),
+    ),
+    option("help", &['h'], OptionValue::None, gettext_noop("this help")),
+    option(
+        "url-file",
+        &['u'],
+        OptionValue::Path,
+        gettext_noop("read RSS feed URLs from <file>"),
+    ),
+    option(
+        "cache-file",
+        &['c'],
+        OptionValue::Path,
+        gettext_noop("use <file> as cache file"),
+    ),
+    option(
+        "config-file",
+        &['C'],
+        OptionValue::Path,
+        gettext_noop("read configuration from <file>"),
+    ),
+    option(
+        "queue-file",
+        &[],
+        OptionValue::Path,
+        gettext_noop("use <file> as podcast queue file"),
+    ),
+    option(
+        "search-history-file",
+        &[],
+        OptionValue::Path,
+        gettext_noop("save the input history of the search to <file>"),
+    ),
+    option(
+        "cmdline-history-file",
+        &[],
+        OptionValue::Path,
+        gettext_noop("save the input history of the command line to <file>"),
+    ),
+    OptionSpec {
+        placeholder: gettext_noop("<name>"),
+        ..option(
+            "profile",
+            &[],
+            OptionValue::Text,
+            gettext_noop("use the configuration and data of profile <name>"),
+        )
+    },
+    OptionSpec {
+        placeholder: gettext_noop("<name>"),
+        ..option(
+            "create-profile",
+            &[],
+            OptionValue::Text,
+            gettext_noop("create profile <name>"),
+        )
+    },
+    option(
+        "list-profiles",
+        &[],
+        OptionValue::None,
+        gettext_noop("list existing profiles"),
+    ),
+    OptionSpec {
+        placeholder: gettext_noop("<name>=<value>"),
+        ..option(
+            "option",
+            &['o'],
+            OptionValue::Text,
+            gettext_noop("override setting <name> for this run (can be repeated)"),
+        )
+    },
+    option(
+        "vacuum",
+        &['X'],
+        OptionValue::None,
+        gettext_noop("compact the cache"),
+    ),
+    option(
+        "cleanup",
+        &[],
+        OptionValue::None,
+        gettext_noop("remove unreferenced items from cache"),
+    ),
+    option(
+        "migrate",
+        &[],
+        OptionValue::None,
+        gettext_noop("bring Newsboat's files up to date with this version and quit"),
+    ),
+    option(
+        "dry-run",
+        &[],
+        OptionValue::None,
+        gettext_noop(
+            "with -x, --vacuum, --cleanup or --migrate: report what would be done, without changing anything",
+        ),
+    ),
+    option(
+        "force-unlock",
+        &[],
+        OptionValue::None,
+        gettext_noop("remove a lock left behind by an instance that isn't running anymore"),
+    ),
+    OptionSpec {
+        placeholder: gettext_noop("<seconds>"),
+        ..option(
+            "wait-lock",
+            &[],
+            OptionValue::Text,
+            gettext_noop("if another instance is running, wait up to <seconds> for it to quit"),
+        )
+    },
+    OptionSpec {
+        placeholder: gettext_noop("<command>"),
+        ..option(
+            "remote",
+            &[],
+            OptionValue::Text,
+            gettext_noop(
+                "send <command> to the running instance (reload, mark-read, unread-count, open)",
+            ),
+        )
+    },
+    option(
+        "lint-keymap",
+        &[],
+        OptionValue::None,
+        gettext_noop("check key bindings for conflicts and mistakes"),
+    ),
+    OptionSpec {
+        placeholder: gettext_noop("<format>"),
+        ..option(
+            "export-keymap",
+            &[],
+            OptionValue::Choice(&["markdown", "roff", "json"]),
+            gettext_noop(
+                "print a reference of the key bindings (valid formats: markdown, roff, json)",
+            ),
+        )
+    },
+    OptionSpec {
+        placeholder: gettext_noop("<shell>"),
+        ..option(
+            "completions",
+            &[],
+            OptionValue::Choice(&["bash", "zsh", "fish"]),
+            gettext_noop("print a completion script for <shell> (valid shells: bash, zsh, fish)"),
+        )
+    },
+    OptionSpec {
+        aliases: &["-V"],
+        ..option(
+            "version",
+            &['v', 'V'],
+            OptionValue::None,
+            gettext_noop("get version information"),
+        )
+    },
+    OptionSpec {
+        placeholder: gettext_noop("<command>..."),
+        ..option(
+            "execute",
+            &['x'],
+            OptionValue::Choices(EXECUTE_COMMANDS),
+            gettext_noop("execute list of commands"),
+        )
+    },
+    option(
+        "quiet",
+        &['q'],
+        OptionValue::None,
+        gettext_noop("quiet startup"),
+    ),
+    option(
+        "import-from-file",
+        &['I'],
+        OptionValue::Path,
+        gettext_noop("import list of read articles from <file>"),
+    ),
+    option(
+        "export-to-file",
+        &['E'],
+        OptionValue::Path,
+        gettext_noop("export list of read articles to <file>"),
+    ),
+    OptionSpec {
+        placeholder: gettext_noop("<format>"),
+        ..option(
+            "read-state-format",
+            &[],
+            OptionValue::Choice(&["guids", "json"]),
+            gettext_noop(
+                "format of the file written by --export-to-file (valid formats: guids, json)",
+            ),
+        )
+    },
+    option(
+        "log-file",
+        &['d'],
+        OptionValue::Path,
+        gettext_noop("use <file> as output log file"),
+    ),
+    OptionSpec {
+        placeholder: gettext_noop("<loglevel>"),
+        ..option(
+            "log-level",
+            &['l'],
+            OptionValue::Choice(&["1", "2", "3", "4", "5", "6"]),
+            gettext_noop(
+                "write a log with a certain log level (valid values: 1 to 6, for user error, critical, error, warning, info, and debug respectively)",
+            ),
+        )
+    },
+    OptionSpec {
+        subcommands: SUBCOMMANDS,
+        ..option(
+            "format",
+            &[],
+            OptionValue::Choice(&["tsv", "json"]),
+            gettext_noop("output format"),
+        )
+    },
+    OptionSpec {
+        subcommands: &["items list"],
+        placeholder: gettext_noop("<url>"),
+        ..option(
+            "feed",
+            &[],
+            OptionValue::Text,
+            gettext_noop("only list items of the feed with this URL"),
+        )
+    },
+    OptionSpec {
+        subcommands: &["items list"],
+        ..option(
+            "unread",
+            &[],
+            OptionValue::None,
+            gettext_noop("only list unread items"),
+        )
+    },
+];
+
+/// Returns the list of options for `--help`, one option per line, with descriptions aligned into
+/// a column. Options that are specific to subcommands are left out.
+pub fn options_usage() -> String {
+    let lines: Vec<(String, String)> = OPTIONS
+        .iter()
+        .filter(|option| option.subcommands.is_empty())
+        .map(|option| {
+            let mut names = String::new();
+            if option.short.is_empty() {
+                names.push_str("    ");
+            }
+            for short in option.short {
+                let _ = write!(names, "-{short}, ");
+            }
+            let _ = write!(names, "--{}", option.long);
+            let placeholder = option.value_placeholder();
+            if !placeholder.is_empty() {
+                let _ = write!(names, "={}", gettext(placeholder));
+            }
+            (names, gettext(option.description))
+        })
+        .collect();
+
+    let widest = lines
+        .iter()
+        .map(|(names, _)| names.chars().count())
+        .max()
+        .unwrap_or_default();
+    let mut usage = String::new();
+    for (names, description) in lines {
+        let _ = writeln!(usage, "        {names:widest$}  {description}");
+    }
+    usage
+}
+
+/// Finds the description of an option given on the command line.
+fn find_option(arg: &lexopt::Arg) -> Option<&'static OptionSpec> {
+    match arg {
+        lexopt::Arg::Short(c) => OPTIONS.iter().find(|option| option.short.contains(c)),
+        lexopt::Arg::Long(name) => OPTIONS
+            .iter()
+            .find(|option| option.long == *name || option.aliases.contains(name)),
+        lexopt::Arg::Value(_) => None,
+    }
+}
+
 #[derive(Default)]
 pub struct CliArgsParser {
     pub do_export: bool,
     pub export_as_opml2: bool,
     pub do_vacuum: bool,
     pub do_cleanup: bool,
+
+    /// If `true`, pending migrations of Newsboat's files should be applied, and Newsboat should
+    /// quit.
+    pub do_migrate: bool,
+
+    /// If `true`, `-x`, `--vacuum`, `--cleanup` and `--migrate` should only report what they
+    /// would do, and neither the cache nor other files should be modified.
+    pub dry_run: bool,
+
+    /// If `true`, the lock file should be removed before locking, unless it's held by a process
+    /// that's running on this host.
+    pub force_unlock: bool,
+
+    /// If this contains some value, and another instance holds the lock, the creator of
+    /// `CliArgsParser` should wait up to this many seconds for the lock to be released.
+    pub wait_lock: Option<u64>,
+
+    /// If this contains some value, the creator of `CliArgsParser` should send this command to the
+    /// running instance through its control socket, print the reply, and exit.
+    pub remote_command: Option<controlsocket::Command>,
     pub program_name: String,
     pub show_version: usize,
     pub silent: bool,
@@ -30,6 +520,9 @@ pub struct CliArgsParser {
     /// should be exported.
     pub readinfo_export_file: Option<PathBuf>,
 
+    /// Format in which the list of read articles should be exported.
+    pub readinfo_export_format: ReadStateFormat,
+
     /// If this contains some value, the creator of `CliArgsParser` object should call
     /// `exit(return_code)`.
     pub return_code: Option<i32>,
@@ -48,6 +541,22 @@ pub struct CliArgsParser {
 
     pub refresh_on_start: bool,
 
+    /// If `true`, the creator of `CliArgsParser` should check key bindings from the config for
+    /// conflicts and mistakes, print the problems it found, and exit.
+    pub lint_keymap: bool,
+
+    /// If this contains some value, the creator of `CliArgsParser` should print a reference of the
+    /// key bindings in this format, and exit.
+    pub export_keymap: Option<ReferenceFormat>,
+
+    /// If this contains some value, the creator of `CliArgsParser` should run this subcommand
+    /// instead of starting the TUI.
+    pub subcommand: Option<Subcommand>,
+
+    /// If this contains some value, the creator of `CliArgsParser` should print a completion
+    /// script for this shell, and exit.
+    pub completions: Option<Shell>,
+
     /// If this contains some value, it's the path to the url file specified by the user.
     pub url_file: Option<PathBuf>,
 
@@ -70,6 +579,21 @@ pub struct CliArgsParser {
     /// If this contains some value, it's the path to the command line history file specified by the user.
     pub cmdline_history_file: Option<PathBuf>,
 
+    /// If this contains some value, it's the name of the profile whose files should be used.
+    pub profile: Option<String>,
+
+    /// If `true`, the creator of `CliArgsParser` should create the directories of `profile`, and
+    /// exit.
+    pub create_profile: bool,
+
+    /// If `true`, the creator of `CliArgsParser` should print the names of existing profiles, and
+    /// exit.
+    pub list_profiles: bool,
+
+    /// Settings to override after the config file is parsed, as (name, value) pairs, in the order
+    /// they were given.
+    pub config_overrides: Vec<(String, String)>,
+
     /// A vector of Newsboat commands to execute. Empty means user didn't specify any commands to
     /// run.
     ///
@@ -120,6 +644,13 @@ impl TryFrom<&OsStr> for Level {
 pub enum CliParseError {
     LexoptError(lexopt::Error),
     InvalidLogLevel(String),
+    InvalidKeymapFormat(String),
+    InvalidOutputFormat(String),
+    InvalidShell(String),
+    InvalidProfileName(String),
+    InvalidConfigOverride(String),
+    InvalidRemoteCommand(String),
+    InvalidWaitLockTimeout(String),
     PrintAndExit,
 }
 
@@ -128,6 +659,13 @@ impl std::fmt::Display for CliParseError {
         match self {
             CliParseError::LexoptError(lexopt) => write!(f, "{lexopt}"),
             CliParseError::InvalidLogLevel(log_message) => write!(f, "{log_message}"),
+            CliParseError::InvalidKeymapFormat(message) => write!(f, "{message}"),
+            CliParseError::InvalidOutputFormat(message) => write!(f, "{message}"),
+            CliParseError::InvalidShell(message) => write!(f, "{message}"),
+            CliParseError::InvalidProfileName(message) => write!(f, "{message}"),
+            CliParseError::InvalidConfigOverride(message) => write!(f, "{message}"),
+            CliParseError::InvalidRemoteCommand(message) => write!(f, "{message}"),
+            CliParseError::InvalidWaitLockTimeout(message) => write!(f, "{message}"),
             CliParseError::PrintAndExit => write!(f, "Erroneous command line arguments"),
         }
     }
@@ -139,6 +677,28 @@ impl From<lexopt::Error> for CliParseError {
     }
 }
 
+/// Parses the name of a subcommand (e.g. `feeds list`), the first word of which is `first`.
+fn parse_subcommand(first: &OsStr, parser: &mut Parser) -> Result<Subcommand, CliParseError> {
+    let second = parser.value()?;
+    let name = format!("{} {}", first.to_string_lossy(), second.to_string_lossy());
+
+    let format = OutputFormat::default();
+    let subcommand = match name.as_str() {
+        "feeds list" => Subcommand::ListFeeds { format },
+        "items list" => Subcommand::ListItems {
+            feed: None,
+            unread_only: false,
+            format,
+        },
+        "items mark-read" => Subcommand::MarkItemsRead {
+            guids: Vec::new(),
+            format,
+        },
+        _ => return Err(CliParseError::PrintAndExit),
+    };
+    Ok(subcommand)
+}
+
 /// Parses command line arguments and stores them in parameter args.
 /// Returns Ok(()) or a CliParseError to indicate additional values on CliArgParser that needs
 /// setting.
@@ -152,31 +712,56 @@ pub fn parse_cliargs(opts: Vec<OsString>, args: &mut CliArgsParser) -> Result<()
     let mut parser = Parser::from_args(opts);
 
     while let Some(arg) = parser.next()? {
-        match arg {
-            Short('i') | Long("import-from-opml") => {
+        if let Value(value) = arg {
+            match &mut args.subcommand {
+                None => {
+                    args.subcommand = Some(parse_subcommand(&value, &mut parser)?);
+                    args.silent = true;
+                }
+                Some(Subcommand::MarkItemsRead { guids, .. }) => {
+                    guids.push(value.to_string_lossy().into_owned());
+                }
+                Some(_) => return Err(CliParseError::from(Value(value).unexpected())),
+            }
+            continue;
+        }
+
+        let option = find_option(&arg).filter(|option| {
+            option.subcommands.is_empty()
+                || args
+                    .subcommand
+                    .as_ref()
+                    .is_some_and(|subcommand| option.subcommands.contains(&subcommand.name()))
+        });
+        let Some(option) = option else {
+            return Err(CliParseError::from(arg.unexpected()));
+        };
+
+        match option.long {
+            "import-from-opml" => {
                 let import_file_str = parser.value()?;
                 args.importfile = resolve_path(&import_file_str);
             }
-            Short('e') | Long("export-to-opml") => {
+            "export-to-opml" => {
                 args.do_export = true;
                 args.export_as_opml2 = false;
                 args.silent = true;
             }
-            Long("export-to-opml2") => {
+            "export-to-opml2" => {
                 args.do_export = true;
                 args.export_as_opml2 = true;
                 args.silent = true;
             }
-            Short('r') | Long("refresh-on-start") => args.refresh_on_start = true,
-            Short('h') | Long("help") => {
+            "refresh-on-start" => args.refresh_on_start = true,
+            "help" => {
                 args.should_print_usage = true;
                 args.return_code = Some(EXIT_SUCCESS);
             }
-            Short('u') | Long("url-file") => {
+            "url-file" => {
                 let url_file = parser.value()?;
                 args.url_file = resolve_path(&url_file);
             }
-            Short('c') | Long("cache-file") => {
+            "cache-file" => {
                 let cache_file = parser.value()?;
                 let cache_file = resolve_path(&cache_file);
                 // unwrap won't panic on cache_file.unwrap(), we know we return Some(...) from ^ utility closure
@@ -184,26 +769,119 @@ pub fn parse_cliargs(opts: Vec<OsString>, args: &mut CliArgsParser) -> Result<()
                 args.cache_file = cache_file;
                 args.lock_file = Some(lock_file);
             }
-            Short('C') | Long("config-file") => {
+            "config-file" => {
                 let config_file = parser.value()?;
                 args.config_file = resolve_path(&config_file);
             }
-            Long("queue-file") => {
+            "queue-file" => {
                 let queue_file = parser.value()?;
                 args.queue_file = resolve_path(&queue_file);
             }
-            Long("search-history-file") => {
+            "search-history-file" => {
                 let search_history_file = parser.value()?;
                 args.search_history_file = resolve_path(&search_history_file);
             }
-            Long("cmdline-history-file") => {
+            "cmdline-history-file" => {
                 let cmdline_history_file = parser.value()?;
                 args.cmdline_history_file = resolve_path(&cmdline_history_file);
             }
-            Short('X') | Long("vacuum") => args.do_vacuum = true,
-            Long("cleanup") => args.do_cleanup = true,
-            Short('v') | Long("version") | Short('V') | Long("-V") => args.show_version += 1,
-            Short('x') | Long("execute") => {
+            "profile" | "create-profile" => {
+                let name = parser.value()?.to_string_lossy().into_owned();
+                if !is_valid_profile_name(&name) {
+                    return Err(CliParseError::InvalidProfileName(fmt!(
+                        &gettext("%s: %s: invalid profile name"),
+                        &args.program_name,
+                        name
+                    )));
+                }
+                if option.long == "create-profile" {
+                    args.create_profile = true;
+                }
+                args.profile = Some(name);
+            }
+            "list-profiles" => args.list_profiles = true,
+            "option" => {
+                let assignment = parser.value()?.to_string_lossy().into_owned();
+                let Some(assignment) = parse_assignment(&assignment) else {
+                    return Err(CliParseError::InvalidConfigOverride(fmt!(
+                        &gettext("%s: %s: expected a setting in the form name=value"),
+                        &args.program_name,
+                        assignment
+                    )));
+                };
+                args.config_overrides.push(assignment);
+            }
+            "vacuum" => args.do_vacuum = true,
+            "cleanup" => args.do_cleanup = true,
+            "migrate" => {
+                args.do_migrate = true;
+                args.silent = true;
+            }
+            "dry-run" => args.dry_run = true,
+            "force-unlock" => args.force_unlock = true,
+            "wait-lock" => {
+                let seconds = parser.value()?.to_string_lossy().into_owned();
+                let Ok(seconds) = seconds.parse::<u64>() else {
+                    return Err(CliParseError::InvalidWaitLockTimeout(fmt!(
+                        &gettext("%s: %s: expected a number of seconds"),
+                        &args.program_name,
+                        seconds
+                    )));
+                };
+                args.wait_lock = Some(seconds);
+            }
+            "remote" => {
+                let command = parser.value()?.to_string_lossy().into_owned();
+                match controlsocket::Command::parse(&command) {
+                    Ok(command) => {
+                        args.remote_command = Some(command);
+                        args.silent = true;
+                    }
+                    Err(reason) => {
+                        return Err(CliParseError::InvalidRemoteCommand(fmt!(
+                            &gettext("%s: %s: %s"),
+                            &args.program_name,
+                            command,
+                            reason
+                        )));
+                    }
+                }
+            }
+            "lint-keymap" => {
+                args.lint_keymap = true;
+                args.silent = true;
+            }
+            "export-keymap" => {
+                let format_str = parser.value()?;
+                match ReferenceFormat::try_from(format_str.to_string_lossy().as_ref()) {
+                    Ok(format) => {
+                        args.export_keymap = Some(format);
+                        args.silent = true;
+                    }
+                    Err(()) => {
+                        return Err(CliParseError::InvalidKeymapFormat(fmt!(
+                            &gettext("%s: %s: invalid key binding reference format"),
+                            &args.program_name,
+                            format_str.to_string_lossy().to_string()
+                        )));
+                    }
+                }
+            }
+            "completions" => {
+                let shell_str = parser.value()?;
+                match Shell::try_from(shell_str.as_ref()) {
+                    Ok(shell) => args.completions = Some(shell),
+                    Err(()) => {
+                        return Err(CliParseError::InvalidShell(fmt!(
+                            &gettext("%s: %s: unsupported shell"),
+                            &args.program_name,
+                            shell_str.to_string_lossy().to_string()
+                        )));
+                    }
+                }
+            }
+            "version" => args.show_version += 1,
+            "execute" => {
                 for cmd in parser.values()? {
                     args.cmds_to_execute.push(cmd.to_string_lossy().into());
                 }
@@ -212,20 +890,31 @@ pub fn parse_cliargs(opts: Vec<OsString>, args: &mut CliArgsParser) -> Result<()
                 }
                 args.silent = true;
             }
-            Short('q') | Long("quiet") => args.silent = true,
-            Short('I') | Long("import-from-file") => {
+            "quiet" => args.silent = true,
+            "import-from-file" => {
                 let importfile = parser.value()?;
                 args.readinfo_import_file = resolve_path(&importfile);
             }
-            Short('E') | Long("export-to-file") => {
+            "export-to-file" => {
                 let exportfile = parser.value()?;
                 args.readinfo_export_file = resolve_path(&exportfile);
             }
-            Short('d') | Long("log-file") => {
+            "read-state-format" => {
+                let format_str = parser.value()?;
+                let Ok(format) = ReadStateFormat::try_from(format_str.as_ref()) else {
+                    return Err(CliParseError::InvalidOutputFormat(fmt!(
+                        &gettext("%s: %s: invalid output format"),
+                        &args.program_name,
+                        format_str.to_string_lossy().to_string()
+                    )));
+                };
+                args.readinfo_export_format = format;
+            }
+            "log-file" => {
                 let log_file = parser.value()?;
                 args.log_file = resolve_path(&log_file);
             }
-            Short('l') | Long("log-level") => {
+            "log-level" => {
                 let log_level_str = parser.value()?;
                 match Level::try_from(log_level_str.as_ref()) {
                     Ok(level) => {
@@ -240,10 +929,41 @@ pub fn parse_cliargs(opts: Vec<OsString>, args: &mut CliArgsParser) -> Result<()
                     }
                 }
             }
-            _ => return Err(CliParseError::from(arg.unexpected())),
+            "format" => {
+                let format_str = parser.value()?;
+                let Ok(format) = OutputFormat::try_from(format_str.as_ref()) else {
+                    return Err(CliParseError::InvalidOutputFormat(fmt!(
+                        &gettext("%s: %s: invalid output format"),
+                        &args.program_name,
+                        format_str.to_string_lossy().to_string()
+                    )));
+                };
+                if let Some(subcommand) = &mut args.subcommand {
+                    *subcommand.format_mut() = format;
+                }
+            }
+            "feed" => {
+                let url = parser.value()?.to_string_lossy().into_owned();
+                if let Some(Subcommand::ListItems { feed, .. }) = &mut args.subcommand {
+                    *feed = Some(url);
+                }
+            }
+            "unread" => {
+                if let Some(Subcommand::ListItems { unread_only, .. }) = &mut args.subcommand {
+                    *unread_only = true;
+                }
+            }
+            // Every option in `OPTIONS` is handled above; tests make sure of that
+            _ => return Err(CliParseError::PrintAndExit),
         }
     }
 
+    if let Some(Subcommand::MarkItemsRead { guids, .. }) = &args.subcommand
+        && guids.is_empty()
+    {
+        return Err(CliParseError::PrintAndExit);
+    }
+
     if args.do_export && args.importfile.is_some() {
         return Err(CliParseError::PrintAndExit);
     }
@@ -252,6 +972,15 @@ pub fn parse_cliargs(opts: Vec<OsString>, args: &mut CliArgsParser) -> Result<()
         return Err(CliParseError::PrintAndExit);
     }
 
+    if args.dry_run
+        && !(args.do_vacuum
+            || args.do_cleanup
+            || args.do_migrate
+            || !args.cmds_to_execute.is_empty())
+    {
+        return Err(CliParseError::PrintAndExit);
+    }
+
     Ok(())
 }
 
@@ -271,7 +1000,14 @@ impl CliArgsParser {
             Ok(()) => args,
             Err(err) => {
                 match err {
-                    CliParseError::InvalidLogLevel(display_msg) => {
+                    CliParseError::InvalidLogLevel(display_msg)
+                    | CliParseError::InvalidKeymapFormat(display_msg)
+                    | CliParseError::InvalidOutputFormat(display_msg)
+                    | CliParseError::InvalidShell(display_msg)
+                    | CliParseError::InvalidProfileName(display_msg)
+                    | CliParseError::InvalidConfigOverride(display_msg)
+                    | CliParseError::InvalidRemoteCommand(display_msg)
+                    | CliParseError::InvalidWaitLockTimeout(display_msg) => {
                         args.display_msg = display_msg;
                     }
                     CliParseError::LexoptError(_) => {
@@ -287,6 +1023,20 @@ impl CliArgsParser {
         }
     }
 
+    /// Checks if the requested actions only read the cache, so they can run while another instance
+    /// holds the lock. That's the case for the UI, exports and listings, but not for anything
+    /// that modifies the cache, or for dry runs (which modify the cache, but don't commit).
+    pub fn can_run_read_only(&self) -> bool {
+        !(self.do_vacuum
+            || self.do_cleanup
+            || self.do_migrate
+            || self.dry_run
+            || self.importfile.is_some()
+            || self.readinfo_import_file.is_some()
+            || self.cmds_to_execute.iter().any(|cmd| cmd != "print-unread")
+            || matches!(self.subcommand, Some(Subcommand::MarkItemsRead { .. })))
+    }
+
     pub fn using_nonstandard_configs(&self) -> bool {
         self.url_file.is_some()
             || self.cache_file.is_some()
@@ -361,6 +1111,295 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn t_sets_lint_keymap_and_silent_if_lint_keymap_is_provided() {
+        let args = CliArgsParser::new(vec!["newsboat".into(), "--lint-keymap".into()]);
+
+        assert!(args.lint_keymap);
+        assert!(args.silent);
+        assert_eq!(args.return_code, None);
+    }
+
+    #[test]
+    fn t_sets_export_keymap_and_silent_if_export_keymap_is_provided() {
+        let check = |opts, expected_format| {
+            let args = CliArgsParser::new(opts);
+
+            assert_eq!(args.export_keymap, Some(expected_format));
+            assert!(args.silent);
+            assert_eq!(args.return_code, None);
+        };
+
+        check(
+            vec!["newsboat".into(), "--export-keymap=markdown".into()],
+            ReferenceFormat::Markdown,
+        );
+        check(
+            vec!["newsboat".into(), "--export-keymap".into(), "roff".into()],
+            ReferenceFormat::Roff,
+        );
+        check(
+            vec!["newsboat".into(), "--export-keymap=json".into()],
+            ReferenceFormat::Json,
+        );
+    }
+
+    #[test]
+    fn t_sets_display_msg_and_asks_to_exit_with_failure_if_export_keymap_format_is_unknown() {
+        let check = |opts| {
+            let args = CliArgsParser::new(opts);
+
+            assert!(!args.display_msg.is_empty());
+            assert_eq!(args.export_keymap, None);
+            assert_eq!(args.return_code, Some(EXIT_FAILURE));
+        };
+
+        check(vec!["newsboat".into(), "--export-keymap=html".into()]);
+        check(vec!["newsboat".into(), "--export-keymap=".into()]);
+    }
+
+    fn opts(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn t_parses_feeds_list_subcommand() {
+        let args = CliArgsParser::new(opts(&["newsboat", "feeds", "list"]));
+        assert_eq!(
+            args.subcommand,
+            Some(Subcommand::ListFeeds {
+                format: OutputFormat::Tsv
+            })
+        );
+        assert!(args.silent);
+        assert_eq!(args.return_code, None);
+
+        let args = CliArgsParser::new(opts(&["newsboat", "feeds", "list", "--format", "json"]));
+        assert_eq!(
+            args.subcommand,
+            Some(Subcommand::ListFeeds {
+                format: OutputFormat::Json
+            })
+        );
+    }
+
+    #[test]
+    fn t_parses_items_list_subcommand() {
+        let args = CliArgsParser::new(opts(&["newsboat", "items", "list"]));
+        assert_eq!(
+            args.subcommand,
+            Some(Subcommand::ListItems {
+                feed: None,
+                unread_only: false,
+                format: OutputFormat::Tsv,
+            })
+        );
+
+        let args = CliArgsParser::new(opts(&[
+            "newsboat",
+            "items",
+            "list",
+            "--unread",
+            "--feed=https://example.com/feed.xml",
+            "--format=json",
+        ]));
+        assert_eq!(
+            args.subcommand,
+            Some(Subcommand::ListItems {
+                feed: Some("https://example.com/feed.xml".to_owned()),
+                unread_only: true,
+                format: OutputFormat::Json,
+            })
+        );
+        assert_eq!(args.return_code, None);
+    }
+
+    #[test]
+    fn t_parses_items_mark_read_subcommand() {
+        let args = CliArgsParser::new(opts(&[
+            "newsboat",
+            "items",
+            "mark-read",
+            "guid-1",
+            "--format=json",
+            "guid 2",
+        ]));
+        assert_eq!(
+            args.subcommand,
+            Some(Subcommand::MarkItemsRead {
+                guids: vec!["guid-1".to_owned(), "guid 2".to_owned()],
+                format: OutputFormat::Json,
+            })
+        );
+        assert_eq!(args.return_code, None);
+    }
+
+    #[test]
+    fn t_global_options_can_be_mixed_with_subcommands() {
+        let args = CliArgsParser::new(opts(&[
+            "newsboat", "-u", "urls", "items", "list", "-c", "cache.db",
+        ]));
+        assert!(matches!(
+            args.subcommand,
+            Some(Subcommand::ListItems { .. })
+        ));
+        assert!(args.url_file.is_some());
+        assert!(args.cache_file.is_some());
+        assert_eq!(args.return_code, None);
+    }
+
+    #[test]
+    fn t_asks_to_print_usage_if_subcommand_is_invalid() {
+        let check = |args: &[&str]| {
+            let args = CliArgsParser::new(opts(args));
+
+            assert!(args.should_print_usage, "{:?}", args.subcommand);
+            assert_eq!(args.return_code, Some(EXIT_FAILURE));
+        };
+
+        check(&["newsboat", "foo"]);
+        check(&["newsboat", "feeds"]);
+        check(&["newsboat", "feeds", "remove"]);
+        check(&["newsboat", "items", "list", "extra"]);
+        check(&["newsboat", "items", "mark-read"]);
+        check(&["newsboat", "feeds", "list", "--unread"]);
+        check(&["newsboat", "feeds", "list", "--feed", "url"]);
+        check(&["newsboat", "items", "mark-read", "guid", "--unread"]);
+        check(&["newsboat", "--format", "json"]);
+    }
+
+    #[test]
+    fn t_sets_display_msg_and_asks_to_exit_with_failure_if_output_format_is_unknown() {
+        let args = CliArgsParser::new(opts(&["newsboat", "feeds", "list", "--format=xml"]));
+
+        assert!(!args.display_msg.is_empty());
+        assert_eq!(args.return_code, Some(EXIT_FAILURE));
+    }
+
+    #[test]
+    fn t_sets_dry_run_for_execute_vacuum_cleanup_and_migrate() {
+        let check = |opts: &[&str]| {
+            let args = CliArgsParser::new(self::opts(opts));
+
+            assert!(args.dry_run);
+            assert_eq!(args.return_code, None);
+        };
+
+        check(&["newsboat", "--dry-run", "-x", "reload"]);
+        check(&["newsboat", "-X", "--dry-run"]);
+        check(&["newsboat", "--cleanup", "--dry-run"]);
+        check(&["newsboat", "--migrate", "--dry-run"]);
+    }
+
+    #[test]
+    fn t_sets_do_migrate_and_silent_if_migrate_is_provided() {
+        let args = CliArgsParser::new(opts(&["newsboat", "--migrate"]));
+
+        assert!(args.do_migrate);
+        assert!(args.silent);
+        assert!(!args.dry_run);
+        assert_eq!(args.return_code, None);
+    }
+
+    #[test]
+    fn t_can_run_read_only_unless_cache_would_be_modified() {
+        let check = |opts: &[&str], expected: bool| {
+            let args = CliArgsParser::new(self::opts(opts));
+            assert_eq!(args.can_run_read_only(), expected, "{opts:?}");
+        };
+
+        check(&["newsboat"], true);
+        check(&["newsboat", "-e"], true);
+        check(&["newsboat", "-E", "read.txt"], true);
+        check(&["newsboat", "-x", "print-unread"], true);
+        check(&["newsboat", "feeds", "list"], true);
+        check(&["newsboat", "items", "list", "--unread"], true);
+
+        check(&["newsboat", "-x", "reload"], false);
+        check(&["newsboat", "-x", "print-unread", "reload"], false);
+        check(&["newsboat", "-x", "reload", "--dry-run"], false);
+        check(&["newsboat", "-X"], false);
+        check(&["newsboat", "--cleanup"], false);
+        check(&["newsboat", "--migrate"], false);
+        check(&["newsboat", "-i", "feeds.opml"], false);
+        check(&["newsboat", "-I", "read.txt"], false);
+        check(&["newsboat", "items", "mark-read", "guid"], false);
+    }
+
+    #[test]
+    fn t_sets_force_unlock_if_force_unlock_is_provided() {
+        let args = CliArgsParser::new(opts(&["newsboat", "--force-unlock"]));
+
+        assert!(args.force_unlock);
+        assert_eq!(args.return_code, None);
+
+        let args = CliArgsParser::new(opts(&["newsboat"]));
+        assert!(!args.force_unlock);
+    }
+
+    #[test]
+    fn t_sets_wait_lock_if_wait_lock_is_provided() {
+        let args = CliArgsParser::new(opts(&["newsboat", "--wait-lock", "300", "-x", "reload"]));
+
+        assert_eq!(args.wait_lock, Some(300));
+        assert_eq!(args.return_code, None);
+
+        let args = CliArgsParser::new(opts(&["newsboat"]));
+        assert_eq!(args.wait_lock, None);
+    }
+
+    #[test]
+    fn t_fails_if_wait_lock_is_not_a_number_of_seconds() {
+        for value in ["-5", "1.5", "forever", ""] {
+            let args = CliArgsParser::new(opts(&["newsboat", "--wait-lock", value]));
+
+            assert_eq!(args.wait_lock, None, "{value}");
+            assert!(args.display_msg.contains("seconds"), "{value}");
+            assert_eq!(args.return_code, Some(EXIT_FAILURE), "{value}");
+        }
+    }
+
+    #[test]
+    fn t_sets_remote_command_if_remote_is_provided() {
+        let args = CliArgsParser::new(opts(&[
+            "newsboat",
+            "--remote",
+            "reload https://example.com/feed.xml",
+        ]));
+
+        assert_eq!(
+            args.remote_command,
+            Some(controlsocket::Command::Reload(Some(
+                "https://example.com/feed.xml".to_owned()
+            )))
+        );
+        assert!(args.silent);
+        assert_eq!(args.return_code, None);
+    }
+
+    #[test]
+    fn t_fails_if_remote_command_is_unknown() {
+        let args = CliArgsParser::new(opts(&["newsboat", "--remote=shutdown"]));
+
+        assert_eq!(args.remote_command, None);
+        assert!(args.display_msg.contains("shutdown"));
+        assert_eq!(args.return_code, Some(EXIT_FAILURE));
+    }
+
+    #[test]
+    fn t_asks_to_print_usage_if_dry_run_is_given_on_its_own() {
+        let check = |opts: &[&str]| {
+            let args = CliArgsParser::new(self::opts(opts));
+
+            assert!(args.should_print_usage);
+            assert_eq!(args.return_code, Some(EXIT_FAILURE));
+        };
+
+        check(&["newsboat", "--dry-run"]);
+        check(&["newsboat", "--dry-run", "-r"]);
+        check(&["newsboat", "--dry-run", "-E", "file"]);
+    }
+
     #[test]
     fn t_sets_refresh_on_start_if_dash_r_is_provided() {
         let check = |opts| {
@@ -763,6 +1802,25 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn t_exports_read_state_as_guids_unless_json_is_requested() {
+        let args = CliArgsParser::new(opts(&["newsboat", "-E", "state"]));
+        assert_eq!(args.readinfo_export_format, ReadStateFormat::Guids);
+
+        let args = CliArgsParser::new(opts(&[
+            "newsboat",
+            "-E",
+            "state",
+            "--read-state-format=json",
+        ]));
+        assert_eq!(args.readinfo_export_format, ReadStateFormat::Json);
+        assert_eq!(args.return_code, None);
+
+        let args = CliArgsParser::new(opts(&["newsboat", "--read-state-format", "xml"]));
+        assert!(!args.display_msg.is_empty());
+        assert_eq!(args.return_code, Some(EXIT_FAILURE));
+    }
+
     #[test]
     fn t_asks_to_print_usage_and_exit_with_failure_if_both_capital_e_and_capital_i_are_provided() {
         let importf: OsString = "import.opml".into();
@@ -880,4 +1938,251 @@ mod tests {
             "/usr/local/bin/app-with-a-path".into(),
         );
     }
+
+    /// Command-line arguments that use `option` under `name` (e.g. `-u` or `--url-file`), with
+    /// `value` as its argument.
+    fn invocation(option: &OptionSpec, name: String, value: &str) -> Vec<OsString> {
+        let mut result = opts(&["newsboat"]);
+        if let Some(subcommand) = option.subcommands.first() {
+            result.extend(subcommand.split(' ').map(OsString::from));
+        }
+        result.push(name.into());
+        if option.value != OptionValue::None {
+            result.push(value.into());
+        }
+        if option.long == "dry-run" {
+            // `--dry-run` is only accepted together with one of the actions it applies to.
+            result.push("--vacuum".into());
+        }
+        result
+    }
+
+    fn example_value(option: &OptionSpec) -> &'static str {
+        match option.value {
+            OptionValue::None => "",
+            OptionValue::Path => "/tmp/newsboat-test-file",
+            OptionValue::Choice(choices) | OptionValue::Choices(choices) => choices[0],
+            OptionValue::Text if option.long == "option" => "browser=lynx",
+            OptionValue::Text if option.long == "remote" => "reload",
+            OptionValue::Text if option.long == "wait-lock" => "60",
+            OptionValue::Text => "example",
+        }
+    }
+
+    #[test]
+    fn t_accepts_every_option_from_the_spec() {
+        for option in OPTIONS {
+            let names = option
+                .short
+                .iter()
+                .map(|c| format!("-{c}"))
+                .chain(std::iter::once(format!("--{}", option.long)))
+                .chain(option.aliases.iter().map(|alias| format!("--{alias}")));
+            for name in names {
+                let args =
+                    CliArgsParser::new(invocation(option, name.clone(), example_value(option)));
+                assert_eq!(args.display_msg, "", "{name} was rejected");
+                assert_ne!(args.return_code, Some(EXIT_FAILURE), "{name} was rejected");
+            }
+        }
+    }
+
+    #[test]
+    fn t_accepts_every_choice_from_the_spec() {
+        for option in OPTIONS {
+            let (OptionValue::Choice(choices) | OptionValue::Choices(choices)) = option.value
+            else {
+                continue;
+            };
+            for choice in choices {
+                let args =
+                    CliArgsParser::new(invocation(option, format!("--{}", option.long), choice));
+                assert_eq!(
+                    args.display_msg, "",
+                    "--{} {choice} was rejected",
+                    option.long
+                );
+                assert_eq!(
+                    args.return_code, None,
+                    "--{} {choice} was rejected",
+                    option.long
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn t_every_option_that_takes_text_has_a_placeholder() {
+        for option in OPTIONS {
+            if option.value == OptionValue::Text {
+                assert_ne!(
+                    option.placeholder, "",
+                    "--{} has no placeholder",
+                    option.long
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn t_options_usage_lists_every_global_option_with_its_value() {
+        let usage = options_usage();
+        for option in OPTIONS {
+            let mut name = format!("--{}", option.long);
+            let placeholder = option.value_placeholder();
+            if !placeholder.is_empty() {
+                name = format!("{name}={placeholder}");
+            }
+            assert_eq!(
+                usage.contains(&name),
+                option.subcommands.is_empty(),
+                "{name}"
+            );
+        }
+
+        assert!(usage.contains("        -u, --url-file=<file>  "));
+        assert!(usage.contains("        -v, -V, --version  "));
+        assert!(usage.contains("            --export-to-opml2  "));
+        assert!(usage.contains("--export-keymap=<format>  "));
+        assert!(usage.contains("-x, --execute=<command>...  "));
+    }
+
+    #[test]
+    fn t_options_usage_aligns_descriptions() {
+        let usage = options_usage();
+        let description_columns: Vec<usize> = OPTIONS
+            .iter()
+            .filter(|option| option.subcommands.is_empty())
+            .zip(usage.lines())
+            .map(|(option, line)| line.find(option.description).unwrap())
+            .collect();
+        assert!(
+            description_columns
+                .windows(2)
+                .all(|pair| pair[0] == pair[1])
+        );
+    }
+
+    #[test]
+    fn t_accepts_every_subcommand_from_the_spec() {
+        for subcommand in SUBCOMMANDS {
+            let mut arguments = opts(&["newsboat"]);
+            arguments.extend(subcommand.split(' ').map(OsString::from));
+            arguments.push("guid".into());
+            let args = CliArgsParser::new(arguments);
+            assert_eq!(
+                args.subcommand.as_ref().map(Subcommand::name),
+                Some(*subcommand)
+            );
+        }
+    }
+
+    #[test]
+    fn t_asks_to_print_usage_if_option_is_unknown_or_outside_of_its_subcommand() {
+        let check = |arguments: &[&str]| {
+            let args = CliArgsParser::new(opts(arguments));
+            assert_eq!(args.return_code, Some(EXIT_FAILURE));
+            assert!(args.should_print_usage);
+        };
+
+        check(&["newsboat", "--frobnicate"]);
+        check(&["newsboat", "--unread"]);
+        check(&["newsboat", "--format=json"]);
+        check(&["newsboat", "feeds", "list", "--unread"]);
+        check(&["newsboat", "items", "mark-read", "--feed", "url", "guid"]);
+    }
+
+    #[test]
+    fn t_sets_completions_if_completions_is_provided() {
+        let check = |arguments: &[&str], expected| {
+            let args = CliArgsParser::new(opts(arguments));
+            assert_eq!(args.completions, Some(expected));
+            assert_eq!(args.return_code, None);
+        };
+
+        check(&["newsboat", "--completions", "bash"], Shell::Bash);
+        check(&["newsboat", "--completions=zsh"], Shell::Zsh);
+        check(&["newsboat", "--completions=fish"], Shell::Fish);
+    }
+
+    #[test]
+    fn t_sets_display_msg_and_asks_to_exit_with_failure_if_shell_is_unsupported() {
+        let args = CliArgsParser::new(opts(&["newsboat", "--completions=tcsh"]));
+        assert_eq!(args.completions, None);
+        assert!(!args.display_msg.is_empty());
+        assert_eq!(args.return_code, Some(EXIT_FAILURE));
+    }
+
+    #[test]
+    fn t_sets_profile_if_profile_is_provided() {
+        let args = CliArgsParser::new(opts(&["newsboat", "--profile", "work"]));
+        assert_eq!(args.profile, Some("work".to_owned()));
+        assert!(!args.create_profile);
+        assert_eq!(args.return_code, None);
+        // A profile on its own is a standard location
+        assert!(!args.using_nonstandard_configs());
+    }
+
+    #[test]
+    fn t_sets_profile_and_create_profile_if_create_profile_is_provided() {
+        let args = CliArgsParser::new(opts(&["newsboat", "--create-profile=personal"]));
+        assert_eq!(args.profile, Some("personal".to_owned()));
+        assert!(args.create_profile);
+        assert_eq!(args.return_code, None);
+    }
+
+    #[test]
+    fn t_sets_list_profiles_if_list_profiles_is_provided() {
+        let args = CliArgsParser::new(opts(&["newsboat", "--list-profiles"]));
+        assert!(args.list_profiles);
+        assert_eq!(args.return_code, None);
+    }
+
+    #[test]
+    fn t_sets_display_msg_and_asks_to_exit_with_failure_if_profile_name_is_invalid() {
+        let check = |arguments: &[&str]| {
+            let args = CliArgsParser::new(opts(arguments));
+            assert_eq!(args.profile, None);
+            assert!(!args.display_msg.is_empty());
+            assert_eq!(args.return_code, Some(EXIT_FAILURE));
+        };
+
+        check(&["newsboat", "--profile="]);
+        check(&["newsboat", "--profile", "../work"]);
+        check(&["newsboat", "--profile", ".hidden"]);
+        check(&["newsboat", "--create-profile", "a/b"]);
+    }
+
+    #[test]
+    fn t_collects_config_overrides_in_order_if_dash_o_is_provided() {
+        let args = CliArgsParser::new(opts(&[
+            "newsboat",
+            "-o",
+            "browser=lynx",
+            "--option=show-read-feeds=no",
+            "-obrowser=w3m %u",
+        ]));
+        assert_eq!(
+            args.config_overrides,
+            vec![
+                ("browser".to_owned(), "lynx".to_owned()),
+                ("show-read-feeds".to_owned(), "no".to_owned()),
+                ("browser".to_owned(), "w3m %u".to_owned()),
+            ]
+        );
+        assert_eq!(args.return_code, None);
+    }
+
+    #[test]
+    fn t_sets_display_msg_and_asks_to_exit_with_failure_if_dash_o_lacks_a_value() {
+        let check = |arguments: &[&str]| {
+            let args = CliArgsParser::new(opts(arguments));
+            assert!(args.config_overrides.is_empty());
+            assert!(!args.display_msg.is_empty());
+            assert_eq!(args.return_code, Some(EXIT_FAILURE));
+        };
+
+        check(&["newsboat", "-o", "browser"]);
+        check(&["newsboat", "--option==lynx"]);
+    }
 }
@@ -0,0 +1,449 @@
+//! Generates shell completion scripts from the description of command-line options in
+//! `cliargsparser::OPTIONS`.
+
+use crate::cliargsparser::{OPTIONS, OptionSpec, OptionValue, SUBCOMMANDS};
+use std::convert::TryFrom;
+use std::ffi::OsStr;
+use std::fmt::Write;
+
+const PROGRAM_NAME: &str = "newsboat";
+
+/// Shells for which `generate()` can produce a completion script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl TryFrom<&OsStr> for Shell {
+    type Error = ();
+
+    fn try_from(value: &OsStr) -> Result<Self, Self::Error> {
+        match value.to_str() {
+            Some("bash") => Ok(Shell::Bash),
+            Some("zsh") => Ok(Shell::Zsh),
+            Some("fish") => Ok(Shell::Fish),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Returns a completion script for `shell`, to be sourced by the user or installed into the
+/// shell's completions directory.
+pub fn generate(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => generate_bash(),
+        Shell::Zsh => generate_zsh(),
+        Shell::Fish => generate_fish(),
+    }
+}
+
+/// All the names an option is advertised under, e.g. `["-u", "--url-file"]`.
+fn option_names(option: &OptionSpec) -> Vec<String> {
+    option
+        .short
+        .iter()
+        .map(|c| format!("-{c}"))
+        .chain(std::iter::once(format!("--{}", option.long)))
+        .collect()
+}
+
+/// First words of subcommands, e.g. `["feeds", "items"]`, without duplicates.
+fn subcommand_first_words() -> Vec<&'static str> {
+    let mut words: Vec<&'static str> = Vec::new();
+    for subcommand in SUBCOMMANDS {
+        let first = subcommand.split(' ').next().unwrap_or_default();
+        if !words.contains(&first) {
+            words.push(first);
+        }
+    }
+    words
+}
+
+/// Second words of the subcommands that start with `first`.
+fn subcommand_second_words(first: &str) -> Vec<&'static str> {
+    SUBCOMMANDS
+        .iter()
+        .filter_map(|subcommand| subcommand.split_once(' '))
+        .filter(|(word, _)| *word == first)
+        .map(|(_, second)| second)
+        .collect()
+}
+
+fn generate_bash() -> String {
+    let global_options: Vec<String> = OPTIONS
+        .iter()
+        .filter(|option| option.subcommands.is_empty())
+        .flat_map(option_names)
+        .collect();
+
+    let mut script = String::new();
+    let _ = writeln!(script, "# bash completion for {PROGRAM_NAME}");
+    let _ = writeln!(script, "_{PROGRAM_NAME}()");
+    script.push_str(concat!(
+        "{\n",
+        "\tlocal cur prev\n",
+        "\tcur=\"${COMP_WORDS[COMP_CWORD]}\"\n",
+        "\tprev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n",
+        "\t# `--option=value` is split into three words: `--option`, `=` and `value`\n",
+        "\tif [[ \"$cur\" == \"=\" ]]; then\n",
+        "\t\tcur=\"\"\n",
+        "\telif [[ \"$prev\" == \"=\" && $COMP_CWORD -gt 1 ]]; then\n",
+        "\t\tprev=\"${COMP_WORDS[COMP_CWORD-2]}\"\n",
+        "\tfi\n",
+        "\n",
+        "\tcase \"$prev\" in\n",
+    ));
+    for option in OPTIONS {
+        let action = match option.value {
+            OptionValue::None | OptionValue::Choices(_) => continue,
+            OptionValue::Path => "COMPREPLY=( $(compgen -f -- \"$cur\") )".to_owned(),
+            OptionValue::Choice(choices) => {
+                format!(
+                    "COMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )",
+                    choices.join(" ")
+                )
+            }
+            OptionValue::Text => "COMPREPLY=()".to_owned(),
+        };
+        let _ = writeln!(script, "\t\t{})", option_names(option).join("|"));
+        let _ = writeln!(script, "\t\t\t{action}");
+        script.push_str("\t\t\treturn\n\t\t\t;;\n");
+    }
+    script.push_str("\tesac\n\n");
+
+    for option in OPTIONS {
+        let OptionValue::Choices(choices) = option.value else {
+            continue;
+        };
+        let _ = writeln!(
+            script,
+            "\t# {} takes any number of arguments, up to the next option",
+            option_names(option).join("/")
+        );
+        script.push_str(concat!(
+            "\tif [[ \"$cur\" != -* ]]; then\n",
+            "\t\tlocal i\n",
+            "\t\tfor (( i = COMP_CWORD - 1; i > 0; i-- )); do\n",
+            "\t\t\tcase \"${COMP_WORDS[i]}\" in\n",
+        ));
+        let _ = writeln!(
+            script,
+            "\t\t\t\t{})\n\t\t\t\t\tCOMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )\n\t\t\t\t\treturn\n\t\t\t\t\t;;",
+            option_names(option).join("|"),
+            choices.join(" ")
+        );
+        script.push_str(concat!(
+            "\t\t\t\t-*)\n",
+            "\t\t\t\t\tbreak\n",
+            "\t\t\t\t\t;;\n",
+            "\t\t\tesac\n",
+            "\t\tdone\n",
+            "\tfi\n",
+            "\n",
+        ));
+    }
+
+    script.push_str(concat!(
+        "\tlocal subcommand=\"\" i\n",
+        "\tfor (( i = 1; i < COMP_CWORD - 1; i++ )); do\n",
+        "\t\tcase \"${COMP_WORDS[i]}\" in\n",
+    ));
+    let _ = writeln!(
+        script,
+        "\t\t\t{})\n\t\t\t\tsubcommand=\"${{COMP_WORDS[i]}} ${{COMP_WORDS[i+1]}}\"\n\t\t\t\tbreak\n\t\t\t\t;;",
+        subcommand_first_words().join("|")
+    );
+    script.push_str("\t\tesac\n\tdone\n\n");
+
+    let _ = writeln!(script, "\tlocal options=\"{}\"", global_options.join(" "));
+    script.push_str("\tcase \"$subcommand\" in\n");
+    for subcommand in SUBCOMMANDS {
+        let options: Vec<String> = OPTIONS
+            .iter()
+            .filter(|option| option.subcommands.contains(subcommand))
+            .flat_map(option_names)
+            .collect();
+        if options.is_empty() {
+            continue;
+        }
+        let _ = writeln!(
+            script,
+            "\t\t\"{subcommand}\")\n\t\t\toptions+=\" {}\"\n\t\t\t;;",
+            options.join(" ")
+        );
+    }
+    script.push_str(concat!(
+        "\tesac\n",
+        "\n",
+        "\tif [[ \"$cur\" == -* ]]; then\n",
+        "\t\tCOMPREPLY=( $(compgen -W \"$options\" -- \"$cur\") )\n",
+        "\telif [[ -z \"$subcommand\" ]]; then\n",
+        "\t\tcase \"$prev\" in\n",
+    ));
+    for first in subcommand_first_words() {
+        let _ = writeln!(
+            script,
+            "\t\t\t{first})\n\t\t\t\tCOMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )\n\t\t\t\t;;",
+            subcommand_second_words(first).join(" ")
+        );
+    }
+    let _ = writeln!(
+        script,
+        "\t\t\t*)\n\t\t\t\tCOMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )\n\t\t\t\t;;",
+        subcommand_first_words().join(" ")
+    );
+    script.push_str("\t\tesac\n\tfi\n}\n");
+    let _ = writeln!(
+        script,
+        "complete -o filenames -F _{PROGRAM_NAME} {PROGRAM_NAME}"
+    );
+    script
+}
+
+/// Quotes `text` so that it can be put between single quotes in a shell script.
+fn single_quote_escape(text: &str) -> String {
+    text.replace('\'', r"'\''")
+}
+
+/// Escapes characters that have special meaning in `_arguments` descriptions.
+fn zsh_description_escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '[' | ']' | ':' | '\\') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+fn generate_zsh() -> String {
+    let mut script = String::new();
+    let _ = writeln!(script, "#compdef {PROGRAM_NAME}\n");
+    let _ = writeln!(script, "_{PROGRAM_NAME}() {{");
+    script.push_str("\tlocal curcontext=\"$curcontext\" state line\n");
+    script.push_str("\t_arguments -s -C \\\n");
+    for option in OPTIONS {
+        let names = option_names(option);
+        // Options that can be given more than once can't be excluded from being repeated
+        let exclusions = if option.short.len() > 1 {
+            "*".to_owned()
+        } else {
+            format!("({})", names.join(" "))
+        };
+        let names: Vec<String> = option
+            .short
+            .iter()
+            .map(|c| match option.value {
+                OptionValue::None | OptionValue::Choices(_) => format!("-{c}"),
+                _ => format!("-{c}+"),
+            })
+            .chain(std::iter::once(match option.value {
+                OptionValue::None | OptionValue::Choices(_) => format!("--{}", option.long),
+                _ => format!("--{}=", option.long),
+            }))
+            .collect();
+        let names = if names.len() > 1 {
+            format!("{{{}}}", names.join(","))
+        } else {
+            names.join("")
+        };
+        let action = match option.value {
+            OptionValue::None => String::new(),
+            OptionValue::Path => ":file:_files".to_owned(),
+            OptionValue::Choice(choices) => format!(":value:({})", choices.join(" ")),
+            OptionValue::Choices(choices) => format!(":*-*:command:({})", choices.join(" ")),
+            OptionValue::Text => ":value: ".to_owned(),
+        };
+        let _ = writeln!(
+            script,
+            "\t\t'{}'{}'[{}]{}' \\",
+            single_quote_escape(&exclusions),
+            names,
+            single_quote_escape(&zsh_description_escape(option.description)),
+            single_quote_escape(&action)
+        );
+    }
+    let _ = writeln!(
+        script,
+        "\t\t'1:subcommand:({})' \\",
+        subcommand_first_words().join(" ")
+    );
+    script.push_str(concat!(
+        "\t\t'2:action:->action'\n",
+        "\n",
+        "\tcase \"$state\" in\n",
+        "\t\taction)\n",
+        "\t\t\tcase \"$line[1]\" in\n",
+    ));
+    for first in subcommand_first_words() {
+        let _ = writeln!(
+            script,
+            "\t\t\t\t{first})\n\t\t\t\t\t_values action {}\n\t\t\t\t\t;;",
+            subcommand_second_words(first).join(" ")
+        );
+    }
+    script.push_str("\t\t\tesac\n\t\t\t;;\n\tesac\n}\n\n");
+    let _ = writeln!(script, "_{PROGRAM_NAME} \"$@\"");
+    script
+}
+
+/// Quotes `text` as a single-quoted fish string.
+fn fish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+/// Fish condition that is true if `subcommand` (e.g. `items list`) was given.
+fn fish_subcommand_condition(subcommand: &str) -> String {
+    subcommand
+        .split(' ')
+        .map(|word| format!("__fish_seen_subcommand_from {word}"))
+        .collect::<Vec<_>>()
+        .join("; and ")
+}
+
+fn generate_fish() -> String {
+    let mut script = String::new();
+    let _ = writeln!(script, "# fish completion for {PROGRAM_NAME}");
+    let _ = writeln!(script, "complete -c {PROGRAM_NAME} -f");
+    for option in OPTIONS {
+        let mut arguments = String::new();
+        if !option.subcommands.is_empty() {
+            let conditions: Vec<String> = option
+                .subcommands
+                .iter()
+                .map(|subcommand| format!("begin; {}; end", fish_subcommand_condition(subcommand)))
+                .collect();
+            let _ = write!(arguments, " -n {}", fish_quote(&conditions.join("; or ")));
+        }
+        match option.value {
+            OptionValue::None => {}
+            OptionValue::Path => arguments.push_str(" -r -F"),
+            OptionValue::Choice(choices) | OptionValue::Choices(choices) => {
+                let _ = write!(arguments, " -x -a {}", fish_quote(&choices.join(" ")));
+            }
+            OptionValue::Text => arguments.push_str(" -x"),
+        }
+        let _ = write!(arguments, " -d {}", fish_quote(option.description));
+
+        // fish only allows one short name per line
+        let mut shorts = option.short.iter();
+        let first_short = shorts
+            .next()
+            .map(|c| format!(" -s {c}"))
+            .unwrap_or_default();
+        let _ = writeln!(
+            script,
+            "complete -c {PROGRAM_NAME}{first_short} -l {}{arguments}",
+            option.long
+        );
+        for c in shorts {
+            let _ = writeln!(script, "complete -c {PROGRAM_NAME} -s {c}{arguments}");
+        }
+    }
+
+    let _ = writeln!(
+        script,
+        "complete -c {PROGRAM_NAME} -n __fish_use_subcommand -a {}",
+        fish_quote(&subcommand_first_words().join(" "))
+    );
+    for first in subcommand_first_words() {
+        let seconds = subcommand_second_words(first);
+        let _ = writeln!(
+            script,
+            "complete -c {PROGRAM_NAME} -n {} -a {}",
+            fish_quote(&format!(
+                "__fish_seen_subcommand_from {first}; and not __fish_seen_subcommand_from {}",
+                seconds.join(" ")
+            )),
+            fish_quote(&seconds.join(" "))
+        );
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_parses_shell_names() {
+        assert_eq!(Shell::try_from(OsStr::new("bash")), Ok(Shell::Bash));
+        assert_eq!(Shell::try_from(OsStr::new("zsh")), Ok(Shell::Zsh));
+        assert_eq!(Shell::try_from(OsStr::new("fish")), Ok(Shell::Fish));
+        assert_eq!(Shell::try_from(OsStr::new("Bash")), Err(()));
+        assert_eq!(Shell::try_from(OsStr::new("tcsh")), Err(()));
+    }
+
+    #[test]
+    fn t_bash_completes_all_advertised_options() {
+        let script = generate(Shell::Bash);
+        assert!(script.contains("complete -o filenames -F _newsboat newsboat"));
+        for option in OPTIONS {
+            for name in option_names(option) {
+                assert!(script.contains(&name), "{name} is missing");
+            }
+            for alias in option.aliases {
+                assert!(!script.contains(&format!(" --{alias} ")));
+            }
+        }
+    }
+
+    #[test]
+    fn t_bash_completes_paths_and_choices() {
+        let script = generate(Shell::Bash);
+        assert!(script.contains(
+            "\t\t--cmdline-history-file)\n\t\t\tCOMPREPLY=( $(compgen -f -- \"$cur\") )\n"
+        ));
+        assert!(script.contains(
+            "\t\t-l|--log-level)\n\t\t\tCOMPREPLY=( $(compgen -W \"1 2 3 4 5 6\" -- \"$cur\") )\n"
+        ));
+        assert!(script.contains(
+            "\t\t\t\t-x|--execute)\n\t\t\t\t\tCOMPREPLY=( $(compgen -W \"reload print-unread\" -- \"$cur\") )\n"
+        ));
+        assert!(
+            script.contains("\t\t\"items list\")\n\t\t\toptions+=\" --format --feed --unread\"\n")
+        );
+    }
+
+    #[test]
+    fn t_zsh_completes_paths_and_choices() {
+        let script = generate(Shell::Zsh);
+        assert!(script.starts_with("#compdef newsboat\n"));
+        assert!(script.contains(
+            "\t\t'(-u --url-file)'{-u+,--url-file=}'[read RSS feed URLs from <file>]:file:_files' \\\n"
+        ));
+        assert!(script.contains(
+            "\t\t'(-x --execute)'{-x,--execute}'[execute list of commands]:*-*:command:(reload print-unread)' \\\n"
+        ));
+        assert!(script.contains("\t\t'*'{-v,-V,--version}'[get version information]' \\\n"));
+        assert!(script.contains("\t\t\t\titems)\n\t\t\t\t\t_values action list mark-read\n"));
+    }
+
+    #[test]
+    fn t_fish_completes_paths_and_choices() {
+        let script = generate(Shell::Fish);
+        assert!(script.contains(
+            "complete -c newsboat -s c -l cache-file -r -F -d 'use <file> as cache file'\n"
+        ));
+        assert!(script.contains(
+            "complete -c newsboat -s x -l execute -x -a 'reload print-unread' -d 'execute list of commands'\n"
+        ));
+        assert!(script.contains("complete -c newsboat -s V -d 'get version information'\n"));
+        assert!(script.contains(
+            "complete -c newsboat -l unread -n 'begin; __fish_seen_subcommand_from items; and __fish_seen_subcommand_from list; end' -d 'only list unread items'\n"
+        ));
+        assert!(
+            script.contains("complete -c newsboat -n __fish_use_subcommand -a 'feeds items'\n")
+        );
+    }
+
+    #[test]
+    fn t_escapes_quotes_and_special_characters() {
+        assert_eq!(single_quote_escape("it's"), r"it'\''s");
+        assert_eq!(zsh_description_escape("a [b]: c"), r"a \[b\]\: c");
+        assert_eq!(fish_quote(r"it's a\b"), r"'it\'s a\\b'");
+    }
+}
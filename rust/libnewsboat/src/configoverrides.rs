@@ -0,0 +1,191 @@
+//! Settings that are overridden for a single run, from the command line (`-o name=value`) or from
+//! the environment (`NEWSBOAT_NAME=value`).
+//!
+//! Overrides are applied after the config files are parsed. The full precedence order, from lowest
+//! to highest, is:
+//!
+//! 1. built-in defaults;
+//! 2. the system-wide config file;
+//! 3. the user's config file and its fragments in `config.d`;
+//! 4. environment variables;
+//! 5. `-o` options, in the order they're given.
+
+use std::ffi::OsString;
+
+/// Prefix of environment variables that override settings.
+pub const ENV_VAR_PREFIX: &str = "NEWSBOAT_";
+
+/// Where an override came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverrideSource {
+    /// The `-o` command-line option.
+    CommandLine,
+    /// The environment variable with this name.
+    Environment(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigOverride {
+    pub name: String,
+    pub value: String,
+    pub source: OverrideSource,
+}
+
+impl ConfigOverride {
+    /// Human-readable description of the override, e.g. `browser = lynx (from NEWSBOAT_BROWSER)`.
+    pub fn describe(&self) -> String {
+        let source = match &self.source {
+            OverrideSource::CommandLine => "-o",
+            OverrideSource::Environment(variable) => variable,
+        };
+        format!("{} = {} (from {})", self.name, self.value, source)
+    }
+}
+
+/// Name of the environment variable that overrides `setting`, e.g. `NEWSBOAT_CACHE_FILE` for
+/// `cache-file`.
+pub fn env_var_name(setting: &str) -> String {
+    ENV_VAR_PREFIX.to_owned() + &setting.to_uppercase().replace('-', "_")
+}
+
+/// Splits `name=value` into its parts. Returns `None` if there is no `=`, or the name is empty or
+/// contains whitespace.
+pub fn parse_assignment(assignment: &str) -> Option<(String, String)> {
+    let (name, value) = assignment.split_once('=')?;
+    if name.is_empty() || name.chars().any(char::is_whitespace) {
+        return None;
+    }
+    Some((name.to_owned(), value.to_owned()))
+}
+
+/// Collects overrides in the order they should be applied.
+///
+/// `settings` are the names of all known settings; environment variables that don't correspond
+/// to any of them are ignored. `assignments` are the arguments of `-o` options.
+pub fn collect<I>(
+    settings: &[&str],
+    environment: I,
+    assignments: &[(String, String)],
+) -> Vec<ConfigOverride>
+where
+    I: IntoIterator<Item = (OsString, OsString)>,
+{
+    let environment: Vec<(String, String)> = environment
+        .into_iter()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .collect();
+
+    let mut result: Vec<ConfigOverride> = Vec::new();
+    for setting in settings {
+        let variable = env_var_name(setting);
+        if let Some((_, value)) = environment.iter().find(|(name, _)| *name == variable) {
+            result.push(ConfigOverride {
+                name: (*setting).to_owned(),
+                value: value.clone(),
+                source: OverrideSource::Environment(variable),
+            });
+        }
+    }
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+
+    result.extend(assignments.iter().map(|(name, value)| ConfigOverride {
+        name: name.clone(),
+        value: value.clone(),
+        source: OverrideSource::CommandLine,
+    }));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(OsString, OsString)> {
+        vars.iter()
+            .map(|(name, value)| (OsString::from(name), OsString::from(value)))
+            .collect()
+    }
+
+    #[test]
+    fn t_env_var_name_is_uppercased_and_uses_underscores() {
+        assert_eq!(env_var_name("browser"), "NEWSBOAT_BROWSER");
+        assert_eq!(env_var_name("cache-file"), "NEWSBOAT_CACHE_FILE");
+        assert_eq!(env_var_name("show-read-feeds"), "NEWSBOAT_SHOW_READ_FEEDS");
+    }
+
+    #[test]
+    fn t_parse_assignment_splits_on_first_equals_sign() {
+        assert_eq!(
+            parse_assignment("browser=lynx"),
+            Some(("browser".to_owned(), "lynx".to_owned()))
+        );
+        assert_eq!(
+            parse_assignment("browser=w3m -o x=y %u"),
+            Some(("browser".to_owned(), "w3m -o x=y %u".to_owned()))
+        );
+        assert_eq!(
+            parse_assignment("save-path="),
+            Some(("save-path".to_owned(), String::new()))
+        );
+    }
+
+    #[test]
+    fn t_parse_assignment_rejects_malformed_input() {
+        assert_eq!(parse_assignment("browser"), None);
+        assert_eq!(parse_assignment("=lynx"), None);
+        assert_eq!(parse_assignment("my browser=lynx"), None);
+        assert_eq!(parse_assignment(""), None);
+    }
+
+    #[test]
+    fn t_collect_only_picks_environment_variables_of_known_settings() {
+        let overrides = collect(
+            &["browser", "cache-file"],
+            env(&[
+                ("NEWSBOAT_CACHE_FILE", "/tmp/cache.db"),
+                ("NEWSBOAT_HOME", "/tmp"),
+                ("BROWSER", "firefox"),
+                ("NEWSBOAT_BROWSER", "lynx"),
+            ]),
+            &[],
+        );
+        assert_eq!(
+            overrides,
+            vec![
+                ConfigOverride {
+                    name: "browser".to_owned(),
+                    value: "lynx".to_owned(),
+                    source: OverrideSource::Environment("NEWSBOAT_BROWSER".to_owned()),
+                },
+                ConfigOverride {
+                    name: "cache-file".to_owned(),
+                    value: "/tmp/cache.db".to_owned(),
+                    source: OverrideSource::Environment("NEWSBOAT_CACHE_FILE".to_owned()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn t_collect_puts_command_line_after_environment_so_it_takes_precedence() {
+        let overrides = collect(
+            &["browser"],
+            env(&[("NEWSBOAT_BROWSER", "lynx")]),
+            &[
+                ("browser".to_owned(), "w3m".to_owned()),
+                ("unknown-setting".to_owned(), "1".to_owned()),
+                ("browser".to_owned(), "elinks".to_owned()),
+            ],
+        );
+        let described: Vec<String> = overrides.iter().map(ConfigOverride::describe).collect();
+        assert_eq!(
+            described,
+            vec![
+                "browser = lynx (from NEWSBOAT_BROWSER)",
+                "browser = w3m (from -o)",
+                "unknown-setting = 1 (from -o)",
+                "browser = elinks (from -o)",
+            ]
+        );
+    }
+}
@@ -1,9 +1,13 @@
 use crate::cliargsparser::CliArgsParser;
 use crate::logger::{self, Level};
+use crate::migrations::{self, MigrationError, MigrationPlan};
 use crate::utils;
 use gettextrs::gettext;
+use std::env;
+use std::ffi::{OsStr, OsString};
 use std::fs::{self, DirBuilder};
 use std::io;
+use std::os::unix::ffi::OsStrExt;
 use std::os::unix::fs::DirBuilderExt;
 use std::path::{Path, PathBuf};
 use strprintf::fmt;
@@ -13,13 +17,26 @@ pub const NEWSBOAT_CONFIG_SUBDIR: &str = ".newsboat";
 pub const NEWSBEUTER_SUBDIR_XDG: &str = "newsbeuter";
 pub const NEWSBEUTER_CONFIG_SUBDIR: &str = ".newsbeuter";
 pub const LOCK_SUFFIX: &str = ".lock";
+/// Subdirectory of Newsboat's XDG config and data dirs that holds profiles.
+pub const PROFILES_SUBDIR: &str = "profiles";
+/// Suffix of directories with drop-in fragments of a file, e.g. `config.d` holds fragments of
+/// `config`.
+pub const FRAGMENTS_DIR_SUFFIX: &str = ".d";
+/// Extension of config file fragments. Other files in the config fragments directory are ignored.
+pub const CONFIG_FRAGMENT_EXTENSION: &str = "conf";
+/// Environment variable that turns on portable mode. It points at the directory that holds all
+/// of Newsboat's files.
+pub const PORTABLE_HOME_ENV_VAR: &str = "NEWSBOAT_HOME";
+/// File that turns on portable mode if it's next to Newsboat's executable. The executable's
+/// directory then holds all of Newsboat's files.
+pub const PORTABLE_MARKER_FILENAME: &str = "newsboat.portable";
 
 const URLS_FILENAME: &str = "urls";
 const CACHE_FILENAME: &str = "cache.db";
 const CONFIG_FILENAME: &str = "config";
 const QUEUE_FILENAME: &str = "queue";
-const SEARCH_HISTORY_FILENAME: &str = "history.search";
-const CMDLINE_HISTORY_FILENAME: &str = "history.cmdline";
+pub(crate) const SEARCH_HISTORY_FILENAME: &str = "history.search";
+pub(crate) const CMDLINE_HISTORY_FILENAME: &str = "history.cmdline";
 
 #[derive(Debug, Default)]
 pub struct ConfigPaths {
@@ -43,18 +60,160 @@ pub struct ConfigPaths {
     /// it through the command line parameter.
     config_dir: PathBuf,
 
+    /// Path to Newsboat's directory for state that shouldn't be synced between machines, like
+    /// history.
+    ///
+    /// This is the same as `data_dir` for the dotdir, and ~/.local/state/newsboat for XDG.
+    state_dir: PathBuf,
+
+    /// Path to the directory with the lock file.
+    ///
+    /// This is the same as `data_dir` for the dotdir. For XDG, it's $XDG_RUNTIME_DIR/newsboat,
+    /// or `state_dir` if there is no runtime directory. If `data_dir` is on a network
+    /// filesystem, though, the lock stays in it; see `lock_dir_for()`.
+    lock_dir: PathBuf,
+
     url_file: PathBuf,
     cache_file: PathBuf,
     config_file: PathBuf,
     lock_file: PathBuf,
+    /// Lock file used by versions that kept it in `data_dir`, if it's not `lock_file`.
+    legacy_lock_file: Option<PathBuf>,
     queue_file: PathBuf,
     search_history_file: PathBuf,
     cmdline_history_file: PathBuf,
 
+    /// Name of the profile that's in use, if any.
+    profile: Option<String>,
+
+    /// The directory that holds all the files if Newsboat runs in portable mode.
+    ///
+    /// Neither the dotdir nor XDG directories are used then.
+    portable_dir: Option<PathBuf>,
+
     silent: bool,
     using_nonstandard_configs: bool,
 }
 
+/// Checks if `name` can be used as a profile name, i.e. as a directory name.
+///
+/// Names are made of letters, digits, dashes, underscores and dots, and can't start with a dot.
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// The directory that holds all of Newsboat's files in portable mode, or `None` if portable mode
+/// is off.
+///
+/// `NEWSBOAT_HOME` takes precedence over the marker file next to the executable.
+pub fn portable_dir() -> Option<PathBuf> {
+    find_portable_dir(env::var_os(PORTABLE_HOME_ENV_VAR), env::current_exe().ok())
+}
+
+/// Implementation of `portable_dir()` that takes the value of `NEWSBOAT_HOME` and the path to the
+/// executable as parameters. A relative `env_home` is resolved against the current directory.
+fn find_portable_dir(env_home: Option<OsString>, executable: Option<PathBuf>) -> Option<PathBuf> {
+    if let Some(dir) = env_home.filter(|dir| !dir.is_empty()) {
+        let dir = PathBuf::from(dir);
+        return Some(std::path::absolute(&dir).unwrap_or(dir));
+    }
+
+    let executable_dir = executable?.parent()?.to_owned();
+    executable_dir
+        .join(PORTABLE_MARKER_FILENAME)
+        .is_file()
+        .then_some(executable_dir)
+}
+
+/// Newsboat's XDG directories for state and for the lock file.
+fn xdg_state_dirs() -> (PathBuf, PathBuf) {
+    // This can't panic because we've tested we can find the home directory in ConfigPaths::new
+    let xdg_dirs = xdg::BaseDirectories::new();
+    let state_dir = xdg_dirs.get_state_home().unwrap().join(NEWSBOAT_SUBDIR_XDG);
+    let lock_dir = xdg_dirs
+        .get_runtime_directory()
+        .map(|dir| dir.join(NEWSBOAT_SUBDIR_XDG))
+        .unwrap_or_else(|_| state_dir.clone());
+    (state_dir, lock_dir)
+}
+
+/// The directory for the lock file that protects the cache in `data_dir`.
+///
+/// That's normally `local_dir`, which only this host can see. But if `data_dir` is on a network
+/// filesystem, instances on other hosts might use the same cache, and only a lock next to it
+/// keeps them out.
+fn lock_dir_for(data_dir: &Path, local_dir: PathBuf) -> PathBuf {
+    if is_on_network_filesystem(data_dir) {
+        log!(
+            Level::Info,
+            "ConfigPaths: {} is on a network filesystem, keeping the lock in it",
+            data_dir.display()
+        );
+        data_dir.to_owned()
+    } else {
+        local_dir
+    }
+}
+
+/// Checks if `path`, or its closest ancestor that exists, is on a network filesystem.
+#[cfg(target_os = "linux")]
+fn is_on_network_filesystem(path: &Path) -> bool {
+    use std::ffi::CString;
+
+    const NETWORK_FILESYSTEMS: [u32; 8] = [
+        0x6969,      // NFS
+        0x517b,      // SMB
+        0xff53_4d42, // CIFS
+        0xfe53_4d42, // SMB2
+        0x5346_414f, // AFS
+        0x0102_1997, // 9P
+        0x00c3_6400, // Ceph
+        0x7375_7245, // Coda
+    ];
+
+    let Some(existing) = path.ancestors().find(|dir| dir.exists()) else {
+        return false;
+    };
+    let Ok(c_path) = CString::new(existing.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    // The type of `f_type` differs between architectures; magic numbers fit into 32 bits.
+    NETWORK_FILESYSTEMS.contains(&(stat.f_type as u32))
+}
+
+/// Checks if `path`, or its closest ancestor that exists, is on a network filesystem.
+///
+/// Only implemented for Linux; elsewhere, all filesystems are considered local.
+#[cfg(not(target_os = "linux"))]
+fn is_on_network_filesystem(_path: &Path) -> bool {
+    false
+}
+
+/// Directories that contain profiles: one for configs, one for data.
+fn profiles_dirs() -> (PathBuf, PathBuf) {
+    // This can't panic because we've tested we can find the home directory in ConfigPaths::new
+    let xdg_dirs = xdg::BaseDirectories::new();
+    let config_dir = xdg_dirs
+        .get_config_home()
+        .unwrap()
+        .join(NEWSBOAT_SUBDIR_XDG)
+        .join(PROFILES_SUBDIR);
+    let data_dir = xdg_dirs
+        .get_data_home()
+        .unwrap()
+        .join(NEWSBOAT_SUBDIR_XDG)
+        .join(PROFILES_SUBDIR);
+    (config_dir, data_dir)
+}
+
 impl ConfigPaths {
     pub fn new() -> ConfigPaths {
         let mut config_paths = ConfigPaths {
@@ -63,19 +222,33 @@ impl ConfigPaths {
 
             data_dir: PathBuf::new(),
             config_dir: PathBuf::new(),
+            state_dir: PathBuf::new(),
+            lock_dir: PathBuf::new(),
 
             url_file: PathBuf::new(),
             cache_file: PathBuf::new(),
             config_file: PathBuf::new(),
             lock_file: PathBuf::new(),
+            legacy_lock_file: None,
             queue_file: PathBuf::new(),
             search_history_file: PathBuf::new(),
             cmdline_history_file: PathBuf::new(),
 
+            profile: None,
+            portable_dir: None,
+
             silent: false,
             using_nonstandard_configs: false,
         };
 
+        // Portable mode doesn't need the home directory at all
+        if let Some(portable_dir) = portable_dir() {
+            config_paths.env_home = utils::home_dir().unwrap_or_default();
+            config_paths.portable_dir = Some(portable_dir);
+            config_paths.find_dirs();
+            return config_paths;
+        }
+
         let env_home = utils::home_dir();
         if env_home.is_none() {
             let uid = unsafe { libc::getuid() };
@@ -246,28 +419,51 @@ impl ConfigPaths {
     }
 
     pub fn create_dirs(&self) -> bool {
-        try_mkdir(&self.config_dir) && try_mkdir(&self.data_dir)
+        try_mkdir(&self.config_dir)
+            && try_mkdir(&self.data_dir)
+            && try_mkdir(&self.state_dir)
+            && try_mkdir(&self.lock_dir)
     }
 
     fn find_dirs(&mut self) {
+        if let Some(ref portable_dir) = self.portable_dir {
+            self.config_dir.clone_from(portable_dir);
+            self.data_dir.clone_from(portable_dir);
+            self.state_dir.clone_from(portable_dir);
+            self.lock_dir.clone_from(portable_dir);
+            self.find_files();
+            return;
+        }
+
         self.config_dir = self.env_home.join(NEWSBOAT_CONFIG_SUBDIR);
 
         self.data_dir.clone_from(&self.config_dir);
+        self.state_dir.clone_from(&self.config_dir);
+        self.lock_dir.clone_from(&self.config_dir);
 
         // Will change config_dir and data_dir to point to XDG if XDG
         // directories are available.
         self.find_dirs_xdg();
 
+        self.find_files();
+    }
+
+    /// Points all the files into `config_dir` and `data_dir`.
+    fn find_files(&mut self) {
         // in config
         self.url_file = self.config_dir.join(URLS_FILENAME);
         self.config_file = self.config_dir.join(CONFIG_FILENAME);
 
         // in data
         self.cache_file = self.data_dir.join(CACHE_FILENAME);
-        self.lock_file = self.data_dir.join(CACHE_FILENAME.to_owned() + LOCK_SUFFIX);
         self.queue_file = self.data_dir.join(QUEUE_FILENAME);
-        self.search_history_file = self.data_dir.join(SEARCH_HISTORY_FILENAME);
-        self.cmdline_history_file = self.data_dir.join(CMDLINE_HISTORY_FILENAME);
+
+        // in state
+        self.lock_file = self.lock_dir.join(CACHE_FILENAME.to_owned() + LOCK_SUFFIX);
+        let legacy_lock_file = self.data_dir.join(CACHE_FILENAME.to_owned() + LOCK_SUFFIX);
+        self.legacy_lock_file = (legacy_lock_file != self.lock_file).then_some(legacy_lock_file);
+        self.search_history_file = self.state_dir.join(SEARCH_HISTORY_FILENAME);
+        self.cmdline_history_file = self.state_dir.join(CMDLINE_HISTORY_FILENAME);
     }
 
     fn find_dirs_xdg(&mut self) {
@@ -289,6 +485,9 @@ impl ConfigPaths {
          * At this point, we're confident we'll be using XDG. We don't check if
          * data dir exists, because if it doesn't we'll create it. */
 
+        let (state_dir, lock_dir) = xdg_state_dirs();
+        self.lock_dir = lock_dir_for(&data_dir, lock_dir);
+        self.state_dir = state_dir;
         self.config_dir = config_dir;
         self.data_dir = data_dir;
     }
@@ -298,7 +497,7 @@ impl ConfigPaths {
     /// If this method returned `false`, the cause for initialization failure can be found using
     /// `error_message()`.
     pub fn initialized(&self) -> bool {
-        !self.env_home.as_os_str().is_empty()
+        self.portable_dir.is_some() || !self.env_home.as_os_str().is_empty()
     }
 
     /// Returns explanation why initialization failed.
@@ -309,17 +508,42 @@ impl ConfigPaths {
     }
 
     /// Initializes paths to config, cache etc. from CLI arguments.
+    ///
+    /// If a profile is chosen, files are looked up in its directories; paths that are given
+    /// explicitly take precedence over that.
     pub fn process_args(&mut self, args: &CliArgsParser) {
+        if let (Some(profile), Some(portable_dir)) = (&args.profile, &self.portable_dir) {
+            let profile_dir = portable_dir.join(PROFILES_SUBDIR).join(profile);
+            self.config_dir.clone_from(&profile_dir);
+            self.data_dir.clone_from(&profile_dir);
+            self.state_dir.clone_from(&profile_dir);
+            self.lock_dir = profile_dir;
+            self.find_files();
+            self.profile = Some(profile.clone());
+        } else if let Some(ref profile) = args.profile {
+            let (config_dir, data_dir) = profiles_dirs();
+            self.config_dir = config_dir.join(profile);
+            self.data_dir = data_dir.join(profile);
+            let (state_dir, lock_dir) = xdg_state_dirs();
+            self.state_dir = state_dir.join(PROFILES_SUBDIR).join(profile);
+            self.lock_dir =
+                lock_dir_for(&self.data_dir, lock_dir.join(PROFILES_SUBDIR).join(profile));
+            self.find_files();
+            self.profile = Some(profile.clone());
+        }
+
         if let Some(ref url_file) = args.url_file {
             self.url_file.clone_from(url_file);
         }
 
         if let Some(ref cache_file) = args.cache_file {
             self.cache_file.clone_from(cache_file);
+            self.legacy_lock_file = None;
         }
 
         if let Some(ref lock_file) = args.lock_file {
             self.lock_file.clone_from(lock_file);
+            self.legacy_lock_file = None;
         }
 
         if let Some(ref config_file) = args.config_file {
@@ -344,8 +568,15 @@ impl ConfigPaths {
 
     /// Migrate configs and data from Newsbeuter if they exist. Return `true` if migrated
     /// something, `false` otherwise.
+    ///
+    /// Newsbeuter's data always goes to the default location, so nothing is migrated while
+    /// a profile is in use or in portable mode.
     pub fn try_migrate_from_newsbeuter(&mut self) -> bool {
-        if !self.using_nonstandard_configs && !&self.url_file.exists() {
+        if !self.using_nonstandard_configs
+            && self.profile.is_none()
+            && self.portable_dir.is_none()
+            && !&self.url_file.exists()
+        {
             return self.migrate_data_from_newsbeuter();
         }
 
@@ -353,6 +584,74 @@ impl ConfigPaths {
         false
     }
 
+    /// Migrations that would bring the files in the config and data directories up to date.
+    pub fn migration_plan(&self) -> Result<MigrationPlan, MigrationError> {
+        migrations::plan(&migrations::Dirs {
+            config_dir: &self.config_dir,
+            data_dir: &self.data_dir,
+            state_dir: &self.state_dir,
+        })
+    }
+
+    /// Applies pending migrations. Returns `true` if any files were changed.
+    ///
+    /// Nothing is done if paths were given on the command line, as the lock doesn't protect the
+    /// default directories then.
+    pub fn try_migrate(&self) -> Result<bool, MigrationError> {
+        if self.using_nonstandard_configs {
+            return Ok(false);
+        }
+
+        let plan = self.migration_plan()?;
+        if !plan.is_needed() {
+            return Ok(false);
+        }
+        plan.apply()?;
+
+        if plan.changes_files() && !self.silent {
+            eprintln!(
+                "{}",
+                fmt!(
+                    &gettext("Migrated files to version %u. Backups are in `%s'."),
+                    plan.to,
+                    plan.backups_dir().display().to_string()
+                )
+            );
+        }
+        Ok(plan.changes_files())
+    }
+
+    /// Name of the profile that's in use, if any.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Checks if the config directory of the current profile exists. Always `true` if no profile
+    /// is in use.
+    pub fn profile_exists(&self) -> bool {
+        self.profile.is_none() || self.config_dir.is_dir()
+    }
+
+    /// Names of all existing profiles, in alphabetical order.
+    pub fn list_profiles(&self) -> Vec<String> {
+        let config_dir = match self.portable_dir {
+            Some(ref portable_dir) => portable_dir.join(PROFILES_SUBDIR),
+            None => profiles_dirs().0,
+        };
+        let mut profiles: Vec<String> = fs::read_dir(config_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.path().is_dir())
+                    .filter_map(|entry| entry.file_name().into_string().ok())
+                    .filter(|name| is_valid_profile_name(name))
+                    .collect()
+            })
+            .unwrap_or_default();
+        profiles.sort();
+        profiles
+    }
+
     /// Path to the URLs file.
     pub fn url_file(&self) -> &Path {
         &self.url_file
@@ -369,6 +668,8 @@ impl ConfigPaths {
     // removed.
     pub fn set_cache_file(&mut self, mut path: PathBuf) {
         path.clone_into(&mut self.cache_file);
+        // Older versions put the lock next to the cache file, too
+        self.legacy_lock_file = None;
         self.lock_file = {
             let current_extension = path
                 .extension()
@@ -391,6 +692,18 @@ impl ConfigPaths {
         &self.lock_file
     }
 
+    /// Lock file that versions which kept the lock next to the cache would use, if it's not
+    /// `lock_file()`. Such a version doesn't notice the current lock, so it shouldn't run
+    /// alongside this one.
+    pub fn legacy_lock_file(&self) -> Option<&Path> {
+        self.legacy_lock_file.as_deref()
+    }
+
+    /// Directory with the lock file, unless the user chose a different lock file.
+    pub fn lock_dir(&self) -> &Path {
+        &self.lock_dir
+    }
+
     /// \brief Path to the queue file.
     ///
     /// Queue file stores enqueued podcasts. It's written by Newsboat, and read by Podboat.
@@ -407,6 +720,64 @@ impl ConfigPaths {
     pub fn cmdline_history_file(&self) -> &Path {
         &self.cmdline_history_file
     }
+
+    /// Directory where log files go if the user didn't specify one, or `None` if Newsboat uses
+    /// the dotdir, in which case logs are written into the current directory.
+    pub fn log_dir(&self) -> Option<&Path> {
+        (self.portable_dir.is_some() || self.state_dir != self.config_dir)
+            .then_some(self.state_dir.as_path())
+    }
+
+    /// The directory that holds all the files if Newsboat runs in portable mode.
+    pub fn portable_dir(&self) -> Option<&Path> {
+        self.portable_dir.as_deref()
+    }
+
+    /// The config file, followed by its fragments: files with the `.conf` extension from the
+    /// `config.d` directory next to it, in lexical order. This is the order in which they're
+    /// loaded.
+    pub fn config_files(&self) -> Vec<PathBuf> {
+        let mut result = vec![self.config_file.clone()];
+        result.extend(list_fragments(
+            &fragments_dir(&self.config_file),
+            Some(CONFIG_FRAGMENT_EXTENSION),
+        ));
+        result
+    }
+
+    /// The URLs file, followed by its fragments: files from the `urls.d` directory next to it, in
+    /// lexical order.
+    pub fn url_files(&self) -> Vec<PathBuf> {
+        let mut result = vec![self.url_file.clone()];
+        result.extend(list_fragments(&fragments_dir(&self.url_file), None));
+        result
+    }
+}
+
+/// Directory with drop-in fragments of `file`.
+fn fragments_dir(file: &Path) -> PathBuf {
+    let mut dir = file.as_os_str().to_owned();
+    dir.push(FRAGMENTS_DIR_SUFFIX);
+    PathBuf::from(dir)
+}
+
+/// Regular files in `dir` (with the given extension, if any), sorted by name. Files whose names
+/// start with a dot are skipped.
+fn list_fragments(dir: &Path, extension: Option<&str>) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut fragments: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| !entry.file_name().as_bytes().starts_with(b"."))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            extension.is_none_or(|extension| path.extension() == Some(OsStr::new(extension)))
+        })
+        .collect();
+    fragments.sort();
+    fragments
 }
 
 fn try_mkdir<R: AsRef<Path>>(path: R) -> bool {
@@ -423,3 +794,78 @@ fn migrate_file<R: AsRef<Path>>(newsbeuter_dir: R, newsboat_dir: R, file: &str)
     eprintln!("{input_filepath:?} -> {output_filepath:?}");
     fs::copy(input_filepath, output_filepath).map(|_| ())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn t_find_portable_dir_is_none_without_variable_and_marker() {
+        let tmp = TempDir::new().unwrap();
+        let executable = tmp.path().join("newsboat");
+
+        assert_eq!(find_portable_dir(None, Some(executable)), None);
+        assert_eq!(find_portable_dir(None, None), None);
+    }
+
+    #[test]
+    fn t_find_portable_dir_uses_directory_of_executable_if_marker_is_next_to_it() {
+        let tmp = TempDir::new().unwrap();
+        let executable = tmp.path().join("newsboat");
+        fs::write(tmp.path().join(PORTABLE_MARKER_FILENAME), "").unwrap();
+
+        assert_eq!(
+            find_portable_dir(None, Some(executable.clone())),
+            Some(tmp.path().to_owned())
+        );
+        assert_eq!(
+            find_portable_dir(Some(OsString::new()), Some(executable)),
+            Some(tmp.path().to_owned())
+        );
+    }
+
+    #[test]
+    fn t_find_portable_dir_prefers_variable_over_marker() {
+        let tmp = TempDir::new().unwrap();
+        let executable = tmp.path().join("newsboat");
+        fs::write(tmp.path().join(PORTABLE_MARKER_FILENAME), "").unwrap();
+
+        assert_eq!(
+            find_portable_dir(Some("/media/usb/newsboat".into()), Some(executable)),
+            Some(PathBuf::from("/media/usb/newsboat"))
+        );
+    }
+
+    #[test]
+    fn t_find_portable_dir_resolves_relative_variable_against_current_directory() {
+        let current_dir = env::current_dir().unwrap();
+
+        assert_eq!(
+            find_portable_dir(Some("portable".into()), None),
+            Some(current_dir.join("portable"))
+        );
+    }
+
+    #[test]
+    fn t_is_on_network_filesystem_checks_closest_existing_ancestor_of_missing_paths() {
+        let tmp = TempDir::new().unwrap();
+        let missing = tmp.path().join("newsboat").join("profiles").join("work");
+
+        assert_eq!(
+            is_on_network_filesystem(&missing),
+            is_on_network_filesystem(tmp.path())
+        );
+    }
+
+    #[test]
+    fn t_lock_dir_for_local_data_dir_is_the_local_dir() {
+        let tmp = TempDir::new().unwrap();
+        if is_on_network_filesystem(tmp.path()) {
+            return;
+        }
+
+        let local_dir = PathBuf::from("/run/user/1000/newsboat");
+        assert_eq!(lock_dir_for(tmp.path(), local_dir.clone()), local_dir);
+    }
+}
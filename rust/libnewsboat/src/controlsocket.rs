@@ -0,0 +1,437 @@
+//! Unix-domain socket through which other programs control a running instance of Newsboat.
+//!
+//! The protocol is line-based. A client sends commands, one per line, and the server answers each
+//! of them with a single line: `OK`, optionally followed by a space and the result, or `ERR`
+//! followed by a space and an error message. Clients may send several commands over one
+//! connection; answers come in the same order.
+
+use crate::logger::{self, Level};
+use gettextrs::gettext;
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use strprintf::fmt;
+
+/// Clients that send a longer line are disconnected.
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// How long `send_command()` waits for the running instance to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A command sent to a running instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Reload the feed with this URL, or all feeds if it's `None`.
+    Reload(Option<String>),
+    /// Mark all articles in the feed with this URL as read, or in all feeds if it's `None`.
+    MarkRead(Option<String>),
+    /// Count unread articles in the feed with this URL, or in all feeds if it's `None`.
+    UnreadCount(Option<String>),
+    /// Open the article with this GUID.
+    Open(String),
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let line = line.trim();
+        let (name, argument) = match line.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, Some(argument.trim().to_owned())),
+            None => (line, None),
+        };
+        match (name, argument) {
+            ("reload", feed_url) => Ok(Command::Reload(feed_url)),
+            ("mark-read", feed_url) => Ok(Command::MarkRead(feed_url)),
+            ("unread-count", feed_url) => Ok(Command::UnreadCount(feed_url)),
+            ("open", Some(guid)) => Ok(Command::Open(guid)),
+            ("open", None) => Err(gettext("`open' requires the GUID of an article")),
+            ("", _) => Err(gettext("empty command")),
+            (name, _) => Err(fmt!(&gettext("unknown command `%s'"), name)),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, argument) = match self {
+            Command::Reload(feed_url) => ("reload", feed_url.as_deref()),
+            Command::MarkRead(feed_url) => ("mark-read", feed_url.as_deref()),
+            Command::UnreadCount(feed_url) => ("unread-count", feed_url.as_deref()),
+            Command::Open(guid) => ("open", Some(guid.as_str())),
+        };
+        match argument {
+            Some(argument) => write!(f, "{name} {argument}"),
+            None => write!(f, "{name}"),
+        }
+    }
+}
+
+/// Turns the result of a command into the line that the server sends back.
+pub fn format_reply(reply: &Result<String, String>) -> String {
+    let (status, text) = match reply {
+        Ok(text) => ("OK", text),
+        Err(text) => ("ERR", text),
+    };
+    // Replies are a single line, so newlines in the text would confuse the client.
+    let text = text.replace('\n', " ");
+    if text.is_empty() {
+        status.to_owned()
+    } else {
+        format!("{status} {text}")
+    }
+}
+
+/// Reverse of `format_reply()`.
+pub fn parse_reply(line: &str) -> Result<String, String> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let (status, text) = line.split_once(' ').unwrap_or((line, ""));
+    match status {
+        "OK" => Ok(text.to_owned()),
+        "ERR" => Err(text.to_owned()),
+        _ => Err(fmt!(&gettext("malformed reply: %s"), line)),
+    }
+}
+
+/// Path to the control socket of the instance that holds the lock at `lock_path`. The socket is
+/// put next to the lock, e.g. _cache.db.lock_ gets _cache.db.sock_.
+pub fn socket_path(lock_path: &Path) -> PathBuf {
+    lock_path.with_extension("sock")
+}
+
+fn path_for_display(path: &Path) -> String {
+    path.to_str()
+        .map(String::from)
+        .unwrap_or_else(|| gettext("<filename containing invalid UTF-8 codepoint>"))
+}
+
+/// Sends `command` to the instance listening on `socket_path`, and returns its answer.
+pub fn send_command(socket_path: &Path, command: &Command) -> Result<String, String> {
+    let io_error = |reason: std::io::Error| {
+        fmt!(
+            &gettext("Couldn't talk to a running instance through '%s': %s"),
+            path_for_display(socket_path),
+            reason.to_string()
+        )
+    };
+
+    let mut stream = UnixStream::connect(socket_path).map_err(io_error)?;
+    stream
+        .set_read_timeout(Some(REPLY_TIMEOUT))
+        .map_err(io_error)?;
+    writeln!(stream, "{command}").map_err(io_error)?;
+    stream.shutdown(Shutdown::Write).map_err(io_error)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .map_err(io_error)?;
+    if reply.is_empty() {
+        return Err(gettext(
+            "The running instance closed the connection without replying",
+        ));
+    }
+    parse_reply(&reply)
+}
+
+/// A connection accepted by `ControlSocket`.
+struct Client {
+    stream: UnixStream,
+    /// Bytes received, but not yet returned as commands.
+    buffer: Vec<u8>,
+    /// The client won't send anything else.
+    finished_sending: bool,
+    /// Writing to the client failed, so it should be dropped.
+    broken: bool,
+}
+
+impl Client {
+    fn new(stream: UnixStream) -> Client {
+        Client {
+            stream,
+            buffer: Vec::new(),
+            finished_sending: false,
+            broken: false,
+        }
+    }
+
+    /// Reads everything that the client has sent so far.
+    fn receive(&mut self) {
+        let mut chunk = [0u8; 4096];
+        while !self.finished_sending {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    // The last command might not end with a newline.
+                    if !self.buffer.is_empty() && !self.buffer.ends_with(b"\n") {
+                        self.buffer.push(b'\n');
+                    }
+                    self.finished_sending = true;
+                }
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => self.broken = true,
+            }
+            if self.broken {
+                break;
+            }
+        }
+
+        if self.buffer.len() > MAX_LINE_LENGTH && !self.buffer.contains(&b'\n') {
+            log!(
+                Level::Warn,
+                "ControlSocket: dropping a client that sent a line longer than {} bytes",
+                MAX_LINE_LENGTH
+            );
+            self.broken = true;
+        }
+    }
+
+    fn take_line(&mut self) -> Option<String> {
+        let end = self.buffer.iter().position(|&byte| byte == b'\n')?;
+        let line: Vec<u8> = self.buffer.drain(..=end).collect();
+        Some(String::from_utf8_lossy(&line).into_owned())
+    }
+
+    fn send(&mut self, line: &str) {
+        if writeln!(self.stream, "{line}").is_err() {
+            self.broken = true;
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.broken || (self.finished_sending && self.buffer.is_empty())
+    }
+}
+
+/// The server side of the control socket.
+///
+/// The socket file is removed when this object is dropped.
+pub struct ControlSocket {
+    listener: UnixListener,
+    path: PathBuf,
+    clients: Vec<Client>,
+    /// Index of the client that sent the command returned by the last call to `next_command()`.
+    current_client: Option<usize>,
+}
+
+impl ControlSocket {
+    /// Starts listening on `path`.
+    ///
+    /// The caller should hold the lock next to which the socket lives. A socket left behind by an
+    /// instance that crashed is replaced.
+    pub fn bind(path: &Path) -> Result<ControlSocket, String> {
+        let io_error = |reason: std::io::Error| {
+            fmt!(
+                &gettext("Failed to create control socket '%s': %s"),
+                path_for_display(path),
+                reason.to_string()
+            )
+        };
+
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => {
+                fs::remove_file(path).map_err(io_error)?
+            }
+            Ok(_) => {
+                return Err(fmt!(
+                    &gettext("Failed to create control socket '%s': file exists"),
+                    path_for_display(path)
+                ));
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(io_error(e)),
+        }
+
+        // Anyone who can connect can control Newsboat, so only the owner should be able to. The
+        // socket gets its permissions when it's created, so restrict them through the umask;
+        // changing them afterwards would leave a window in which others could connect.
+        let old_umask = unsafe { libc::umask(0o177) };
+        let listener = UnixListener::bind(path);
+        unsafe { libc::umask(old_umask) };
+        let listener = listener.map_err(io_error)?;
+        let socket = ControlSocket {
+            listener,
+            path: path.to_owned(),
+            clients: Vec::new(),
+            current_client: None,
+        };
+        socket.listener.set_nonblocking(true).map_err(io_error)?;
+        log!(
+            Level::Info,
+            "ControlSocket: listening on {}",
+            socket.path.display()
+        );
+        Ok(socket)
+    }
+
+    /// Waits up to `timeout` for a command from one of the clients. Commands that couldn't be
+    /// parsed are answered with an error right away.
+    ///
+    /// The command should be answered with `reply()` before this method is called again.
+    pub fn next_command(&mut self, timeout: Duration) -> Option<Command> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.clients.retain(|client| !client.is_done());
+
+            for index in 0..self.clients.len() {
+                while let Some(line) = self.clients[index].take_line() {
+                    log!(Level::Debug, "ControlSocket: received `{}'", line.trim());
+                    match Command::parse(&line) {
+                        Ok(command) => {
+                            self.current_client = Some(index);
+                            return Some(command);
+                        }
+                        Err(message) => self.clients[index].send(&format_reply(&Err(message))),
+                    }
+                }
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if !self.wait_for_input(remaining) {
+                return None;
+            }
+
+            self.accept_clients();
+            for client in &mut self.clients {
+                client.receive();
+            }
+        }
+    }
+
+    /// Sends the result of the command returned by the last call to `next_command()`.
+    pub fn reply(&mut self, reply: Result<String, String>) {
+        let client = self
+            .current_client
+            .take()
+            .and_then(|index| self.clients.get_mut(index));
+        if let Some(client) = client {
+            client.send(&format_reply(&reply));
+        }
+    }
+
+    /// Returns `true` if the listener or one of the clients has something to read.
+    fn wait_for_input(&self, timeout: Duration) -> bool {
+        let mut fds = vec![libc::pollfd {
+            fd: self.listener.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        }];
+        for client in self.clients.iter().filter(|c| !c.finished_sending) {
+            fds.push(libc::pollfd {
+                fd: client.stream.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            });
+        }
+
+        let timeout_ms = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout_ms) };
+        ready > 0
+    }
+
+    fn accept_clients(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if stream.set_nonblocking(true).is_ok() {
+                        self.clients.push(Client::new(stream));
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    if e.kind() != ErrorKind::WouldBlock {
+                        log!(
+                            Level::Warn,
+                            "ControlSocket: failed to accept a connection: {}",
+                            e
+                        );
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_parse_accepts_commands_with_and_without_arguments() {
+        assert_eq!(Command::parse("reload"), Ok(Command::Reload(None)));
+        assert_eq!(
+            Command::parse("  reload   https://example.com/feed.xml \n"),
+            Ok(Command::Reload(Some(
+                "https://example.com/feed.xml".to_owned()
+            )))
+        );
+        assert_eq!(Command::parse("mark-read"), Ok(Command::MarkRead(None)));
+        assert_eq!(
+            Command::parse("unread-count https://example.com/feed.xml"),
+            Ok(Command::UnreadCount(Some(
+                "https://example.com/feed.xml".to_owned()
+            )))
+        );
+        assert_eq!(
+            Command::parse("open some guid"),
+            Ok(Command::Open("some guid".to_owned()))
+        );
+    }
+
+    #[test]
+    fn t_parse_rejects_unknown_and_incomplete_commands() {
+        assert!(Command::parse("").is_err());
+        assert!(Command::parse("open").is_err());
+        assert!(Command::parse("quit").is_err());
+        assert!(Command::parse("Reload").is_err());
+    }
+
+    #[test]
+    fn t_display_is_the_reverse_of_parse() {
+        let commands = [
+            Command::Reload(None),
+            Command::Reload(Some("https://example.com/feed.xml".to_owned())),
+            Command::MarkRead(Some("https://example.com/feed.xml".to_owned())),
+            Command::UnreadCount(None),
+            Command::Open("guid".to_owned()),
+        ];
+        for command in commands {
+            assert_eq!(Command::parse(&command.to_string()), Ok(command));
+        }
+    }
+
+    #[test]
+    fn t_replies_survive_a_round_trip() {
+        for reply in [
+            Ok(String::new()),
+            Ok("42".to_owned()),
+            Err("no such feed".to_owned()),
+        ] {
+            assert_eq!(parse_reply(&format_reply(&reply)), reply);
+        }
+        assert_eq!(format_reply(&Ok(String::new())), "OK");
+        assert_eq!(format_reply(&Err("a\nb".to_owned())), "ERR a b");
+        assert!(parse_reply("hello").is_err());
+    }
+
+    #[test]
+    fn t_socket_path_is_next_to_the_lock() {
+        assert_eq!(
+            socket_path(Path::new("/run/user/1000/newsboat/cache.db.lock")),
+            Path::new("/run/user/1000/newsboat/cache.db.sock")
+        );
+    }
+}
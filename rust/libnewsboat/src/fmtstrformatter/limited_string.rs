@@ -3,14 +3,28 @@
 //! The length is calculated by summing the "displayed width" of all character.
 //! This counts the number of "columns" the text would occupy if displayed with a monospace font.
 //! For example, "abc" has length 3, but "ＡＢＣ" has length 6, even though they both contain 3 graphemes.
+//!
+//! Grapheme clusters (e.g. a letter with combining accents, or an emoji ZWJ sequence) are never
+//! split: once some content didn't fit, the characters that would've extended it are dropped too.
+//! If the string is truncated, it can optionally end with an ellipsis.
 
 use crate::utils;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub struct LimitedString {
     /// Maximum length of this string, counted by "displayed width".
     max_length: Option<usize>,
     /// The contents of the limited string.
     content: String,
+    /// The "displayed width" of `content`, kept up to date so it doesn't have to be recomputed on
+    /// every push.
+    width: usize,
+    /// Text that replaces the end of the string if some content didn't fit.
+    ellipsis: String,
+    /// Whether some content didn't fit into the string.
+    truncated: bool,
+    /// Whether the last thing pushed into the string didn't fit (or only partially did).
+    last_dropped: bool,
 }
 
 impl LimitedString {
@@ -19,44 +33,100 @@ impl LimitedString {
         LimitedString {
             max_length,
             content: String::new(),
+            width: 0,
+            ellipsis: String::new(),
+            truncated: false,
+            last_dropped: false,
         }
     }
 
+    /// Makes the string end with `ellipsis` if some of the content didn't fit.
+    pub fn with_ellipsis(mut self, ellipsis: &str) -> LimitedString {
+        ellipsis.clone_into(&mut self.ellipsis);
+        self
+    }
+
     /// Returns the "displayed width" of the string
     pub fn length(&self) -> usize {
-        utils::strwidth(&self.content)
+        self.width
     }
 
     /// Adds given character to the end of the string, or does nothing if the string length reached
     /// the limit.
     pub fn push(&mut self, c: char) {
-        if let Some(limit) = self.max_length {
-            if self.length() + utils::strwidth(&c.to_string()) <= limit {
-                self.content.push(c);
-            }
-        } else {
-            self.content.push(c);
-        }
+        let mut buffer = [0; 4];
+        self.push_str(c.encode_utf8(&mut buffer));
     }
 
     /// Adds given string to the end of the string, or does nothing if the string length reached
     /// the limit.
     pub fn push_str(&mut self, s: &str) {
+        // Characters that continue a grapheme cluster which we dropped can't be added on their
+        // own: they'd attach to whatever is at the end of the string now.
+        let s = if self.last_dropped {
+            &s[grapheme_extension_length(s)..]
+        } else {
+            s
+        };
+        if s.is_empty() {
+            return;
+        }
+
         if let Some(limit) = self.max_length {
-            let remaining_length = limit - self.length();
-            self.content
-                .push_str(&utils::substr_with_width(s, remaining_length));
+            let remaining_length = limit.saturating_sub(self.length());
+            let fitting = utils::substr_with_width(s, remaining_length);
+            self.last_dropped = fitting.len() < s.len();
+            self.truncated |= self.last_dropped;
+            self.append(&fitting);
         } else {
-            self.content.push_str(s);
+            self.append(s);
         }
     }
 
+    /// Appends `s` to the content and updates the cached width.
+    ///
+    /// `s` might extend the last grapheme cluster of the content (e.g. with a combining accent), so
+    /// that cluster is measured again together with `s`.
+    fn append(&mut self, s: &str) {
+        let last_grapheme_start = self
+            .content
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(self.content.len(), |(index, _)| index);
+        let tail_width = utils::graphemes_width(&self.content[last_grapheme_start..]);
+
+        self.content.push_str(s);
+        self.width =
+            self.width - tail_width + utils::graphemes_width(&self.content[last_grapheme_start..]);
+    }
+
     /// Consumes LimitedString and turns it into an ordinary String.
+    ///
+    /// If some content didn't fit, the end of the string is replaced by the ellipsis (unless the
+    /// ellipsis is wider than the limit itself).
     pub fn into_string(self) -> String {
-        self.content
+        let ellipsis_width = utils::graphemes_width(&self.ellipsis);
+        match self.max_length {
+            Some(limit)
+                if self.truncated && !self.ellipsis.is_empty() && ellipsis_width <= limit =>
+            {
+                let mut result = utils::substr_with_width(&self.content, limit - ellipsis_width);
+                result.push_str(&self.ellipsis);
+                result
+            }
+            _ => self.content,
+        }
     }
 }
 
+/// Returns the length (in bytes) of the characters at the start of `s` that would become part of
+/// the preceding grapheme cluster, rather than start a new one.
+fn grapheme_extension_length(s: &str) -> usize {
+    let with_base = format!("x{s}");
+    let first_grapheme = with_base.graphemes(true).next().unwrap_or_default();
+    first_grapheme.len() - "x".len()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,7 +195,97 @@ mod tests {
         assert_eq!(s.length(), limit);
     }
 
+    #[test]
+    fn t_does_not_add_combining_characters_of_dropped_graphemes() {
+        let mut s = LimitedString::new(Some(2));
+
+        s.push_str("ab");
+        // "e" with combining acute accent, pushed one character at a time
+        s.push('e');
+        s.push('\u{0301}');
+        assert_eq!(s.into_string(), "ab");
+
+        let mut s = LimitedString::new(Some(2));
+        s.push_str("abe");
+        s.push_str("\u{0301}\u{0302}");
+        assert_eq!(s.into_string(), "ab");
+    }
+
+    #[test]
+    fn t_adds_combining_characters_of_kept_graphemes() {
+        let mut s = LimitedString::new(Some(2));
+
+        s.push('e');
+        s.push('\u{0301}');
+        s.push_str("\u{0302}x");
+        assert_eq!(s.into_string(), "e\u{0301}\u{0302}x");
+    }
+
+    #[test]
+    fn t_ellipsis_is_only_added_if_content_was_truncated() {
+        let mut s = LimitedString::new(Some(5)).with_ellipsis("…");
+        s.push_str("hello");
+        assert_eq!(s.into_string(), "hello");
+
+        let mut s = LimitedString::new(Some(5)).with_ellipsis("…");
+        s.push_str("hello!");
+        assert_eq!(s.into_string(), "hell…");
+
+        let mut s = LimitedString::new(None).with_ellipsis("…");
+        s.push_str("hello, world!");
+        assert_eq!(s.into_string(), "hello, world!");
+    }
+
+    #[test]
+    fn t_ellipsis_replaces_whole_wide_characters() {
+        let mut s = LimitedString::new(Some(5)).with_ellipsis("…");
+        s.push_str("ＡＢＣ");
+        assert_eq!(s.into_string(), "ＡＢ…");
+
+        let mut s = LimitedString::new(Some(4)).with_ellipsis("…");
+        s.push_str("ＡＢＣ");
+        assert_eq!(s.into_string(), "Ａ…");
+    }
+
+    #[test]
+    fn t_ellipsis_is_omitted_if_it_does_not_fit() {
+        let mut s = LimitedString::new(Some(2)).with_ellipsis("...");
+        s.push_str("hello");
+        assert_eq!(s.into_string(), "he");
+    }
+
+    #[test]
+    fn t_length_accounts_for_graphemes_extended_by_later_pushes() {
+        let mut s = LimitedString::new(None);
+
+        s.push('e');
+        s.push('\u{0301}');
+        assert_eq!(s.length(), utils::graphemes_width("e\u{0301}"));
+
+        // Woman, zero-width joiner, laptop: a single emoji
+        s.push('\u{1F469}');
+        s.push('\u{200D}');
+        s.push('\u{1F4BB}');
+        assert_eq!(
+            s.length(),
+            utils::graphemes_width("e\u{0301}\u{1F469}\u{200D}\u{1F4BB}")
+        );
+    }
+
     proptest::proptest! {
+        #[test]
+        fn length_matches_the_width_of_the_content(
+            limit in proptest::option::of(1usize..1024),
+            ref input in "\\PC*")
+        {
+            let mut s = LimitedString::new(limit);
+            let _ = input
+                .chars()
+                .map(|chr| s.push(chr))
+                .collect::<Vec<_>>();
+            assert_eq!(s.length(), utils::graphemes_width(&s.content));
+        }
+
         #[test]
         fn length_never_exceeds_the_limit_one_string(
             limit in 1usize..1024,
@@ -165,6 +325,17 @@ mod tests {
             assert!(s.length() <= limit);
         }
 
+        #[test]
+        fn length_never_exceeds_the_limit_with_ellipsis(
+            limit in 1usize..1024,
+            ref ellipsis in "\\PC{0,4}",
+            ref input in "\\PC*")
+        {
+            let mut s = LimitedString::new(Some(limit)).with_ellipsis(ellipsis);
+            s.push_str(input);
+            assert!(utils::graphemes_width(&s.into_string()) <= limit);
+        }
+
         #[test]
         fn push_cannot_make_the_string_exceed_the_limit(
             limit in 1usize..1024,
@@ -2,11 +2,13 @@
 
 mod limited_string;
 mod parser;
+mod validator;
 
 use crate::utils;
 use limited_string::LimitedString;
 use parser::{Padding, Specifier, parse};
 use std::collections::BTreeMap;
+pub use validator::{FormatError, validate};
 
 /// Produces strings of values in a specified format, strftime(3)-like.
 ///
@@ -56,20 +58,25 @@ use std::collections::BTreeMap;
 pub struct FmtStrFormatter {
     /// Stores keys and their values.
     fmts: BTreeMap<char, String>,
+
+    /// Text that marks the place where a value or the whole result was truncated.
+    ellipsis: String,
 }
 
-struct StringParts {
+struct StringParts<'a> {
     head: LimitedString,
     spaced_tail: Option<(char, LimitedString)>,
     width: Option<usize>,
+    ellipsis: &'a str,
 }
 
-impl StringParts {
-    fn new(width: Option<usize>) -> Self {
+impl<'a> StringParts<'a> {
+    fn new(width: Option<usize>, ellipsis: &'a str) -> Self {
         Self {
-            head: LimitedString::new(width),
+            head: LimitedString::new(width).with_ellipsis(ellipsis),
             spaced_tail: None,
             width,
+            ellipsis,
         }
     }
 
@@ -79,7 +86,10 @@ impl StringParts {
         } else {
             self.head.push(spacing);
             let remaining = self.width.map(|w| w.saturating_sub(self.head.length()));
-            self.spaced_tail = Some((spacing, LimitedString::new(remaining)));
+            self.spaced_tail = Some((
+                spacing,
+                LimitedString::new(remaining).with_ellipsis(self.ellipsis),
+            ));
         }
     }
 
@@ -91,7 +101,7 @@ impl StringParts {
         }
     }
 
-    fn join(&mut self, parts: Self) {
+    fn join(&mut self, parts: StringParts<'_>) {
         if let Some((_, ref mut tail)) = self.spaced_tail {
             tail.push_str(&parts.head.into_string());
             if let Some((_, rest)) = parts.spaced_tail {
@@ -127,9 +137,20 @@ impl FmtStrFormatter {
     pub fn new() -> FmtStrFormatter {
         FmtStrFormatter {
             fmts: BTreeMap::new(),
+            ellipsis: String::new(),
         }
     }
 
+    /// Sets the text that replaces the end of truncated values.
+    ///
+    /// Values are truncated when they don't fit into the width specified by their padding, and
+    /// the whole result is truncated when it doesn't fit into the width passed to `do_format`.
+    /// By default, the ellipsis is empty, i.e. the text is simply cut. Truncation never splits
+    /// grapheme clusters or wide characters, and the ellipsis counts towards the width.
+    pub fn set_ellipsis(&mut self, ellipsis: &str) {
+        self.ellipsis = ellipsis.to_owned();
+    }
+
     /// Adds a key-value pair to the formatter.
     pub fn register_fmt(&mut self, key: char, value: String) {
         self.fmts.insert(key, value);
@@ -138,30 +159,27 @@ impl FmtStrFormatter {
     /// Takes a format string and replaces format specifiers with their values.
     pub fn do_format(&self, format: &str, width: u32) -> String {
         let ast = parse(format);
-        self.formatting_helper(&ast, width).into_string()
+        self.formatting_helper(&ast, width, &BTreeMap::new())
+            .into_string()
     }
 
-    fn format_format(&self, c: char, padding: &Padding, width: u32, result: &mut StringParts) {
+    fn format_format(
+        &self,
+        c: char,
+        padding: &Padding,
+        width: u32,
+        auto_widths: &BTreeMap<char, usize>,
+        result: &mut StringParts,
+    ) {
         let empty_string = String::new();
         let value = self.fmts.get(&c).unwrap_or(&empty_string);
+        let ellipsis = &self.ellipsis;
         match *padding {
             Padding::None => result.push_str(value),
 
-            Padding::Left(total_width) => {
-                let text = &utils::substr_with_width(value, total_width);
-                let padding_width = total_width - utils::strwidth(text);
-                let padding = String::from(" ").repeat(padding_width);
-                result.push_str(&padding);
-                result.push_str(text);
-            }
+            Padding::Left(total_width) => pad_left(value, total_width, ellipsis, result),
 
-            Padding::Right(total_width) => {
-                let text = &utils::substr_with_width(value, total_width);
-                let padding_width = total_width - utils::strwidth(text);
-                let padding = String::from(" ").repeat(padding_width);
-                result.push_str(text);
-                result.push_str(&padding);
-            }
+            Padding::Right(total_width) => pad_right(value, total_width, ellipsis, result),
 
             Padding::Center(total_width) => {
                 let w = if total_width == 0 {
@@ -169,8 +187,8 @@ impl FmtStrFormatter {
                 } else {
                     total_width
                 };
-                let text = &utils::substr_with_width(value, w);
-                let padding_width = w - utils::strwidth(text);
+                let text = &utils::truncate_with_ellipsis(value, w, ellipsis);
+                let padding_width = w - utils::graphemes_width(text);
                 if padding_width > 0 {
                     let left: usize = padding_width / 2;
                     let right: usize = padding_width - left;
@@ -183,6 +201,16 @@ impl FmtStrFormatter {
                     result.push_str(text);
                 }
             }
+
+            Padding::AutoLeft(limit) => match auto_widths.get(&c) {
+                Some(&total_width) => pad_left(value, total_width, ellipsis, result),
+                None => push_limited(value, limit, ellipsis, result),
+            },
+
+            Padding::AutoRight(limit) => match auto_widths.get(&c) {
+                Some(&total_width) => pad_right(value, total_width, ellipsis, result),
+                None => push_limited(value, limit, ellipsis, result),
+            },
         }
     }
 
@@ -192,38 +220,129 @@ impl FmtStrFormatter {
         then: &[Specifier],
         els: &Option<Vec<Specifier>>,
         width: u32,
+        auto_widths: &BTreeMap<char, usize>,
         result: &mut StringParts,
     ) {
+        if let Some(branch) = self.conditional_branch(cond, then, els) {
+            result.join(self.formatting_helper(branch, width, auto_widths));
+        }
+    }
+
+    /// Returns the branch of the conditional that should be displayed, if any.
+    fn conditional_branch<'a, 'b>(
+        &self,
+        cond: char,
+        then: &'a [Specifier<'b>],
+        els: &'a Option<Vec<Specifier<'b>>>,
+    ) -> Option<&'a [Specifier<'b>]> {
         match self.fmts.get(&cond) {
-            Some(value) if !value.trim().is_empty() => {
-                result.join(self.formatting_helper(then, width));
-            }
-            _ => {
-                if let Some(ref els) = *els {
-                    result.join(self.formatting_helper(els, width));
-                }
-            }
+            Some(value) if !value.trim().is_empty() => Some(then),
+            _ => els.as_deref(),
         }
     }
 
-    fn formatting_helper(&self, format_ast: &[Specifier], width: u32) -> StringParts {
-        let mut result = StringParts::new((width != 0).then_some(width as usize));
+    fn formatting_helper(
+        &self,
+        format_ast: &[Specifier],
+        width: u32,
+        auto_widths: &BTreeMap<char, usize>,
+    ) -> StringParts<'_> {
+        let mut result = StringParts::new((width != 0).then_some(width as usize), &self.ellipsis);
 
         for specifier in format_ast.iter() {
             match *specifier {
                 Specifier::Spacing(c) => result.add_spacing(c),
-                Specifier::Format(c, ref padding) => {
-                    self.format_format(c, padding, width, &mut result)
+                Specifier::Format(c, ref padding, _) => {
+                    self.format_format(c, padding, width, auto_widths, &mut result)
                 }
                 Specifier::Text(s) => result.push_str(s),
-                Specifier::Conditional(cond, ref then, ref els) => {
-                    self.format_conditional(cond, then, els, width, &mut result)
+                Specifier::Conditional(cond, ref then, ref els, _) => {
+                    self.format_conditional(cond, then, els, width, auto_widths, &mut result)
                 }
             }
         }
 
         result
     }
+
+    /// Records the widths of all "auto width" values that would be displayed by this formatter.
+    ///
+    /// For each key, `auto_widths` ends up containing the largest width seen so far, capped by
+    /// the limit specified in the format.
+    fn measure_auto_widths(
+        &self,
+        format_ast: &[Specifier],
+        auto_widths: &mut BTreeMap<char, usize>,
+    ) {
+        for specifier in format_ast.iter() {
+            match *specifier {
+                Specifier::Format(c, Padding::AutoLeft(limit) | Padding::AutoRight(limit), _) => {
+                    let value_width = self.fmts.get(&c).map_or(0, |value| utils::strwidth(value));
+                    let value_width = limit.map_or(value_width, |limit| value_width.min(limit));
+                    let column_width = auto_widths.entry(c).or_insert(0);
+                    *column_width = (*column_width).max(value_width);
+                }
+                Specifier::Conditional(cond, ref then, ref els, _) => {
+                    if let Some(branch) = self.conditional_branch(cond, then, els) {
+                        self.measure_auto_widths(branch, auto_widths);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Formats a list of rows using the same format string, aligning "auto width" columns.
+///
+/// This is the multi-row counterpart of `FmtStrFormatter::do_format`. Each row is represented by
+/// a formatter with its own values. Format specifiers with "auto width" padding (`%*a` pads on the
+/// left, `%-*a` pads on the right) are padded to the width of the widest value of that key among
+/// all the rows. An optional limit can be specified after the asterisk (e.g. `%-*30t`), in which
+/// case the column is never wider than that, and longer values are truncated.
+///
+/// If the same key is used with "auto width" padding more than once, all its occurrences share
+/// the same width.
+///
+/// Returns formatted rows, in the same order as `rows`.
+pub fn do_format_rows(rows: &[FmtStrFormatter], format: &str, width: u32) -> Vec<String> {
+    let ast = parse(format);
+
+    let mut auto_widths = BTreeMap::new();
+    for row in rows {
+        row.measure_auto_widths(&ast, &mut auto_widths);
+    }
+
+    rows.iter()
+        .map(|row| {
+            row.formatting_helper(&ast, width, &auto_widths)
+                .into_string()
+        })
+        .collect()
+}
+
+fn pad_left(value: &str, total_width: usize, ellipsis: &str, result: &mut StringParts) {
+    let text = &utils::truncate_with_ellipsis(value, total_width, ellipsis);
+    let padding_width = total_width - utils::graphemes_width(text);
+    let padding = String::from(" ").repeat(padding_width);
+    result.push_str(&padding);
+    result.push_str(text);
+}
+
+/// Adds the value without padding, as is done when "auto width" value is formatted on its own.
+fn push_limited(value: &str, limit: Option<usize>, ellipsis: &str, result: &mut StringParts) {
+    match limit {
+        Some(limit) => result.push_str(&utils::truncate_with_ellipsis(value, limit, ellipsis)),
+        None => result.push_str(value),
+    }
+}
+
+fn pad_right(value: &str, total_width: usize, ellipsis: &str, result: &mut StringParts) {
+    let text = &utils::truncate_with_ellipsis(value, total_width, ellipsis);
+    let padding_width = total_width - utils::graphemes_width(text);
+    let padding = String::from(" ").repeat(padding_width);
+    result.push_str(text);
+    result.push_str(&padding);
 }
 
 #[cfg(test)]
@@ -651,6 +770,191 @@ mod tests {
         assert_eq!(fmt.do_format("%x? %y", 0), "What's the ultimate answer? 42");
     }
 
+    #[test]
+    fn t_do_format_treats_auto_padding_as_no_padding_for_a_single_row() {
+        let mut fmt = FmtStrFormatter::new();
+
+        fmt.register_fmt('a', "hello".to_string());
+
+        assert_eq!(fmt.do_format("[%*a]", 0), "[hello]");
+        assert_eq!(fmt.do_format("[%-*a]", 0), "[hello]");
+        assert_eq!(fmt.do_format("[%*3a]", 0), "[hel]");
+        assert_eq!(fmt.do_format("[%-*10a]", 0), "[hello]");
+        assert_eq!(fmt.do_format("[%*b]", 0), "[]");
+    }
+
+    fn make_rows(values: &[(&str, &str)]) -> Vec<FmtStrFormatter> {
+        values
+            .iter()
+            .map(|(title, author)| {
+                let mut fmt = FmtStrFormatter::new();
+                fmt.register_fmt('t', title.to_string());
+                fmt.register_fmt('a', author.to_string());
+                fmt
+            })
+            .collect()
+    }
+
+    #[test]
+    fn t_do_format_rows_pads_auto_columns_to_the_widest_value() {
+        let rows = make_rows(&[("Short", "Jane Doe"), ("A longer title", "Bob"), ("", "")]);
+
+        assert_eq!(
+            do_format_rows(&rows, "%-*t | %*a |", 0),
+            vec![
+                "Short          | Jane Doe |",
+                "A longer title |      Bob |",
+                "               |          |",
+            ]
+        );
+    }
+
+    #[test]
+    fn t_do_format_rows_caps_auto_columns_at_the_limit() {
+        let rows = make_rows(&[("Short", "Jane Doe"), ("A longer title", "Bob")]);
+
+        assert_eq!(
+            do_format_rows(&rows, "%-*8t|%-*20a|", 0),
+            vec!["Short   |Jane Doe|", "A longer|Bob     |"]
+        );
+    }
+
+    #[test]
+    fn t_do_format_rows_leaves_fixed_width_columns_alone() {
+        let rows = make_rows(&[("Short", "Jane Doe"), ("A longer title", "Bob")]);
+
+        assert_eq!(
+            do_format_rows(&rows, "%-6t|%a", 0),
+            vec!["Short |Jane Doe", "A long|Bob"]
+        );
+    }
+
+    #[test]
+    fn t_do_format_rows_measures_wide_characters_by_their_display_width() {
+        let rows = make_rows(&[("ＡＢＣ", "x"), ("abc", "y")]);
+
+        assert_eq!(
+            do_format_rows(&rows, "%-*t|%a", 0),
+            vec!["ＡＢＣ|x", "abc   |y"]
+        );
+    }
+
+    #[test]
+    fn t_do_format_rows_only_measures_values_in_displayed_conditional_branches() {
+        let rows = make_rows(&[
+            ("A title which is not shown", ""),
+            ("Short", "Bob"),
+            ("Tiny", "Alice"),
+        ]);
+
+        assert_eq!(
+            do_format_rows(&rows, "%?a?%-*t&-?|", 0),
+            vec!["-|", "Short|", "Tiny |"]
+        );
+    }
+
+    #[test]
+    fn t_do_format_rows_respects_total_width() {
+        let rows = make_rows(&[("Short", "Jane Doe"), ("A longer title", "Bob")]);
+
+        assert_eq!(
+            do_format_rows(&rows, "%-*t %a", 10),
+            vec!["Short     ", "A longer t"]
+        );
+    }
+
+    #[test]
+    fn t_do_format_rows_returns_nothing_for_no_rows() {
+        assert!(do_format_rows(&[], "%-*t", 0).is_empty());
+    }
+
+    #[test]
+    fn t_do_format_truncates_padded_values_with_ellipsis() {
+        let mut fmt = FmtStrFormatter::new();
+        fmt.set_ellipsis("…");
+
+        fmt.register_fmt('a', "hello".to_string());
+
+        assert_eq!(fmt.do_format("[%4a]", 0), "[hel…]");
+        assert_eq!(fmt.do_format("[%-4a]", 0), "[hel…]");
+        assert_eq!(fmt.do_format("[%=4a]", 0), "[hel…]");
+        assert_eq!(fmt.do_format("[%-*3a]", 0), "[he…]");
+        assert_eq!(fmt.do_format("[%5a]", 0), "[hello]");
+        assert_eq!(fmt.do_format("[%-7a]", 0), "[hello  ]");
+    }
+
+    #[test]
+    fn t_do_format_truncates_the_whole_result_with_ellipsis() {
+        let mut fmt = FmtStrFormatter::new();
+        fmt.set_ellipsis("…");
+
+        fmt.register_fmt('a', "hello".to_string());
+        fmt.register_fmt('b', "world".to_string());
+
+        assert_eq!(fmt.do_format("%a %b", 11), "hello world");
+        assert_eq!(fmt.do_format("%a %b", 10), "hello wor…");
+        assert_eq!(fmt.do_format("%a%> %b", 8), "hello w…");
+        assert_eq!(fmt.do_format("%?a?%a %b?", 8), "hello w…");
+    }
+
+    #[test]
+    fn t_do_format_truncates_cjk_titles_by_display_width() {
+        let mut fmt = FmtStrFormatter::new();
+        fmt.set_ellipsis("…");
+
+        // "Newsboat is an RSS reader" in Japanese
+        fmt.register_fmt('t', "ニュースボートはRSSリーダー".to_string());
+
+        assert_eq!(fmt.do_format("%-10t|", 0), "ニュース… |");
+        assert_eq!(fmt.do_format("%-9t|", 0), "ニュース…|");
+        assert_eq!(fmt.do_format("%-8t|", 0), "ニュー… |");
+        assert_eq!(fmt.do_format("%t", 7), "ニュー…");
+        assert_eq!(fmt.do_format("%t", 6), "ニュ…");
+    }
+
+    #[test]
+    fn t_do_format_never_splits_emoji_sequences() {
+        let mut fmt = FmtStrFormatter::new();
+        fmt.set_ellipsis("…");
+
+        // Family emoji (man, ZWJ, woman, ZWJ, girl) and a flag (two regional indicators)
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let flag = "\u{1F1F3}\u{1F1F1}";
+        fmt.register_fmt('t', format!("{family}{flag} news"));
+
+        assert_eq!(fmt.do_format("%-5t|", 0), format!("{family}{flag}…|"));
+        assert_eq!(fmt.do_format("%-4t|", 0), format!("{family}… |"));
+        assert_eq!(fmt.do_format("%-3t|", 0), format!("{family}…|"));
+        assert_eq!(fmt.do_format("%-2t|", 0), "… |");
+        assert_eq!(fmt.do_format("%t", 4), format!("{family}…"));
+    }
+
+    #[test]
+    fn t_do_format_never_splits_combining_characters() {
+        let mut fmt = FmtStrFormatter::new();
+        fmt.set_ellipsis("…");
+
+        // "Crème brûlée", written with combining accents
+        fmt.register_fmt('t', "Cre\u{0300}me bru\u{0302}le\u{0301}e".to_string());
+
+        assert_eq!(fmt.do_format("%-4t", 0), "Cre\u{0300}…");
+        assert_eq!(fmt.do_format("%t", 3), "Cr…");
+        assert_eq!(
+            fmt.do_format("%t", 12),
+            "Cre\u{0300}me bru\u{0302}le\u{0301}e"
+        );
+    }
+
+    #[test]
+    fn t_do_format_without_ellipsis_just_cuts_the_text() {
+        let mut fmt = FmtStrFormatter::new();
+
+        fmt.register_fmt('t', "ニュースボート".to_string());
+
+        assert_eq!(fmt.do_format("%-9t|", 0), "ニュース |");
+        assert_eq!(fmt.do_format("%t", 7), "ニュー");
+    }
+
     proptest::proptest! {
         #[test]
         fn does_not_crash_when_formatting_with_no_formats_registered(ref input in "\\PC*") {
@@ -672,6 +976,18 @@ mod tests {
             fmt.do_format(&format, 0);
         }
 
+        #[test]
+        fn result_with_ellipsis_is_never_longer_than_specified_width(
+            length in 1u32..10000,
+            ref ellipsis in "\\PC{0,3}",
+            ref input in "\\PC*")
+        {
+            let mut fmt = FmtStrFormatter::new();
+            fmt.set_ellipsis(ellipsis);
+            let result = fmt.do_format(input, length);
+            assert!(utils::graphemes_width(&result) <= length as usize);
+        }
+
         #[test]
         fn result_is_never_longer_than_specified_width(length in 1u32..10000, ref input in "\\PC*") {
             let fmt = FmtStrFormatter::new();
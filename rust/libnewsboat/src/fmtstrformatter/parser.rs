@@ -2,6 +2,7 @@ use nom::IResult;
 use nom::Parser;
 use nom::branch::alt;
 use nom::bytes::complete::{tag, take, take_till1, take_while};
+use nom::combinator::opt;
 use nom::multi::many0;
 use std::cmp::Ordering;
 use std::str;
@@ -17,93 +18,241 @@ pub enum Padding {
     Right(usize),
     /// Pad the value on the left and right equally until it reaches specified width.
     Center(usize),
+    /// Pad the value on the left until it's as wide as the widest value of the same key in
+    /// a list of rows, but no wider than the optional limit.
+    AutoLeft(Option<usize>),
+    /// Pad the value on the right until it's as wide as the widest value of the same key in
+    /// a list of rows, but no wider than the optional limit.
+    AutoRight(Option<usize>),
 }
 
 /// Describes all the different "format specifiers" we support, plus a chunk of text that would be
 /// copied to the output verbatim.
+///
+/// Positions are zero-based offsets (in characters, not bytes) of the `%` sign that starts the
+/// specifier.
 #[derive(PartialEq, Eq, Debug)]
 pub enum Specifier<'a> {
     /// Will expand to pad everything that comes next to the right. Given char is used for padding.
     Spacing(char),
-    /// A format to be replaced with a value (`%a`, `%t` etc.), padded to the given width on the
-    /// left (if it's positive) or on the right (if it's negative).
-    Format(char, Padding),
+    /// A format to be replaced with a value (`%a`, `%t` etc.), padded as described by `Padding`.
+    /// The last field is the position.
+    Format(char, Padding, usize),
     /// A chunk of text that will be copied to the output verbatim.
     Text(&'a str),
     /// Conditional format that is replaced by one of the sub-formats depending on the value of the
-    /// given key. "Else" branch might be missing.
-    Conditional(char, Vec<Specifier<'a>>, Option<Vec<Specifier<'a>>>),
+    /// given key. "Else" branch might be missing. The last field is the position.
+    Conditional(char, Vec<Specifier<'a>>, Option<Vec<Specifier<'a>>>, usize),
 }
 
-fn escaped_percent_sign(input: &str) -> IResult<&str, Specifier<'_>> {
+/// A problem that stops the format string from being parsed.
+#[derive(PartialEq, Eq, Debug)]
+pub enum ErrorKind {
+    /// Conditional is missing its condition key, one of its question marks, or contains another
+    /// conditional.
+    MalformedConditional,
+    /// Format string ends in the middle of a format specifier.
+    UnterminatedSpecifier,
+    /// Width or limit is not a number, or is too large.
+    InvalidWidth,
+}
+
+/// A problem that stops the format string from being parsed, and the position (in characters) of
+/// the `%` sign that starts the offending format specifier.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ParseError {
+    pub kind: ErrorKind,
+    pub position: usize,
+}
+
+/// Error of the nom parsers below.
+///
+/// In strict mode, once a parser is sure it's looking at a particular specifier, it turns its
+/// errors into failures with a `kind`, so that `alt` doesn't try other alternatives and the whole
+/// parse stops at the specifier's `%` sign.
+#[derive(Debug)]
+struct Error<'a> {
+    input: &'a str,
+    kind: Option<ErrorKind>,
+}
+
+impl<'a> nom::error::ParseError<&'a str> for Error<'a> {
+    fn from_error_kind(input: &'a str, _kind: nom::error::ErrorKind) -> Self {
+        Error { input, kind: None }
+    }
+
+    fn append(_input: &'a str, _kind: nom::error::ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+type PResult<'a, O> = IResult<&'a str, O, Error<'a>>;
+
+/// Fails the specifier that starts at `start`.
+fn fail<'a, O>(start: &'a str, kind: ErrorKind) -> PResult<'a, O> {
+    Err(nom::Err::Failure(Error {
+        input: start,
+        kind: Some(kind),
+    }))
+}
+
+/// In strict mode, turns an error of `result` into a failure of the specifier that starts at
+/// `start`. Otherwise, returns `result` as is, so that other alternatives get a chance.
+fn commit<'a, O>(
+    strict: bool,
+    start: &'a str,
+    kind: ErrorKind,
+    result: PResult<'a, O>,
+) -> PResult<'a, O> {
+    match result {
+        Err(nom::Err::Error(_)) if strict => fail(start, kind),
+        other => other,
+    }
+}
+
+/// Parses a width or a limit. If it's invalid, strict mode fails the specifier that starts at
+/// `start`, while lenient mode returns `None`.
+fn number<'a, T: str::FromStr>(
+    strict: bool,
+    start: &'a str,
+    digits: &str,
+) -> Result<Option<T>, nom::Err<Error<'a>>> {
+    match digits.parse::<T>() {
+        Ok(number) => Ok(Some(number)),
+        Err(_) if strict => fail(start, ErrorKind::InvalidWidth).map(|(_, number)| number),
+        Err(_) => Ok(None),
+    }
+}
+
+fn escaped_percent_sign(input: &str) -> PResult<'_, Specifier<'_>> {
     tag("%%")(input).map(|result| (result.0, Specifier::Text(&result.1[0..1])))
 }
 
-fn spacing(input: &str) -> IResult<&str, Specifier<'_>> {
-    let (input, _) = tag("%>")(input)?;
-    let (input, c) = take(1usize)(input)?;
+fn spacing(input: &str, strict: bool) -> PResult<'_, Specifier<'_>> {
+    let (rest, _) = tag("%>")(input)?;
+    let (rest, c) = commit(
+        strict,
+        input,
+        ErrorKind::UnterminatedSpecifier,
+        take(1usize)(rest),
+    )?;
 
     // unwrap() won't panic because we use take!(1) in parser above
     let chr = c.chars().next().unwrap();
 
-    Ok((input, Specifier::Spacing(chr)))
+    Ok((rest, Specifier::Spacing(chr)))
 }
 
-fn center_format(input: &str) -> IResult<&str, Specifier<'_>> {
-    let (input, _) = tag("%=")(input)?;
-    let (input, width) = take_while(|chr: char| chr.is_ascii() && (chr.is_numeric()))(input)?;
-    let (input, format) = take(1usize)(input)?;
+fn center_format(input: &str, strict: bool) -> PResult<'_, Specifier<'_>> {
+    let (rest, _) = tag("%=")(input)?;
+    let (rest, width) = take_while(|chr: char| chr.is_ascii_digit())(rest)?;
+    let (rest, format) = commit(
+        strict,
+        input,
+        ErrorKind::UnterminatedSpecifier,
+        take(1usize)(rest),
+    )?;
 
     // unwrap() won't fail because parser uses take!(1) to get exactly one character
     let format = format.chars().next().unwrap();
-    let width = width.parse::<usize>().unwrap_or(0);
+    let width = if width.is_empty() {
+        0
+    } else {
+        number(strict, input, width)?.unwrap_or(0)
+    };
 
-    Ok((input, Specifier::Format(format, Padding::Center(width))))
+    Ok((
+        rest,
+        Specifier::Format(format, Padding::Center(width), input.len()),
+    ))
 }
 
-fn padded_format(input: &str) -> IResult<&str, Specifier<'_>> {
-    let (input, _) = tag("%")(input)?;
-    let (input, width) =
-        take_while(|chr: char| chr.is_ascii() && (chr.is_numeric() || chr == '-'))(input)?;
-    let (input, format) = take(1usize)(input)?;
+fn padded_format(input: &str, strict: bool) -> PResult<'_, Specifier<'_>> {
+    let (rest, _) = tag("%")(input)?;
+    let (rest, width) =
+        take_while(|chr: char| chr.is_ascii() && (chr.is_numeric() || chr == '-'))(rest)?;
+    let (rest, format) = commit(
+        strict,
+        input,
+        ErrorKind::UnterminatedSpecifier,
+        take(1usize)(rest),
+    )?;
 
     // unwrap() won't fail because parser uses take!(1) to get exactly one character
     let format = format.chars().next().unwrap();
 
-    let width = width.parse::<isize>().unwrap_or(0);
+    // Conditionals are parsed before this, so this is a conditional inside of another one
+    if strict && width.is_empty() && format == '?' {
+        return fail(input, ErrorKind::MalformedConditional);
+    }
+
+    let width = if width.is_empty() {
+        0
+    } else {
+        number(strict, input, width)?.unwrap_or(0isize)
+    };
     let padding = match width.cmp(&0isize) {
         Ordering::Equal => Padding::None,
         Ordering::Greater => Padding::Left(width.unsigned_abs()),
         Ordering::Less => Padding::Right(width.unsigned_abs()),
     };
 
-    Ok((input, Specifier::Format(format, padding)))
+    Ok((rest, Specifier::Format(format, padding, input.len())))
 }
 
-fn text_outside_conditional(input: &str) -> IResult<&str, Specifier<'_>> {
+fn auto_format(input: &str, strict: bool) -> PResult<'_, Specifier<'_>> {
+    let (rest, _) = tag("%")(input)?;
+    let (rest, minus) = opt(tag("-")).parse(rest)?;
+    let (rest, _) = tag("*")(rest)?;
+    let (rest, limit) = take_while(|chr: char| chr.is_ascii_digit())(rest)?;
+    let (rest, format) = commit(
+        strict,
+        input,
+        ErrorKind::UnterminatedSpecifier,
+        take(1usize)(rest),
+    )?;
+
+    // unwrap() won't fail because parser uses take!(1) to get exactly one character
+    let format = format.chars().next().unwrap();
+
+    let limit = if limit.is_empty() {
+        None
+    } else {
+        number(strict, input, limit)?
+    };
+    let padding = match minus {
+        Some(_) => Padding::AutoRight(limit),
+        None => Padding::AutoLeft(limit),
+    };
+
+    Ok((rest, Specifier::Format(format, padding, input.len())))
+}
+
+fn text_outside_conditional(input: &str) -> PResult<'_, Specifier<'_>> {
     let (input, text) = take_till1(|chr: char| chr == '%')(input)?;
 
     Ok((input, Specifier::Text(text)))
 }
 
-fn text_inside_conditional(input: &str) -> IResult<&str, Specifier<'_>> {
+fn text_inside_conditional(input: &str) -> PResult<'_, Specifier<'_>> {
     let (input, text) = take_till1(|chr: char| chr == '%' || chr == '&' || chr == '?')(input)?;
 
     Ok((input, Specifier::Text(text)))
 }
 
-fn conditional(input: &str) -> IResult<&str, Specifier<'_>> {
+type ConditionalParts<'a> = (char, Vec<Specifier<'a>>, Option<Vec<Specifier<'a>>>);
+
+fn conditional_parts(input: &str, strict: bool) -> PResult<'_, ConditionalParts<'_>> {
     // Prepared partial parsers
-    let start_tag = tag("%?");
     let mut condition = take(1usize);
     let then_tag = tag("?");
-    let then_branch = conditional_branch;
+    let then_branch = |input| conditional_branch(input, strict);
     let else_tag = tag("&");
     let end_tag = tag("?");
 
     let some_else_branch = |input| {
         let (input, _) = else_tag(input)?;
-        let (input, els) = conditional_branch(input)?;
+        let (input, els) = conditional_branch(input, strict)?;
         let (input, _) = end_tag(input)?;
         Ok((input, Some(els)))
     };
@@ -114,7 +263,6 @@ fn conditional(input: &str) -> IResult<&str, Specifier<'_>> {
     let mut else_branch = alt((some_else_branch, none_else_branch));
 
     // Input parsing
-    let (input, _) = start_tag(input)?;
     let (input, cond) = condition(input)?;
     let (input, _) = then_tag(input)?;
     let (input, then) = then_branch(input)?;
@@ -123,27 +271,45 @@ fn conditional(input: &str) -> IResult<&str, Specifier<'_>> {
     // unwrap() won't panic because we're using take!(1) to get exactly one character
     let cond = cond.chars().next().unwrap();
 
-    Ok((input, Specifier::Conditional(cond, then, els)))
+    Ok((input, (cond, then, els)))
+}
+
+fn conditional(input: &str, strict: bool) -> PResult<'_, Specifier<'_>> {
+    let (rest, _) = tag("%?")(input)?;
+    let (rest, (cond, then, els)) = commit(
+        strict,
+        input,
+        ErrorKind::MalformedConditional,
+        conditional_parts(rest, strict),
+    )?;
+
+    Ok((rest, Specifier::Conditional(cond, then, els, input.len())))
 }
 
-fn conditional_branch(input: &str) -> IResult<&str, Vec<Specifier<'_>>> {
+fn conditional_branch(input: &str, strict: bool) -> PResult<'_, Vec<Specifier<'_>>> {
     let alternatives = (
         escaped_percent_sign,
-        spacing,
-        center_format,
-        padded_format,
+        |input| spacing(input, strict),
+        |input| center_format(input, strict),
+        |input| auto_format(input, strict),
+        |input| padded_format(input, strict),
         text_inside_conditional,
     );
     many0(alt(alternatives)).parse(input)
 }
 
-fn parser(input: &str) -> IResult<&str, Vec<Specifier<'_>>> {
+/// Parses `input`. In strict mode, the first specifier that can't be parsed fails the whole
+/// parse. Otherwise, parsers make the best of what they're given: invalid widths are ignored,
+/// malformed conditionals are treated as `%?` formats, and parsing stops (without an error) at
+/// a `%` sign that ends the input.
+fn parser(input: &str, strict: bool) -> PResult<'_, Vec<Specifier<'_>>> {
     let alternatives = (
-        conditional,
+        |input| conditional(input, strict),
         escaped_percent_sign,
-        spacing,
-        center_format,
-        padded_format,
+        |input| spacing(input, strict),
+        |input| center_format(input, strict),
+        |input| auto_format(input, strict),
+        |input| padded_format(input, strict),
         text_outside_conditional,
     );
     many0(alt(alternatives)).parse(input)
@@ -151,7 +317,7 @@ fn parser(input: &str) -> IResult<&str, Vec<Specifier<'_>>> {
 
 fn sanitize(mut input: Vec<Specifier>) -> Vec<Specifier> {
     input.retain(|s| {
-        if let Specifier::Format(c, ref _b) = *s {
+        if let Specifier::Format(c, _, _) = *s {
             c.is_ascii()
         } else {
             true
@@ -160,9 +326,59 @@ fn sanitize(mut input: Vec<Specifier>) -> Vec<Specifier> {
     input
 }
 
+/// Converts a position recorded by the parsers, which is the length of the remaining input, into
+/// the position in characters from the start of `input`.
+fn char_position(input: &str, remaining: usize) -> usize {
+    input[..input.len() - remaining].chars().count()
+}
+
+fn locate(specifiers: &mut [Specifier], input: &str) {
+    for specifier in specifiers {
+        match specifier {
+            Specifier::Format(_, _, position) => *position = char_position(input, *position),
+            Specifier::Conditional(_, then, els, position) => {
+                *position = char_position(input, *position);
+                locate(then, input);
+                if let Some(els) = els {
+                    locate(els, input);
+                }
+            }
+            Specifier::Spacing(_) | Specifier::Text(_) => {}
+        }
+    }
+}
+
+/// Parses `input`, returning an error for the first specifier that can't be parsed.
+pub fn parse_strict(input: &str) -> Result<Vec<Specifier<'_>>, ParseError> {
+    match parser(input, true) {
+        // In strict mode, `parser` consumes any text, and fails if it can't parse a specifier, so
+        // there are no leftovers
+        Ok((_leftovers, mut ast)) => {
+            locate(&mut ast, input);
+            Ok(ast)
+        }
+        Err(nom::Err::Failure(Error {
+            input: rest,
+            kind: Some(kind),
+        })) => Err(ParseError {
+            kind,
+            position: char_position(input, rest.len()),
+        }),
+        // Can't happen, as the parsers turn all their errors into failures
+        Err(_) => Err(ParseError {
+            kind: ErrorKind::UnterminatedSpecifier,
+            position: 0,
+        }),
+    }
+}
+
+/// Parses `input`, making the best of specifiers that can't be parsed (see `parser`).
 pub fn parse(input: &str) -> Vec<Specifier<'_>> {
-    match parser(input) {
-        Ok((_leftovers, ast)) => sanitize(ast),
+    match parser(input, false) {
+        Ok((_leftovers, mut ast)) => {
+            locate(&mut ast, input);
+            sanitize(ast)
+        }
         Err(_) => vec![Specifier::Text("")],
     }
 }
@@ -174,24 +390,21 @@ mod tests {
     #[test]
     fn t_parses_formats_without_specifiers() {
         let input = "Hello, world!";
-        let (leftovers, result) = parser(input).unwrap();
-        assert_eq!(leftovers, "");
+        let result = parse_strict(input).unwrap();
         assert_eq!(result, vec![Specifier::Text("Hello, world!")]);
     }
 
     #[test]
     fn t_replaces_double_percent_with_a_single_percent() {
         let input = "%%";
-        let (leftovers, result) = parser(input).unwrap();
-        assert_eq!(leftovers, "");
+        let result = parse_strict(input).unwrap();
         assert_eq!(result, vec![Specifier::Text("%")]);
     }
 
     #[test]
     fn t_parses_sequences_of_specifiers() {
         let input = "100%% pure Ceylon tea";
-        let (leftovers, result) = parser(input).unwrap();
-        assert_eq!(leftovers, "");
+        let result = parse_strict(input).unwrap();
 
         let expected = vec![
             Specifier::Text("100"),
@@ -204,14 +417,12 @@ mod tests {
     #[test]
     fn t_parses_formats_with_letters() {
         let input = "%t (%a)";
-        let (leftovers, result) = parser(input).unwrap();
-
-        assert_eq!(leftovers, "");
+        let result = parse_strict(input).unwrap();
 
         let expected = vec![
-            Specifier::Format('t', Padding::None),
+            Specifier::Format('t', Padding::None, 0),
             Specifier::Text(" ("),
-            Specifier::Format('a', Padding::None),
+            Specifier::Format('a', Padding::None, 4),
             Specifier::Text(")"),
         ];
         assert_eq!(result, expected);
@@ -220,14 +431,12 @@ mod tests {
     #[test]
     fn t_parses_formats_with_positive_padding() {
         let input = "%8a%4b%13x";
-        let (leftovers, result) = parser(input).unwrap();
-
-        assert_eq!(leftovers, "");
+        let result = parse_strict(input).unwrap();
 
         let expected = vec![
-            Specifier::Format('a', Padding::Left(8)),
-            Specifier::Format('b', Padding::Left(4)),
-            Specifier::Format('x', Padding::Left(13)),
+            Specifier::Format('a', Padding::Left(8), 0),
+            Specifier::Format('b', Padding::Left(4), 3),
+            Specifier::Format('x', Padding::Left(13), 6),
         ];
         assert_eq!(result, expected);
     }
@@ -235,14 +444,26 @@ mod tests {
     #[test]
     fn t_parses_formats_with_negative_padding() {
         let input = "%-8a%-4b%-13x";
-        let (leftovers, result) = parser(input).unwrap();
+        let result = parse_strict(input).unwrap();
+
+        let expected = vec![
+            Specifier::Format('a', Padding::Right(8), 0),
+            Specifier::Format('b', Padding::Right(4), 4),
+            Specifier::Format('x', Padding::Right(13), 8),
+        ];
+        assert_eq!(result, expected);
+    }
 
-        assert_eq!(leftovers, "");
+    #[test]
+    fn t_parses_formats_with_auto_padding() {
+        let input = "%*a%-*b%*12x%-*7y";
+        let result = parse_strict(input).unwrap();
 
         let expected = vec![
-            Specifier::Format('a', Padding::Right(8)),
-            Specifier::Format('b', Padding::Right(4)),
-            Specifier::Format('x', Padding::Right(13)),
+            Specifier::Format('a', Padding::AutoLeft(None), 0),
+            Specifier::Format('b', Padding::AutoRight(None), 3),
+            Specifier::Format('x', Padding::AutoLeft(Some(12)), 7),
+            Specifier::Format('y', Padding::AutoRight(Some(7)), 12),
         ];
         assert_eq!(result, expected);
     }
@@ -250,16 +471,14 @@ mod tests {
     #[test]
     fn t_parses_spacing_format() {
         let input = "%-8a%>m%4b%> %-13x";
-        let (leftovers, result) = parser(input).unwrap();
-
-        assert_eq!(leftovers, "");
+        let result = parse_strict(input).unwrap();
 
         let expected = vec![
-            Specifier::Format('a', Padding::Right(8)),
+            Specifier::Format('a', Padding::Right(8), 0),
             Specifier::Spacing('m'),
-            Specifier::Format('b', Padding::Left(4)),
+            Specifier::Format('b', Padding::Left(4), 7),
             Specifier::Spacing(' '),
-            Specifier::Format('x', Padding::Right(13)),
+            Specifier::Format('x', Padding::Right(13), 13),
         ];
         assert_eq!(result, expected);
     }
@@ -267,14 +486,13 @@ mod tests {
     #[test]
     fn t_parses_conditionals() {
         let input = "%?x?success&failure?";
-        let (leftovers, result) = parser(input).unwrap();
-
-        assert_eq!(leftovers, "");
+        let result = parse_strict(input).unwrap();
 
         let expected = vec![Specifier::Conditional(
             'x',
             vec![Specifier::Text("success")],
             Some(vec![Specifier::Text("failure")]),
+            0,
         )];
         assert_eq!(result, expected);
     }
@@ -282,14 +500,13 @@ mod tests {
     #[test]
     fn t_parses_conditionals_without_else_branch() {
         let input = "%?x?success?";
-        let (leftovers, result) = parser(input).unwrap();
-
-        assert_eq!(leftovers, "");
+        let result = parse_strict(input).unwrap();
 
         let expected = vec![Specifier::Conditional(
             'x',
             vec![Specifier::Text("success")],
             None,
+            0,
         )];
         assert_eq!(result, expected);
     }
@@ -297,25 +514,22 @@ mod tests {
     #[test]
     fn t_parses_conditionals_with_empty_then_branch() {
         let input = "%?x??";
-        let (leftovers, result) = parser(input).unwrap();
-
-        assert_eq!(leftovers, "");
+        let result = parse_strict(input).unwrap();
 
-        let expected = vec![Specifier::Conditional('x', vec![], None)];
+        let expected = vec![Specifier::Conditional('x', vec![], None, 0)];
         assert_eq!(result, expected);
     }
 
     #[test]
     fn t_parses_conditionals_with_empty_then_nonempty_else_branches() {
         let input = "%?x?&nonempty?";
-        let (leftovers, result) = parser(input).unwrap();
-
-        assert_eq!(leftovers, "");
+        let result = parse_strict(input).unwrap();
 
         let expected = vec![Specifier::Conditional(
             'x',
             vec![],
             Some(vec![Specifier::Text("nonempty")]),
+            0,
         )];
         assert_eq!(result, expected);
     }
@@ -323,11 +537,89 @@ mod tests {
     #[test]
     fn t_parses_conditionals_with_empty_then_and_else_branches() {
         let input = "%?x?&?";
-        let (leftovers, result) = parser(input).unwrap();
+        let result = parse_strict(input).unwrap();
+
+        let expected = vec![Specifier::Conditional('x', vec![], Some(vec![]), 0)];
+        assert_eq!(result, expected);
+    }
 
-        assert_eq!(leftovers, "");
+    #[test]
+    fn t_positions_are_counted_in_characters() {
+        let result = parse_strict("ёжик %a%?b?%-3c?").unwrap();
 
-        let expected = vec![Specifier::Conditional('x', vec![], Some(vec![]))];
+        let expected = vec![
+            Specifier::Text("ёжик "),
+            Specifier::Format('a', Padding::None, 5),
+            Specifier::Conditional(
+                'b',
+                vec![Specifier::Format('c', Padding::Right(3), 11)],
+                None,
+                7,
+            ),
+        ];
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn t_reports_the_first_specifier_that_cannot_be_parsed() {
+        let error = |kind, position| Err(ParseError { kind, position });
+
+        assert_eq!(
+            parse_strict("%a %"),
+            error(ErrorKind::UnterminatedSpecifier, 3)
+        );
+        assert_eq!(
+            parse_strict("ё %?a?x&y&z? %"),
+            error(ErrorKind::MalformedConditional, 2)
+        );
+        assert_eq!(
+            parse_strict("%?a?%?b?x??"),
+            error(ErrorKind::MalformedConditional, 4)
+        );
+        assert_eq!(parse_strict("%5-3a"), error(ErrorKind::InvalidWidth, 0));
+    }
+
+    #[test]
+    fn t_reports_widths_that_are_too_large_instead_of_ignoring_them() {
+        let error = Err(ParseError {
+            kind: ErrorKind::InvalidWidth,
+            position: 1,
+        });
+
+        assert_eq!(parse_strict("x%99999999999999999999a"), error);
+        assert_eq!(parse_strict("x%-99999999999999999999a"), error);
+        assert_eq!(parse_strict("x%=99999999999999999999a"), error);
+        assert_eq!(parse_strict("x%*99999999999999999999a"), error);
+    }
+
+    #[test]
+    fn t_parse_makes_the_best_of_specifiers_that_cannot_be_parsed() {
+        assert_eq!(
+            parse("%a %"),
+            vec![
+                Specifier::Format('a', Padding::None, 0),
+                Specifier::Text(" ")
+            ]
+        );
+        assert_eq!(
+            parse("x%99999999999999999999a y"),
+            vec![
+                Specifier::Text("x"),
+                Specifier::Format('a', Padding::None, 1),
+                Specifier::Text(" y")
+            ]
+        );
+        assert_eq!(parse("%-a"), vec![Specifier::Format('a', Padding::None, 0)]);
+        assert_eq!(
+            parse("%*99999999999999999999a"),
+            vec![Specifier::Format('a', Padding::AutoLeft(None), 0)]
+        );
+        assert_eq!(
+            parse("%?a?x"),
+            vec![
+                Specifier::Format('?', Padding::None, 0),
+                Specifier::Text("a?x")
+            ]
+        );
+    }
 }
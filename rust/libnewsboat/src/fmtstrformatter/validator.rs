@@ -0,0 +1,288 @@
+//! Checks format strings for mistakes that the formatter would silently ignore.
+
+use super::parser::{ErrorKind, ParseError, Specifier, parse_strict};
+use gettextrs::gettext;
+use std::collections::BTreeSet;
+use strprintf::fmt;
+
+/// A problem found in a format string.
+///
+/// All positions are zero-based offsets (in characters, not bytes) of the `%` sign that starts the
+/// offending format specifier.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormatError {
+    /// Format specifier uses a key that's not valid in this context.
+    UnknownKey { position: usize, key: char },
+
+    /// Conditional is missing its condition key, one of its question marks, or contains another
+    /// conditional.
+    MalformedConditional { position: usize },
+
+    /// Format string ends in the middle of a format specifier.
+    UnterminatedSpecifier { position: usize },
+
+    /// Width or limit of a format specifier is not a number, or is too large.
+    InvalidWidth { position: usize },
+}
+
+impl From<ParseError> for FormatError {
+    fn from(error: ParseError) -> Self {
+        let position = error.position;
+        match error.kind {
+            ErrorKind::MalformedConditional => FormatError::MalformedConditional { position },
+            ErrorKind::UnterminatedSpecifier => FormatError::UnterminatedSpecifier { position },
+            ErrorKind::InvalidWidth => FormatError::InvalidWidth { position },
+        }
+    }
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match *self {
+            FormatError::UnknownKey { position, key } => fmt!(
+                // The first %s is the key, e.g. "T". The second %s is a zero-based offset into
+                // the format string.
+                &gettext("Unknown format key `%s' at position %s"),
+                &key.to_string(),
+                &position.to_string()
+            ),
+            FormatError::MalformedConditional { position } => fmt!(
+                &gettext("Malformed conditional at position %s"),
+                &position.to_string()
+            ),
+            FormatError::UnterminatedSpecifier { position } => fmt!(
+                &gettext("Unterminated format specifier at position %s"),
+                &position.to_string()
+            ),
+            FormatError::InvalidWidth { position } => fmt!(
+                &gettext("Invalid width at position %s"),
+                &position.to_string()
+            ),
+        };
+        write!(f, "{message}")
+    }
+}
+
+fn check_keys(ast: &[Specifier], valid_keys: &BTreeSet<char>, errors: &mut Vec<FormatError>) {
+    let check_key = |key: char, position: usize, errors: &mut Vec<FormatError>| {
+        if !valid_keys.contains(&key) {
+            errors.push(FormatError::UnknownKey { position, key });
+        }
+    };
+
+    for specifier in ast {
+        match specifier {
+            Specifier::Format(key, _, position) => check_key(*key, *position, errors),
+            Specifier::Conditional(key, then, els, position) => {
+                check_key(*key, *position, errors);
+                check_keys(then, valid_keys, errors);
+                if let Some(els) = els {
+                    check_keys(els, valid_keys, errors);
+                }
+            }
+            Specifier::Spacing(_) | Specifier::Text(_) => {}
+        }
+    }
+}
+
+/// Checks `format` for keys that are not in `valid_keys`, malformed conditionals, unterminated
+/// format specifiers, and invalid widths.
+///
+/// Returns the problems found, in the order in which they appear in the format string. If the
+/// format string can't be parsed, only the first specifier that stopped the parser is reported.
+/// An empty vector means the format string is valid.
+pub fn validate(format: &str, valid_keys: &BTreeSet<char>) -> Vec<FormatError> {
+    match parse_strict(format) {
+        Ok(ast) => {
+            let mut errors = Vec::new();
+            check_keys(&ast, valid_keys, &mut errors);
+            errors
+        }
+        Err(error) => vec![error.into()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(keys: &str) -> BTreeSet<char> {
+        keys.chars().collect()
+    }
+
+    #[test]
+    fn t_accepts_valid_format_strings() {
+        let valid_keys = keys("atD");
+
+        assert_eq!(validate("", &valid_keys), vec![]);
+        assert_eq!(validate("just text", &valid_keys), vec![]);
+        assert_eq!(validate("%t (%a)", &valid_keys), vec![]);
+        assert_eq!(validate("100%% %-20t %5a %=10D", &valid_keys), vec![]);
+        assert_eq!(validate("%*t %-*a %-*30D", &valid_keys), vec![]);
+        assert_eq!(validate("%t%> %a", &valid_keys), vec![]);
+        assert_eq!(validate("%?a?(%a)&anonymous? %t", &valid_keys), vec![]);
+        assert_eq!(validate("%?a?%a?", &valid_keys), vec![]);
+        assert_eq!(validate("%?a?&?", &valid_keys), vec![]);
+        assert_eq!(validate("a & b? c", &valid_keys), vec![]);
+    }
+
+    #[test]
+    fn t_reports_unknown_keys_with_their_positions() {
+        let valid_keys = keys("at");
+
+        assert_eq!(
+            validate("%t %T", &valid_keys),
+            vec![FormatError::UnknownKey {
+                position: 3,
+                key: 'T'
+            }]
+        );
+        assert_eq!(
+            validate("%-20x|%*y|%=5z", &valid_keys),
+            vec![
+                FormatError::UnknownKey {
+                    position: 0,
+                    key: 'x'
+                },
+                FormatError::UnknownKey {
+                    position: 6,
+                    key: 'y'
+                },
+                FormatError::UnknownKey {
+                    position: 10,
+                    key: 'z'
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn t_reports_unknown_keys_in_conditions_and_branches() {
+        let valid_keys = keys("at");
+
+        assert_eq!(
+            validate("%?q?%a&%b?", &valid_keys),
+            vec![
+                FormatError::UnknownKey {
+                    position: 0,
+                    key: 'q'
+                },
+                FormatError::UnknownKey {
+                    position: 7,
+                    key: 'b'
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn t_counts_positions_in_characters() {
+        let valid_keys = keys("a");
+
+        assert_eq!(
+            validate("ёжик %b", &valid_keys),
+            vec![FormatError::UnknownKey {
+                position: 5,
+                key: 'b'
+            }]
+        );
+    }
+
+    #[test]
+    fn t_reports_unterminated_specifiers() {
+        let valid_keys = keys("a");
+
+        assert_eq!(
+            validate("%a %", &valid_keys),
+            vec![FormatError::UnterminatedSpecifier { position: 3 }]
+        );
+        assert_eq!(
+            validate("%-20", &valid_keys),
+            vec![FormatError::UnterminatedSpecifier { position: 0 }]
+        );
+        assert_eq!(
+            validate("%a%>", &valid_keys),
+            vec![FormatError::UnterminatedSpecifier { position: 2 }]
+        );
+        assert_eq!(
+            validate("%=", &valid_keys),
+            vec![FormatError::UnterminatedSpecifier { position: 0 }]
+        );
+        assert_eq!(
+            validate("%-*", &valid_keys),
+            vec![FormatError::UnterminatedSpecifier { position: 0 }]
+        );
+    }
+
+    #[test]
+    fn t_reports_malformed_conditionals() {
+        let valid_keys = keys("ab");
+
+        assert_eq!(
+            validate("%?", &valid_keys),
+            vec![FormatError::MalformedConditional { position: 0 }]
+        );
+        assert_eq!(
+            validate("x %?a", &valid_keys),
+            vec![FormatError::MalformedConditional { position: 2 }]
+        );
+        assert_eq!(
+            validate("%?ab", &valid_keys),
+            vec![FormatError::MalformedConditional { position: 0 }]
+        );
+        assert_eq!(
+            validate("%?a?then&else", &valid_keys),
+            vec![FormatError::MalformedConditional { position: 0 }]
+        );
+        assert_eq!(
+            validate("%?a?one&two&three?", &valid_keys),
+            vec![FormatError::MalformedConditional { position: 0 }]
+        );
+        assert_eq!(
+            validate("%?a?%?b?x??", &valid_keys),
+            vec![FormatError::MalformedConditional { position: 4 }]
+        );
+    }
+
+    #[test]
+    fn t_reports_invalid_widths() {
+        let valid_keys = keys("a");
+
+        assert_eq!(
+            validate("%a %99999999999999999999a", &valid_keys),
+            vec![FormatError::InvalidWidth { position: 3 }]
+        );
+        assert_eq!(
+            validate("%-*99999999999999999999a", &valid_keys),
+            vec![FormatError::InvalidWidth { position: 0 }]
+        );
+    }
+
+    #[test]
+    fn t_error_messages_mention_position() {
+        let error = FormatError::UnknownKey {
+            position: 3,
+            key: 'T',
+        };
+        assert_eq!(error.to_string(), "Unknown format key `T' at position 3");
+
+        let error = FormatError::MalformedConditional { position: 7 };
+        assert_eq!(error.to_string(), "Malformed conditional at position 7");
+
+        let error = FormatError::UnterminatedSpecifier { position: 0 };
+        assert_eq!(
+            error.to_string(),
+            "Unterminated format specifier at position 0"
+        );
+
+        let error = FormatError::InvalidWidth { position: 2 };
+        assert_eq!(error.to_string(), "Invalid width at position 2");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn does_not_crash_on_any_input(ref input in "\\PC*") {
+            let _ = validate(input, &keys("abc"));
+        }
+    }
+}
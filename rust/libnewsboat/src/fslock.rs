@@ -1,14 +1,254 @@
 use crate::logger::{self, Level};
 use gettextrs::gettext;
+use std::ffi::CStr;
 use std::fs::{self, File, OpenOptions};
-use std::io::{Error, Read, Write};
+use std::io::{Error, ErrorKind, Read, Write};
 use std::os::unix::fs::OpenOptionsExt;
 use std::os::unix::io::AsRawFd;
 use std::path::{Path, PathBuf};
 use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use strprintf::fmt;
 
+/// File that holds an ID that changes on every boot.
+const BOOT_ID_PATH: &str = "/proc/sys/kernel/random/boot_id";
+
+/// How often `lock_with_timeout()` checks if the lock was released.
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The process that holds a lock, as recorded in the lock file.
+///
+/// The file starts with the PID on a line of its own, so that older versions and scripts can
+/// still read it. The rest are `key=value` lines; they're missing from files written by older
+/// versions.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LockOwner {
+    pub pid: libc::pid_t,
+    pub hostname: Option<String>,
+    /// ID of the boot during which the lock was taken (Linux only).
+    pub boot_id: Option<String>,
+    /// Start time of the process, in clock ticks since boot (Linux only). Tells a process apart
+    /// from a later one that got the same PID.
+    pub start_time: Option<u64>,
+}
+
+/// What is known about the owner of a lock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerStatus {
+    /// The owner runs on this host.
+    Running,
+    /// The owner ran on this host, but it's gone: it exited, or the host rebooted since.
+    Dead,
+    /// The owner runs on a different host, so we can't tell if it's still there.
+    OtherHost,
+    /// The lock file doesn't say which host the owner runs on.
+    Unknown,
+}
+
+/// How `FsLock::try_lock_or_go_unlocked()` got access to the files protected by the lock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// This process holds the lock, and can modify the files.
+    Exclusive,
+    /// Another process on this host holds the lock, and this process goes on without one. It
+    /// must not modify the files, and nothing stops the other process from modifying them.
+    Unlocked,
+}
+
+impl LockOwner {
+    /// Information about the current process.
+    pub fn current() -> LockOwner {
+        let pid = process::id() as libc::pid_t;
+        LockOwner {
+            pid,
+            hostname: hostname(),
+            boot_id: boot_id(),
+            start_time: process_start_time(pid),
+        }
+    }
+
+    /// Parses the contents of a lock file. Unknown lines are ignored.
+    pub fn parse(contents: &str) -> LockOwner {
+        let mut lines = contents.lines();
+        let mut owner = LockOwner {
+            pid: lines
+                .next()
+                .and_then(|line| line.trim().parse().ok())
+                .unwrap_or(0),
+            ..LockOwner::default()
+        };
+        for line in lines {
+            match line.split_once('=') {
+                Some(("host", value)) => owner.hostname = Some(value.to_owned()),
+                Some(("boot_id", value)) => owner.boot_id = Some(value.to_owned()),
+                Some(("start_time", value)) => owner.start_time = value.parse().ok(),
+                _ => {}
+            }
+        }
+        owner
+    }
+
+    /// Contents of a lock file that records this owner.
+    pub fn serialize(&self) -> String {
+        let mut result = format!("{}\n", self.pid);
+        if let Some(ref hostname) = self.hostname {
+            result += &format!("host={hostname}\n");
+        }
+        if let Some(ref boot_id) = self.boot_id {
+            result += &format!("boot_id={boot_id}\n");
+        }
+        if let Some(start_time) = self.start_time {
+            result += &format!("start_time={start_time}\n");
+        }
+        result
+    }
+
+    /// Checks if the owner is still around, comparing it to `current`, which describes the
+    /// current process.
+    ///
+    /// `start_time_of` returns the start time of a running process with a given PID, `Some(None)`
+    /// if the process is running but its start time is unknown, and `None` if there is no such
+    /// process.
+    pub fn status_with<F>(&self, current: &LockOwner, start_time_of: F) -> OwnerStatus
+    where
+        F: Fn(libc::pid_t) -> Option<Option<u64>>,
+    {
+        let Some(ref hostname) = self.hostname else {
+            return OwnerStatus::Unknown;
+        };
+        if current.hostname.as_ref() != Some(hostname) {
+            return OwnerStatus::OtherHost;
+        }
+
+        if let (Some(boot_id), Some(current_boot_id)) = (&self.boot_id, &current.boot_id)
+            && boot_id != current_boot_id
+        {
+            return OwnerStatus::Dead;
+        }
+
+        match start_time_of(self.pid) {
+            None => OwnerStatus::Dead,
+            Some(Some(start_time)) if self.start_time.is_some_and(|t| t != start_time) => {
+                // The PID was reused by another process
+                OwnerStatus::Dead
+            }
+            Some(_) => OwnerStatus::Running,
+        }
+    }
+
+    /// Checks if the owner is still around.
+    pub fn status(&self) -> OwnerStatus {
+        self.status_with(&LockOwner::current(), |pid| {
+            is_running(pid).then(|| process_start_time(pid))
+        })
+    }
+}
+
+fn hostname() -> Option<String> {
+    let mut buf = [0 as libc::c_char; 256];
+    if unsafe { libc::gethostname(buf.as_mut_ptr(), buf.len()) } != 0 {
+        return None;
+    }
+    // gethostname() doesn't guarantee NUL termination if the name was truncated
+    buf[buf.len() - 1] = 0;
+    let hostname = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    Some(hostname.to_string_lossy().into_owned())
+}
+
+fn boot_id() -> Option<String> {
+    fs::read_to_string(BOOT_ID_PATH)
+        .ok()
+        .map(|id| id.trim().to_owned())
+        .filter(|id| !id.is_empty())
+}
+
+/// Start time of the process, as reported in the 22nd field of `/proc/<pid>/stat`.
+fn process_start_time(pid: libc::pid_t) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The second field is the name of the executable in parentheses, and it can contain spaces
+    // and parentheses itself, so we skip past the last closing parenthesis.
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.split_whitespace().nth(19)?.parse().ok()
+}
+
+fn is_running(pid: libc::pid_t) -> bool {
+    if pid <= 0 {
+        return false;
+    }
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+fn path_for_display(path: &Path) -> String {
+    path.to_str()
+        .map(String::from)
+        .unwrap_or_else(|| gettext("<filename containing invalid UTF-8 codepoint>"))
+}
+
+/// Returns the owner of the lock at `lock_path` if another process holds it. Neither creates the
+/// file nor takes the lock.
+pub fn lock_holder(lock_path: &Path) -> Option<LockOwner> {
+    let mut file = File::open(lock_path).ok()?;
+    if unsafe { libc::lockf(file.as_raw_fd(), libc::F_TEST, 0) } == 0 {
+        return None;
+    }
+    Some(read_owner(&mut file))
+}
+
+/// Removes the lock file at `lock_path` if the process that holds it isn't running on this host,
+/// e.g. if it ran on another host that crashed. Returns `true` if the file was removed, and
+/// `false` if there was nothing to remove.
+///
+/// Locks held by processes that are running on this host are never removed.
+pub fn force_unlock(lock_path: &Path) -> Result<bool, String> {
+    let mut file = match OpenOptions::new().read(true).write(true).open(lock_path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(reason) => {
+            return Err(fmt!(
+                &gettext("Failed to open lock file '%s': %s"),
+                path_for_display(lock_path),
+                reason.to_string()
+            ));
+        }
+    };
+
+    // Nobody holds the lock, so there is nothing to remove. The lock is released when `file` is
+    // closed.
+    if unsafe { libc::lockf(file.as_raw_fd(), libc::F_TLOCK, 0) } == 0 {
+        return Ok(false);
+    }
+
+    let mut buf = String::new();
+    file.read_to_string(&mut buf).ok();
+    let owner = LockOwner::parse(&buf);
+    let status = owner.status();
+    if status == OwnerStatus::Running || (status == OwnerStatus::Unknown && is_running(owner.pid)) {
+        return Err(fmt!(
+            &gettext("Refusing to remove lock '%s': process with PID %s is still running"),
+            path_for_display(lock_path),
+            &owner.pid.to_string()
+        ));
+    }
+
+    log!(
+        Level::Info,
+        "FsLock: forcibly removing lock {} held by {:?}",
+        lock_path.display(),
+        owner
+    );
+    fs::remove_file(lock_path).map_err(|reason| {
+        fmt!(
+            &gettext("Failed to remove lock file '%s': %s"),
+            path_for_display(lock_path),
+            reason.to_string()
+        )
+    })?;
+    Ok(true)
+}
+
 fn remove_lock(lock_path: &Path) {
     fs::remove_file(lock_path).ok();
     log!(
@@ -32,7 +272,86 @@ impl Drop for FsLock {
     }
 }
 
+/// Opens (and possibly creates) the lock file.
+fn open_lock_file(lock_path: &Path) -> Result<File, String> {
+    let mut options = OpenOptions::new();
+    options.create(true).read(true).write(true).mode(0o600);
+    options.open(lock_path).map_err(|reason| {
+        fmt!(
+            &gettext("Failed to open lock file '%s': %s"),
+            path_for_display(lock_path),
+            reason.to_string()
+        )
+    })
+}
+
+/// Locks the file. Returns immediately if locking is not possible.
+fn lock(file: &File) -> bool {
+    unsafe { libc::lockf(file.as_raw_fd(), libc::F_TLOCK, 0) == 0 }
+}
+
+fn read_owner(file: &mut File) -> LockOwner {
+    let mut buf = String::new();
+    file.read_to_string(&mut buf).ok();
+    LockOwner::parse(&buf)
+}
+
+/// Explains why `lock_path` couldn't be locked. If the owner might be running on this host, its
+/// PID is stored into `pid`.
+fn locked_error(
+    lock_path: &Path,
+    owner: &LockOwner,
+    status: OwnerStatus,
+    pid: &mut libc::pid_t,
+) -> String {
+    match status {
+        OwnerStatus::OtherHost => fmt!(
+            &gettext(
+                "Failed to lock '%s', already locked by process with PID %s on host %s. \
+                 If that process isn't running anymore, remove the lock with --force-unlock"
+            ),
+            path_for_display(lock_path),
+            &owner.pid.to_string(),
+            owner.hostname.as_deref().unwrap_or_default()
+        ),
+        OwnerStatus::Dead => fmt!(
+            &gettext(
+                "Failed to lock '%s', already locked by process with PID %s, which isn't \
+                 running anymore. If no other process uses the file, remove the lock with \
+                 --force-unlock"
+            ),
+            path_for_display(lock_path),
+            &owner.pid.to_string()
+        ),
+        OwnerStatus::Unknown if !is_running(owner.pid) => fmt!(
+            &gettext(
+                "Failed to lock '%s', already locked by process with PID %s, which isn't \
+                 running on this host. If it isn't running on other hosts either, remove the \
+                 lock with --force-unlock"
+            ),
+            path_for_display(lock_path),
+            &owner.pid.to_string()
+        ),
+        _ => {
+            *pid = owner.pid;
+            fmt!(
+                &gettext("Failed to lock '%s', already locked by process with PID %s"),
+                path_for_display(lock_path),
+                &owner.pid.to_string()
+            )
+        }
+    }
+}
+
 impl FsLock {
+    /// Locks `new_lock_path`, releasing the lock that was held before, if any.
+    ///
+    /// A lock that the system considers held is never taken over, even if the process recorded
+    /// in the file is gone (e.g. the lock survived a reboot on a network filesystem); the error
+    /// explains what was found, and `force_unlock()` can remove such a lock.
+    ///
+    /// If locking fails because another process on this host holds the lock, its PID is stored
+    /// into `pid`; otherwise, `pid` is set to 0.
     pub fn try_lock(&mut self, new_lock_path: &Path, pid: &mut libc::pid_t) -> Result<(), String> {
         if self.lock_file.is_some() && self.lock_path == new_lock_path {
             return Ok(());
@@ -47,71 +366,135 @@ impl FsLock {
             new_lock_path.display()
         );
 
-        // first we open (and possibly create) the lock file
-        let mut options = OpenOptions::new();
-        options.create(true).read(true).write(true).mode(0o600);
-        let mut file = match options.open(new_lock_path) {
-            Ok(file) => file,
-            Err(reason) => {
-                return Err(fmt!(
-                    &gettext("Failed to open lock file '%s': %s"),
-                    new_lock_path
-                        .to_str()
-                        .unwrap_or(&gettext("<filename containing invalid UTF-8 codepoint>")),
-                    reason.to_string()
-                ));
-            }
-        };
+        let mut file = open_lock_file(new_lock_path)?;
 
-        // then we lock it (returns immediately if locking is not possible)
-        if unsafe { libc::lockf(file.as_raw_fd(), libc::F_TLOCK, 0) } == 0 {
-            log!(
-                Level::Debug,
-                "FsLock: locked `{}', writing PID...",
-                new_lock_path.display()
-            );
-            let pid = process::id().to_string();
-            if let Err(reason) = file.set_len(0).and_then(|_| file.write_all(pid.as_bytes())) {
-                log!(Level::Debug, "FsLock: Failed to write PID");
-                return Err(fmt!(
-                    &gettext("Failed to write PID to lock file '%s': %s"),
-                    new_lock_path
-                        .to_str()
-                        .unwrap_or(&gettext("<filename containing invalid UTF-8 codepoint>")),
-                    reason.to_string()
-                ));
-            }
-            log!(Level::Debug, "FsLock: PID written successfully");
-            if self.lock_file.take().is_some() {
-                remove_lock(&self.lock_path);
-            }
-            self.lock_file = Some(file);
-            self.lock_path = new_lock_path.to_owned();
-            Ok(())
-        } else {
+        if !lock(&file) {
             log!(
                 Level::Error,
                 "FsLock: something went wrong during locking: {}",
                 Error::last_os_error()
             );
 
-            // locking was not successful -> read PID of locking process from the file
-            let mut buf = String::new();
-            if file.read_to_string(&mut buf).is_ok() {
-                *pid = buf.parse().unwrap_or(0);
-            }
+            // locking was not successful -> find out who holds the lock
+            let owner = read_owner(&mut file);
+            let status = owner.status();
             log!(
                 Level::Debug,
-                "FsLock: locking failed, already locked by {}",
-                pid
+                "FsLock: locking failed, already locked by {:?} ({:?})",
+                owner,
+                status
             );
-            Err(fmt!(
-                &gettext("Failed to lock '%s', already locked by process with PID %s"),
-                new_lock_path
-                    .to_str()
-                    .unwrap_or(&gettext("<filename containing invalid UTF-8 codepoint>")),
-                &(*pid).to_string()
-            ))
+            // Even if the recorded owner is gone, the system still considers the file locked:
+            // the lock might've survived a reboot on a network filesystem, or someone else might
+            // have just locked the file and not yet written their PID. Replacing the file could
+            // leave two processes working on the same data, so we only explain what we found.
+            return Err(locked_error(new_lock_path, &owner, status, pid));
+        }
+
+        log!(
+            Level::Debug,
+            "FsLock: locked `{}', writing PID...",
+            new_lock_path.display()
+        );
+        let owner = LockOwner::current().serialize();
+        if let Err(reason) = file
+            .set_len(0)
+            .and_then(|_| file.write_all(owner.as_bytes()))
+        {
+            log!(Level::Debug, "FsLock: Failed to write PID");
+            return Err(fmt!(
+                &gettext("Failed to write PID to lock file '%s': %s"),
+                path_for_display(new_lock_path),
+                reason.to_string()
+            ));
+        }
+        log!(Level::Debug, "FsLock: PID written successfully");
+        if self.lock_file.take().is_some() {
+            remove_lock(&self.lock_path);
+        }
+        self.lock_file = Some(file);
+        self.lock_path = new_lock_path.to_owned();
+        Ok(())
+    }
+
+    /// Like `try_lock()`, but if the lock is held by another process that runs on this host,
+    /// waits up to `timeout` for that process to release it. If the time runs out, the error of
+    /// the last attempt is returned, and the PID of the process is stored into `pid`.
+    pub fn lock_with_timeout(
+        &mut self,
+        new_lock_path: &Path,
+        pid: &mut libc::pid_t,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        let started = Instant::now();
+        let deadline = started + timeout;
+        let mut waiting = false;
+        loop {
+            let result = self.try_lock(new_lock_path, pid);
+            if result.is_ok() {
+                if waiting {
+                    log!(
+                        Level::Info,
+                        "FsLock: locked `{}' after waiting for {:?}",
+                        new_lock_path.display(),
+                        started.elapsed()
+                    );
+                }
+                return result;
+            }
+            // Only a running process releases the lock; other errors won't go away by waiting.
+            if *pid == 0 {
+                return result;
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                log!(
+                    Level::Info,
+                    "FsLock: gave up waiting for `{}' after {:?}",
+                    new_lock_path.display(),
+                    started.elapsed()
+                );
+                return result;
+            }
+            if !waiting {
+                log!(
+                    Level::Info,
+                    "FsLock: `{}' is locked by process {}, waiting up to {:?} for it",
+                    new_lock_path.display(),
+                    pid,
+                    timeout
+                );
+                waiting = true;
+            }
+            thread::sleep(LOCK_RETRY_INTERVAL.min(deadline - now));
+        }
+    }
+
+    /// Like `try_lock()`, but if the lock is held by another process that runs on this host,
+    /// returns `LockMode::Unlocked` instead of failing. The PID of that process is stored into
+    /// `pid`.
+    ///
+    /// This doesn't take any lock at all, so the files aren't protected in any way: the other
+    /// process keeps its exclusive lock and is free to modify them while this process reads
+    /// them. The caller has to cope with that, e.g. by relying on SQLite's own locking.
+    pub fn try_lock_or_go_unlocked(
+        &mut self,
+        new_lock_path: &Path,
+        pid: &mut libc::pid_t,
+    ) -> Result<LockMode, String> {
+        match self.try_lock(new_lock_path, pid) {
+            Ok(()) => Ok(LockMode::Exclusive),
+            Err(_) if *pid != 0 => {
+                log!(
+                    Level::Info,
+                    "FsLock: `{}' is locked by process {}, going on without a lock",
+                    new_lock_path.display(),
+                    pid
+                );
+                Ok(LockMode::Unlocked)
+            }
+            Err(message) => Err(message),
         }
     }
 }
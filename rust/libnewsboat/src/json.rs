@@ -0,0 +1,311 @@
+//! A small JSON parser, for reading back the files that Newsboat writes itself (e.g. exported read
+//! state).
+//!
+//! Numbers are stored as `f64`, and objects keep their members in the order they appear in the
+//! input.
+
+use nom::Parser;
+use nom::{
+    IResult,
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, multispace0},
+    combinator::{all_consuming, map, value},
+    error::{Error, ErrorKind},
+    multi::separated_list0,
+    number::complete::double,
+    sequence::{delimited, separated_pair},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Value of the member `key`, if this is an object that has one. If the key is repeated, the
+    /// first value is returned.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// The number, if this is a number without a fractional part that fits into `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::Number(n)
+                if n.fract() == 0.0 && *n >= i64::MIN as f64 && *n <= i64::MAX as f64 =>
+            {
+                Some(*n as i64)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+}
+
+/// How deeply arrays and objects may be nested. Deeper documents are rejected so that the
+/// recursive parser can't run out of stack.
+const MAX_NESTING: usize = 128;
+
+/// Parses a JSON document. Returns `None` if `input` isn't valid JSON, or if it nests arrays and
+/// objects more than 128 levels deep.
+pub fn parse(input: &str) -> Option<JsonValue> {
+    let mut parser = all_consuming(delimited(
+        multispace0,
+        |input| json_value(input, 0),
+        multispace0,
+    ));
+    parser.parse(input).ok().map(|(_, value)| value)
+}
+
+/// Parses a value that is enclosed by `depth` arrays and objects.
+fn json_value(input: &str, depth: usize) -> IResult<&str, JsonValue> {
+    let mut parser = alt((
+        value(JsonValue::Null, tag("null")),
+        value(JsonValue::Boolean(true), tag("true")),
+        value(JsonValue::Boolean(false), tag("false")),
+        map(json_string, JsonValue::String),
+        map(|input| array(input, depth + 1), JsonValue::Array),
+        map(|input| object(input, depth + 1), JsonValue::Object),
+        map(double, JsonValue::Number),
+    ));
+    parser.parse(input)
+}
+
+fn separator<'a>(c: char) -> impl Parser<&'a str, Output = char, Error = Error<&'a str>> {
+    delimited(multispace0, char(c), multispace0)
+}
+
+/// Fails for good (instead of letting `alt` try other parsers) if an array or object that starts
+/// at `input` would be nested `depth` levels deep, which is more than allowed.
+fn check_nesting(input: &str, depth: usize) -> IResult<&str, ()> {
+    if depth > MAX_NESTING {
+        return Err(nom::Err::Failure(Error::new(input, ErrorKind::TooLarge)));
+    }
+    Ok((input, ()))
+}
+
+fn array(input: &str, depth: usize) -> IResult<&str, Vec<JsonValue>> {
+    let mut parser = delimited(
+        (separator('['), |input| check_nesting(input, depth)),
+        separated_list0(separator(','), |input| json_value(input, depth)),
+        (multispace0, char(']')),
+    );
+    parser.parse(input)
+}
+
+fn object(input: &str, depth: usize) -> IResult<&str, Vec<(String, JsonValue)>> {
+    let member = separated_pair(json_string, separator(':'), |input| {
+        json_value(input, depth)
+    });
+    let mut parser = delimited(
+        (separator('{'), |input| check_nesting(input, depth)),
+        separated_list0(separator(','), member),
+        (multispace0, char('}')),
+    );
+    parser.parse(input)
+}
+
+/// Parses four hex digits of a `\u` escape.
+fn hex4(input: &str) -> Option<(u32, &str)> {
+    let digits = input.get(..4)?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some((u32::from_str_radix(digits, 16).ok()?, &input[4..]))
+}
+
+/// Parses what follows `\u`, including the second half of a surrogate pair.
+fn unicode_escape(input: &str) -> Option<(char, &str)> {
+    let (code, rest) = hex4(input)?;
+    if (0xD800..0xDC00).contains(&code) {
+        let (low, rest) = hex4(rest.strip_prefix("\\u")?)?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return None;
+        }
+        let combined = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+        return Some((char::from_u32(combined)?, rest));
+    }
+    Some((char::from_u32(code)?, rest))
+}
+
+fn json_string(input: &str) -> IResult<&str, String> {
+    let fail = |at| nom::Err::Error(Error::new(at, ErrorKind::Char));
+
+    let (mut rest, _) = char('"').parse(input)?;
+    let mut result = String::new();
+    loop {
+        let mut chars = rest.chars();
+        match chars.next() {
+            None => return Err(fail(rest)),
+            Some('"') => return Ok((chars.as_str(), result)),
+            Some('\\') => {
+                let unescaped = match chars.next() {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('/') => '/',
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{c}',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('u') => {
+                        let (c, after) =
+                            unicode_escape(chars.as_str()).ok_or_else(|| fail(rest))?;
+                        chars = after.chars();
+                        c
+                    }
+                    _ => return Err(fail(rest)),
+                };
+                result.push(unescaped);
+            }
+            Some(c) if (c as u32) < 0x20 => return Err(fail(rest)),
+            Some(c) => result.push(c),
+        }
+        rest = chars.as_str();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::quote_for_json;
+
+    #[test]
+    fn t_parses_scalars() {
+        assert_eq!(parse("null"), Some(JsonValue::Null));
+        assert_eq!(parse(" true "), Some(JsonValue::Boolean(true)));
+        assert_eq!(parse("false"), Some(JsonValue::Boolean(false)));
+        assert_eq!(parse("42"), Some(JsonValue::Number(42.0)));
+        assert_eq!(parse("-1.5e3"), Some(JsonValue::Number(-1500.0)));
+        assert_eq!(
+            parse(r#""hello""#),
+            Some(JsonValue::String("hello".to_owned()))
+        );
+    }
+
+    #[test]
+    fn t_parses_nested_arrays_and_objects() {
+        let parsed = parse(
+            r#"
+            {
+                "name": "feed",
+                "items": [1, [], {}, {"read": true}],
+                "empty": null
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.get("name").and_then(JsonValue::as_str), Some("feed"));
+        assert_eq!(parsed.get("empty"), Some(&JsonValue::Null));
+        assert_eq!(parsed.get("missing"), None);
+
+        let items = parsed.get("items").and_then(JsonValue::as_array).unwrap();
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[0].as_i64(), Some(1));
+        assert_eq!(items[1], JsonValue::Array(vec![]));
+        assert_eq!(items[2], JsonValue::Object(vec![]));
+        assert_eq!(
+            items[3].get("read").and_then(JsonValue::as_bool),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn t_unescapes_strings() {
+        assert_eq!(
+            parse(r#""a\"b\\c\/d\n\t\r\b\f""#),
+            Some(JsonValue::String("a\"b\\c/d\n\t\r\u{8}\u{c}".to_owned()))
+        );
+        assert_eq!(
+            parse(r#""\u00e9\u20AC""#),
+            Some(JsonValue::String("é€".to_owned()))
+        );
+        assert_eq!(
+            parse(r#""\ud83d\ude00""#),
+            Some(JsonValue::String("😀".to_owned()))
+        );
+    }
+
+    #[test]
+    fn t_reads_back_what_quote_for_json_writes() {
+        let original = "\"quoted\"\\path\n\ttab\u{1}\u{7f} ünïcödé";
+        assert_eq!(
+            parse(&quote_for_json(original)),
+            Some(JsonValue::String(original.to_owned()))
+        );
+    }
+
+    #[test]
+    fn t_rejects_invalid_documents() {
+        for input in [
+            "",
+            "{",
+            "[1, 2",
+            "[1,]",
+            r#"{"a" 1}"#,
+            r#"{1: 2}"#,
+            r#""unterminated"#,
+            r#""bad \x escape""#,
+            r#""\ud83d alone""#,
+            "\"raw\nnewline\"",
+            "nul",
+            "1 2",
+        ] {
+            assert_eq!(parse(input), None, "input: {input:?}");
+        }
+    }
+
+    #[test]
+    fn t_limits_nesting_depth() {
+        let nested = |depth| format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(MAX_NESTING)).is_some());
+        assert_eq!(parse(&nested(MAX_NESTING + 1)), None);
+        assert_eq!(parse(&"[".repeat(100_000)), None);
+
+        let objects = format!(
+            "{}1{}",
+            r#"{"a":"#.repeat(MAX_NESTING + 1),
+            "}".repeat(MAX_NESTING + 1)
+        );
+        assert_eq!(parse(&objects), None);
+    }
+
+    #[test]
+    fn t_as_i64_only_accepts_whole_numbers() {
+        assert_eq!(JsonValue::Number(1700000000.0).as_i64(), Some(1700000000));
+        assert_eq!(JsonValue::Number(-3.0).as_i64(), Some(-3));
+        assert_eq!(JsonValue::Number(1.5).as_i64(), None);
+        assert_eq!(JsonValue::String("1".to_owned()).as_i64(), None);
+    }
+}
@@ -0,0 +1,975 @@
+//! Turns raw bytes read from the terminal into `KeyCombination`s.
+//!
+//! Besides plain characters and the usual escape sequences for cursor and function keys, this
+//! understands two protocols which report modifiers for keys that can't carry them otherwise (like
+//! `<C-S-UP>` or `<M-ENTER>`): xterm's `modifyOtherKeys`, and "CSI u", also known as the kitty
+//! keyboard protocol.
+//!
+//! Mouse events are decoded from SGR mouse reports (xterm's mode 1006).
+
+use super::{KeyCombination, MouseButton, MouseEvent, bindkey};
+use std::time::{Duration, Instant};
+
+const ESC: u8 = 0x1b;
+
+/// Escape sequences longer than this are skipped rather than buffered until they're complete.
+const MAX_SEQUENCE_LENGTH: usize = 64;
+
+/// Kitty encodes keys that don't have a Unicode code point using code points from the Private Use
+/// Area. These are the ones we map to our key names.
+const KITTY_F13: u32 = 57376;
+const KITTY_F35: u32 = 57398;
+const KITTY_KP_0: u32 = 57399;
+const KITTY_KP_9: u32 = 57408;
+
+/// Two presses of the same button in the same cell within this time make a double click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// A mouse event together with the cell it happened in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MouseReport {
+    pub key: KeyCombination,
+    /// Column of the cell, starting from 1.
+    pub column: u32,
+    /// Row of the cell, starting from 1.
+    pub row: u32,
+}
+
+/// Outcome of decoding the beginning of the terminal input.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Decoded {
+    /// The first `length` bytes of input encode `key`.
+    Key { key: KeyCombination, length: usize },
+
+    /// The first `length` bytes of input are a mouse report.
+    Mouse { report: MouseReport, length: usize },
+
+    /// Input ends in the middle of a character or an escape sequence; more bytes are needed.
+    Incomplete,
+
+    /// The first `length` bytes of input are not a key we recognize (e.g. mouse movement or
+    /// a key release event), and should be skipped.
+    Unknown { length: usize },
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Modifiers {
+    shift: bool,
+    alt: bool,
+    control: bool,
+}
+
+impl Modifiers {
+    /// Parses the modifier parameter used by both xterm and kitty: one plus a bitmask, where 1 is
+    /// Shift, 2 is Alt, 4 is Control and 32 is Meta. Other bits (Super, Hyper, lock keys) are
+    /// ignored.
+    fn from_param(param: u32) -> Modifiers {
+        let bits = param.saturating_sub(1);
+        Modifiers {
+            shift: bits & 1 != 0,
+            alt: bits & (2 | 32) != 0,
+            control: bits & 4 != 0,
+        }
+    }
+
+    fn apply(self, mut key: KeyCombination) -> KeyCombination {
+        if self.shift {
+            key = key.with_shift();
+        }
+        if self.alt {
+            key = key.with_alt();
+        }
+        if self.control {
+            key = key.with_control();
+        }
+        key
+    }
+}
+
+fn named(name: &str) -> KeyCombination {
+    KeyCombination::new(name.to_owned())
+}
+
+/// Builds a key out of a printable character, using the same conventions as the config parser:
+/// uppercase ASCII letters turn into lowercase ones with Shift, and `<` and `>` get names.
+fn key_from_char(c: char) -> KeyCombination {
+    match c {
+        ' ' => named("SPACE"),
+        '<' => named("LT"),
+        '>' => named("GT"),
+        c if c.is_ascii_uppercase() => {
+            KeyCombination::new(c.to_ascii_lowercase().to_string()).with_shift()
+        }
+        c => KeyCombination::new(c.to_string()),
+    }
+}
+
+/// Decodes a single byte below 0x20 (or 0x7f), which terminals send for Control combinations and
+/// for a few special keys.
+fn key_from_control_byte(byte: u8) -> KeyCombination {
+    match byte {
+        b'\r' | b'\n' => named("ENTER"),
+        b'\t' => named("TAB"),
+        0x08 | 0x7f => named("BACKSPACE"),
+        ESC => named("ESC"),
+        0x00 => named("SPACE").with_control(),
+        0x01..=0x1a => KeyCombination::new(char::from(b'a' + byte - 1).to_string()).with_control(),
+        _ => KeyCombination::new(char::from(byte + 0x40).to_string()).with_control(),
+    }
+}
+
+/// Decodes a code point reported by `modifyOtherKeys` or kitty protocol.
+fn key_from_code_point(code: u32) -> Option<KeyCombination> {
+    match code {
+        13 => Some(named("ENTER")),
+        9 => Some(named("TAB")),
+        27 => Some(named("ESC")),
+        8 | 127 => Some(named("BACKSPACE")),
+        0..=31 => u8::try_from(code).ok().map(key_from_control_byte),
+        KITTY_F13..=KITTY_F35 => Some(named(&format!("F{}", 13 + code - KITTY_F13))),
+        KITTY_KP_0..=KITTY_KP_9 => Some(KeyCombination::new((code - KITTY_KP_0).to_string())),
+        57414 => Some(named("ENTER")),
+        57417 => Some(named("LEFT")),
+        57418 => Some(named("RIGHT")),
+        57419 => Some(named("UP")),
+        57420 => Some(named("DOWN")),
+        57421 => Some(named("PPAGE")),
+        57422 => Some(named("NPAGE")),
+        57423 => Some(named("HOME")),
+        57424 => Some(named("END")),
+        57425 => Some(named("IC")),
+        57426 => Some(named("DC")),
+        // The rest of the Private Use Area: lock keys, media keys, modifiers pressed on their own
+        0xe000..=0xf8ff => None,
+        code => char::from_u32(code).map(key_from_char),
+    }
+}
+
+/// Key names for `CSI <number> ~` sequences.
+fn key_from_tilde_number(number: u32) -> Option<KeyCombination> {
+    let name = match number {
+        1 | 7 => "HOME".to_owned(),
+        2 => "IC".to_owned(),
+        3 => "DC".to_owned(),
+        4 | 8 => "END".to_owned(),
+        5 => "PPAGE".to_owned(),
+        6 => "NPAGE".to_owned(),
+        11..=15 => format!("F{}", number - 10),
+        17..=21 => format!("F{}", number - 11),
+        23..=26 => format!("F{}", number - 12),
+        28 | 29 => format!("F{}", number - 13),
+        31..=34 => format!("F{}", number - 14),
+        _ => return None,
+    };
+    Some(named(&name))
+}
+
+/// Key names for the final bytes of `CSI 1 ; <modifiers> <final>` and `SS3 <final>` sequences.
+fn key_from_final_byte(byte: u8) -> Option<KeyCombination> {
+    let name = match byte {
+        b'A' => "UP",
+        b'B' => "DOWN",
+        b'C' => "RIGHT",
+        b'D' => "LEFT",
+        b'H' => "HOME",
+        b'F' => "END",
+        b'P' => "F1",
+        b'Q' => "F2",
+        b'R' => "F3",
+        b'S' => "F4",
+        _ => return None,
+    };
+    Some(named(name))
+}
+
+/// Parameters of a CSI sequence: a list of semicolon-separated fields, each of which is a list of
+/// colon-separated numbers (any of which can be omitted).
+type CsiParams = Vec<Vec<Option<u32>>>;
+
+fn parse_csi_params(params: &[u8]) -> Option<CsiParams> {
+    if params.is_empty() {
+        return Some(Vec::new());
+    }
+
+    // Parameter bytes are all ASCII, so this can't fail
+    let params = std::str::from_utf8(params).ok()?;
+    params
+        .split(';')
+        .map(|field| {
+            field
+                .split(':')
+                .map(|number| match number {
+                    "" => Some(None),
+                    number => number.parse().ok().map(Some),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn csi_param(params: &CsiParams, field: usize, index: usize) -> Option<u32> {
+    params.get(field)?.get(index).copied().flatten()
+}
+
+/// Decodes a complete CSI sequence, given its parameter bytes and its final byte.
+fn decode_csi_sequence(params: &[u8], final_byte: u8) -> Option<KeyCombination> {
+    // Private sequences, like SGR mouse reports or focus events, are not keys
+    if params.first().is_some_and(|b| b"<=>?".contains(b)) {
+        return None;
+    }
+
+    let params = parse_csi_params(params)?;
+    let modifiers = Modifiers::from_param(csi_param(&params, 1, 0).unwrap_or(1));
+    // Kitty protocol can report key releases; we only care about presses (1) and repeats (2)
+    if csi_param(&params, 1, 1).unwrap_or(1) > 2 {
+        return None;
+    }
+
+    match final_byte {
+        b'~' => {
+            let number = csi_param(&params, 0, 0)?;
+            if number == 27 {
+                // xterm's modifyOtherKeys: CSI 27 ; <modifiers> ; <code point> ~
+                let code = csi_param(&params, 2, 0)?;
+                key_from_code_point(code).map(|key| modifiers.apply(key))
+            } else {
+                key_from_tilde_number(number).map(|key| modifiers.apply(key))
+            }
+        }
+
+        b'u' => {
+            // Kitty protocol: CSI <code point>[:<shifted code point>] ; <modifiers> u
+            let code = csi_param(&params, 0, 0)?;
+            let shifted = csi_param(&params, 0, 1);
+            match shifted {
+                // If the terminal told us what Shift turns the key into, use that instead of
+                // reporting Shift (so Shift+1 is `!`, like in the config). Letters are the
+                // exception, because the config represents them as lowercase letter plus Shift.
+                Some(shifted)
+                    if modifiers.shift
+                        && !char::from_u32(code).is_some_and(|c| c.is_ascii_alphabetic()) =>
+                {
+                    let modifiers = Modifiers {
+                        shift: false,
+                        ..modifiers
+                    };
+                    key_from_code_point(shifted).map(|key| modifiers.apply(key))
+                }
+                _ => key_from_code_point(code).map(|key| modifiers.apply(key)),
+            }
+        }
+
+        b'Z' => Some(modifiers.apply(named("TAB").with_shift())),
+
+        final_byte => {
+            if csi_param(&params, 0, 0).unwrap_or(1) != 1 {
+                return None;
+            }
+            key_from_final_byte(final_byte).map(|key| modifiers.apply(key))
+        }
+    }
+}
+
+/// Decodes the parameters and the final byte of an SGR mouse report: `CSI < button ; column ; row
+/// M` for presses, or with final `m` for releases. Only presses of the three main buttons and
+/// wheel scrolls are reported; releases, movement and other buttons are not.
+fn decode_sgr_mouse(params: &[u8], final_byte: u8) -> Option<MouseReport> {
+    if final_byte != b'M' {
+        return None;
+    }
+
+    let params = parse_csi_params(params)?;
+    if params.len() != 3 || params.iter().any(|field| field.len() != 1) {
+        return None;
+    }
+    let button = csi_param(&params, 0, 0)?;
+    let column = csi_param(&params, 1, 0)?;
+    let row = csi_param(&params, 2, 0)?;
+
+    const SHIFT: u32 = 4;
+    const ALT: u32 = 8;
+    const CONTROL: u32 = 16;
+    const MOTION: u32 = 32;
+    if button & MOTION != 0 {
+        return None;
+    }
+
+    let event = match button & !(SHIFT | ALT | CONTROL) {
+        0 => MouseEvent::Click(MouseButton::Left),
+        1 => MouseEvent::Click(MouseButton::Middle),
+        2 => MouseEvent::Click(MouseButton::Right),
+        64 => MouseEvent::WheelUp,
+        65 => MouseEvent::WheelDown,
+        _ => return None,
+    };
+    let modifiers = Modifiers {
+        shift: button & SHIFT != 0,
+        alt: button & ALT != 0,
+        control: button & CONTROL != 0,
+    };
+
+    Some(MouseReport {
+        key: modifiers.apply(KeyCombination::mouse(event)),
+        column,
+        row,
+    })
+}
+
+/// Decodes input that starts with `ESC [`.
+fn decode_csi(input: &[u8]) -> Decoded {
+    let mut i = 2;
+    while input.get(i).is_some_and(|b| (0x30..=0x3f).contains(b)) {
+        i += 1;
+    }
+    let params_end = i;
+    while input.get(i).is_some_and(|b| (0x20..=0x2f).contains(b)) {
+        i += 1;
+    }
+    let intermediates_end = i;
+
+    match input.get(i) {
+        None if i >= MAX_SEQUENCE_LENGTH => Decoded::Unknown { length: i },
+        None => Decoded::Incomplete,
+        Some(&final_byte) if (0x40..=0x7e).contains(&final_byte) => {
+            let length = i + 1;
+            if let [b'<', params @ ..] = &input[2..params_end] {
+                return match decode_sgr_mouse(params, final_byte) {
+                    Some(report) if intermediates_end == params_end => {
+                        Decoded::Mouse { report, length }
+                    }
+                    _ => Decoded::Unknown { length },
+                };
+            }
+            let key = if intermediates_end == params_end {
+                decode_csi_sequence(&input[2..params_end], final_byte)
+            } else {
+                None
+            };
+            match key {
+                Some(key) => Decoded::Key { key, length },
+                None => Decoded::Unknown { length },
+            }
+        }
+        // Not a valid CSI sequence; skip the part we've looked at
+        Some(_) => Decoded::Unknown { length: i },
+    }
+}
+
+/// Decodes input that starts with `ESC O`.
+fn decode_ss3(input: &[u8]) -> Decoded {
+    match input.get(2) {
+        None => Decoded::Incomplete,
+        Some(b'M') => Decoded::Key {
+            key: named("ENTER"),
+            length: 3,
+        },
+        Some(&byte) => match key_from_final_byte(byte) {
+            Some(key) => Decoded::Key { key, length: 3 },
+            // Not SS3 after all, but Alt+Shift+O followed by some other key
+            None => Decoded::Key {
+                key: key_from_char('O').with_alt(),
+                length: 2,
+            },
+        },
+    }
+}
+
+/// Decodes input that doesn't start with ESC: a single byte or a UTF-8 encoded character.
+fn decode_plain(input: &[u8]) -> Decoded {
+    let Some(&first) = input.first() else {
+        return Decoded::Incomplete;
+    };
+
+    if first < 0x20 || first == 0x7f {
+        return Decoded::Key {
+            key: key_from_control_byte(first),
+            length: 1,
+        };
+    }
+
+    let length = match first {
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return Decoded::Unknown { length: 1 },
+    };
+
+    if input.len() < length {
+        let continuation_is_valid = input[1..].iter().all(|b| (0x80..=0xbf).contains(b));
+        return if continuation_is_valid {
+            Decoded::Incomplete
+        } else {
+            Decoded::Unknown { length: 1 }
+        };
+    }
+
+    match std::str::from_utf8(&input[..length]) {
+        Ok(s) => match s.chars().next() {
+            Some(c) => Decoded::Key {
+                key: key_from_char(c),
+                length,
+            },
+            None => Decoded::Unknown { length: 1 },
+        },
+        Err(_) => Decoded::Unknown { length: 1 },
+    }
+}
+
+/// Decodes the first key from the terminal input.
+///
+/// A lone ESC is reported as `Incomplete`, because it might be the start of an escape sequence.
+/// If no more input arrives in a reasonable time, the caller should treat it as the Esc key (see
+/// `KeyDecoder::flush()`).
+pub fn decode(input: &[u8]) -> Decoded {
+    match input {
+        [] | [ESC] => Decoded::Incomplete,
+        [ESC, b'[', ..] => decode_csi(input),
+        [ESC, b'O', ..] => decode_ss3(input),
+        [ESC, ESC, ..] => Decoded::Key {
+            key: named("ESC"),
+            length: 1,
+        },
+        // ESC followed by a key means that key was pressed with Alt
+        [ESC, rest @ ..] => match decode_plain(rest) {
+            Decoded::Key { key, length } => Decoded::Key {
+                key: key.with_alt(),
+                length: length + 1,
+            },
+            Decoded::Incomplete => Decoded::Incomplete,
+            Decoded::Mouse { length, .. } | Decoded::Unknown { length } => {
+                Decoded::Unknown { length: length + 1 }
+            }
+        },
+        _ => decode_plain(input),
+    }
+}
+
+/// Returns the bytes a terminal sent for the STFL key event `event`, if it can be part of an
+/// escape sequence. Outside of a sequence, only ESC can start one.
+fn event_bytes(event: &str, in_sequence: bool) -> Option<Vec<u8>> {
+    if event == "ESC" {
+        return Some(vec![ESC]);
+    }
+    if !in_sequence {
+        return None;
+    }
+    match event {
+        "ENTER" => Some(b"\r".to_vec()),
+        "TAB" => Some(b"\t".to_vec()),
+        "SPACE" => Some(b" ".to_vec()),
+        "BACKSPACE" => Some(vec![0x7f]),
+        _ => {
+            let mut chars = event.chars();
+            match (chars.next(), chars.next(), chars.next()) {
+                (Some(c), None, None) => Some(c.to_string().into_bytes()),
+                (Some('^'), Some(c), None) if c.is_ascii_uppercase() => Some(vec![c as u8 - b'@']),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Buffers terminal input and turns it into keys.
+///
+/// Terminals can split escape sequences and multi-byte characters across reads, so the bytes that
+/// don't form a complete key yet are kept until more input arrives.
+///
+/// Mouse events are returned as keys too. Terminals don't report double clicks, so the decoder
+/// turns a second click of the same button in the same cell into a double click (instead of
+/// a click) if it comes soon enough after the first one.
+#[derive(Default)]
+pub struct KeyDecoder {
+    buffer: Vec<u8>,
+    mouse_position: Option<(u32, u32)>,
+    last_click: Option<(MouseReport, Instant)>,
+}
+
+impl KeyDecoder {
+    pub fn new() -> KeyDecoder {
+        KeyDecoder::default()
+    }
+
+    /// Adds `bytes` to the input, and returns all the keys that can be decoded from it.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<KeyCombination> {
+        self.push_at(bytes, Instant::now())
+    }
+
+    /// Adds a key event as reported by STFL (e.g. `j`, `^G`, `ENTER` or `UP`), and returns all the
+    /// keys that can be decoded so far.
+    ///
+    /// Curses decodes the escape sequences it knows about, but reports the others byte by byte,
+    /// starting with `ESC`. Such events are collected and decoded here. Other events are returned
+    /// the same way `bindkey()` parses them, after the keys left over from an unfinished sequence.
+    pub fn push_event(&mut self, event: &str) -> Vec<KeyCombination> {
+        if let Some(bytes) = event_bytes(event, self.has_pending()) {
+            return self.push(&bytes);
+        }
+        let mut keys = self.flush();
+        keys.push(bindkey(event));
+        keys
+    }
+
+    fn push_at(&mut self, bytes: &[u8], now: Instant) -> Vec<KeyCombination> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut keys = Vec::new();
+        let mut start = 0;
+        loop {
+            match decode(&self.buffer[start..]) {
+                Decoded::Key { key, length } => {
+                    keys.push(key);
+                    start += length;
+                }
+                Decoded::Mouse { report, length } => {
+                    keys.push(self.mouse_key(report, now));
+                    start += length;
+                }
+                Decoded::Unknown { length } => start += length,
+                Decoded::Incomplete => break,
+            }
+        }
+        self.buffer.drain(..start);
+
+        keys
+    }
+
+    /// Returns the cell (column and row, starting from 1) of the most recent mouse event, if
+    /// there was one.
+    pub fn mouse_position(&self) -> Option<(u32, u32)> {
+        self.mouse_position
+    }
+
+    fn mouse_key(&mut self, report: MouseReport, now: Instant) -> KeyCombination {
+        self.mouse_position = Some((report.column, report.row));
+
+        let Some(MouseEvent::Click(button)) = report.key.mouse_event() else {
+            return report.key;
+        };
+        let is_double_click = self.last_click.take().is_some_and(|(last, time)| {
+            last == report && now.saturating_duration_since(time) <= DOUBLE_CLICK_INTERVAL
+        });
+        if !is_double_click {
+            self.last_click = Some((report.clone(), now));
+            return report.key;
+        }
+
+        let mut key = KeyCombination::mouse(MouseEvent::DoubleClick(button));
+        key.shift = report.key.shift;
+        key.control = report.key.control;
+        key.alt = report.key.alt;
+        key
+    }
+
+    /// Returns `true` if some of the input couldn't be decoded yet.
+    pub fn has_pending(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    /// Decodes the buffered input as if no more bytes will follow. The caller should do this when
+    /// no input arrived for a while after `has_pending()` started returning `true`.
+    ///
+    /// A lone ESC becomes the Esc key; so does the ESC that starts an incomplete escape sequence,
+    /// and the rest of such sequence is decoded as ordinary keys. Incomplete characters are
+    /// dropped.
+    pub fn flush(&mut self) -> Vec<KeyCombination> {
+        let now = Instant::now();
+        let mut keys = Vec::new();
+        while !self.buffer.is_empty() {
+            let consumed = match decode(&self.buffer) {
+                Decoded::Key { key, length } => {
+                    keys.push(key);
+                    length
+                }
+                Decoded::Mouse { report, length } => {
+                    keys.push(self.mouse_key(report, now));
+                    length
+                }
+                Decoded::Unknown { length } => length,
+                Decoded::Incomplete => {
+                    if self.buffer[0] == ESC {
+                        keys.push(named("ESC"));
+                    }
+                    1
+                }
+            };
+            self.buffer.drain(..consumed);
+        }
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keycombination::bind;
+
+    /// Decodes `input` completely, as if nothing else will follow.
+    fn keys(input: &[u8]) -> Vec<KeyCombination> {
+        let mut decoder = KeyDecoder::new();
+        let mut keys = decoder.push(input);
+        keys.extend(decoder.flush());
+        keys
+    }
+
+    /// Checks that `input` decodes into the same keys that `bind` parses from `expected`.
+    fn check(input: &[u8], expected: &str) {
+        assert_eq!(keys(input), bind(expected), "input: {input:?}");
+    }
+
+    #[test]
+    fn t_decodes_printable_characters() {
+        check(b"jk", "jk");
+        check(b"J", "J");
+        check(b"!?1", "!?1");
+        check("ёЖ€😀".as_bytes(), "ёЖ€😀");
+        check(b"< >", "<LT><SPACE><GT>");
+    }
+
+    #[test]
+    fn t_decodes_control_bytes() {
+        check(b"\x01\x12\x1a", "^A^R^Z");
+        check(b"\r\n\t", "<ENTER><ENTER><TAB>");
+        check(b"\x7f\x08", "<BACKSPACE><BACKSPACE>");
+        check(b"\x00", "<C-SPACE>");
+        check(b"\x1c\x1d\x1e\x1f", r"<C-\><C-]><C-^><C-_>");
+    }
+
+    #[test]
+    fn t_decodes_alt_combinations() {
+        check(b"\x1bj", "<M-j>");
+        check(b"\x1bJ", "<M-S-j>");
+        check(b"\x1b\r", "<M-ENTER>");
+        check(b"\x1b\x0e", "<M-C-n>");
+        check("\x1bж".as_bytes(), "<M-ж>");
+    }
+
+    #[test]
+    fn t_decodes_lone_escape_only_when_flushed() {
+        let mut decoder = KeyDecoder::new();
+        assert_eq!(decoder.push(b"\x1b"), vec![]);
+        assert!(decoder.has_pending());
+        assert_eq!(decoder.flush(), bind("<ESC>"));
+        assert!(!decoder.has_pending());
+
+        check(b"\x1b\x1b", "<ESC><ESC>");
+        check(b"\x1b\x1bj", "<ESC><M-j>");
+    }
+
+    #[test]
+    fn t_push_event_decodes_sequences_that_curses_reports_byte_by_byte() {
+        let mut decoder = KeyDecoder::new();
+        let mut keys = Vec::new();
+        for event in ["ESC", "[", "2", "7", ";", "5", ";", "1", "3", "~"] {
+            keys.extend(decoder.push_event(event));
+        }
+        assert_eq!(keys, bind("<C-ENTER>"));
+        assert!(!decoder.has_pending());
+
+        assert_eq!(decoder.push_event("ESC"), vec![]);
+        assert_eq!(decoder.push_event("ENTER"), bind("<M-ENTER>"));
+        assert_eq!(decoder.push_event("ESC"), vec![]);
+        assert_eq!(decoder.push_event("^N"), bind("<M-C-n>"));
+
+        let mut keys = Vec::new();
+        for event in "\x1b[<65;10;5M".chars() {
+            let event = if event == '\x1b' {
+                "ESC".to_owned()
+            } else {
+                event.to_string()
+            };
+            keys.extend(decoder.push_event(&event));
+        }
+        assert_eq!(keys, bind("<MouseWheelDown>"));
+        assert_eq!(decoder.mouse_position(), Some((10, 5)));
+    }
+
+    #[test]
+    fn t_push_event_passes_other_events_through_like_bindkey() {
+        let mut decoder = KeyDecoder::new();
+        for event in ["j", "J", "^G", "ENTER", "SPACE", "UP", "F5", "<", "^H"] {
+            assert_eq!(
+                decoder.push_event(event),
+                vec![bindkey(event)],
+                "event: {event}"
+            );
+        }
+
+        assert_eq!(decoder.push_event("ESC"), vec![]);
+        assert_eq!(decoder.push_event("UP"), bind("<ESC><UP>"));
+        assert_eq!(decoder.push_event("ESC"), vec![]);
+        assert_eq!(decoder.flush(), bind("<ESC>"));
+    }
+
+    #[test]
+    fn t_flush_turns_incomplete_sequences_into_ordinary_keys() {
+        let mut decoder = KeyDecoder::new();
+        assert_eq!(decoder.push(b"\x1b[1;"), vec![]);
+        assert_eq!(decoder.flush(), bind("<ESC>[1;"));
+
+        let mut decoder = KeyDecoder::new();
+        assert_eq!(decoder.push(&"ж".as_bytes()[..1]), vec![]);
+        assert_eq!(decoder.flush(), vec![]);
+    }
+
+    #[test]
+    fn t_keeps_sequences_split_across_reads() {
+        let mut decoder = KeyDecoder::new();
+        assert_eq!(decoder.push(b"j\x1b["), bind("j"));
+        assert_eq!(decoder.push(b"1;5"), vec![]);
+        assert_eq!(decoder.push(b"Ak"), bind("<C-UP>k"));
+
+        let bytes = "ж".as_bytes();
+        assert_eq!(decoder.push(&bytes[..1]), vec![]);
+        assert_eq!(decoder.push(&bytes[1..]), bind("ж"));
+        assert!(!decoder.has_pending());
+    }
+
+    #[test]
+    fn t_decodes_cursor_keys() {
+        check(b"\x1b[A\x1b[B\x1b[C\x1b[D", "<UP><DOWN><RIGHT><LEFT>");
+        check(b"\x1bOA\x1bOB\x1bOC\x1bOD", "<UP><DOWN><RIGHT><LEFT>");
+        check(b"\x1b[H\x1b[F\x1bOH\x1bOF", "<HOME><END><HOME><END>");
+        check(b"\x1b[1~\x1b[4~\x1b[7~\x1b[8~", "<HOME><END><HOME><END>");
+        check(b"\x1b[5~\x1b[6~", "<PPAGE><NPAGE>");
+        check(b"\x1b[2~\x1b[3~", "<IC><DC>");
+        check(b"\x1bOM", "<ENTER>");
+        check(b"\x1b[Z", "<S-TAB>");
+    }
+
+    #[test]
+    fn t_decodes_modified_cursor_keys() {
+        check(b"\x1b[1;2A", "<S-UP>");
+        check(b"\x1b[1;3B", "<M-DOWN>");
+        check(b"\x1b[1;5C", "<C-RIGHT>");
+        check(b"\x1b[1;6A", "<C-S-UP>");
+        check(b"\x1b[1;8D", "<C-M-S-LEFT>");
+        check(b"\x1b[5;5~", "<C-PPAGE>");
+        check(b"\x1b[1;9A", "<UP>");
+        check(b"\x1b[1;33A", "<M-UP>");
+    }
+
+    #[test]
+    fn t_decodes_function_keys() {
+        check(b"\x1bOP\x1bOQ\x1bOR\x1bOS", "<F1><F2><F3><F4>");
+        check(b"\x1b[P\x1b[1;2Q", "<F1><S-F2>");
+        check(
+            b"\x1b[11~\x1b[12~\x1b[13~\x1b[14~\x1b[15~\x1b[17~\x1b[18~\x1b[19~\x1b[20~\x1b[21~",
+            "<F1><F2><F3><F4><F5><F6><F7><F8><F9><F10>",
+        );
+        check(
+            b"\x1b[23~\x1b[24~\x1b[25~\x1b[26~\x1b[28~\x1b[29~\x1b[31~\x1b[32~\x1b[33~\x1b[34~",
+            "<F11><F12><F13><F14><F15><F16><F17><F18><F19><F20>",
+        );
+        check(b"\x1b[15;5~", "<C-F5>");
+        check(
+            b"\x1b[57376u\x1b[57384u\x1b[57385u\x1b[57386u\x1b[57387u",
+            "<F13><F21><F22><F23><F24>",
+        );
+        check(b"\x1b[57387;3u", "<M-F24>");
+    }
+
+    #[test]
+    fn t_decodes_modify_other_keys() {
+        check(b"\x1b[27;5;13~", "<C-ENTER>");
+        check(b"\x1b[27;3;13~", "<M-ENTER>");
+        check(b"\x1b[27;2;9~", "<S-TAB>");
+        check(b"\x1b[27;6;65~", "<C-S-a>");
+        check(b"\x1b[27;5;49~", "<C-1>");
+        check(b"\x1b[27;5;60~", "<C-LT>");
+    }
+
+    #[test]
+    fn t_decodes_kitty_keyboard_protocol() {
+        check(b"\x1b[97;5u", "^A");
+        check(b"\x1b[97;6u", "<C-S-a>");
+        check(b"\x1b[97;2u", "A");
+        check(b"\x1b[13u\x1b[13;2u\x1b[13;3u", "<ENTER><S-ENTER><M-ENTER>");
+        check(b"\x1b[27u\x1b[9;5u\x1b[127;5u", "<ESC><C-TAB><C-BACKSPACE>");
+        check(b"\x1b[1078;5u", "<C-ж>");
+        check(b"\x1b[49:33;2u", "!");
+        check(b"\x1b[97:65;2u", "A");
+        check(b"\x1b[49:33;6u", "<C-!>");
+        check(b"\x1b[57399u\x1b[57414u\x1b[57419;5u", "0<ENTER><C-UP>");
+        check(b"\x1b[97;5:1u\x1b[97;5:2u", "^A^A");
+    }
+
+    #[test]
+    fn t_skips_sequences_that_are_not_keys() {
+        // Key release in kitty protocol
+        check(b"\x1b[97;5:3uj", "j");
+        // Shift pressed on its own
+        check(b"\x1b[57441;2uj", "j");
+        // Mouse release and movement, focus in, bracketed paste start
+        check(b"\x1b[<0;10;5mj\x1b[<35;1;1M\x1b[Ik\x1b[200~", "jk");
+        // Unknown tilde and final bytes
+        check(b"\x1b[99~\x1b[1;5Xj", "j");
+        // Parameters on keys that don't take them, and invalid numbers
+        check(b"\x1b[2A\x1b[99999999999;5uj", "j");
+        // Intermediate bytes
+        check(b"\x1b[1 Aj", "j");
+        // Invalid UTF-8
+        check(b"\xff\xc3j\x80k", "jk");
+    }
+
+    #[test]
+    fn t_skips_malformed_and_overlong_sequences() {
+        check(b"\x1b[1\x01j", "<C-a>j");
+
+        let mut overlong = b"\x1b[".to_vec();
+        overlong.extend(std::iter::repeat_n(b'1', MAX_SEQUENCE_LENGTH));
+        let mut decoder = KeyDecoder::new();
+        assert_eq!(decoder.push(&overlong), vec![]);
+        assert!(!decoder.has_pending());
+    }
+
+    #[test]
+    fn t_decodes_sgr_mouse_reports() {
+        check(
+            b"\x1b[<0;1;1M\x1b[<1;2;1M\x1b[<2;3;1M",
+            "<MouseLeft><MouseMiddle><MouseRight>",
+        );
+        check(
+            b"\x1b[<64;5;5M\x1b[<65;5;5M",
+            "<MouseWheelUp><MouseWheelDown>",
+        );
+        // Shift, Alt and Control
+        check(
+            b"\x1b[<68;1;1M\x1b[<73;1;1M\x1b[<16;1;1M\x1b[<28;1;1M",
+            "<S-MouseWheelUp><M-MouseWheelDown><C-MouseLeft><C-S-M-MouseLeft>",
+        );
+        // Releases, movement, drags and extra buttons are skipped
+        check(
+            b"\x1b[<0;1;1m\x1b[<35;1;1M\x1b[<32;1;1M\x1b[<66;1;1M\x1b[<128;1;1Mj",
+            "j",
+        );
+        // Malformed reports are skipped too
+        check(b"\x1b[<0;1M\x1b[<0;1;1;1M\x1b[<0:1;1;1M\x1b[<0;1;1Xj", "j");
+    }
+
+    #[test]
+    fn t_decode_reports_mouse_position() {
+        assert_eq!(
+            decode(b"\x1b[<2;120;45Mx"),
+            Decoded::Mouse {
+                report: MouseReport {
+                    key: KeyCombination::mouse(MouseEvent::Click(MouseButton::Right)),
+                    column: 120,
+                    row: 45,
+                },
+                length: 12,
+            }
+        );
+        assert_eq!(decode(b"\x1b[<2;120;4"), Decoded::Incomplete);
+
+        let mut decoder = KeyDecoder::new();
+        assert_eq!(decoder.mouse_position(), None);
+        decoder.push(b"\x1b[<65;7;3M");
+        assert_eq!(decoder.mouse_position(), Some((7, 3)));
+        decoder.push(b"j");
+        assert_eq!(decoder.mouse_position(), Some((7, 3)));
+    }
+
+    #[test]
+    fn t_detects_double_clicks() {
+        let click = b"\x1b[<0;10;5M";
+        let start = Instant::now();
+        let soon = start + DOUBLE_CLICK_INTERVAL;
+        let late = soon + Duration::from_millis(1);
+
+        let mut decoder = KeyDecoder::new();
+        assert_eq!(decoder.push_at(click, start), bind("<MouseLeft>"));
+        assert_eq!(decoder.push_at(click, soon), bind("<MouseDoubleLeft>"));
+        // A third click starts over
+        assert_eq!(decoder.push_at(click, soon), bind("<MouseLeft>"));
+        assert_eq!(
+            decoder.push_at(click, late + DOUBLE_CLICK_INTERVAL),
+            bind("<MouseLeft>")
+        );
+
+        // Too slow
+        let mut decoder = KeyDecoder::new();
+        decoder.push_at(click, start);
+        assert_eq!(decoder.push_at(click, late), bind("<MouseLeft>"));
+
+        // Different cell, button, or modifiers
+        let mut decoder = KeyDecoder::new();
+        decoder.push_at(click, start);
+        assert_eq!(
+            decoder.push_at(b"\x1b[<0;11;5M", start),
+            bind("<MouseLeft>")
+        );
+        assert_eq!(
+            decoder.push_at(b"\x1b[<2;11;5M", start),
+            bind("<MouseRight>")
+        );
+        assert_eq!(
+            decoder.push_at(b"\x1b[<18;11;5M", start),
+            bind("<C-MouseRight>")
+        );
+        assert_eq!(
+            decoder.push_at(b"\x1b[<18;11;5M", start),
+            bind("<C-MouseDoubleRight>")
+        );
+
+        // Wheel events are never doubled, and don't interrupt a double click
+        let mut decoder = KeyDecoder::new();
+        decoder.push_at(click, start);
+        let wheel = b"\x1b[<65;10;5M";
+        assert_eq!(decoder.push_at(wheel, start), bind("<MouseWheelDown>"));
+        assert_eq!(decoder.push_at(wheel, start), bind("<MouseWheelDown>"));
+        assert_eq!(decoder.push_at(click, start), bind("<MouseDoubleLeft>"));
+    }
+
+    #[test]
+    fn t_decode_reports_how_much_input_was_used() {
+        assert_eq!(decode(b""), Decoded::Incomplete);
+        assert_eq!(decode(b"\x1b"), Decoded::Incomplete);
+        assert_eq!(decode(b"\x1b["), Decoded::Incomplete);
+        assert_eq!(decode(b"\x1bO"), Decoded::Incomplete);
+        assert_eq!(
+            decode(b"\x1b[1;5Ajk"),
+            Decoded::Key {
+                key: named("UP").with_control(),
+                length: 6
+            }
+        );
+        assert_eq!(
+            decode(b"\x1bOx"),
+            Decoded::Key {
+                key: KeyCombination::new("o".to_owned()).with_shift().with_alt(),
+                length: 2
+            }
+        );
+        assert_eq!(decode(b"\x1b[200~x"), Decoded::Unknown { length: 6 });
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn t_decode_does_not_crash_and_consumes_input(ref input in proptest::collection::vec(proptest::num::u8::ANY, 0..32)) {
+            match decode(input) {
+                Decoded::Key { length, .. }
+                | Decoded::Mouse { length, .. }
+                | Decoded::Unknown { length } => {
+                    proptest::prop_assert!(length > 0);
+                    proptest::prop_assert!(length <= input.len());
+                }
+                Decoded::Incomplete => {}
+            }
+        }
+
+        #[test]
+        fn t_decoder_does_not_depend_on_how_input_is_split(
+            ref input in proptest::collection::vec(proptest::num::u8::ANY, 0..32),
+            split in 0usize..32,
+        ) {
+            let split = split.min(input.len());
+            let mut decoder = KeyDecoder::new();
+            let mut split_keys = decoder.push(&input[..split]);
+            split_keys.extend(decoder.push(&input[split..]));
+            split_keys.extend(decoder.flush());
+
+            proptest::prop_assert_eq!(split_keys, keys(input));
+        }
+    }
+}
@@ -1,3 +1,6 @@
+mod decoder;
+mod mouse;
+
 use nom::AsChar;
 use nom::Parser;
 use nom::{
@@ -10,25 +13,28 @@ use nom::{
     sequence::terminated,
 };
 
-#[derive(Debug, PartialEq)]
+pub use decoder::{Decoded, KeyDecoder, MouseReport, decode};
+pub use mouse::{MouseButton, MouseEvent};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ShiftState {
     Shift,
     NoShift,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ControlState {
     Control,
     NoControl,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AltState {
     Alt,
     NoAlt,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct KeyCombination {
     key: String,
     shift: ShiftState,
@@ -76,6 +82,16 @@ impl KeyCombination {
     pub fn has_alt(&self) -> bool {
         self.alt == AltState::Alt
     }
+
+    /// Creates a combination that represents a mouse event, without modifiers.
+    pub fn mouse(event: MouseEvent) -> Self {
+        Self::new(event.key_name().to_owned())
+    }
+
+    /// Returns the mouse event that this combination represents, or `None` if it's a key.
+    pub fn mouse_event(&self) -> Option<MouseEvent> {
+        MouseEvent::from_key_name(&self.key)
+    }
 }
 
 fn alphabetic(input: &str) -> IResult<&str, char> {
@@ -185,6 +201,7 @@ mod tests {
     use super::KeyCombination;
     use super::bind;
     use super::bindkey;
+    use super::{MouseButton, MouseEvent};
 
     proptest::proptest! {
         #[test]
@@ -199,6 +216,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn t_bind_mouse_events() {
+        assert_eq!(
+            bind("<MouseWheelDown>"),
+            vec![KeyCombination::mouse(MouseEvent::WheelDown)]
+        );
+        assert_eq!(
+            bind("<C-S-MouseLeft>"),
+            vec![
+                KeyCombination::mouse(MouseEvent::Click(MouseButton::Left))
+                    .with_control()
+                    .with_shift()
+            ]
+        );
+        assert_eq!(
+            bind("g<MouseDoubleRight>")[1].mouse_event(),
+            Some(MouseEvent::DoubleClick(MouseButton::Right))
+        );
+        assert_eq!(bind("<ENTER>")[0].mouse_event(), None);
+        assert_eq!(bind("M")[0].mouse_event(), None);
+    }
+
     #[test]
     fn t_bindkey_no_modifiers() {
         assert_eq!(bindkey("a"), KeyCombination::new("a".to_owned()));
@@ -0,0 +1,77 @@
+//! Mouse events that can be bound like keys, e.g. `<MouseWheelDown>` or `<C-MouseLeft>`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseEvent {
+    Click(MouseButton),
+    DoubleClick(MouseButton),
+    WheelUp,
+    WheelDown,
+}
+
+const NAMES: [(MouseEvent, &str); 8] = [
+    (MouseEvent::Click(MouseButton::Left), "MouseLeft"),
+    (MouseEvent::Click(MouseButton::Middle), "MouseMiddle"),
+    (MouseEvent::Click(MouseButton::Right), "MouseRight"),
+    (
+        MouseEvent::DoubleClick(MouseButton::Left),
+        "MouseDoubleLeft",
+    ),
+    (
+        MouseEvent::DoubleClick(MouseButton::Middle),
+        "MouseDoubleMiddle",
+    ),
+    (
+        MouseEvent::DoubleClick(MouseButton::Right),
+        "MouseDoubleRight",
+    ),
+    (MouseEvent::WheelUp, "MouseWheelUp"),
+    (MouseEvent::WheelDown, "MouseWheelDown"),
+];
+
+impl MouseEvent {
+    /// Name of the key that represents this event in `bind` commands (without the angle
+    /// brackets).
+    pub fn key_name(self) -> &'static str {
+        NAMES
+            .iter()
+            .find(|(event, _)| *event == self)
+            .map(|(_, name)| *name)
+            // All events are in the table
+            .unwrap_or_default()
+    }
+
+    /// Inverse of `key_name()`.
+    pub fn from_key_name(name: &str) -> Option<MouseEvent> {
+        NAMES
+            .iter()
+            .find(|(_, event_name)| *event_name == name)
+            .map(|(event, _)| *event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_key_names_round_trip() {
+        for (event, name) in NAMES {
+            assert_eq!(event.key_name(), name);
+            assert_eq!(MouseEvent::from_key_name(name), Some(event));
+        }
+    }
+
+    #[test]
+    fn t_other_names_are_not_mouse_events() {
+        assert_eq!(MouseEvent::from_key_name("ENTER"), None);
+        assert_eq!(MouseEvent::from_key_name("mousewheelup"), None);
+        assert_eq!(MouseEvent::from_key_name(""), None);
+    }
+}
@@ -0,0 +1,368 @@
+//! Finds problems in `bind` commands which Newsboat would otherwise resolve silently.
+
+use super::Binding;
+use crate::keycombination::{KeyCombination, bind};
+use gettextrs::gettext;
+use std::collections::{BTreeMap, BTreeSet};
+use strprintf::fmt;
+
+/// Context name that `bind` expands to all the known contexts.
+const ALL_CONTEXTS: &str = "everywhere";
+
+/// A problem found in a list of bindings.
+///
+/// All indices point into the slice of bindings that was passed to `lint()`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum KeymapIssue {
+    /// Binding `later` binds the same key sequence in the same context as `earlier`, so `earlier`
+    /// has no effect there.
+    DuplicateBinding {
+        earlier: usize,
+        later: usize,
+        context: String,
+        key_sequence: String,
+    },
+
+    /// Key sequence of one binding is a prefix of the other's (e.g. `g` and `gg`). Newsboat can't
+    /// have both in the same context, so the `later` binding replaces the `earlier` one.
+    ShadowedPrefix {
+        earlier: usize,
+        later: usize,
+        context: String,
+        earlier_key_sequence: String,
+        later_key_sequence: String,
+    },
+
+    /// Binding uses a context that doesn't exist.
+    UnknownContext { binding: usize, context: String },
+
+    /// Binding uses an operation that doesn't exist.
+    UnknownOperation { binding: usize, operation: String },
+}
+
+impl KeymapIssue {
+    /// Index of the binding that this issue should be reported at. For conflicts, that's the
+    /// binding that wins.
+    pub fn binding(&self) -> usize {
+        match *self {
+            KeymapIssue::DuplicateBinding { later, .. } => later,
+            KeymapIssue::ShadowedPrefix { later, .. } => later,
+            KeymapIssue::UnknownContext { binding, .. } => binding,
+            KeymapIssue::UnknownOperation { binding, .. } => binding,
+        }
+    }
+}
+
+impl std::fmt::Display for KeymapIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            KeymapIssue::DuplicateBinding {
+                context,
+                key_sequence,
+                ..
+            } => fmt!(
+                // The first %s is a key sequence, e.g. "<C-n>"; the second one is a context name,
+                // e.g. "feedlist".
+                &gettext("`%s' is already bound in context `%s'; the earlier binding is ignored"),
+                key_sequence,
+                context
+            ),
+            KeymapIssue::ShadowedPrefix {
+                context,
+                earlier_key_sequence,
+                later_key_sequence,
+                ..
+            } => fmt!(
+                // The first and the third %s are key sequences, e.g. "gg" and "g"; the second %s
+                // is a context name, e.g. "feedlist".
+                &gettext(
+                    "`%s' replaces the binding for `%s' in context `%s', because one is a prefix of the other"
+                ),
+                later_key_sequence,
+                earlier_key_sequence,
+                context
+            ),
+            KeymapIssue::UnknownContext { context, .. } => {
+                fmt!(&gettext("`%s' is not a valid context"), context)
+            }
+            KeymapIssue::UnknownOperation { operation, .. } => {
+                fmt!(&gettext("`%s' is not a valid operation"), operation)
+            }
+        };
+        write!(f, "{message}")
+    }
+}
+
+/// A binding that's currently in effect in some context.
+struct ActiveBinding {
+    index: usize,
+    keys: Vec<KeyCombination>,
+}
+
+/// Checks `bindings` for unknown contexts and operations, and for bindings that override each
+/// other.
+///
+/// Bindings are processed in order, just like Newsboat applies them while reading the config. An
+/// overridden binding is reported only once, against the binding which overrode it. Contexts that
+/// aren't in `known_contexts` are reported and then ignored.
+///
+/// Returns all the problems found, grouped by binding in the order of `bindings`. An empty vector
+/// means there are no problems.
+pub fn lint(
+    bindings: &[Binding],
+    known_contexts: &BTreeSet<String>,
+    known_operations: &BTreeSet<String>,
+) -> Vec<KeymapIssue> {
+    let mut issues = Vec::new();
+    let mut active: BTreeMap<&str, Vec<ActiveBinding>> = BTreeMap::new();
+
+    for (index, binding) in bindings.iter().enumerate() {
+        let mut contexts = Vec::new();
+        if binding.contexts == [ALL_CONTEXTS] {
+            contexts.extend(known_contexts.iter().map(String::as_str));
+        } else {
+            for context in &binding.contexts {
+                if known_contexts.contains(context) {
+                    contexts.push(context.as_str());
+                } else {
+                    issues.push(KeymapIssue::UnknownContext {
+                        binding: index,
+                        context: context.clone(),
+                    });
+                }
+            }
+        }
+
+        for operation in &binding.operations {
+            if let Some(name) = operation.first()
+                && !known_operations.contains(name)
+            {
+                issues.push(KeymapIssue::UnknownOperation {
+                    binding: index,
+                    operation: name.clone(),
+                });
+            }
+        }
+
+        let keys = bind(&binding.key_sequence);
+        if keys.is_empty() {
+            continue;
+        }
+
+        for context in contexts {
+            let context_bindings = active.entry(context).or_default();
+            context_bindings.retain(|other| {
+                let common = other.keys.len().min(keys.len());
+                if other.keys[..common] != keys[..common] {
+                    return true;
+                }
+
+                let earlier_key_sequence = &bindings[other.index].key_sequence;
+                issues.push(if other.keys.len() == keys.len() {
+                    KeymapIssue::DuplicateBinding {
+                        earlier: other.index,
+                        later: index,
+                        context: context.to_owned(),
+                        key_sequence: binding.key_sequence.clone(),
+                    }
+                } else {
+                    KeymapIssue::ShadowedPrefix {
+                        earlier: other.index,
+                        later: index,
+                        context: context.to_owned(),
+                        earlier_key_sequence: earlier_key_sequence.clone(),
+                        later_key_sequence: binding.key_sequence.clone(),
+                    }
+                });
+                false
+            });
+            context_bindings.push(ActiveBinding {
+                index,
+                keys: keys.clone(),
+            });
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tokenize_binding;
+    use super::*;
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn check(lines: &[&str]) -> Vec<KeymapIssue> {
+        let bindings: Vec<Binding> = lines
+            .iter()
+            .map(|line| tokenize_binding(line).unwrap())
+            .collect();
+        lint(
+            &bindings,
+            &names(&["feedlist", "articlelist", "article"]),
+            &names(&["open", "quit", "down", "up", "home"]),
+        )
+    }
+
+    #[test]
+    fn t_accepts_bindings_without_conflicts() {
+        assert_eq!(check(&[]), vec![]);
+        assert_eq!(
+            check(&[
+                "j feedlist,articlelist down",
+                "k feedlist,articlelist up",
+                "j article quit",
+                "gh feedlist home",
+                "gj feedlist down ; open",
+            ]),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn t_reports_duplicate_bindings_in_overlapping_contexts() {
+        assert_eq!(
+            check(&["<C-n> feedlist,articlelist down", "<C-n> everywhere up"]),
+            vec![
+                KeymapIssue::DuplicateBinding {
+                    earlier: 0,
+                    later: 1,
+                    context: "articlelist".to_string(),
+                    key_sequence: "<C-n>".to_string(),
+                },
+                KeymapIssue::DuplicateBinding {
+                    earlier: 0,
+                    later: 1,
+                    context: "feedlist".to_string(),
+                    key_sequence: "<C-n>".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn t_compares_parsed_key_sequences() {
+        assert_eq!(
+            check(&["^N feedlist down", "<C-n> feedlist up"]),
+            vec![KeymapIssue::DuplicateBinding {
+                earlier: 0,
+                later: 1,
+                context: "feedlist".to_string(),
+                key_sequence: "<C-n>".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn t_reports_overridden_binding_only_once() {
+        assert_eq!(
+            check(&["x feedlist open", "x feedlist up", "x feedlist down"]),
+            vec![
+                KeymapIssue::DuplicateBinding {
+                    earlier: 0,
+                    later: 1,
+                    context: "feedlist".to_string(),
+                    key_sequence: "x".to_string(),
+                },
+                KeymapIssue::DuplicateBinding {
+                    earlier: 1,
+                    later: 2,
+                    context: "feedlist".to_string(),
+                    key_sequence: "x".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn t_reports_prefix_shadowing_in_both_directions() {
+        assert_eq!(
+            check(&["g feedlist home", "gg feedlist open"]),
+            vec![KeymapIssue::ShadowedPrefix {
+                earlier: 0,
+                later: 1,
+                context: "feedlist".to_string(),
+                earlier_key_sequence: "g".to_string(),
+                later_key_sequence: "gg".to_string(),
+            }]
+        );
+        assert_eq!(
+            check(&["gg feedlist open", "g feedlist home"]),
+            vec![KeymapIssue::ShadowedPrefix {
+                earlier: 0,
+                later: 1,
+                context: "feedlist".to_string(),
+                earlier_key_sequence: "gg".to_string(),
+                later_key_sequence: "g".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn t_does_not_report_conflicts_across_contexts() {
+        assert_eq!(check(&["g feedlist home", "gg article open"]), vec![]);
+    }
+
+    #[test]
+    fn t_reports_unknown_contexts_and_operations() {
+        assert_eq!(
+            check(&["x feedlist,podcasts open ; frobnicate ; quit 1 ; explode"]),
+            vec![
+                KeymapIssue::UnknownContext {
+                    binding: 0,
+                    context: "podcasts".to_string(),
+                },
+                KeymapIssue::UnknownOperation {
+                    binding: 0,
+                    operation: "frobnicate".to_string(),
+                },
+                KeymapIssue::UnknownOperation {
+                    binding: 0,
+                    operation: "explode".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn t_everywhere_is_only_special_on_its_own() {
+        assert_eq!(
+            check(&["x everywhere,feedlist open"]),
+            vec![KeymapIssue::UnknownContext {
+                binding: 0,
+                context: "everywhere".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn t_issue_is_reported_at_the_winning_binding() {
+        let issues = check(&["g feedlist home", "x nowhere open", "gg feedlist open"]);
+        let bindings: Vec<usize> = issues.iter().map(KeymapIssue::binding).collect();
+        assert_eq!(bindings, vec![1, 2]);
+    }
+
+    #[test]
+    fn t_issue_messages_mention_keys_and_contexts() {
+        let issues = check(&[
+            "<C-n> feedlist down",
+            "<C-n> feedlist up",
+            "g feedlist home",
+            "gg feedlist open",
+            "z bogus frobnicate",
+        ]);
+        let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "`<C-n>' is already bound in context `feedlist'; the earlier binding is ignored",
+                "`gg' replaces the binding for `g' in context `feedlist', because one is a prefix of the other",
+                "`bogus' is not a valid context",
+                "`frobnicate' is not a valid operation",
+            ]
+        );
+    }
+}
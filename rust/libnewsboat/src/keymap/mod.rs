@@ -1,3 +1,9 @@
+mod lint;
+mod program;
+mod recorder;
+mod reference;
+mod trie;
+
 use nom::Parser;
 use nom::{
     IResult,
@@ -9,6 +15,15 @@ use nom::{
     sequence::{delimited, preceded},
 };
 
+pub use lint::{KeymapIssue, lint};
+pub use program::{
+    Argument, Comparison, Condition, Fragment, Interpreter, Operand, ProgramError, RUN_PROGRAM,
+    Statement, parse_operation_sequence, parse_program,
+};
+pub use recorder::{MacroError, MacroRecorder};
+pub use reference::{ReferenceFormat, render_reference};
+pub use trie::{KeyMatch, KeyTrie, MAX_COUNT};
+
 fn unquoted_token(input: &str) -> IResult<&str, String> {
     let parser = map(recognize(is_not("\t\" ;")), String::from);
     let mut parser = verify(parser, |t: &str| t != "--");
@@ -106,15 +121,22 @@ fn binding(input: &str) -> IResult<&str, Binding> {
     let (input, _) = space1(input)?;
     let (input, contexts) = contexts(input)?;
     let (input, _) = space1(input)?;
-    let (input, (operations, description)) = operation_sequence(input, true)?;
+    let Some((operations, description)) = parse_operation_sequence(input, true) else {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    };
+    let count = CountUsage::for_operations(&operations);
 
     Ok((
-        input,
+        "",
         Binding {
             key_sequence,
             contexts: contexts.into_iter().map(|s| s.to_owned()).collect(),
             operations,
             description,
+            count,
         },
     ))
 }
@@ -144,12 +166,82 @@ pub fn tokenize_operation_sequence(
     }
 }
 
+/// Operations that treat a numeric prefix (like `5` in `5<ENTER>`) as their argument rather than
+/// as a number of repetitions.
+const OPERATIONS_WITH_COUNT_ARGUMENT: &[&str] = &["goto-url", "goto-title"];
+
+/// Describes what a numeric prefix typed before a key sequence means for the bound operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountUsage {
+    /// Run the whole operation sequence that many times, e.g. `5j` moves down five times.
+    Repeat,
+    /// Pass the number to the operation as its argument, e.g. `3` followed by the key bound to
+    /// `goto-url` opens the third URL.
+    Argument,
+}
+
+impl CountUsage {
+    /// Picks the count usage for a sequence of operations.
+    ///
+    /// The count is passed as an argument only if the sequence consists of a single operation
+    /// which accepts a number, and which doesn't have any arguments already. Everything else is
+    /// repeated.
+    pub fn for_operations(operations: &[Vec<String>]) -> CountUsage {
+        match operations {
+            [operation] if operation.len() == 1 => {
+                if OPERATIONS_WITH_COUNT_ARGUMENT.contains(&operation[0].as_str()) {
+                    CountUsage::Argument
+                } else {
+                    CountUsage::Repeat
+                }
+            }
+            _ => CountUsage::Repeat,
+        }
+    }
+}
+
+/// Returns `operations` repeated `count` times in a row.
+pub fn repeat_operations(operations: &[Vec<String>], count: u32) -> Vec<Vec<String>> {
+    let times = usize::try_from(count).unwrap_or(usize::MAX);
+    operations
+        .iter()
+        .cycle()
+        .take(operations.len().saturating_mul(times))
+        .cloned()
+        .collect()
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Binding {
     pub key_sequence: String,
     pub contexts: Vec<String>,
     pub operations: Vec<Vec<String>>,
     pub description: Option<String>,
+    /// What a numeric prefix typed before `key_sequence` means for `operations`.
+    pub count: CountUsage,
+}
+
+impl Binding {
+    /// Returns the operations that should be run when the binding is triggered with a numeric
+    /// prefix `count` (or without one, if `count` is `None`).
+    pub fn operations_with_count(&self, count: Option<u32>) -> Vec<Vec<String>> {
+        let Some(count) = count else {
+            return self.operations.clone();
+        };
+
+        match self.count {
+            CountUsage::Repeat => repeat_operations(&self.operations, count),
+            CountUsage::Argument => self
+                .operations
+                .iter()
+                .map(|operation| {
+                    let mut operation = operation.clone();
+                    operation.push(count.to_string());
+                    operation
+                })
+                .collect(),
+        }
+    }
 }
 
 pub fn tokenize_binding(input: &str) -> Option<Binding> {
@@ -159,10 +251,83 @@ pub fn tokenize_binding(input: &str) -> Option<Binding> {
     }
 }
 
+/// Quotes `token` so that `tokenize_operation_sequence()` reads it back unchanged.
+///
+/// Tokens that are safe to leave as they are (e.g. `open` or `https://example.com`) are returned
+/// verbatim. Everything else is put into double quotes, with special characters escaped. Backticks
+/// and `#` are quoted too, so that the result can be written into the config file without being
+/// mistaken for a command substitution or a comment.
+pub fn quote_token(token: &str) -> String {
+    let needs_quotes = token.is_empty()
+        || token == "--"
+        || token
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | ';' | '\\' | '#' | '`'));
+    if !needs_quotes {
+        return token.to_owned();
+    }
+
+    let mut result = String::with_capacity(token.len() + 2);
+    result.push('"');
+    for c in token.chars() {
+        match c {
+            '"' => result.push_str(r#"\""#),
+            '\\' => result.push_str(r"\\"),
+            '`' => result.push_str(r"\`"),
+            '\n' => result.push_str(r"\n"),
+            '\r' => result.push_str(r"\r"),
+            '\t' => result.push_str(r"\t"),
+            _ => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Turns operations (and an optional description) back into a string that
+/// `tokenize_operation_sequence()` would parse into the same values.
+///
+/// This is the inverse of `tokenize_operation_sequence()`, up to whitespace and quoting. Empty
+/// operations are skipped, since there is no way to write them down. Empty tokens are written as
+/// `""`, which the tokenizer doesn't accept, so callers should avoid them. An empty description is
+/// left out.
+pub fn format_operation_sequence(operations: &[Vec<String>], description: Option<&str>) -> String {
+    let mut result = operations
+        .iter()
+        .filter(|operation| !operation.is_empty())
+        .map(|operation| {
+            operation
+                .iter()
+                .map(|token| quote_token(token))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(" ; ");
+
+    if let Some(description) = description.filter(|d| !d.is_empty()) {
+        result.push_str(" -- \"");
+        for c in description.chars() {
+            match c {
+                '"' => result.push_str(r#"\""#),
+                '\\' => result.push_str(r"\\"),
+                '`' => result.push_str(r"\`"),
+                _ => result.push(c),
+            }
+        }
+        result.push('"');
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
+    use super::CountUsage;
     use super::contexts;
+    use super::format_operation_sequence;
     use super::key_sequence;
+    use super::quote_token;
     use super::tokenize_binding;
     use super::tokenize_operation_sequence;
 
@@ -637,6 +802,94 @@ mod tests {
         );
     }
 
+    #[test]
+    fn t_test_tokenize_binding_repeats_operations_for_count_by_default() {
+        let binding = tokenize_binding("j articlelist down").unwrap();
+        assert_eq!(binding.count, CountUsage::Repeat);
+
+        let binding = tokenize_binding("J articlelist next ; open").unwrap();
+        assert_eq!(binding.count, CountUsage::Repeat);
+
+        let binding = tokenize_binding("u article goto-url ; open").unwrap();
+        assert_eq!(binding.count, CountUsage::Repeat);
+
+        let binding = tokenize_binding("u article goto-url 1").unwrap();
+        assert_eq!(binding.count, CountUsage::Repeat);
+    }
+
+    #[test]
+    fn t_test_tokenize_binding_passes_count_as_argument_to_goto_url() {
+        let binding = tokenize_binding("u article goto-url").unwrap();
+        assert_eq!(binding.count, CountUsage::Argument);
+    }
+
+    #[test]
+    fn t_test_tokenize_binding_passes_count_as_argument_to_goto_title() {
+        let binding = tokenize_binding("T articlelist goto-title").unwrap();
+        assert_eq!(binding.count, CountUsage::Argument);
+
+        let binding = tokenize_binding("T articlelist goto-title Rust").unwrap();
+        assert_eq!(binding.count, CountUsage::Repeat);
+    }
+
+    #[test]
+    fn t_operations_with_count_repeats_operations() {
+        let binding = tokenize_binding("J articlelist next ; open").unwrap();
+
+        assert_eq!(
+            binding.operations_with_count(None),
+            vec![vec_of_strings!("next"), vec_of_strings!("open")]
+        );
+        assert_eq!(
+            binding.operations_with_count(Some(1)),
+            vec![vec_of_strings!("next"), vec_of_strings!("open")]
+        );
+        assert_eq!(
+            binding.operations_with_count(Some(2)),
+            vec![
+                vec_of_strings!("next"),
+                vec_of_strings!("open"),
+                vec_of_strings!("next"),
+                vec_of_strings!("open")
+            ]
+        );
+        assert!(binding.operations_with_count(Some(0)).is_empty());
+    }
+
+    #[test]
+    fn t_operations_with_count_passes_count_as_argument() {
+        let binding = tokenize_binding("u article goto-url").unwrap();
+
+        assert_eq!(
+            binding.operations_with_count(None),
+            vec![vec_of_strings!("goto-url")]
+        );
+        assert_eq!(
+            binding.operations_with_count(Some(12)),
+            vec![vec_of_strings!("goto-url", "12")]
+        );
+    }
+
+    #[test]
+    fn t_test_tokenize_binding_with_conditions() {
+        let input = r#"o articlelist if unread then open else next-unread end -- "Open if unread""#;
+
+        let parsed_binding = tokenize_binding(input).unwrap();
+
+        assert_eq!(parsed_binding.contexts, vec!["articlelist"]);
+        assert_eq!(
+            parsed_binding.operations,
+            vec![vec_of_strings!(
+                "run-program",
+                "if unread then open else next-unread end"
+            )]
+        );
+        assert_eq!(
+            parsed_binding.description,
+            Some("Open if unread".to_string())
+        );
+    }
+
     #[test]
     fn t_test_tokenize_binding_missing_parts() {
         assert_eq!(tokenize_binding(""), None);
@@ -648,4 +901,66 @@ mod tests {
     fn t_test_tokenize_binding_incomplete_description_syntax() {
         assert_eq!(tokenize_binding("q everywhere quit -- "), None);
     }
+
+    #[test]
+    fn t_quote_token_leaves_plain_tokens_alone() {
+        assert_eq!(quote_token("open"), "open");
+        assert_eq!(quote_token("set-tag"), "set-tag");
+        assert_eq!(
+            quote_token("https://example.com/?a=1&b=2"),
+            "https://example.com/?a=1&b=2"
+        );
+        assert_eq!(quote_token("ёжик"), "ёжик");
+    }
+
+    #[test]
+    fn t_quote_token_quotes_and_escapes_special_tokens() {
+        assert_eq!(quote_token(""), r#""""#);
+        assert_eq!(quote_token("--"), r#""--""#);
+        assert_eq!(quote_token("two words"), r#""two words""#);
+        assert_eq!(quote_token("a;b"), r#""a;b""#);
+        assert_eq!(quote_token(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote_token(r"C:\dir"), r#""C:\\dir""#);
+        assert_eq!(quote_token("line\nbreak\ttab"), r#""line\nbreak\ttab""#);
+        assert_eq!(quote_token("#hash"), r##""#hash""##);
+        assert_eq!(quote_token("`date`"), r#""\`date\`""#);
+    }
+
+    #[test]
+    fn t_format_operation_sequence_joins_operations_with_semicolons() {
+        let operations = vec![
+            vec_of_strings!["set", "browser", "firefox %u"],
+            vec_of_strings!["open-in-browser"],
+            vec_of_strings![],
+            vec_of_strings!["set", "browser", "lynx"],
+        ];
+        assert_eq!(
+            format_operation_sequence(&operations, None),
+            r#"set browser "firefox %u" ; open-in-browser ; set browser lynx"#
+        );
+        assert_eq!(
+            format_operation_sequence(&operations[1..2], Some(r#"Open in "the" browser"#)),
+            r#"open-in-browser -- "Open in \"the\" browser""#
+        );
+        assert_eq!(
+            format_operation_sequence(&operations[1..2], Some("")),
+            "open-in-browser"
+        );
+        assert_eq!(format_operation_sequence(&[], None), "");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn t_format_operation_sequence_round_trips(
+            operations in proptest::collection::vec(
+                proptest::collection::vec("\\PC+", 1..4),
+                1..4
+            ),
+            description in proptest::option::of("\\PC+"),
+        ) {
+            let formatted = format_operation_sequence(&operations, description.as_deref());
+            let parsed = tokenize_operation_sequence(&formatted, true);
+            proptest::prop_assert_eq!(parsed, Some((operations, description)));
+        }
+    }
 }
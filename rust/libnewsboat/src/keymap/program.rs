@@ -0,0 +1,837 @@
+//! Operation sequences with conditions and variables, e.g.
+//! `set-var n 3 ; if unread > 0 then open else next-feed end ; open-in-browser "%{link}"`.
+//!
+//! This is a superset of the syntax that `tokenize_operation_sequence()` accepts: any sequence
+//! that doesn't use the `if` keyword, `set-var` or `%{...}` means exactly what it used to.
+
+use super::{operation_description, quoted_token, semicolon, unquoted_token};
+use gettextrs::gettext;
+use nom::Parser;
+use nom::{
+    IResult,
+    branch::alt,
+    character::complete::{space0, space1},
+    combinator::{complete, consumed, eof, map, opt, value, verify},
+    multi::{many0, many1, separated_list0},
+    sequence::preceded,
+};
+use std::collections::BTreeMap;
+use strprintf::fmt;
+
+/// Name of the operation that assigns a variable.
+const SET_VARIABLE: &str = "set-var";
+
+/// Name of the operation that runs a program. Its only argument is the program's source.
+pub const RUN_PROGRAM: &str = "run-program";
+
+/// A piece of an argument: either literal text, or a reference to a variable (`%{name}`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fragment {
+    Text(String),
+    Variable(String),
+}
+
+/// An argument, which is a concatenation of its fragments.
+pub type Argument = Vec<Fragment>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// One side of a condition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    /// An unquoted name, like `unread`.
+    Variable(String),
+    /// Anything else: a number, a quoted string, or a string with `%{...}` in it.
+    Value(Argument),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// True if the operand is neither empty nor zero.
+    Truthy(Operand),
+    Compare {
+        left: Operand,
+        comparison: Comparison,
+        right: Operand,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    /// An operation with its arguments, e.g. `set browser lynx`.
+    Operation {
+        name: String,
+        arguments: Vec<Argument>,
+    },
+
+    /// `set-var <name> <value>`
+    SetVariable { name: String, value: Argument },
+
+    /// `if <condition> then <statements> [else <statements>] [end]`
+    If {
+        condition: Condition,
+        then: Vec<Statement>,
+        otherwise: Vec<Statement>,
+    },
+}
+
+/// A token, remembering whether it was quoted: keywords and variable names have to be unquoted.
+#[derive(Debug, PartialEq)]
+enum Word {
+    Quoted(String),
+    Unquoted(String),
+}
+
+impl Word {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Word::Unquoted(word) if word == keyword)
+    }
+
+    fn into_string(self) -> String {
+        match self {
+            Word::Quoted(word) | Word::Unquoted(word) => word,
+        }
+    }
+}
+
+fn word(input: &str) -> IResult<&str, Word> {
+    alt((
+        map(quoted_token, Word::Quoted),
+        map(unquoted_token, Word::Unquoted),
+    ))
+    .parse(input)
+}
+
+/// Parses `keyword` as an unquoted word.
+fn keyword<'a>(keyword: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, Word> {
+    move |input| verify(word, |w: &Word| w.is_keyword(keyword)).parse(input)
+}
+
+/// A word that can be used inside a statement. Inside of `if` branches, `else` and `end` end the
+/// branch, so they can't be used unquoted.
+fn statement_word(nested: bool) -> impl FnMut(&str) -> IResult<&str, Word> {
+    move |input| {
+        verify(word, |w: &Word| {
+            !(nested && (w.is_keyword("else") || w.is_keyword("end")))
+        })
+        .parse(input)
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Splits a token into literal text and `%{name}` references. A `%{` which isn't followed by
+/// a valid name and a closing brace is kept as text.
+fn interpolated(token: &str) -> Argument {
+    let mut fragments = Vec::new();
+    let mut text = String::new();
+    let mut rest = token;
+
+    while let Some(start) = rest.find("%{") {
+        let after = &rest[start + 2..];
+        let reference = after
+            .find('}')
+            .map(|end| &after[..end])
+            .filter(|name| is_identifier(name));
+        match reference {
+            Some(name) => {
+                text.push_str(&rest[..start]);
+                if !text.is_empty() {
+                    fragments.push(Fragment::Text(std::mem::take(&mut text)));
+                }
+                fragments.push(Fragment::Variable(name.to_owned()));
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                text.push_str(&rest[..start + 2]);
+                rest = after;
+            }
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() || fragments.is_empty() {
+        fragments.push(Fragment::Text(text));
+    }
+
+    fragments
+}
+
+fn operand(input: &str) -> IResult<&str, Operand> {
+    let (input, word) = verify(word, |w: &Word| !w.is_keyword("then")).parse(input)?;
+    let operand = match word {
+        Word::Unquoted(name) if is_identifier(&name) => Operand::Variable(name),
+        word => Operand::Value(interpolated(&word.into_string())),
+    };
+    Ok((input, operand))
+}
+
+fn comparison(input: &str) -> IResult<&str, Comparison> {
+    let operator = |op: &'static str| keyword(op);
+    alt((
+        value(Comparison::Equal, operator("==")),
+        value(Comparison::NotEqual, operator("!=")),
+        value(Comparison::LessOrEqual, operator("<=")),
+        value(Comparison::Less, operator("<")),
+        value(Comparison::GreaterOrEqual, operator(">=")),
+        value(Comparison::Greater, operator(">")),
+    ))
+    .parse(input)
+}
+
+fn condition(input: &str) -> IResult<&str, Condition> {
+    let (input, left) = operand(input)?;
+    let (input, rest) =
+        opt((preceded(space1, comparison), preceded(space1, operand))).parse(input)?;
+    let condition = match rest {
+        Some((comparison, right)) => Condition::Compare {
+            left,
+            comparison,
+            right,
+        },
+        None => Condition::Truthy(left),
+    };
+    Ok((input, condition))
+}
+
+fn if_statement(input: &str) -> IResult<&str, Statement> {
+    let (input, _) = keyword("if").parse(input)?;
+    let (input, condition) = preceded(space1, condition).parse(input)?;
+    let (input, _) = preceded(space1, keyword("then")).parse(input)?;
+    let (input, then) = branch(input)?;
+    let (input, otherwise) = opt(preceded((space0, keyword("else")), branch)).parse(input)?;
+    let (input, _) = opt(preceded(space0, keyword("end"))).parse(input)?;
+
+    Ok((
+        input,
+        Statement::If {
+            condition,
+            then,
+            otherwise: otherwise.unwrap_or_default(),
+        },
+    ))
+}
+
+/// Statements of a `then` or `else` branch, up to (but not including) the next `else` or `end`.
+fn branch(input: &str) -> IResult<&str, Vec<Statement>> {
+    let (input, _) = space0(input)?;
+    let (input, _) = many0(semicolon).parse(input)?;
+    let (input, statements) = statements(input, true)?;
+    let (input, _) = many0(semicolon).parse(input)?;
+    Ok((input, statements))
+}
+
+fn operation(input: &str, nested: bool) -> IResult<&str, Statement> {
+    // An unquoted `if` always starts an `if` statement, so it can't be an operation name
+    let (input, name) =
+        verify(statement_word(nested), |w: &Word| !w.is_keyword("if")).parse(input)?;
+    let (input, arguments) = many0(preceded(space1, statement_word(nested))).parse(input)?;
+
+    let name = name.into_string();
+    if name == SET_VARIABLE {
+        return match <[Word; 2]>::try_from(arguments) {
+            Ok([Word::Unquoted(variable), value]) if is_identifier(&variable) => Ok((
+                input,
+                Statement::SetVariable {
+                    name: variable,
+                    value: interpolated(&value.into_string()),
+                },
+            )),
+            _ => Err(nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::Verify,
+            ))),
+        };
+    }
+
+    let arguments = arguments
+        .into_iter()
+        .map(|argument| interpolated(&argument.into_string()))
+        .collect();
+    Ok((input, Statement::Operation { name, arguments }))
+}
+
+fn statement(input: &str, nested: bool) -> IResult<&str, Statement> {
+    alt((if_statement, |i| operation(i, nested))).parse(input)
+}
+
+fn statements(input: &str, nested: bool) -> IResult<&str, Vec<Statement>> {
+    separated_list0(many1(semicolon), |i| statement(i, nested)).parse(input)
+}
+
+/// Parses a program, returning its statements together with their source (without the
+/// description) and the description.
+fn program(
+    input: &str,
+    allow_description: bool,
+) -> IResult<&str, (Vec<Statement>, &str, Option<String>)> {
+    let (input, _) = space0(input)?;
+    let (input, (source, statements)) = consumed(|i| {
+        let (i, _) = many0(semicolon).parse(i)?;
+        let (i, statements) = statements(i, false)?;
+        let (i, _) = many0(semicolon).parse(i)?;
+        Ok((i, statements))
+    })
+    .parse(input)?;
+
+    let (input, description) = if allow_description {
+        opt(operation_description).parse(input)?
+    } else {
+        (input, None)
+    };
+
+    let (input, _) = space0(input)?;
+    let (input, _) = complete(eof).parse(input)?;
+
+    Ok((input, (statements, source.trim_end(), description)))
+}
+
+/// Parses an operation sequence which can contain conditions and variables.
+///
+/// The syntax is that of `tokenize_operation_sequence()`, with these additions:
+/// - `if <condition> then <statements> else <statements> end`, where `else` and `end` are
+///   optional. A condition is either a single operand, which is true unless it's empty or `0`, or
+///   two operands compared with one of `==`, `!=`, `<`, `<=`, `>`, `>=`. An unquoted name in
+///   a condition is a variable; anything else is a value;
+/// - `set-var <name> <value>` assigns a variable;
+/// - `%{name}` inside an argument is replaced by the value of the variable.
+///
+/// Keywords only have their special meaning when unquoted. Returns the statements together with
+/// an optional description, or `None` if the input could not be parsed.
+pub fn parse_program(
+    input: &str,
+    allow_description: bool,
+) -> Option<(Vec<Statement>, Option<String>)> {
+    match program(input, allow_description) {
+        Ok((_leftovers, (statements, _source, description))) => Some((statements, description)),
+        Err(_error) => None,
+    }
+}
+
+/// Returns the operations of `statements`, or `None` if they need to be evaluated while they run
+/// (because they use `if`, `set-var` or `%{...}`).
+fn plain_operations(statements: &[Statement]) -> Option<Vec<Vec<String>>> {
+    statements
+        .iter()
+        .map(|statement| match statement {
+            Statement::Operation { name, arguments } => {
+                let mut operation = vec![name.clone()];
+                for argument in arguments {
+                    match argument.as_slice() {
+                        [Fragment::Text(text)] => operation.push(text.clone()),
+                        _ => return None,
+                    }
+                }
+                Some(operation)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parses an operation sequence with `parse_program()`, and returns its operations the way
+/// `tokenize_operation_sequence()` does.
+///
+/// A program that uses conditions or variables can only be evaluated while it runs, so it's
+/// returned as a single `run-program` operation with the program's source as its argument.
+pub fn parse_operation_sequence(
+    input: &str,
+    allow_description: bool,
+) -> Option<(Vec<Vec<String>>, Option<String>)> {
+    let (_leftovers, (statements, source, description)) = program(input, allow_description).ok()?;
+    let operations = plain_operations(&statements)
+        .unwrap_or_else(|| vec![vec![RUN_PROGRAM.to_owned(), source.to_owned()]]);
+    Some((operations, description))
+}
+
+/// Errors that can happen while running a program.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProgramError {
+    /// The variable is neither set by `set-var` nor provided by the caller.
+    UndefinedVariable(String),
+}
+
+impl std::fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            ProgramError::UndefinedVariable(name) => {
+                fmt!(&gettext("Undefined variable `%s'"), name)
+            }
+        };
+        write!(f, "{message}")
+    }
+}
+
+/// Runs a program one operation at a time.
+///
+/// Conditions and arguments are evaluated only when the interpreter gets to them, so that they see
+/// the effects of the operations executed before them (e.g. `next-unread ; if unread > 0 then
+/// ...` checks the item that `next-unread` moved to).
+pub struct Interpreter {
+    /// Statements that are yet to be run, innermost block last.
+    blocks: Vec<std::vec::IntoIter<Statement>>,
+    variables: BTreeMap<String, String>,
+}
+
+impl Interpreter {
+    pub fn new(program: Vec<Statement>) -> Interpreter {
+        Interpreter {
+            blocks: vec![program.into_iter()],
+            variables: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the variables set by `set-var` so far.
+    pub fn variables(&self) -> &BTreeMap<String, String> {
+        &self.variables
+    }
+
+    /// Runs the program up to the next operation, and returns that operation (name followed by
+    /// arguments), or `None` if the program is finished.
+    ///
+    /// `lookup` provides the variables that aren't set by `set-var`, e.g. attributes of the
+    /// current item. Variables set by `set-var` take precedence.
+    pub fn next_operation(
+        &mut self,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Option<Vec<String>>, ProgramError> {
+        while let Some(block) = self.blocks.last_mut() {
+            let Some(statement) = block.next() else {
+                self.blocks.pop();
+                continue;
+            };
+
+            match statement {
+                Statement::Operation { name, arguments } => {
+                    let mut operation = vec![name];
+                    for argument in &arguments {
+                        operation.push(self.evaluate(argument, lookup)?);
+                    }
+                    return Ok(Some(operation));
+                }
+                Statement::SetVariable { name, value } => {
+                    let value = self.evaluate(&value, lookup)?;
+                    self.variables.insert(name, value);
+                }
+                Statement::If {
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    let branch = if self.check(&condition, lookup)? {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    self.blocks.push(branch.into_iter());
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn variable(
+        &self,
+        name: &str,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<String, ProgramError> {
+        self.variables
+            .get(name)
+            .cloned()
+            .or_else(|| lookup(name))
+            .ok_or_else(|| ProgramError::UndefinedVariable(name.to_owned()))
+    }
+
+    fn evaluate(
+        &self,
+        argument: &Argument,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<String, ProgramError> {
+        let mut result = String::new();
+        for fragment in argument {
+            match fragment {
+                Fragment::Text(text) => result.push_str(text),
+                Fragment::Variable(name) => result.push_str(&self.variable(name, lookup)?),
+            }
+        }
+        Ok(result)
+    }
+
+    fn operand(
+        &self,
+        operand: &Operand,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<String, ProgramError> {
+        match operand {
+            Operand::Variable(name) => self.variable(name, lookup),
+            Operand::Value(argument) => self.evaluate(argument, lookup),
+        }
+    }
+
+    fn check(
+        &self,
+        condition: &Condition,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<bool, ProgramError> {
+        match condition {
+            Condition::Truthy(operand) => {
+                let value = self.operand(operand, lookup)?;
+                Ok(!value.is_empty() && !matches!(value.parse::<f64>(), Ok(n) if n == 0.0))
+            }
+            Condition::Compare {
+                left,
+                comparison,
+                right,
+            } => {
+                let left = self.operand(left, lookup)?;
+                let right = self.operand(right, lookup)?;
+                // Compare as numbers if both sides are numbers, and as strings otherwise
+                let ordering = match (left.parse::<f64>(), right.parse::<f64>()) {
+                    (Ok(left), Ok(right)) => left.partial_cmp(&right),
+                    _ => Some(left.cmp(&right)),
+                };
+                let Some(ordering) = ordering else {
+                    // NaN isn't equal to anything, not even itself
+                    return Ok(*comparison == Comparison::NotEqual);
+                };
+                Ok(match comparison {
+                    Comparison::Equal => ordering.is_eq(),
+                    Comparison::NotEqual => ordering.is_ne(),
+                    Comparison::Less => ordering.is_lt(),
+                    Comparison::LessOrEqual => ordering.is_le(),
+                    Comparison::Greater => ordering.is_gt(),
+                    Comparison::GreaterOrEqual => ordering.is_ge(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tokenize_operation_sequence;
+    use super::*;
+
+    fn text(text: &str) -> Argument {
+        vec![Fragment::Text(text.to_owned())]
+    }
+
+    fn op(name: &str, arguments: &[&str]) -> Statement {
+        Statement::Operation {
+            name: name.to_owned(),
+            arguments: arguments.iter().map(|a| text(a)).collect(),
+        }
+    }
+
+    fn parse(input: &str) -> Vec<Statement> {
+        parse_program(input, false).unwrap().0
+    }
+
+    /// Runs `input` to completion, with variables from `attributes` available.
+    fn run(input: &str, attributes: &[(&str, &str)]) -> Result<Vec<Vec<String>>, ProgramError> {
+        let program = parse(input);
+        let lookup = |name: &str| {
+            attributes
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        };
+        let mut interpreter = Interpreter::new(program);
+        let mut operations = Vec::new();
+        while let Some(operation) = interpreter.next_operation(&lookup)? {
+            operations.push(operation);
+        }
+        Ok(operations)
+    }
+
+    fn ops(operations: &[&[&str]]) -> Vec<Vec<String>> {
+        operations
+            .iter()
+            .map(|operation| operation.iter().map(|token| token.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn t_parses_plain_sequences_like_tokenize_operation_sequence() {
+        let inputs = [
+            "open",
+            r#"set browser "firefox %u" ; open-in-browser ; set browser lynx"#,
+            ";; quit ;; ",
+            r#"set "arg 1" "if" then else end -- "description""#,
+        ];
+        for input in inputs {
+            let (statements, description) = parse_program(input, true).unwrap();
+            let (operations, expected_description) =
+                tokenize_operation_sequence(input, true).unwrap();
+            let expected: Vec<Statement> = operations
+                .iter()
+                .map(|operation| Statement::Operation {
+                    name: operation[0].clone(),
+                    arguments: operation[1..].iter().map(|a| text(a)).collect(),
+                })
+                .collect();
+            assert_eq!(statements, expected, "input: {input}");
+            assert_eq!(description, expected_description);
+        }
+    }
+
+    #[test]
+    fn t_parses_if_statements() {
+        assert_eq!(
+            parse("if unread > 0 then open else next-feed"),
+            vec![Statement::If {
+                condition: Condition::Compare {
+                    left: Operand::Variable("unread".to_owned()),
+                    comparison: Comparison::Greater,
+                    right: Operand::Value(text("0")),
+                },
+                then: vec![op("open", &[])],
+                otherwise: vec![op("next-feed", &[])],
+            }]
+        );
+
+        assert_eq!(
+            parse("if flagged then toggle-article-read ; next end ; quit"),
+            vec![
+                Statement::If {
+                    condition: Condition::Truthy(Operand::Variable("flagged".to_owned())),
+                    then: vec![op("toggle-article-read", &[]), op("next", &[])],
+                    otherwise: vec![],
+                },
+                op("quit", &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn t_parses_nested_if_statements() {
+        assert_eq!(
+            parse(r#"if a then if b == "x y" then one end else two end"#),
+            vec![Statement::If {
+                condition: Condition::Truthy(Operand::Variable("a".to_owned())),
+                then: vec![Statement::If {
+                    condition: Condition::Compare {
+                        left: Operand::Variable("b".to_owned()),
+                        comparison: Comparison::Equal,
+                        right: Operand::Value(text("x y")),
+                    },
+                    then: vec![op("one", &[])],
+                    otherwise: vec![],
+                }],
+                otherwise: vec![op("two", &[])],
+            }]
+        );
+    }
+
+    #[test]
+    fn t_quoted_keywords_are_ordinary_arguments() {
+        assert_eq!(
+            parse(r#"if x then set-tag "else" ; echo "end" end"#),
+            vec![Statement::If {
+                condition: Condition::Truthy(Operand::Variable("x".to_owned())),
+                then: vec![op("set-tag", &["else"]), op("echo", &["end"])],
+                otherwise: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn t_parses_set_var_and_interpolation() {
+        assert_eq!(
+            parse(r#"set-var n 3 ; open-in-browser "%{link}?page=%{n}" ; echo 100%{"#),
+            vec![
+                Statement::SetVariable {
+                    name: "n".to_owned(),
+                    value: text("3"),
+                },
+                Statement::Operation {
+                    name: "open-in-browser".to_owned(),
+                    arguments: vec![vec![
+                        Fragment::Variable("link".to_owned()),
+                        Fragment::Text("?page=".to_owned()),
+                        Fragment::Variable("n".to_owned()),
+                    ]],
+                },
+                op("echo", &["100%{"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn t_interpolation_requires_valid_names() {
+        assert_eq!(interpolated("%u"), text("%u"));
+        assert_eq!(interpolated("%{}"), text("%{}"));
+        assert_eq!(interpolated("%{a b}"), text("%{a b}"));
+        assert_eq!(
+            interpolated("%{%{x}"),
+            vec![
+                Fragment::Text("%{".to_owned()),
+                Fragment::Variable("x".to_owned())
+            ]
+        );
+        assert_eq!(interpolated(""), text(""));
+    }
+
+    #[test]
+    fn t_rejects_malformed_programs() {
+        assert_eq!(parse_program("if then open", false), None);
+        assert_eq!(parse_program("if x open", false), None);
+        assert_eq!(parse_program("if x > then open", false), None);
+        assert_eq!(parse_program("if x then open else else", false), None);
+        assert_eq!(parse_program("set-var n", false), None);
+        assert_eq!(parse_program("set-var \"n\" 1", false), None);
+        assert_eq!(parse_program("set-var 1n 1", false), None);
+        assert_eq!(parse_program("open -- \"description\"", false), None);
+    }
+
+    #[test]
+    fn t_runs_branches_depending_on_condition() {
+        let program = "if unread > 0 then open else next-feed";
+        assert_eq!(run(program, &[("unread", "3")]).unwrap(), ops(&[&["open"]]));
+        assert_eq!(
+            run(program, &[("unread", "0")]).unwrap(),
+            ops(&[&["next-feed"]])
+        );
+    }
+
+    #[test]
+    fn t_compares_numbers_numerically_and_strings_lexicographically() {
+        let check = |condition: &str, expected: bool| {
+            let program = format!("if {condition} then yes else no");
+            let expected = if expected { "yes" } else { "no" };
+            assert_eq!(
+                run(&program, &[("empty", "")]).unwrap(),
+                ops(&[&[expected]]),
+                "condition: {condition}"
+            );
+        };
+
+        check("10 > 9", true);
+        check("\"10\" > \"9\"", true);
+        check("1.5 <= 1.50", true);
+        check("abc! < abd!", true);
+        check("\"b\" >= \"a\"", true);
+        check("x!  ==  x!", true);
+        check("\"a\" != \"a\"", false);
+        check("0", false);
+        check("0.0", false);
+        check("empty", false);
+        check("\"no\"", true);
+        check("\"NaN\" == \"NaN\"", false);
+        check("\"NaN\" != \"NaN\"", true);
+    }
+
+    #[test]
+    fn t_set_var_overrides_lookup_and_is_used_in_arguments() {
+        assert_eq!(
+            run(
+                r#"echo %{title} ; set-var title "new %{title}" ; echo %{title}"#,
+                &[("title", "old")]
+            )
+            .unwrap(),
+            ops(&[&["echo", "old"], &["echo", "new old"]])
+        );
+    }
+
+    #[test]
+    fn t_reports_undefined_variables() {
+        assert_eq!(
+            run("open ; if missing then quit", &[]),
+            Err(ProgramError::UndefinedVariable("missing".to_owned()))
+        );
+        assert_eq!(
+            ProgramError::UndefinedVariable("link".to_owned()).to_string(),
+            "Undefined variable `link'"
+        );
+    }
+
+    #[test]
+    fn t_evaluates_conditions_lazily() {
+        let program = parse("next-unread ; if unread > 0 then open");
+        let unread = std::cell::Cell::new(0);
+        let lookup = |name: &str| (name == "unread").then(|| unread.get().to_string());
+
+        let mut interpreter = Interpreter::new(program);
+        assert_eq!(
+            interpreter.next_operation(&lookup).unwrap(),
+            Some(vec!["next-unread".to_owned()])
+        );
+        // Executing `next-unread` moved us to an unread article
+        unread.set(1);
+        assert_eq!(
+            interpreter.next_operation(&lookup).unwrap(),
+            Some(vec!["open".to_owned()])
+        );
+        assert_eq!(interpreter.next_operation(&lookup).unwrap(), None);
+    }
+
+    #[test]
+    fn t_runs_nested_blocks_in_order() {
+        assert_eq!(
+            run(
+                "a ; if x then b ; if y then c else d end ; e end ; f",
+                &[("x", "1"), ("y", "")]
+            )
+            .unwrap(),
+            ops(&[&["a"], &["b"], &["d"], &["e"], &["f"]])
+        );
+    }
+
+    #[test]
+    fn t_parse_operation_sequence_keeps_plain_sequences_as_operations() {
+        for input in [
+            "open",
+            r#"set browser "firefox %u" ; open-in-browser -- "description""#,
+            ";; quit ;; ",
+        ] {
+            assert_eq!(
+                parse_operation_sequence(input, true),
+                tokenize_operation_sequence(input, true),
+                "input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn t_parse_operation_sequence_wraps_programs_into_run_program() {
+        assert_eq!(
+            parse_operation_sequence(
+                r#" ; if unread > 0 then open else next-feed ;  -- "Open or skip""#,
+                true
+            ),
+            Some((
+                ops(&[&["run-program", "; if unread > 0 then open else next-feed ;"]]),
+                Some("Open or skip".to_owned())
+            ))
+        );
+        assert_eq!(
+            parse_operation_sequence(r#"open-in-browser "%{link}""#, false),
+            Some((
+                ops(&[&["run-program", r#"open-in-browser "%{link}""#]]),
+                None
+            ))
+        );
+        assert_eq!(parse_operation_sequence("if then", false), None);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn t_does_not_crash_on_any_input(ref input in "\\PC*") {
+            let _ = parse_program(input, true);
+        }
+    }
+}
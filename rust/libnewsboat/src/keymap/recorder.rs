@@ -0,0 +1,397 @@
+//! Records operations executed in the UI into named registers, so that they can be replayed later
+//! or saved as a `macro` command.
+
+use super::{format_operation_sequence, quote_token, repeat_operations};
+use gettextrs::gettext;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use strprintf::fmt;
+
+/// Errors that `MacroRecorder` can return.
+#[derive(Debug)]
+pub enum MacroError {
+    /// Registers are named by a single ASCII letter or digit; this one isn't.
+    InvalidRegister(char),
+
+    /// A recording into given register is already in progress.
+    AlreadyRecording(char),
+
+    /// Recording was asked to stop, but it was never started.
+    NotRecording,
+
+    /// Given register doesn't contain any operations.
+    EmptyRegister(char),
+
+    /// Couldn't append a `macro` command to the config file.
+    CantWriteConfig { path: PathBuf, reason: io::Error },
+}
+
+impl std::fmt::Display for MacroError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            MacroError::InvalidRegister(register) => fmt!(
+                &gettext("`%s' is not a valid register name (use a letter or a digit)"),
+                &register.to_string()
+            ),
+            MacroError::AlreadyRecording(register) => fmt!(
+                &gettext("Already recording into register `%s'"),
+                &register.to_string()
+            ),
+            MacroError::NotRecording => gettext("Not recording"),
+            MacroError::EmptyRegister(register) => {
+                fmt!(&gettext("Register `%s' is empty"), &register.to_string())
+            }
+            MacroError::CantWriteConfig { path, reason } => fmt!(
+                &gettext("Couldn't write macro to `%s': %s"),
+                &path.display().to_string(),
+                &reason.to_string()
+            ),
+        };
+        write!(f, "{message}")
+    }
+}
+
+/// A recording that's currently in progress.
+struct Recording {
+    register: char,
+    operations: Vec<Vec<String>>,
+}
+
+/// Vim-style macro recorder: the user starts recording into a register, performs some
+/// operations, and stops the recording. The operations can then be replayed, or saved into the
+/// config as a `macro` command.
+///
+/// Operations are stored the same way `tokenize_operation_sequence()` returns them: the name of
+/// the operation followed by its arguments.
+#[derive(Default)]
+pub struct MacroRecorder {
+    registers: BTreeMap<char, Vec<Vec<String>>>,
+    recording: Option<Recording>,
+}
+
+fn check_register(register: char) -> Result<(), MacroError> {
+    if register.is_ascii_alphanumeric() {
+        Ok(())
+    } else {
+        Err(MacroError::InvalidRegister(register))
+    }
+}
+
+impl MacroRecorder {
+    pub fn new() -> MacroRecorder {
+        MacroRecorder::default()
+    }
+
+    /// Starts recording into `register`. The register's current contents are kept until the
+    /// recording is stopped.
+    pub fn start_recording(&mut self, register: char) -> Result<(), MacroError> {
+        check_register(register)?;
+        if let Some(ref recording) = self.recording {
+            return Err(MacroError::AlreadyRecording(recording.register));
+        }
+
+        self.recording = Some(Recording {
+            register,
+            operations: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Stops recording and stores the recorded operations in the register. Returns the name of
+    /// that register.
+    ///
+    /// If no operations were recorded, the register is cleared.
+    pub fn stop_recording(&mut self) -> Result<char, MacroError> {
+        let recording = self.recording.take().ok_or(MacroError::NotRecording)?;
+        if recording.operations.is_empty() {
+            self.registers.remove(&recording.register);
+        } else {
+            self.registers
+                .insert(recording.register, recording.operations);
+        }
+        Ok(recording.register)
+    }
+
+    /// Stops recording without touching the register.
+    pub fn cancel_recording(&mut self) {
+        self.recording = None;
+    }
+
+    /// Returns the register that's being recorded into, or `None` if there's no recording in
+    /// progress.
+    pub fn recording_register(&self) -> Option<char> {
+        self.recording.as_ref().map(|recording| recording.register)
+    }
+
+    /// Adds an operation that was just executed to the recording. Does nothing if there's no
+    /// recording in progress.
+    ///
+    /// The caller shouldn't pass the operations that control the recording itself, or they will
+    /// end up in the macro.
+    pub fn record(&mut self, operation: &[String]) {
+        if operation.is_empty() {
+            return;
+        }
+        if let Some(ref mut recording) = self.recording {
+            recording.operations.push(operation.to_vec());
+        }
+    }
+
+    /// Returns the operations stored in `register`, if any.
+    pub fn register(&self, register: char) -> Option<&[Vec<String>]> {
+        self.registers.get(&register).map(Vec::as_slice)
+    }
+
+    /// Returns the operations that should be executed to replay `register` `count` times (once if
+    /// `count` is `None`).
+    pub fn replay(
+        &self,
+        register: char,
+        count: Option<u32>,
+    ) -> Result<Vec<Vec<String>>, MacroError> {
+        check_register(register)?;
+        let operations = self
+            .register(register)
+            .ok_or(MacroError::EmptyRegister(register))?;
+        Ok(repeat_operations(operations, count.unwrap_or(1)))
+    }
+
+    /// Returns a `macro` command that binds the contents of `register` to `key`, e.g.
+    /// `macro a set browser "lynx %u" ; open-in-browser -- "Open in Lynx"`.
+    pub fn macro_command(
+        &self,
+        register: char,
+        key: &str,
+        description: Option<&str>,
+    ) -> Result<String, MacroError> {
+        check_register(register)?;
+        let operations = self
+            .register(register)
+            .ok_or(MacroError::EmptyRegister(register))?;
+        Ok(format!(
+            "macro {} {}",
+            quote_token(key),
+            format_operation_sequence(operations, description)
+        ))
+    }
+
+    /// Appends the `macro` command for `register` (see `macro_command()`) to the config file at
+    /// `path`, creating the file if it doesn't exist.
+    pub fn save_macro(
+        &self,
+        register: char,
+        key: &str,
+        description: Option<&str>,
+        path: &Path,
+    ) -> Result<(), MacroError> {
+        let command = self.macro_command(register, key, description)?;
+
+        let cant_write = |reason| MacroError::CantWriteConfig {
+            path: path.to_owned(),
+            reason,
+        };
+
+        // If the file doesn't end with a newline, our command would be glued to its last line
+        let needs_newline = match fs::read(path) {
+            Ok(contents) => contents.last().is_some_and(|&byte| byte != b'\n'),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => false,
+            Err(e) => return Err(cant_write(e)),
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(cant_write)?;
+        let separator = if needs_newline { "\n" } else { "" };
+        writeln!(file, "{separator}{command}").map_err(cant_write)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tokenize_operation_sequence;
+    use super::*;
+    use tempfile::TempDir;
+
+    fn op(tokens: &[&str]) -> Vec<String> {
+        tokens.iter().map(|token| token.to_string()).collect()
+    }
+
+    fn recorded(register: char, operations: &[&[&str]]) -> MacroRecorder {
+        let mut recorder = MacroRecorder::new();
+        recorder.start_recording(register).unwrap();
+        for operation in operations {
+            recorder.record(&op(operation));
+        }
+        recorder.stop_recording().unwrap();
+        recorder
+    }
+
+    #[test]
+    fn t_records_operations_into_register() {
+        let mut recorder = MacroRecorder::new();
+        assert_eq!(recorder.recording_register(), None);
+
+        recorder.record(&op(&["quit"]));
+        recorder.start_recording('a').unwrap();
+        assert_eq!(recorder.recording_register(), Some('a'));
+        recorder.record(&op(&["down"]));
+        recorder.record(&[]);
+        recorder.record(&op(&["set-tag", "news"]));
+        assert_eq!(recorder.register('a'), None);
+
+        assert_eq!(recorder.stop_recording().unwrap(), 'a');
+        assert_eq!(recorder.recording_register(), None);
+        recorder.record(&op(&["quit"]));
+
+        let expected = vec![op(&["down"]), op(&["set-tag", "news"])];
+        assert_eq!(recorder.register('a'), Some(expected.as_slice()));
+    }
+
+    #[test]
+    fn t_recording_replaces_register_contents() {
+        let mut recorder = recorded('x', &[&["open"]]);
+        recorder.start_recording('x').unwrap();
+        recorder.record(&op(&["quit"]));
+        recorder.stop_recording().unwrap();
+        assert_eq!(recorder.register('x'), Some([op(&["quit"])].as_slice()));
+
+        recorder.start_recording('x').unwrap();
+        recorder.stop_recording().unwrap();
+        assert_eq!(recorder.register('x'), None);
+    }
+
+    #[test]
+    fn t_cancelled_recording_keeps_register_contents() {
+        let mut recorder = recorded('x', &[&["open"]]);
+        recorder.start_recording('x').unwrap();
+        recorder.record(&op(&["quit"]));
+        recorder.cancel_recording();
+
+        assert_eq!(recorder.recording_register(), None);
+        assert_eq!(recorder.register('x'), Some([op(&["open"])].as_slice()));
+    }
+
+    #[test]
+    fn t_rejects_invalid_recording_requests() {
+        let mut recorder = MacroRecorder::new();
+        assert!(matches!(
+            recorder.start_recording('@'),
+            Err(MacroError::InvalidRegister('@'))
+        ));
+        assert!(matches!(
+            recorder.stop_recording(),
+            Err(MacroError::NotRecording)
+        ));
+
+        recorder.start_recording('a').unwrap();
+        assert!(matches!(
+            recorder.start_recording('b'),
+            Err(MacroError::AlreadyRecording('a'))
+        ));
+    }
+
+    #[test]
+    fn t_replays_register_count_times() {
+        let recorder = recorded('1', &[&["down"], &["toggle-article-read"]]);
+
+        assert_eq!(
+            recorder.replay('1', None).unwrap(),
+            vec![op(&["down"]), op(&["toggle-article-read"])]
+        );
+        assert_eq!(
+            recorder.replay('1', Some(2)).unwrap(),
+            vec![
+                op(&["down"]),
+                op(&["toggle-article-read"]),
+                op(&["down"]),
+                op(&["toggle-article-read"]),
+            ]
+        );
+        assert_eq!(
+            recorder.replay('1', Some(0)).unwrap(),
+            Vec::<Vec<String>>::new()
+        );
+        assert!(matches!(
+            recorder.replay('2', None),
+            Err(MacroError::EmptyRegister('2'))
+        ));
+        assert!(matches!(
+            recorder.replay(' ', None),
+            Err(MacroError::InvalidRegister(' '))
+        ));
+    }
+
+    #[test]
+    fn t_macro_command_quotes_tokens() {
+        let recorder = recorded(
+            'a',
+            &[&["set", "browser", "lynx \"%u\""], &["open-in-browser"]],
+        );
+
+        let command = recorder
+            .macro_command('a', "l", Some("Open in Lynx"))
+            .unwrap();
+        assert_eq!(
+            command,
+            r#"macro l set browser "lynx \"%u\"" ; open-in-browser -- "Open in Lynx""#
+        );
+
+        let operations = command.strip_prefix("macro l ").unwrap();
+        assert_eq!(
+            tokenize_operation_sequence(operations, true),
+            Some((
+                recorder.register('a').unwrap().to_vec(),
+                Some("Open in Lynx".to_string())
+            ))
+        );
+
+        assert_eq!(
+            recorder.macro_command('a', "^A", None).unwrap(),
+            r#"macro ^A set browser "lynx \"%u\"" ; open-in-browser"#
+        );
+        assert!(matches!(
+            recorder.macro_command('b', "x", None),
+            Err(MacroError::EmptyRegister('b'))
+        ));
+    }
+
+    #[test]
+    fn t_save_macro_appends_command_to_config() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("config");
+        let recorder = recorded('a', &[&["reload"]]);
+
+        recorder.save_macro('a', "r", None, &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "macro r reload\n");
+
+        fs::write(&path, "# no trailing newline").unwrap();
+        recorder
+            .save_macro('a', "r", Some("Reload"), &path)
+            .unwrap();
+        recorder.save_macro('a', "R", None, &path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# no trailing newline\nmacro r reload -- \"Reload\"\nmacro R reload\n"
+        );
+    }
+
+    #[test]
+    fn t_save_macro_reports_write_errors() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("missing-dir").join("config");
+        let recorder = recorded('a', &[&["reload"]]);
+
+        match recorder.save_macro('a', "r", None, &path) {
+            Err(MacroError::CantWriteConfig {
+                path: error_path, ..
+            }) => {
+                assert_eq!(error_path, path)
+            }
+            other => panic!("Expected CantWriteConfig, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,413 @@
+//! Renders the key bindings that are in effect as a human-readable reference.
+
+use super::{Binding, format_operation_sequence};
+use crate::keycombination::{KeyCombination, bind};
+use crate::utils::quote_for_json;
+use gettextrs::gettext;
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+/// Context name that `bind` expands to all the known contexts.
+const ALL_CONTEXTS: &str = "everywhere";
+
+/// Output format of `render_reference()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceFormat {
+    Markdown,
+    /// A man page, to be fed to `man` or `groff -man`.
+    Roff,
+    Json,
+}
+
+impl ReferenceFormat {
+    /// Name of the format, as accepted by `ReferenceFormat::try_from()`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ReferenceFormat::Markdown => "markdown",
+            ReferenceFormat::Roff => "roff",
+            ReferenceFormat::Json => "json",
+        }
+    }
+}
+
+impl TryFrom<&str> for ReferenceFormat {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "markdown" | "md" => Ok(ReferenceFormat::Markdown),
+            "roff" | "man" => Ok(ReferenceFormat::Roff),
+            "json" => Ok(ReferenceFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Bindings that are in effect in one context, in the order they were bound.
+struct ContextReference<'a> {
+    name: &'a str,
+    bindings: Vec<(&'a Binding, Vec<KeyCombination>)>,
+}
+
+/// Replays `bindings` the way Newsboat applies them: a later binding replaces the earlier ones
+/// which have the same key sequence, or a key sequence which is a prefix of its own (or vice
+/// versa). Bindings for contexts not in `contexts` are ignored.
+fn effective_bindings<'a>(
+    bindings: &'a [Binding],
+    contexts: &[&'a str],
+) -> Vec<ContextReference<'a>> {
+    let mut result: Vec<ContextReference> = contexts
+        .iter()
+        .map(|&name| ContextReference {
+            name,
+            bindings: Vec::new(),
+        })
+        .collect();
+
+    for binding in bindings {
+        let keys = bind(&binding.key_sequence);
+        if keys.is_empty() {
+            continue;
+        }
+
+        let everywhere = binding.contexts == [ALL_CONTEXTS];
+        for context in &mut result {
+            if !everywhere && !binding.contexts.iter().any(|c| c == context.name) {
+                continue;
+            }
+
+            context.bindings.retain(|(_, other)| {
+                let common = other.len().min(keys.len());
+                other[..common] != keys[..common]
+            });
+            context.bindings.push((binding, keys.clone()));
+        }
+    }
+
+    result.retain(|context| !context.bindings.is_empty());
+    result
+}
+
+fn operations_string(binding: &Binding) -> String {
+    format_operation_sequence(&binding.operations, None)
+}
+
+/// Wraps `text` in a Markdown code span, choosing a fence that doesn't occur in it.
+fn markdown_code(text: &str) -> String {
+    let mut fence = String::from("`");
+    while text.contains(fence.as_str()) {
+        fence.push('`');
+    }
+    let padding = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{fence}{padding}{text}{padding}{fence}").replace('|', r"\|")
+}
+
+fn markdown_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#'
+        ) {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+fn render_markdown(contexts: &[ContextReference]) -> String {
+    let mut output = format!("# {}\n", gettext("Key bindings"));
+    for context in contexts {
+        let _ = write!(
+            output,
+            "\n## {}\n\n| {} | {} | {} |\n| --- | --- | --- |\n",
+            markdown_text(context.name),
+            gettext("Key"),
+            gettext("Operations"),
+            gettext("Description")
+        );
+        for (binding, _) in &context.bindings {
+            let _ = writeln!(
+                output,
+                "| {} | {} | {} |",
+                markdown_code(&binding.key_sequence),
+                markdown_code(&operations_string(binding)),
+                markdown_text(binding.description.as_deref().unwrap_or_default())
+            );
+        }
+    }
+    output
+}
+
+/// Escapes `text` so that roff prints it verbatim.
+fn roff_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => result.push_str(r"\e"),
+            '-' => result.push_str(r"\-"),
+            // Newlines would start a new request
+            '\n' | '\r' => result.push(' '),
+            _ => result.push(c),
+        }
+    }
+    // A period or an apostrophe at the start of a line would start a request
+    if result.starts_with(['.', '\'']) {
+        result.insert_str(0, r"\&");
+    }
+    result
+}
+
+fn render_roff(contexts: &[ContextReference]) -> String {
+    let mut output = String::from(".TH NEWSBOAT\\-KEYS 7\n");
+    let _ = write!(
+        output,
+        ".SH {}\n{}\n",
+        roff_text(&gettext("NAME")),
+        roff_text(&gettext("newsboat-keys - key bindings"))
+    );
+    for context in contexts {
+        let _ = writeln!(
+            output,
+            ".SH \"{}\"",
+            roff_text(context.name).replace('"', "\"\"")
+        );
+        for (binding, _) in &context.bindings {
+            let _ = write!(
+                output,
+                ".TP\n.B \"{}\"\n{}\n",
+                roff_text(&binding.key_sequence).replace('"', "\"\""),
+                roff_text(&operations_string(binding))
+            );
+            if let Some(description) = binding.description.as_deref().filter(|d| !d.is_empty()) {
+                let _ = write!(output, ".br\n{}\n", roff_text(description));
+            }
+        }
+    }
+    output
+}
+
+fn json_array<T>(items: &[T], render: impl Fn(&T) -> String) -> String {
+    let items: Vec<String> = items.iter().map(render).collect();
+    format!("[{}]", items.join(","))
+}
+
+fn render_json(contexts: &[ContextReference]) -> String {
+    let contexts = json_array(contexts, |context| {
+        let bindings = json_array(&context.bindings, |(binding, _)| {
+            let operations = json_array(&binding.operations, |operation| {
+                json_array(operation, |token| quote_for_json(token))
+            });
+            let description = binding
+                .description
+                .as_deref()
+                .map_or_else(|| "null".to_owned(), quote_for_json);
+            format!(
+                r#"{{"key":{},"operations":{},"description":{}}}"#,
+                quote_for_json(&binding.key_sequence),
+                operations,
+                description
+            )
+        });
+        format!(
+            r#"{{"name":{},"bindings":{}}}"#,
+            quote_for_json(context.name),
+            bindings
+        )
+    });
+    format!("{{\"contexts\":{contexts}}}\n")
+}
+
+/// Renders a reference of the key bindings which are in effect after applying `bindings` in order.
+///
+/// `bindings` should start with the defaults, followed by the user's bindings, so that the latter
+/// override the former. The reference has one section per context, in the order of `contexts`;
+/// contexts without any bindings are left out.
+pub fn render_reference(
+    bindings: &[Binding],
+    contexts: &[&str],
+    format: ReferenceFormat,
+) -> String {
+    let contexts = effective_bindings(bindings, contexts);
+    match format {
+        ReferenceFormat::Markdown => render_markdown(&contexts),
+        ReferenceFormat::Roff => render_roff(&contexts),
+        ReferenceFormat::Json => render_json(&contexts),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tokenize_binding;
+    use super::*;
+
+    fn bindings(lines: &[&str]) -> Vec<Binding> {
+        lines
+            .iter()
+            .map(|line| tokenize_binding(line).unwrap())
+            .collect()
+    }
+
+    fn effective(lines: &[&str], contexts: &[&str]) -> Vec<(String, Vec<String>)> {
+        let bindings = bindings(lines);
+        effective_bindings(&bindings, contexts)
+            .into_iter()
+            .map(|context| {
+                let keys = context
+                    .bindings
+                    .iter()
+                    .map(|(binding, _)| binding.key_sequence.clone())
+                    .collect();
+                (context.name.to_owned(), keys)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn t_format_names_round_trip() {
+        for format in [
+            ReferenceFormat::Markdown,
+            ReferenceFormat::Roff,
+            ReferenceFormat::Json,
+        ] {
+            assert_eq!(ReferenceFormat::try_from(format.name()), Ok(format));
+        }
+        assert_eq!(
+            ReferenceFormat::try_from("md"),
+            Ok(ReferenceFormat::Markdown)
+        );
+        assert_eq!(ReferenceFormat::try_from("man"), Ok(ReferenceFormat::Roff));
+        assert_eq!(ReferenceFormat::try_from("html"), Err(()));
+        assert_eq!(ReferenceFormat::try_from(""), Err(()));
+    }
+
+    #[test]
+    fn t_later_bindings_override_earlier_ones() {
+        assert_eq!(
+            effective(
+                &[
+                    "j feedlist,articlelist down",
+                    "k feedlist up",
+                    "^N feedlist down",
+                    "<C-n> feedlist next",
+                    "g feedlist home",
+                    "gg feedlist open",
+                    "j articlelist next",
+                ],
+                &["feedlist", "articlelist", "article"]
+            ),
+            vec![
+                (
+                    "feedlist".to_owned(),
+                    vec!["j", "k", "<C-n>", "gg"]
+                        .into_iter()
+                        .map(String::from)
+                        .collect()
+                ),
+                ("articlelist".to_owned(), vec!["j".to_owned()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn t_everywhere_applies_to_all_contexts_and_unknown_contexts_are_ignored() {
+        assert_eq!(
+            effective(
+                &["q everywhere quit", "x podcasts open"],
+                &["feedlist", "article"]
+            ),
+            vec![
+                ("feedlist".to_owned(), vec!["q".to_owned()]),
+                ("article".to_owned(), vec!["q".to_owned()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn t_renders_markdown() {
+        let bindings = bindings(&[
+            r#"ENTER feedlist open -- "Open feed/article""#,
+            r#"o feedlist set browser "lynx | less" ; open-in-browser"#,
+            r#"` feedlist quit -- "Leave *now*""#,
+        ]);
+        assert_eq!(
+            render_reference(&bindings, &["feedlist"], ReferenceFormat::Markdown),
+            concat!(
+                "# Key bindings\n",
+                "\n",
+                "## feedlist\n",
+                "\n",
+                "| Key | Operations | Description |\n",
+                "| --- | --- | --- |\n",
+                "| `ENTER` | `open` | Open feed/article |\n",
+                "| `o` | `set browser \"lynx \\| less\" ; open-in-browser` |  |\n",
+                "| `` ` `` | `quit` | Leave \\*now\\* |\n",
+            )
+        );
+    }
+
+    #[test]
+    fn t_renders_roff() {
+        let bindings = bindings(&[
+            r#"^R feedlist reload-all -- "Reload all feeds""#,
+            r#". article set-tag "\\" ; open-in-browser"#,
+        ]);
+        assert_eq!(
+            render_reference(&bindings, &["feedlist", "article"], ReferenceFormat::Roff),
+            concat!(
+                ".TH NEWSBOAT\\-KEYS 7\n",
+                ".SH NAME\n",
+                "newsboat\\-keys \\- key bindings\n",
+                ".SH \"feedlist\"\n",
+                ".TP\n",
+                ".B \"^R\"\n",
+                "reload\\-all\n",
+                ".br\n",
+                "Reload all feeds\n",
+                ".SH \"article\"\n",
+                ".TP\n",
+                ".B \"\\&.\"\n",
+                "set\\-tag \"\\e\\e\" ; open\\-in\\-browser\n",
+            )
+        );
+    }
+
+    #[test]
+    fn t_renders_json() {
+        let bindings = bindings(&[
+            r#"ENTER feedlist open -- "Open \"it\"""#,
+            r#"x feedlist,article set browser "a\tb" ; quit"#,
+        ]);
+        assert_eq!(
+            render_reference(&bindings, &["feedlist", "article"], ReferenceFormat::Json),
+            concat!(
+                r#"{"contexts":["#,
+                r#"{"name":"feedlist","bindings":["#,
+                r#"{"key":"ENTER","operations":[["open"]],"description":"Open \"it\""},"#,
+                r#"{"key":"x","operations":[["set","browser","a\tb"],["quit"]],"description":null}"#,
+                r#"]},"#,
+                r#"{"name":"article","bindings":["#,
+                r#"{"key":"x","operations":[["set","browser","a\tb"],["quit"]],"description":null}"#,
+                r#"]}"#,
+                "]}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn t_renders_empty_reference() {
+        assert_eq!(
+            render_reference(&[], &["feedlist"], ReferenceFormat::Json),
+            "{\"contexts\":[]}\n"
+        );
+        assert_eq!(
+            render_reference(&[], &["feedlist"], ReferenceFormat::Markdown),
+            "# Key bindings\n"
+        );
+    }
+}
@@ -0,0 +1,806 @@
+//! Resolves key sequences (like `gg` or `^G<ENTER>`) as keys arrive one by one.
+
+use crate::keycombination::KeyCombination;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Outcome of feeding keys into `KeyTrie`.
+#[derive(Debug, PartialEq)]
+pub enum KeyMatch<T> {
+    /// Keys typed so far are a prefix of some longer sequence; more keys are needed.
+    Pending,
+
+    /// Keys typed so far form a sequence that's bound to given value. If the sequence was
+    /// preceded by a numeric prefix (and those are enabled), the number is included too.
+    Matched(T, Option<u32>),
+
+    /// Keys typed so far can't be completed to any bound sequence. Contains the keys that were
+    /// discarded because of that.
+    NoMatch(Vec<KeyCombination>),
+}
+
+struct Node<T> {
+    value: Option<T>,
+    continuations: HashMap<KeyCombination, Node<T>>,
+}
+
+impl<T> Default for Node<T> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            continuations: HashMap::new(),
+        }
+    }
+}
+
+/// A prefix tree of key sequences, along with the state of the sequence that's currently being
+/// typed.
+///
+/// A sequence can be a prefix of another one, e.g. both `g` and `gg` can be bound at the same
+/// time. When user presses `g`, we can't tell which one they mean, so the trie waits: if the next
+/// key is another `g`, it's `gg`; if it's some other key, or if no key arrives before the
+/// timeout, it's `g`. The same timeout also applies to prefixes which aren't bound by themselves:
+/// if the user doesn't finish the sequence in time, it's discarded.
+///
+/// When a longer sequence is abandoned midway, the trie falls back to its longest prefix that is
+/// bound, and the keys after that prefix are processed again as if they were typed anew. For
+/// example, if `g` and `gab` are bound, typing `gax` triggers `g` and then handles `a` and `x`.
+/// If no prefix is bound, the keys are discarded. Digits among the keys that are processed again
+/// are never taken for a numeric prefix (see below).
+///
+/// The trie doesn't read the clock itself; all the methods that care about time take the current
+/// time as an argument. The UI is expected to call `poll()` when `deadline()` passes.
+///
+/// Optionally, a sequence can be preceded by a number, like in Vim: `5j`. The number is reported
+/// along with the match, and it's up to the caller to decide what it means (see
+/// `Binding::operations_with_count`). While numeric prefixes are enabled, digits can't start
+/// a sequence (except for `0`, which is only treated as part of a number that's already begun).
+pub struct KeyTrie<T> {
+    root: Node<T>,
+
+    /// How long to wait for the next key of a sequence. `None` means "wait forever".
+    timeout: Option<Duration>,
+
+    /// Keys of the sequence that's currently being typed.
+    pending: Vec<KeyCombination>,
+
+    /// When the last key of `pending` arrived.
+    last_key_time: Option<Instant>,
+
+    /// Whether digits typed before a sequence are treated as a numeric prefix.
+    counts_enabled: bool,
+
+    /// Digits of the numeric prefix that's currently being typed.
+    count_keys: Vec<KeyCombination>,
+}
+
+/// The largest numeric prefix that `KeyTrie` accepts; typing more digits doesn't make it bigger.
+pub const MAX_COUNT: u32 = 99_999;
+
+impl<T: Clone> KeyTrie<T> {
+    /// Creates an empty trie that waits for the next key of a sequence for at most `timeout`.
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            root: Node::default(),
+            timeout,
+            pending: Vec::new(),
+            last_key_time: None,
+            counts_enabled: false,
+            count_keys: Vec::new(),
+        }
+    }
+
+    /// Enables or disables numeric prefixes.
+    pub fn set_counts_enabled(&mut self, enabled: bool) {
+        self.counts_enabled = enabled;
+        self.count_keys.clear();
+    }
+
+    /// Binds `sequence` to `value`. Returns the value that was previously bound to the same
+    /// sequence, if any.
+    ///
+    /// Binding an empty sequence does nothing and returns `None`.
+    pub fn insert(&mut self, sequence: &[KeyCombination], value: T) -> Option<T> {
+        if sequence.is_empty() {
+            return None;
+        }
+
+        let mut node = &mut self.root;
+        for key in sequence {
+            node = node.continuations.entry(key.clone()).or_default();
+        }
+        node.value.replace(value)
+    }
+
+    /// Unbinds `sequence`, returning the value it was bound to.
+    ///
+    /// Longer sequences that start with `sequence` are left intact.
+    pub fn remove(&mut self, sequence: &[KeyCombination]) -> Option<T> {
+        fn remove_from<T>(node: &mut Node<T>, sequence: &[KeyCombination]) -> Option<T> {
+            let Some((key, rest)) = sequence.split_first() else {
+                return node.value.take();
+            };
+            let child = node.continuations.get_mut(key)?;
+            let value = remove_from(child, rest);
+            if child.value.is_none() && child.continuations.is_empty() {
+                node.continuations.remove(key);
+            }
+            value
+        }
+
+        if sequence.is_empty() {
+            return None;
+        }
+        remove_from(&mut self.root, sequence)
+    }
+
+    /// Returns the value bound to exactly `sequence`, if any.
+    pub fn get(&self, sequence: &[KeyCombination]) -> Option<&T> {
+        self.find(sequence).and_then(|node| node.value.as_ref())
+    }
+
+    /// Returns the keys of the sequence that's currently being typed, not including the
+    /// numeric prefix.
+    pub fn pending_keys(&self) -> &[KeyCombination] {
+        &self.pending
+    }
+
+    /// Returns the numeric prefix that's been typed so far, if any.
+    pub fn pending_count(&self) -> Option<u32> {
+        if self.count_keys.is_empty() {
+            return None;
+        }
+
+        let count = self
+            .count_keys
+            .iter()
+            .filter_map(|key| key.get_key().parse::<u32>().ok())
+            .fold(0u32, |count, digit| {
+                count
+                    .saturating_mul(10)
+                    .saturating_add(digit)
+                    .min(MAX_COUNT)
+            });
+        Some(count)
+    }
+
+    /// Returns the moment after which `poll()` will resolve the pending sequence, or `None` if
+    /// nothing is pending or the trie waits forever.
+    pub fn deadline(&self) -> Option<Instant> {
+        match (self.timeout, self.last_key_time) {
+            (Some(timeout), Some(last_key_time)) if !self.pending.is_empty() => {
+                Some(last_key_time + timeout)
+            }
+            _ => None,
+        }
+    }
+
+    /// Forgets the sequence that's currently being typed, along with its numeric prefix.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.count_keys.clear();
+        self.last_key_time = None;
+    }
+
+    /// Resolves the pending sequence if the timeout has passed.
+    ///
+    /// Returns an empty vector if nothing happened, i.e. there is no pending sequence, or it
+    /// hasn't timed out yet. There can be more than one outcome if the sequence is resolved to
+    /// its prefix; in that case, the rest of the keys are processed again and might start a new
+    /// pending sequence, which is reported as the last outcome.
+    pub fn poll(&mut self, now: Instant) -> Vec<KeyMatch<T>> {
+        let mut outcomes = Vec::new();
+        match self.deadline() {
+            Some(deadline) if now >= deadline => self.resolve_pending(now, &mut outcomes),
+            _ => return outcomes,
+        }
+        self.finish(outcomes)
+    }
+
+    /// Processes a key that was pressed at time `now`.
+    ///
+    /// Usually this returns a single outcome, but there can be more. For example, if both `g` and
+    /// `gg` are bound, then pressing `g` and then `j` returns two outcomes: the match for `g`, and
+    /// then whatever `j` resolves to by itself. Outcomes are in the order in which the caller
+    /// should act on them.
+    pub fn feed(&mut self, key: KeyCombination, now: Instant) -> Vec<KeyMatch<T>> {
+        let mut outcomes = Vec::new();
+        if matches!(self.deadline(), Some(deadline) if now >= deadline) {
+            self.resolve_pending(now, &mut outcomes);
+        }
+        self.process(key, now, true, &mut outcomes);
+        self.finish(outcomes)
+    }
+
+    /// Handles a single key, appending outcomes to `outcomes`. Unlike `feed()`, this might add
+    /// `Pending` outcomes in the middle; `finish()` cleans them up.
+    ///
+    /// If `allow_count` is false, digits are never treated as a numeric prefix. That's used for
+    /// keys that are processed again after their sequence was broken: they were typed after the
+    /// sequence had started, so they can't be a count.
+    fn process(
+        &mut self,
+        key: KeyCombination,
+        now: Instant,
+        allow_count: bool,
+        outcomes: &mut Vec<KeyMatch<T>>,
+    ) {
+        if allow_count && self.is_count_digit(&key) {
+            self.count_keys.push(key);
+            return;
+        }
+
+        let mut extended = self.pending.clone();
+        extended.push(key.clone());
+        if self.find(&extended).is_some() {
+            self.pending = extended;
+            self.last_key_time = Some(now);
+            self.match_pending(now, outcomes);
+            return;
+        }
+
+        if !self.pending.is_empty() {
+            // The key doesn't continue the pending sequence. Resolve what we have, then process
+            // the key again: it might continue whatever the resolution left pending, or start
+            // a new sequence. The numeric prefix, if any, belonged to the resolved sequence.
+            self.resolve_pending(now, outcomes);
+            self.process(key, now, allow_count, outcomes);
+            return;
+        }
+
+        let mut discarded = std::mem::take(&mut self.count_keys);
+        discarded.push(key);
+        outcomes.push(KeyMatch::NoMatch(discarded));
+    }
+
+    /// Drops intermediate `Pending` outcomes, and adds one at the end if the trie is waiting for
+    /// more keys.
+    fn finish(&self, mut outcomes: Vec<KeyMatch<T>>) -> Vec<KeyMatch<T>> {
+        outcomes.retain(|outcome| !matches!(outcome, KeyMatch::Pending));
+        if !self.pending.is_empty() || !self.count_keys.is_empty() {
+            outcomes.push(KeyMatch::Pending);
+        }
+        outcomes
+    }
+
+    /// Checks if `key` should be treated as a digit of a numeric prefix.
+    fn is_count_digit(&self, key: &KeyCombination) -> bool {
+        if !self.counts_enabled || !self.pending.is_empty() {
+            return false;
+        }
+        if key.has_shift() || key.has_control() || key.has_alt() {
+            return false;
+        }
+        match key.get_key() {
+            "0" => !self.count_keys.is_empty(),
+            "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => true,
+            _ => false,
+        }
+    }
+
+    fn find(&self, sequence: &[KeyCombination]) -> Option<&Node<T>> {
+        let mut node = &self.root;
+        for key in sequence {
+            node = node.continuations.get(key)?;
+        }
+        Some(node)
+    }
+
+    /// Handles the pending sequence, which is known to be in the trie. If the sequence can't be
+    /// extended any further, it's resolved right away.
+    fn match_pending(&mut self, now: Instant, outcomes: &mut Vec<KeyMatch<T>>) {
+        // unwrap() is safe because callers only get here with sequences that are in the trie
+        let node = self.find(&self.pending).unwrap();
+        if node.continuations.is_empty() {
+            self.resolve_pending(now, outcomes);
+        }
+    }
+
+    /// Ends the pending sequence without waiting for more keys.
+    ///
+    /// The longest bound prefix of the sequence is matched, and the keys after it are processed
+    /// again. If no prefix is bound, the whole sequence is discarded along with its numeric
+    /// prefix.
+    fn resolve_pending(&mut self, now: Instant, outcomes: &mut Vec<KeyMatch<T>>) {
+        let count = self.pending_count();
+        let count_keys = std::mem::take(&mut self.count_keys);
+        let keys = std::mem::take(&mut self.pending);
+        self.last_key_time = None;
+
+        let longest_bound_prefix = (1..=keys.len())
+            .rev()
+            .find_map(|length| Some((length, self.get(&keys[..length])?.clone())));
+        match longest_bound_prefix {
+            Some((length, value)) => {
+                outcomes.push(KeyMatch::Matched(value, count));
+                for key in &keys[length..] {
+                    self.process(key.clone(), now, false, outcomes);
+                }
+            }
+            None => {
+                let mut discarded = count_keys;
+                discarded.extend(keys);
+                outcomes.push(KeyMatch::NoMatch(discarded));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keycombination::bind;
+
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    fn trie(bindings: &[(&str, &str)]) -> KeyTrie<String> {
+        let mut trie = KeyTrie::new(Some(TIMEOUT));
+        for (sequence, value) in bindings {
+            trie.insert(&bind(sequence), value.to_string());
+        }
+        trie
+    }
+
+    fn key(key: &str) -> KeyCombination {
+        KeyCombination::new(key.to_owned())
+    }
+
+    fn matched(value: &str) -> KeyMatch<String> {
+        KeyMatch::Matched(value.to_owned(), None)
+    }
+
+    fn matched_with_count(value: &str, count: u32) -> KeyMatch<String> {
+        KeyMatch::Matched(value.to_owned(), Some(count))
+    }
+
+    /// `g` and `a` are bound, and so are some longer sequences that start with `ga`.
+    fn trie_with_longer_sequences() -> KeyTrie<String> {
+        trie(&[("g", "top"), ("gab", "gab"), ("gac", "gac"), ("a", "all")])
+    }
+
+    fn counting_trie(bindings: &[(&str, &str)]) -> KeyTrie<String> {
+        let mut trie = trie(bindings);
+        trie.set_counts_enabled(true);
+        trie
+    }
+
+    #[test]
+    fn t_single_key_is_matched_immediately() {
+        let mut trie = trie(&[("j", "down"), ("k", "up")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("j"), now), vec![matched("down")]);
+        assert_eq!(trie.feed(key("k"), now), vec![matched("up")]);
+        assert!(trie.pending_keys().is_empty());
+    }
+
+    #[test]
+    fn t_modifiers_are_part_of_the_key() {
+        let mut trie = trie(&[("^N", "next"), ("N", "toggle")]);
+        let now = Instant::now();
+
+        assert_eq!(
+            trie.feed(key("n").with_control(), now),
+            vec![matched("next")]
+        );
+        assert_eq!(
+            trie.feed(key("n").with_shift(), now),
+            vec![matched("toggle")]
+        );
+        assert_eq!(
+            trie.feed(key("n"), now),
+            vec![KeyMatch::NoMatch(vec![key("n")])]
+        );
+    }
+
+    #[test]
+    fn t_multi_key_sequence_is_pending_until_complete() {
+        let mut trie = trie(&[("gg", "home"), ("^G<ENTER>", "goto")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.pending_keys(), &[key("g")]);
+        assert_eq!(trie.feed(key("g"), now), vec![matched("home")]);
+
+        assert_eq!(
+            trie.feed(key("g").with_control(), now),
+            vec![KeyMatch::Pending]
+        );
+        assert_eq!(trie.feed(key("ENTER"), now), vec![matched("goto")]);
+    }
+
+    #[test]
+    fn t_unknown_key_is_not_matched() {
+        let mut trie = trie(&[("j", "down")]);
+
+        assert_eq!(
+            trie.feed(key("x"), Instant::now()),
+            vec![KeyMatch::NoMatch(vec![key("x")])]
+        );
+    }
+
+    #[test]
+    fn t_abandoned_prefix_is_discarded_and_new_key_is_processed() {
+        let mut trie = trie(&[("gg", "home"), ("j", "down")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("j"), now),
+            vec![KeyMatch::NoMatch(vec![key("g")]), matched("down")]
+        );
+
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("x"), now),
+            vec![
+                KeyMatch::NoMatch(vec![key("g")]),
+                KeyMatch::NoMatch(vec![key("x")])
+            ]
+        );
+        assert!(trie.pending_keys().is_empty());
+    }
+
+    #[test]
+    fn t_ambiguous_prefix_waits_for_the_next_key() {
+        let mut trie = trie(&[("g", "top"), ("gg", "home"), ("j", "down")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("g"), now), vec![matched("home")]);
+
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("j"), now),
+            vec![matched("top"), matched("down")]
+        );
+
+        // The interrupting key can start a new sequence
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("x"), now),
+            vec![matched("top"), KeyMatch::NoMatch(vec![key("x")])]
+        );
+    }
+
+    #[test]
+    fn t_broken_sequence_falls_back_to_the_longest_bound_prefix() {
+        // `g` and `gg` are bound, `gx` isn't
+        let mut trie = trie(&[("g", "top"), ("gg", "home"), ("x", "delete")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("x"), now),
+            vec![matched("top"), matched("delete")]
+        );
+        assert!(trie.pending_keys().is_empty());
+
+        // Keys after the bound prefix are processed again
+        let mut trie = trie_with_longer_sequences();
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("a"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("x"), now),
+            vec![
+                matched("top"),
+                matched("all"),
+                KeyMatch::NoMatch(vec![key("x")])
+            ]
+        );
+
+        // ...and can start a new pending sequence
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("a"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("g"), now),
+            vec![matched("top"), matched("all"), KeyMatch::Pending]
+        );
+        assert_eq!(trie.pending_keys(), &[key("g")]);
+    }
+
+    #[test]
+    fn t_timed_out_sequence_falls_back_to_the_longest_bound_prefix() {
+        let mut trie = trie_with_longer_sequences();
+        let start = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), start), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("a"), start), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.poll(start + TIMEOUT),
+            vec![matched("top"), matched("all")]
+        );
+        assert!(trie.pending_keys().is_empty());
+    }
+
+    #[test]
+    fn t_numeric_prefix_goes_to_the_bound_prefix_of_broken_sequence() {
+        let mut trie = trie_with_longer_sequences();
+        trie.set_counts_enabled(true);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("3"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("a"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("j"), now),
+            vec![
+                matched_with_count("top", 3),
+                matched("all"),
+                KeyMatch::NoMatch(vec![key("j")])
+            ]
+        );
+    }
+
+    #[test]
+    fn t_ambiguous_prefix_is_matched_after_timeout() {
+        let mut trie = trie(&[("g", "top"), ("gg", "home")]);
+        let start = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), start), vec![KeyMatch::Pending]);
+        assert_eq!(trie.deadline(), Some(start + TIMEOUT));
+
+        assert_eq!(trie.poll(start + TIMEOUT / 2), vec![]);
+        assert_eq!(trie.poll(start + TIMEOUT), vec![matched("top")]);
+        assert_eq!(trie.deadline(), None);
+        assert_eq!(trie.poll(start + TIMEOUT * 2), vec![]);
+    }
+
+    #[test]
+    fn t_unbound_prefix_is_discarded_after_timeout() {
+        let mut trie = trie(&[("gg", "home")]);
+        let start = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), start), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.poll(start + TIMEOUT),
+            vec![KeyMatch::NoMatch(vec![key("g")])]
+        );
+    }
+
+    #[test]
+    fn t_timeout_is_counted_from_the_last_key() {
+        let mut trie = trie(&[("abc", "alphabet")]);
+        let start = Instant::now();
+        let later = start + TIMEOUT * 3 / 4;
+
+        assert_eq!(trie.feed(key("a"), start), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("b"), later), vec![KeyMatch::Pending]);
+        assert_eq!(trie.poll(start + TIMEOUT), vec![]);
+        assert_eq!(trie.deadline(), Some(later + TIMEOUT));
+        assert_eq!(
+            trie.feed(key("c"), later + TIMEOUT / 2),
+            vec![matched("alphabet")]
+        );
+    }
+
+    #[test]
+    fn t_key_arriving_after_timeout_starts_a_new_sequence() {
+        let mut trie = trie(&[("g", "top"), ("gg", "home")]);
+        let start = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), start), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("g"), start + TIMEOUT),
+            vec![matched("top"), KeyMatch::Pending]
+        );
+    }
+
+    #[test]
+    fn t_without_timeout_trie_waits_forever() {
+        let mut trie = KeyTrie::new(None);
+        trie.insert(&bind("g"), "top".to_string());
+        trie.insert(&bind("gg"), "home".to_string());
+        let start = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), start), vec![KeyMatch::Pending]);
+        assert_eq!(trie.deadline(), None);
+        let much_later = start + Duration::from_secs(3600);
+        assert_eq!(trie.poll(much_later), vec![]);
+        assert_eq!(trie.feed(key("g"), much_later), vec![matched("home")]);
+    }
+
+    #[test]
+    fn t_reset_forgets_pending_keys() {
+        let mut trie = trie(&[("gg", "home")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        trie.reset();
+        assert!(trie.pending_keys().is_empty());
+        assert_eq!(trie.deadline(), None);
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+    }
+
+    #[test]
+    fn t_insert_get_and_remove() {
+        let mut trie = trie(&[("gg", "home")]);
+
+        assert_eq!(trie.get(&bind("gg")), Some(&"home".to_string()));
+        assert_eq!(trie.get(&bind("g")), None);
+        assert_eq!(trie.get(&bind("ggg")), None);
+
+        assert_eq!(
+            trie.insert(&bind("gg"), "top".to_string()),
+            Some("home".to_string())
+        );
+        assert_eq!(trie.insert(&bind(""), "nothing".to_string()), None);
+        assert_eq!(trie.insert(&bind("g"), "prefix".to_string()), None);
+
+        assert_eq!(trie.remove(&bind("g")), Some("prefix".to_string()));
+        assert_eq!(trie.remove(&bind("g")), None);
+        assert_eq!(trie.get(&bind("gg")), Some(&"top".to_string()));
+    }
+
+    #[test]
+    fn t_removing_the_only_long_sequence_removes_its_prefixes() {
+        let mut trie = trie(&[("gg", "home")]);
+
+        trie.remove(&bind("gg"));
+
+        assert_eq!(
+            trie.feed(key("g"), Instant::now()),
+            vec![KeyMatch::NoMatch(vec![key("g")])]
+        );
+    }
+
+    #[test]
+    fn t_removed_sequence_is_no_longer_matched() {
+        let mut trie = trie(&[("g", "top"), ("gg", "home")]);
+        let start = Instant::now();
+
+        trie.remove(&bind("g"));
+
+        assert_eq!(trie.feed(key("g"), start), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.poll(start + TIMEOUT),
+            vec![KeyMatch::NoMatch(vec![key("g")])]
+        );
+    }
+
+    #[test]
+    fn t_digits_are_ordinary_keys_when_counts_are_disabled() {
+        let mut trie = trie(&[("5", "five"), ("j", "down")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("5"), now), vec![matched("five")]);
+        assert_eq!(trie.pending_count(), None);
+        assert_eq!(trie.feed(key("j"), now), vec![matched("down")]);
+    }
+
+    #[test]
+    fn t_numeric_prefix_is_reported_with_the_match() {
+        let mut trie = counting_trie(&[("j", "down"), ("gg", "home")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("5"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.pending_count(), Some(5));
+        assert_eq!(
+            trie.feed(key("j"), now),
+            vec![matched_with_count("down", 5)]
+        );
+        assert_eq!(trie.pending_count(), None);
+
+        assert_eq!(trie.feed(key("1"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("0"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("2"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.pending_count(), Some(102));
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("g"), now),
+            vec![matched_with_count("home", 102)]
+        );
+
+        assert_eq!(trie.feed(key("j"), now), vec![matched("down")]);
+    }
+
+    #[test]
+    fn t_zero_does_not_start_a_numeric_prefix() {
+        let mut trie = counting_trie(&[("0", "zero")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("0"), now), vec![matched("zero")]);
+    }
+
+    #[test]
+    fn t_digits_inside_a_sequence_are_ordinary_keys() {
+        let mut trie = counting_trie(&[("g5", "fifth")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("2"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("5"), now),
+            vec![matched_with_count("fifth", 2)]
+        );
+    }
+
+    #[test]
+    fn t_digit_after_bound_prefix_of_broken_sequence_is_not_a_count() {
+        let mut trie = counting_trie(&[("g", "top"), ("g5x", "fifth"), ("j", "down")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("g"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("5"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("j"), now),
+            vec![
+                matched("top"),
+                KeyMatch::NoMatch(vec![key("5")]),
+                matched("down")
+            ]
+        );
+        assert_eq!(trie.pending_count(), None);
+    }
+
+    #[test]
+    fn t_digits_with_modifiers_are_not_part_of_numeric_prefix() {
+        let mut trie = counting_trie(&[("<M-5>", "alt five")]);
+        let now = Instant::now();
+
+        assert_eq!(
+            trie.feed(key("5").with_alt(), now),
+            vec![matched("alt five")]
+        );
+    }
+
+    #[test]
+    fn t_numeric_prefix_is_discarded_along_with_unknown_key() {
+        let mut trie = counting_trie(&[("j", "down")]);
+        let now = Instant::now();
+
+        assert_eq!(trie.feed(key("4"), now), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("2"), now), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("x"), now),
+            vec![KeyMatch::NoMatch(vec![key("4"), key("2"), key("x")])]
+        );
+        assert_eq!(trie.feed(key("j"), now), vec![matched("down")]);
+    }
+
+    #[test]
+    fn t_numeric_prefix_belongs_to_the_ambiguous_prefix() {
+        let mut trie = counting_trie(&[("g", "top"), ("gg", "home"), ("j", "down")]);
+        let start = Instant::now();
+
+        assert_eq!(trie.feed(key("3"), start), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("g"), start), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.feed(key("j"), start),
+            vec![matched_with_count("top", 3), matched("down")]
+        );
+
+        assert_eq!(trie.feed(key("3"), start), vec![KeyMatch::Pending]);
+        assert_eq!(trie.feed(key("g"), start), vec![KeyMatch::Pending]);
+        assert_eq!(
+            trie.poll(start + TIMEOUT),
+            vec![matched_with_count("top", 3)]
+        );
+    }
+
+    #[test]
+    fn t_numeric_prefix_is_capped() {
+        let mut trie = counting_trie(&[("j", "down")]);
+        let now = Instant::now();
+
+        for _ in 0..12 {
+            trie.feed(key("9"), now);
+        }
+        assert_eq!(trie.pending_count(), Some(MAX_COUNT));
+        assert_eq!(
+            trie.feed(key("j"), now),
+            vec![matched_with_count("down", MAX_COUNT)]
+        );
+    }
+
+    #[test]
+    fn t_reset_forgets_numeric_prefix() {
+        let mut trie = counting_trie(&[("j", "down")]);
+        let now = Instant::now();
+
+        trie.feed(key("7"), now);
+        trie.reset();
+        assert_eq!(trie.pending_count(), None);
+        assert_eq!(trie.feed(key("j"), now), vec![matched("down")]);
+    }
+}
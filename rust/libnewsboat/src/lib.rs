@@ -11,17 +11,24 @@ pub mod utils;
 
 pub mod charencoding;
 pub mod cliargsparser;
+pub mod completions;
+pub mod configoverrides;
 pub mod configpaths;
+pub mod controlsocket;
 pub mod filepath;
 pub mod filterparser;
 pub mod fmtstrformatter;
 pub mod fslock;
 pub mod history;
+pub mod json;
 pub mod keycombination;
 pub mod keymap;
 pub mod links;
 pub mod matchable;
 pub mod matcher;
 pub mod matchererror;
+pub mod migrations;
+pub mod readstate;
 pub mod scopemeasure;
+pub mod scriptoutput;
 pub mod stflrichtext;
@@ -0,0 +1,607 @@
+//! Versioned migrations of the files in Newsboat's config and data directories.
+//!
+//! The data directory contains a marker file with the version of the files' layout; without
+//! a marker, the version is 0. Each of the `STEPS` upgrades the files to the next version, and the
+//! marker is updated after every step that changed files, so an interrupted migration resumes
+//! where it stopped. Steps that had nothing to do leave the marker alone; they're planned again
+//! on the next run, which is harmless because they skip the files that don't exist.
+//!
+//! A step is applied atomically: the files it's going to change are copied into a backup
+//! directory first, and if any of its actions fail, the ones that already succeeded are undone.
+//!
+//! The cache's schema is versioned separately, and is upgraded by the cache itself when it's
+//! opened.
+
+use crate::configpaths::{CMDLINE_HISTORY_FILENAME, SEARCH_HISTORY_FILENAME};
+use crate::logger::{self, Level};
+use crate::utils;
+use gettextrs::gettext;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use strprintf::fmt;
+
+/// Name of the file, in the data directory, that holds the version of the files' layout.
+pub const VERSION_FILENAME: &str = "data-version";
+/// Subdirectory of the data directory where files are backed up before they're migrated.
+pub const BACKUPS_SUBDIR: &str = "migration-backups";
+
+/// Directories that migrations work on.
+pub struct Dirs<'a> {
+    pub config_dir: &'a Path,
+    pub data_dir: &'a Path,
+    pub state_dir: &'a Path,
+}
+
+/// A single change to the files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Move (or rename) a file. The destination must not exist.
+    Move { from: PathBuf, to: PathBuf },
+}
+
+/// A step that upgrades the files from the previous version to `version`.
+pub struct Step {
+    pub version: u32,
+    pub description: fn() -> String,
+    /// Actions that make up the step. Moves of files that don't exist are skipped.
+    pub actions: fn(&Dirs) -> Vec<Action>,
+}
+
+/// All migration steps, in the order of their versions.
+pub const STEPS: &[Step] = &[Step {
+    version: 1,
+    description: || gettext("move history files into the state directory"),
+    actions: |dirs| {
+        if dirs.state_dir == dirs.data_dir {
+            return Vec::new();
+        }
+        [SEARCH_HISTORY_FILENAME, CMDLINE_HISTORY_FILENAME]
+            .iter()
+            .map(|name| Action::Move {
+                from: dirs.data_dir.join(name),
+                to: dirs.state_dir.join(name),
+            })
+            .collect()
+    },
+}];
+
+/// The version that `STEPS` bring the files to.
+pub fn current_version() -> u32 {
+    STEPS.last().map_or(0, |step| step.version)
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The version file exists, but doesn't contain a version number.
+    InvalidVersionFile(PathBuf),
+    /// The destination of a move already exists.
+    DestinationExists(PathBuf),
+    /// An operation on `path` failed.
+    Io { path: PathBuf, error: io::Error },
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::InvalidVersionFile(path) => write!(
+                f,
+                "{}",
+                fmt!(
+                    &gettext("`%s' doesn't contain a version number"),
+                    path.display().to_string()
+                )
+            ),
+            MigrationError::DestinationExists(path) => write!(
+                f,
+                "{}",
+                fmt!(&gettext("`%s' already exists"), path.display().to_string())
+            ),
+            MigrationError::Io { path, error } => write!(
+                f,
+                "{}",
+                fmt!(
+                    &gettext("`%s': %s"),
+                    path.display().to_string(),
+                    error.to_string()
+                )
+            ),
+        }
+    }
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> MigrationError + '_ {
+    move |error| MigrationError::Io {
+        path: path.to_owned(),
+        error,
+    }
+}
+
+/// A step, with the actions that will actually be performed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedStep {
+    pub version: u32,
+    pub description: String,
+    pub actions: Vec<Action>,
+}
+
+/// Steps that are needed to bring the files up to date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationPlan {
+    /// Version the files are at.
+    pub from: u32,
+    /// Version the files will be at after the migration.
+    pub to: u32,
+    pub steps: Vec<PlannedStep>,
+    version_file: PathBuf,
+    backups_dir: PathBuf,
+}
+
+/// Figures out which of the `STEPS` haven't been applied to the files in `dirs` yet.
+pub fn plan(dirs: &Dirs) -> Result<MigrationPlan, MigrationError> {
+    plan_steps(STEPS, dirs)
+}
+
+fn plan_steps(steps: &[Step], dirs: &Dirs) -> Result<MigrationPlan, MigrationError> {
+    let version_file = dirs.data_dir.join(VERSION_FILENAME);
+    let from = read_version(&version_file)?;
+    let to = steps.last().map_or(0, |step| step.version);
+
+    let steps = steps
+        .iter()
+        .filter(|step| step.version > from)
+        .map(|step| PlannedStep {
+            version: step.version,
+            description: (step.description)(),
+            actions: (step.actions)(dirs)
+                .into_iter()
+                .filter(|action| match action {
+                    Action::Move { from, .. } => from.exists(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(MigrationPlan {
+        from,
+        to,
+        steps,
+        version_file,
+        backups_dir: dirs.data_dir.join(BACKUPS_SUBDIR),
+    })
+}
+
+fn read_version(path: &Path) -> Result<u32, MigrationError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => contents
+            .trim()
+            .parse()
+            .map_err(|_| MigrationError::InvalidVersionFile(path.to_owned())),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(error) => Err(io_error(path)(error)),
+    }
+}
+
+/// Replaces the version file, so that it's never left half-written.
+fn write_version(path: &Path, version: u32) -> Result<(), MigrationError> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, format!("{version}\n")).map_err(io_error(&tmp))?;
+    fs::rename(&tmp, path).map_err(io_error(path))
+}
+
+impl Action {
+    /// Human-readable description of the action.
+    pub fn describe(&self) -> String {
+        match self {
+            Action::Move { from, to } => fmt!(
+                &gettext("move `%s' to `%s'"),
+                from.display().to_string(),
+                to.display().to_string()
+            ),
+        }
+    }
+
+    fn back_up(&self, backup_dir: &Path) -> Result<(), MigrationError> {
+        match self {
+            Action::Move { from, .. } => {
+                let Some(name) = from.file_name() else {
+                    return Ok(());
+                };
+                utils::mkdir_parents(&backup_dir, 0o700).map_err(io_error(backup_dir))?;
+                let backup = backup_dir.join(name);
+                fs::copy(from, &backup).map_err(io_error(&backup))?;
+                Ok(())
+            }
+        }
+    }
+
+    fn apply(&self) -> Result<(), MigrationError> {
+        match self {
+            Action::Move { from, to } => {
+                if to.exists() {
+                    return Err(MigrationError::DestinationExists(to.clone()));
+                }
+                if let Some(parent) = to.parent() {
+                    utils::mkdir_parents(&parent, 0o700).map_err(io_error(parent))?;
+                }
+                move_file(from, to)
+            }
+        }
+    }
+
+    fn undo(&self) -> Result<(), MigrationError> {
+        match self {
+            Action::Move { from, to } => move_file(to, from),
+        }
+    }
+}
+
+/// Renames `from` to `to`, falling back to copying if they're on different filesystems.
+fn move_file(from: &Path, to: &Path) -> Result<(), MigrationError> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {
+            fs::copy(from, to).map_err(io_error(to))?;
+            if let Err(error) = fs::remove_file(from) {
+                let _ = fs::remove_file(to);
+                return Err(io_error(from)(error));
+            }
+            Ok(())
+        }
+        Err(error) => Err(io_error(from)(error)),
+    }
+}
+
+impl MigrationPlan {
+    /// Whether the files are behind the current version.
+    pub fn is_needed(&self) -> bool {
+        self.from < self.to
+    }
+
+    /// Whether any files would be changed, as opposed to just the version being bumped.
+    pub fn changes_files(&self) -> bool {
+        self.steps.iter().any(|step| !step.actions.is_empty())
+    }
+
+    /// Directory where files are backed up before they're changed.
+    pub fn backups_dir(&self) -> &Path {
+        &self.backups_dir
+    }
+
+    /// Human-readable report of what the migration does, one line per element.
+    pub fn describe(&self) -> Vec<String> {
+        if self.from > self.to {
+            return vec![fmt!(
+                &gettext(
+                    "Files are at version %u, which is newer than this version of Newsboat \
+                     supports (%u). Nothing to migrate."
+                ),
+                self.from,
+                self.to
+            )];
+        }
+        if !self.is_needed() {
+            return vec![fmt!(
+                &gettext("Files are up to date (version %u). Nothing to migrate."),
+                self.to
+            )];
+        }
+
+        let mut result = vec![fmt!(
+            &gettext("Migrating files from version %u to version %u:"),
+            self.from,
+            self.to
+        )];
+        for step in &self.steps {
+            result.push(fmt!(
+                &gettext("  version %u: %s"),
+                step.version,
+                step.description.as_str()
+            ));
+            if step.actions.is_empty() {
+                result.push(gettext("    no files need to be changed"));
+            }
+            for action in &step.actions {
+                result.push(format!("    {}", action.describe()));
+            }
+        }
+        if self.changes_files() {
+            result.push(fmt!(
+                &gettext("Files are backed up into `%s' before they're changed."),
+                self.backups_dir.display().to_string()
+            ));
+        }
+        result
+    }
+
+    /// Applies the steps in order, recording the version after each one that changed files.
+    ///
+    /// If a step fails, the files it already changed are put back, and the version stays at that
+    /// of the last successful step.
+    pub fn apply(&self) -> Result<(), MigrationError> {
+        for step in &self.steps {
+            if step.actions.is_empty() {
+                log!(
+                    Level::Debug,
+                    "migrations: step {} ({}) has nothing to do",
+                    step.version,
+                    step.description
+                );
+                continue;
+            }
+            log!(
+                Level::Info,
+                "migrations: applying step {} ({})",
+                step.version,
+                step.description
+            );
+            self.apply_step(step)?;
+            write_version(&self.version_file, step.version)?;
+        }
+        Ok(())
+    }
+
+    fn apply_step(&self, step: &PlannedStep) -> Result<(), MigrationError> {
+        let backup_dir = self.backups_dir.join(step.version.to_string());
+        for action in &step.actions {
+            action.back_up(&backup_dir)?;
+        }
+
+        for (i, action) in step.actions.iter().enumerate() {
+            if let Err(error) = action.apply() {
+                for done in step.actions[..i].iter().rev() {
+                    if let Err(undo_error) = done.undo() {
+                        log!(
+                            Level::Error,
+                            "migrations: couldn't undo `{}': {}",
+                            done.describe(),
+                            undo_error
+                        );
+                    }
+                }
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const TEST_STEPS: &[Step] = &[
+        Step {
+            version: 1,
+            description: || "baseline".to_owned(),
+            actions: |_| Vec::new(),
+        },
+        Step {
+            version: 2,
+            description: || "move history".to_owned(),
+            actions: |dirs| {
+                vec![
+                    Action::Move {
+                        from: dirs.data_dir.join("history"),
+                        to: dirs.data_dir.join("state/history"),
+                    },
+                    Action::Move {
+                        from: dirs.data_dir.join("missing"),
+                        to: dirs.data_dir.join("state/missing"),
+                    },
+                    Action::Move {
+                        from: dirs.config_dir.join("queue"),
+                        to: dirs.data_dir.join("queue"),
+                    },
+                ]
+            },
+        },
+    ];
+
+    struct Fixture {
+        _tmp: TempDir,
+        config_dir: PathBuf,
+        data_dir: PathBuf,
+        state_dir: PathBuf,
+    }
+
+    impl Fixture {
+        fn new() -> Fixture {
+            let tmp = TempDir::new().unwrap();
+            let config_dir = tmp.path().join("config");
+            let data_dir = tmp.path().join("data");
+            let state_dir = tmp.path().join("state");
+            fs::create_dir(&config_dir).unwrap();
+            fs::create_dir(&data_dir).unwrap();
+            Fixture {
+                _tmp: tmp,
+                config_dir,
+                data_dir,
+                state_dir,
+            }
+        }
+
+        fn dirs(&self) -> Dirs<'_> {
+            Dirs {
+                config_dir: &self.config_dir,
+                data_dir: &self.data_dir,
+                state_dir: &self.state_dir,
+            }
+        }
+
+        fn version(&self) -> u32 {
+            read_version(&self.data_dir.join(VERSION_FILENAME)).unwrap()
+        }
+    }
+
+    #[test]
+    fn t_directories_without_a_marker_are_at_version_zero() {
+        let fixture = Fixture::new();
+        let plan = plan_steps(TEST_STEPS, &fixture.dirs()).unwrap();
+        assert_eq!(plan.from, 0);
+        assert_eq!(plan.to, 2);
+        assert!(plan.is_needed());
+        assert!(!plan.changes_files());
+        assert_eq!(plan.steps.len(), 2);
+    }
+
+    #[test]
+    fn t_plan_skips_applied_steps_and_missing_files() {
+        let fixture = Fixture::new();
+        fs::write(fixture.data_dir.join(VERSION_FILENAME), "1\n").unwrap();
+        fs::write(fixture.data_dir.join("history"), "query").unwrap();
+
+        let plan = plan_steps(TEST_STEPS, &fixture.dirs()).unwrap();
+        assert_eq!(plan.from, 1);
+        assert_eq!(
+            plan.steps,
+            vec![PlannedStep {
+                version: 2,
+                description: "move history".to_owned(),
+                actions: vec![Action::Move {
+                    from: fixture.data_dir.join("history"),
+                    to: fixture.data_dir.join("state/history"),
+                }],
+            }]
+        );
+        assert!(plan.changes_files());
+
+        let report = plan.describe();
+        assert_eq!(report[0], "Migrating files from version 1 to version 2:");
+        assert_eq!(report[1], "  version 2: move history");
+        assert!(report[2].starts_with("    move `"));
+        assert!(report[3].contains(BACKUPS_SUBDIR));
+    }
+
+    #[test]
+    fn t_invalid_marker_is_an_error() {
+        let fixture = Fixture::new();
+        fs::write(fixture.data_dir.join(VERSION_FILENAME), "two").unwrap();
+        assert!(matches!(
+            plan_steps(TEST_STEPS, &fixture.dirs()),
+            Err(MigrationError::InvalidVersionFile(_))
+        ));
+    }
+
+    #[test]
+    fn t_apply_moves_files_backs_them_up_and_records_the_version() {
+        let fixture = Fixture::new();
+        fs::write(fixture.data_dir.join("history"), "query").unwrap();
+        fs::write(fixture.config_dir.join("queue"), "podcast").unwrap();
+
+        let plan = plan_steps(TEST_STEPS, &fixture.dirs()).unwrap();
+        plan.apply().unwrap();
+
+        assert_eq!(fixture.version(), 2);
+        assert!(!fixture.data_dir.join("history").exists());
+        assert_eq!(
+            fs::read_to_string(fixture.data_dir.join("state/history")).unwrap(),
+            "query"
+        );
+        assert_eq!(
+            fs::read_to_string(fixture.data_dir.join("queue")).unwrap(),
+            "podcast"
+        );
+        assert_eq!(
+            fs::read_to_string(plan.backups_dir().join("2").join("history")).unwrap(),
+            "query"
+        );
+
+        let plan = plan_steps(TEST_STEPS, &fixture.dirs()).unwrap();
+        assert!(!plan.is_needed());
+        assert!(plan.steps.is_empty());
+    }
+
+    #[test]
+    fn t_failed_step_is_rolled_back() {
+        let fixture = Fixture::new();
+        fs::write(fixture.data_dir.join("history"), "query").unwrap();
+        fs::write(fixture.config_dir.join("queue"), "podcast").unwrap();
+        // The destination of the second move is taken, so the step fails after the first move
+        fs::write(fixture.data_dir.join("queue"), "other").unwrap();
+
+        let plan = plan_steps(TEST_STEPS, &fixture.dirs()).unwrap();
+        assert!(matches!(
+            plan.apply(),
+            Err(MigrationError::DestinationExists(_))
+        ));
+
+        // The first step has nothing to do, so no version was recorded
+        assert_eq!(fixture.version(), 0);
+        assert_eq!(
+            fs::read_to_string(fixture.data_dir.join("history")).unwrap(),
+            "query"
+        );
+        assert!(!fixture.data_dir.join("state/history").exists());
+        assert_eq!(
+            fs::read_to_string(fixture.config_dir.join("queue")).unwrap(),
+            "podcast"
+        );
+    }
+
+    #[test]
+    fn t_steps_that_have_nothing_to_do_dont_record_the_version() {
+        let fixture = Fixture::new();
+
+        let plan = plan_steps(TEST_STEPS, &fixture.dirs()).unwrap();
+        plan.apply().unwrap();
+
+        assert!(!fixture.data_dir.join(VERSION_FILENAME).exists());
+        let plan = plan_steps(TEST_STEPS, &fixture.dirs()).unwrap();
+        assert_eq!(plan.from, 0);
+        assert!(plan.is_needed());
+        assert!(!plan.changes_files());
+    }
+
+    #[test]
+    fn t_newer_versions_are_left_alone() {
+        let fixture = Fixture::new();
+        fs::write(fixture.data_dir.join(VERSION_FILENAME), "5").unwrap();
+
+        let plan = plan_steps(TEST_STEPS, &fixture.dirs()).unwrap();
+        assert!(!plan.is_needed());
+        assert!(plan.steps.is_empty());
+        assert!(plan.describe()[0].contains("newer"));
+    }
+
+    #[test]
+    fn t_history_files_are_moved_into_the_state_directory() {
+        let fixture = Fixture::new();
+        fs::write(fixture.data_dir.join(SEARCH_HISTORY_FILENAME), "query").unwrap();
+        fs::write(fixture.data_dir.join(CMDLINE_HISTORY_FILENAME), "set").unwrap();
+
+        plan(&fixture.dirs()).unwrap().apply().unwrap();
+
+        assert_eq!(fixture.version(), current_version());
+        assert!(!fixture.data_dir.join(SEARCH_HISTORY_FILENAME).exists());
+        assert_eq!(
+            fs::read_to_string(fixture.state_dir.join(SEARCH_HISTORY_FILENAME)).unwrap(),
+            "query"
+        );
+        assert_eq!(
+            fs::read_to_string(fixture.state_dir.join(CMDLINE_HISTORY_FILENAME)).unwrap(),
+            "set"
+        );
+    }
+
+    #[test]
+    fn t_history_files_stay_in_place_if_state_and_data_directories_are_the_same() {
+        let fixture = Fixture::new();
+        fs::write(fixture.data_dir.join(SEARCH_HISTORY_FILENAME), "query").unwrap();
+
+        let dirs = Dirs {
+            config_dir: &fixture.data_dir,
+            data_dir: &fixture.data_dir,
+            state_dir: &fixture.data_dir,
+        };
+        let plan = plan(&dirs).unwrap();
+        assert!(!plan.changes_files());
+        plan.apply().unwrap();
+
+        assert!(fixture.data_dir.join(SEARCH_HISTORY_FILENAME).exists());
+        assert!(!fixture.data_dir.join(VERSION_FILENAME).exists());
+    }
+}
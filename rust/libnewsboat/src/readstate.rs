@@ -0,0 +1,496 @@
+//! Read state of articles, as moved between machines with `--export-to-file` and
+//! `--import-from-file`.
+//!
+//! Two formats are supported:
+//!
+//! - the legacy one, a list of GUIDs of read articles, one per line;
+//! - a versioned JSON document that also carries unread articles, flags, feed URLs, and the time
+//!   when the read state and flags last changed:
+//!
+//! ```json
+//! {"format":"newsboat-read-state","version":1,"articles":[
+//! {"guid":"...","feed":"https://...","unread":false,"read_changed":1700000000,"flags":"ab","flags_changed":1700000000}
+//! ]}
+//! ```
+//!
+//! On import, the format is detected automatically. Imported state is merged into the local one:
+//! the more recent read state wins, and flags are combined.
+
+use crate::json::{self, JsonValue};
+use crate::utils::quote_for_json;
+use gettextrs::gettext;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt::Write;
+use strprintf::fmt;
+
+/// Value of the `format` field of JSON documents.
+pub const FORMAT_NAME: &str = "newsboat-read-state";
+
+/// Version of the JSON format that this module writes. Documents of newer versions are rejected.
+pub const FORMAT_VERSION: i64 = 1;
+
+/// Format of exported read state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReadStateFormat {
+    /// GUIDs of read articles, one per line.
+    #[default]
+    Guids,
+    /// A versioned JSON document.
+    Json,
+}
+
+impl ReadStateFormat {
+    /// Name of the format, as accepted by `--read-state-format`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ReadStateFormat::Guids => "guids",
+            ReadStateFormat::Json => "json",
+        }
+    }
+}
+
+impl TryFrom<&OsStr> for ReadStateFormat {
+    type Error = ();
+
+    fn try_from(value: &OsStr) -> Result<Self, Self::Error> {
+        match value.to_str() {
+            Some("guids") => Ok(ReadStateFormat::Guids),
+            Some("json") => Ok(ReadStateFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Read state and flags of a single article.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ArticleState {
+    pub guid: String,
+    /// URL of the feed the article belongs to. Empty if unknown.
+    pub feed_url: String,
+    pub unread: bool,
+    /// When `unread` last changed, as a Unix timestamp. Zero if unknown.
+    pub read_changed: i64,
+    pub flags: String,
+    /// When `flags` last changed, as a Unix timestamp. Zero if unknown.
+    pub flags_changed: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadStateError {
+    /// The input looks like JSON, but can't be parsed.
+    InvalidJson,
+    /// The input is JSON, but not a read state document.
+    NotReadState,
+    /// The document is of a version this Newsboat doesn't support.
+    UnsupportedVersion(i64),
+    /// An article lacks a mandatory field, or the field has the wrong type.
+    InvalidArticle { index: usize, field: &'static str },
+}
+
+impl std::fmt::Display for ReadStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadStateError::InvalidJson => write!(f, "{}", gettext("the file is not valid JSON")),
+            ReadStateError::NotReadState => write!(
+                f,
+                "{}",
+                gettext("the file doesn't contain Newsboat's read state")
+            ),
+            ReadStateError::UnsupportedVersion(version) => write!(
+                f,
+                "{}",
+                fmt!(
+                    &gettext("unsupported read state format version %s (at most %s is supported)"),
+                    version.to_string(),
+                    FORMAT_VERSION.to_string()
+                )
+            ),
+            ReadStateError::InvalidArticle { index, field } => write!(
+                f,
+                "{}",
+                fmt!(
+                    &gettext("article #%u: missing or invalid field \"%s\""),
+                    *index as u32 + 1,
+                    *field
+                )
+            ),
+        }
+    }
+}
+
+/// Writes `articles` in the given format. The legacy format only includes read articles.
+pub fn export(articles: &[ArticleState], format: ReadStateFormat) -> String {
+    let mut result = String::new();
+    match format {
+        ReadStateFormat::Guids => {
+            for article in articles.iter().filter(|article| !article.unread) {
+                result.push_str(&article.guid);
+                result.push('\n');
+            }
+        }
+        ReadStateFormat::Json => {
+            let _ = write!(
+                result,
+                "{{\"format\":{},\"version\":{},\"articles\":[",
+                quote_for_json(FORMAT_NAME),
+                FORMAT_VERSION
+            );
+            for (i, article) in articles.iter().enumerate() {
+                if i > 0 {
+                    result.push(',');
+                }
+                let _ = write!(
+                    result,
+                    "\n{{\"guid\":{},\"feed\":{},\"unread\":{},\"read_changed\":{},\"flags\":{},\"flags_changed\":{}}}",
+                    quote_for_json(&article.guid),
+                    quote_for_json(&article.feed_url),
+                    article.unread,
+                    article.read_changed,
+                    quote_for_json(&article.flags),
+                    article.flags_changed
+                );
+            }
+            result.push_str("\n]}\n");
+        }
+    }
+    result
+}
+
+/// Reads articles in either format.
+///
+/// Articles from the legacy format are read, with `now` as the time of the change, so that they
+/// win over the local state (that's how importing has always worked).
+pub fn import(input: &str, now: i64) -> Result<Vec<ArticleState>, ReadStateError> {
+    if input.trim_start().starts_with('{') {
+        return import_json(input);
+    }
+
+    Ok(input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|guid| ArticleState {
+            guid: guid.to_owned(),
+            unread: false,
+            read_changed: now,
+            ..ArticleState::default()
+        })
+        .collect())
+}
+
+fn import_json(input: &str) -> Result<Vec<ArticleState>, ReadStateError> {
+    let document = json::parse(input).ok_or(ReadStateError::InvalidJson)?;
+    if document.get("format").and_then(JsonValue::as_str) != Some(FORMAT_NAME) {
+        return Err(ReadStateError::NotReadState);
+    }
+    let version = document
+        .get("version")
+        .and_then(JsonValue::as_i64)
+        .ok_or(ReadStateError::NotReadState)?;
+    if !(1..=FORMAT_VERSION).contains(&version) {
+        return Err(ReadStateError::UnsupportedVersion(version));
+    }
+    let articles = document
+        .get("articles")
+        .and_then(JsonValue::as_array)
+        .ok_or(ReadStateError::NotReadState)?;
+
+    articles
+        .iter()
+        .enumerate()
+        .map(|(index, article)| {
+            let invalid = |field| ReadStateError::InvalidArticle { index, field };
+
+            let guid = article
+                .get("guid")
+                .and_then(JsonValue::as_str)
+                .filter(|guid| !guid.is_empty())
+                .ok_or_else(|| invalid("guid"))?;
+            let unread = article
+                .get("unread")
+                .and_then(JsonValue::as_bool)
+                .ok_or_else(|| invalid("unread"))?;
+            let flags = optional_field(article, "flags", JsonValue::as_str)
+                .ok_or_else(|| invalid("flags"))?;
+
+            Ok(ArticleState {
+                guid: guid.to_owned(),
+                feed_url: optional_field(article, "feed", JsonValue::as_str)
+                    .ok_or_else(|| invalid("feed"))?
+                    .to_owned(),
+                unread,
+                read_changed: optional_field(article, "read_changed", JsonValue::as_i64)
+                    .ok_or_else(|| invalid("read_changed"))?,
+                flags: normalize_flags(flags),
+                flags_changed: optional_field(article, "flags_changed", JsonValue::as_i64)
+                    .ok_or_else(|| invalid("flags_changed"))?,
+            })
+        })
+        .collect()
+}
+
+/// Value of a field that may be absent, in which case the default value of its type is returned.
+/// Returns `None` if the field is present but has the wrong type.
+fn optional_field<'a, T: Default>(
+    article: &'a JsonValue,
+    field: &str,
+    convert: impl Fn(&'a JsonValue) -> Option<T>,
+) -> Option<T> {
+    match article.get(field) {
+        None => Some(T::default()),
+        Some(value) => convert(value),
+    }
+}
+
+/// Sorts flags and removes duplicates and anything that isn't a letter.
+fn normalize_flags(flags: &str) -> String {
+    let mut chars: Vec<char> = flags.chars().filter(char::is_ascii_alphabetic).collect();
+    chars.sort_unstable();
+    chars.dedup();
+    chars.into_iter().collect()
+}
+
+/// Merges `imported` state into `local` one, and returns the local articles whose state changed,
+/// with their new state.
+///
+/// Imported articles are matched by GUID, and also by feed URL if they have one. The read state
+/// with the more recent `read_changed` wins; on a tie, the local state is kept. Flags are combined,
+/// so merging never removes a flag.
+pub fn merge(local: &[ArticleState], imported: &[ArticleState]) -> Vec<ArticleState> {
+    let mut by_guid: HashMap<&str, Vec<&ArticleState>> = HashMap::new();
+    for article in imported {
+        by_guid.entry(&article.guid).or_default().push(article);
+    }
+
+    let mut result = Vec::new();
+    for article in local {
+        let Some(candidates) = by_guid.get(article.guid.as_str()) else {
+            continue;
+        };
+        let mut merged = article.clone();
+        for other in candidates
+            .iter()
+            .filter(|other| other.feed_url.is_empty() || other.feed_url == article.feed_url)
+        {
+            if other.read_changed > merged.read_changed {
+                merged.unread = other.unread;
+                merged.read_changed = other.read_changed;
+            }
+            let flags = normalize_flags(&(merged.flags.clone() + &other.flags));
+            if flags != merged.flags {
+                merged.flags = flags;
+                merged.flags_changed = merged.flags_changed.max(other.flags_changed);
+            }
+        }
+        if merged != *article {
+            result.push(merged);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(guid: &str, unread: bool, read_changed: i64) -> ArticleState {
+        ArticleState {
+            guid: guid.to_owned(),
+            feed_url: "https://example.com/feed.xml".to_owned(),
+            unread,
+            read_changed,
+            ..ArticleState::default()
+        }
+    }
+
+    #[test]
+    fn t_legacy_export_lists_only_read_articles() {
+        let articles = [
+            article("read", false, 10),
+            article("unread", true, 20),
+            article("also-read", false, 0),
+        ];
+        assert_eq!(
+            export(&articles, ReadStateFormat::Guids),
+            "read\nalso-read\n"
+        );
+    }
+
+    #[test]
+    fn t_legacy_import_marks_articles_read_now() {
+        let imported = import("first\n\nsecond\n", 1234).unwrap();
+        assert_eq!(
+            imported,
+            vec![
+                ArticleState {
+                    guid: "first".to_owned(),
+                    read_changed: 1234,
+                    ..ArticleState::default()
+                },
+                ArticleState {
+                    guid: "second".to_owned(),
+                    read_changed: 1234,
+                    ..ArticleState::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn t_json_export_can_be_imported_back() {
+        let articles = vec![
+            ArticleState {
+                flags: "ab".to_owned(),
+                flags_changed: 300,
+                ..article("tag:example.com,2024:\"quoted\"", false, 100)
+            },
+            article("unread", true, 200),
+        ];
+        let exported = export(&articles, ReadStateFormat::Json);
+        assert!(exported.starts_with(r#"{"format":"newsboat-read-state","version":1,"#));
+        assert_eq!(import(&exported, 0), Ok(articles));
+    }
+
+    #[test]
+    fn t_json_import_fills_in_optional_fields() {
+        let input = r#"{"format":"newsboat-read-state","version":1,"articles":[
+            {"guid":"a","unread":false,"flags":"zab1a"}
+        ]}"#;
+        assert_eq!(
+            import(input, 999),
+            Ok(vec![ArticleState {
+                guid: "a".to_owned(),
+                flags: "abz".to_owned(),
+                ..ArticleState::default()
+            }])
+        );
+    }
+
+    #[test]
+    fn t_json_import_rejects_other_documents() {
+        assert_eq!(import("{not json", 0), Err(ReadStateError::InvalidJson));
+        assert_eq!(
+            import(
+                r#"{"format":"something-else","version":1,"articles":[]}"#,
+                0
+            ),
+            Err(ReadStateError::NotReadState)
+        );
+        assert_eq!(
+            import(r#"{"format":"newsboat-read-state","articles":[]}"#, 0),
+            Err(ReadStateError::NotReadState)
+        );
+        assert_eq!(
+            import(
+                r#"{"format":"newsboat-read-state","version":2,"articles":[]}"#,
+                0
+            ),
+            Err(ReadStateError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn t_json_import_reports_invalid_articles() {
+        let input = r#"{"format":"newsboat-read-state","version":1,"articles":[
+            {"guid":"a","unread":false},
+            {"guid":"b","unread":"no"}
+        ]}"#;
+        assert_eq!(
+            import(input, 0),
+            Err(ReadStateError::InvalidArticle {
+                index: 1,
+                field: "unread"
+            })
+        );
+
+        let input = r#"{"format":"newsboat-read-state","version":1,"articles":[
+            {"guid":"a","unread":false,"read_changed":"yesterday"}
+        ]}"#;
+        assert_eq!(
+            import(input, 0),
+            Err(ReadStateError::InvalidArticle {
+                index: 0,
+                field: "read_changed"
+            })
+        );
+    }
+
+    #[test]
+    fn t_merge_newest_read_state_wins() {
+        let local = [
+            article("older-locally", true, 100),
+            article("newer-locally", true, 300),
+            article("same-time", true, 200),
+            article("not-imported", true, 100),
+        ];
+        let imported = [
+            article("older-locally", false, 200),
+            article("newer-locally", false, 200),
+            article("same-time", false, 200),
+            article("not-in-cache", false, 200),
+        ];
+        assert_eq!(
+            merge(&local, &imported),
+            vec![article("older-locally", false, 200)]
+        );
+    }
+
+    #[test]
+    fn t_merge_combines_flags() {
+        let local = [
+            ArticleState {
+                flags: "ac".to_owned(),
+                flags_changed: 100,
+                ..article("a", false, 0)
+            },
+            ArticleState {
+                flags: "ab".to_owned(),
+                flags_changed: 100,
+                ..article("b", false, 0)
+            },
+        ];
+        let imported = [
+            ArticleState {
+                flags: "b".to_owned(),
+                flags_changed: 50,
+                ..article("a", false, 0)
+            },
+            ArticleState {
+                flags: "a".to_owned(),
+                flags_changed: 200,
+                ..article("b", false, 0)
+            },
+        ];
+        assert_eq!(
+            merge(&local, &imported),
+            vec![ArticleState {
+                flags: "abc".to_owned(),
+                flags_changed: 100,
+                ..article("a", false, 0)
+            }]
+        );
+    }
+
+    #[test]
+    fn t_merge_respects_feed_url_when_present() {
+        let local = [
+            article("shared-guid", true, 0),
+            ArticleState {
+                feed_url: "https://other.example.com/".to_owned(),
+                ..article("shared-guid", true, 0)
+            },
+        ];
+
+        let imported = [article("shared-guid", false, 10)];
+        assert_eq!(
+            merge(&local, &imported),
+            vec![article("shared-guid", false, 10)]
+        );
+
+        let imported = [ArticleState {
+            feed_url: String::new(),
+            ..article("shared-guid", false, 10)
+        }];
+        assert_eq!(merge(&local, &imported).len(), 2);
+    }
+}
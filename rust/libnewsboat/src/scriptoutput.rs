@@ -0,0 +1,251 @@
+//! Formats the results of subcommands (like `newsboat items list`) for consumption by scripts.
+
+use crate::cliargsparser::OutputFormat;
+use crate::utils::quote_for_json;
+
+/// Value of a single field of a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field<'a> {
+    Text(&'a str),
+    Integer(i64),
+    Boolean(bool),
+}
+
+/// Something that can be printed as a line of TSV or a JSON object.
+pub trait Record {
+    /// Names and values of the fields, in the order they should be printed.
+    fn fields(&self) -> Vec<(&'static str, Field<'_>)>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedRecord {
+    pub url: String,
+    pub title: String,
+    pub unread_count: u32,
+    pub total_count: u32,
+}
+
+impl Record for FeedRecord {
+    fn fields(&self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("url", Field::Text(&self.url)),
+            ("title", Field::Text(&self.title)),
+            ("unread", Field::Integer(self.unread_count.into())),
+            ("total", Field::Integer(self.total_count.into())),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemRecord {
+    pub guid: String,
+    pub feed_url: String,
+    pub title: String,
+    pub link: String,
+    pub author: String,
+    /// Publication date as a Unix timestamp.
+    pub pub_date: i64,
+    pub unread: bool,
+}
+
+impl Record for ItemRecord {
+    fn fields(&self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("guid", Field::Text(&self.guid)),
+            ("feed", Field::Text(&self.feed_url)),
+            ("title", Field::Text(&self.title)),
+            ("link", Field::Text(&self.link)),
+            ("author", Field::Text(&self.author)),
+            ("date", Field::Integer(self.pub_date)),
+            ("unread", Field::Boolean(self.unread)),
+        ]
+    }
+}
+
+/// Result of marking a single item as read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkReadRecord {
+    pub guid: String,
+    /// `false` if there is no item with this GUID in the cache.
+    pub found: bool,
+}
+
+impl Record for MarkReadRecord {
+    fn fields(&self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("guid", Field::Text(&self.guid)),
+            ("found", Field::Boolean(self.found)),
+        ]
+    }
+}
+
+/// Escapes characters that would break up a TSV record: backslash, tab and line breaks are
+/// written as `\\`, `\t`, `\n` and `\r`.
+fn tsv_escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => result.push_str(r"\\"),
+            '\t' => result.push_str(r"\t"),
+            '\n' => result.push_str(r"\n"),
+            '\r' => result.push_str(r"\r"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn tsv_field(field: Field) -> String {
+    match field {
+        Field::Text(text) => tsv_escape(text),
+        Field::Integer(number) => number.to_string(),
+        Field::Boolean(value) => value.to_string(),
+    }
+}
+
+fn json_field(field: Field) -> String {
+    match field {
+        Field::Text(text) => quote_for_json(text),
+        Field::Integer(number) => number.to_string(),
+        Field::Boolean(value) => value.to_string(),
+    }
+}
+
+/// Formats `records` in the given format.
+///
+/// TSV has one line per record, with fields in the order of `Record::fields()`. JSON is an array
+/// of objects, one per record. Either way, the output ends with a newline, unless it's an empty
+/// TSV.
+pub fn format_records<R: Record>(records: &[R], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Tsv => records
+            .iter()
+            .map(|record| {
+                let fields: Vec<String> = record
+                    .fields()
+                    .into_iter()
+                    .map(|(_, field)| tsv_field(field))
+                    .collect();
+                fields.join("\t") + "\n"
+            })
+            .collect(),
+        OutputFormat::Json => {
+            let objects: Vec<String> = records
+                .iter()
+                .map(|record| {
+                    let fields: Vec<String> = record
+                        .fields()
+                        .into_iter()
+                        .map(|(name, field)| {
+                            format!("{}:{}", quote_for_json(name), json_field(field))
+                        })
+                        .collect();
+                    format!("{{{}}}", fields.join(","))
+                })
+                .collect();
+            format!("[{}]\n", objects.join(","))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items() -> Vec<ItemRecord> {
+        vec![
+            ItemRecord {
+                guid: "guid-1".to_owned(),
+                feed_url: "https://example.com/feed.xml".to_owned(),
+                title: "Tabs\tand\nnewlines".to_owned(),
+                link: "https://example.com/1".to_owned(),
+                author: "Jane \"JD\" Doe".to_owned(),
+                pub_date: 1700000000,
+                unread: true,
+            },
+            ItemRecord {
+                guid: r"C:\guid".to_owned(),
+                feed_url: "https://example.com/feed.xml".to_owned(),
+                title: String::new(),
+                link: String::new(),
+                author: String::new(),
+                pub_date: -1,
+                unread: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn t_formats_items_as_tsv() {
+        assert_eq!(
+            format_records(&items(), OutputFormat::Tsv),
+            concat!(
+                "guid-1\thttps://example.com/feed.xml\tTabs\\tand\\nnewlines\t",
+                "https://example.com/1\tJane \"JD\" Doe\t1700000000\ttrue\n",
+                "C:\\\\guid\thttps://example.com/feed.xml\t\t\t\t-1\tfalse\n",
+            )
+        );
+    }
+
+    #[test]
+    fn t_formats_items_as_json() {
+        assert_eq!(
+            format_records(&items(), OutputFormat::Json),
+            concat!(
+                r#"[{"guid":"guid-1","feed":"https://example.com/feed.xml","#,
+                r#""title":"Tabs\tand\nnewlines","link":"https://example.com/1","#,
+                r#""author":"Jane \"JD\" Doe","date":1700000000,"unread":true},"#,
+                r#"{"guid":"C:\\guid","feed":"https://example.com/feed.xml","title":"","#,
+                r#""link":"","author":"","date":-1,"unread":false}]"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn t_formats_feeds() {
+        let feeds = [FeedRecord {
+            url: "https://example.com/feed.xml".to_owned(),
+            title: "Example".to_owned(),
+            unread_count: 3,
+            total_count: 10,
+        }];
+        assert_eq!(
+            format_records(&feeds, OutputFormat::Tsv),
+            "https://example.com/feed.xml\tExample\t3\t10\n"
+        );
+        assert_eq!(
+            format_records(&feeds, OutputFormat::Json),
+            "[{\"url\":\"https://example.com/feed.xml\",\"title\":\"Example\",\"unread\":3,\"total\":10}]\n"
+        );
+    }
+
+    #[test]
+    fn t_formats_mark_read_results() {
+        let results = [
+            MarkReadRecord {
+                guid: "a".to_owned(),
+                found: true,
+            },
+            MarkReadRecord {
+                guid: "b".to_owned(),
+                found: false,
+            },
+        ];
+        assert_eq!(
+            format_records(&results, OutputFormat::Tsv),
+            "a\ttrue\nb\tfalse\n"
+        );
+        assert_eq!(
+            format_records(&results, OutputFormat::Json),
+            "[{\"guid\":\"a\",\"found\":true},{\"guid\":\"b\",\"found\":false}]\n"
+        );
+    }
+
+    #[test]
+    fn t_formats_empty_results() {
+        let empty: [FeedRecord; 0] = [];
+        assert_eq!(format_records(&empty, OutputFormat::Tsv), "");
+        assert_eq!(format_records(&empty, OutputFormat::Json), "[]\n");
+    }
+}
@@ -179,6 +179,25 @@ pub fn quote_if_necessary(input: &str) -> String {
     }
 }
 
+/// Turns `input` into a JSON string literal, quotes included.
+pub fn quote_for_json(input: &str) -> String {
+    let mut result = String::with_capacity(input.len() + 2);
+    result.push('"');
+    for c in input.chars() {
+        match c {
+            '"' => result.push_str(r#"\""#),
+            '\\' => result.push_str(r"\\"),
+            '\n' => result.push_str(r"\n"),
+            '\r' => result.push_str(r"\r"),
+            '\t' => result.push_str(r"\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
 pub fn get_random_value(max: u32) -> u32 {
     fastrand::u32(..) % max
 }
@@ -267,6 +286,34 @@ pub fn substr_with_width(string: &str, max_width: usize) -> String {
     result
 }
 
+/// Returns the width of `rs_str` when displayed on screen, measured one grapheme cluster at a time.
+///
+/// This is how `substr_with_width` measures text, so a string that's been cut to some width by
+/// that function is exactly that wide according to this one.
+pub fn graphemes_width(rs_str: &str) -> usize {
+    rs_str.graphemes(true).map(UnicodeWidthStr::width).sum()
+}
+
+/// Returns a longest prefix of `string` that fits into `max_width`, followed by `ellipsis` if
+/// anything had to be cut off.
+///
+/// Grapheme clusters are never split. The ellipsis counts towards `max_width`; if it doesn't fit
+/// by itself, the string is cut without it.
+pub fn truncate_with_ellipsis(string: &str, max_width: usize, ellipsis: &str) -> String {
+    if graphemes_width(string) <= max_width {
+        return string.to_owned();
+    }
+
+    let ellipsis_width = graphemes_width(ellipsis);
+    if ellipsis.is_empty() || ellipsis_width > max_width {
+        return substr_with_width(string, max_width);
+    }
+
+    let mut result = substr_with_width(string, max_width - ellipsis_width);
+    result.push_str(ellipsis);
+    result
+}
+
 /// Returns a longest substring fits to the given width.
 /// Returns an empty string if `str` is an empty string or `max_width` is zero.
 ///
@@ -1378,6 +1425,18 @@ mod tests {
         assert_eq!(quote_if_necessary("Hello World!"), "\"Hello World!\"");
     }
 
+    #[test]
+    fn t_quote_for_json() {
+        assert_eq!(quote_for_json(""), r#""""#);
+        assert_eq!(quote_for_json("Hello, World!"), r#""Hello, World!""#);
+        assert_eq!(
+            quote_for_json("\"quoted\"\\path\n\ttab"),
+            r#""\"quoted\"\\path\n\ttab""#
+        );
+        assert_eq!(quote_for_json("a\u{1}b\u{7f}"), r#""a\u0001b\u007f""#);
+        assert_eq!(quote_for_json("ünïcødé ✓"), r#""ünïcødé ✓""#);
+    }
+
     #[test]
     fn t_is_valid_color() {
         let invalid = [
@@ -1492,6 +1551,56 @@ mod tests {
         assert_eq!(substr_with_width(input, 4), "a⚛️b");
     }
 
+    #[test]
+    fn t_graphemes_width() {
+        assert_eq!(graphemes_width(""), 0);
+        assert_eq!(graphemes_width("abc"), 3);
+        assert_eq!(graphemes_width("ＡＢＣ"), 6);
+        // "e" followed by a combining acute accent
+        assert_eq!(graphemes_width("e\u{0301}"), 1);
+        // Family emoji: man, ZWJ, woman, ZWJ, girl
+        assert_eq!(
+            graphemes_width("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}"),
+            2
+        );
+    }
+
+    #[test]
+    fn t_truncate_with_ellipsis_keeps_strings_that_fit() {
+        assert_eq!(truncate_with_ellipsis("", 0, "…"), "");
+        assert_eq!(truncate_with_ellipsis("hello", 5, "…"), "hello");
+        assert_eq!(truncate_with_ellipsis("hello", 10, "..."), "hello");
+        assert_eq!(truncate_with_ellipsis("ＡＢＣ", 6, "…"), "ＡＢＣ");
+    }
+
+    #[test]
+    fn t_truncate_with_ellipsis_appends_ellipsis_to_cut_strings() {
+        assert_eq!(truncate_with_ellipsis("hello", 4, "…"), "hel…");
+        assert_eq!(truncate_with_ellipsis("hello", 4, "..."), "h...");
+        assert_eq!(truncate_with_ellipsis("hello", 4, ""), "hell");
+    }
+
+    #[test]
+    fn t_truncate_with_ellipsis_omits_ellipsis_that_does_not_fit() {
+        assert_eq!(truncate_with_ellipsis("hello", 2, "..."), "he");
+        assert_eq!(truncate_with_ellipsis("hello", 0, "…"), "");
+    }
+
+    #[test]
+    fn t_truncate_with_ellipsis_never_splits_wide_characters_or_graphemes() {
+        // Only one column is left for the wide character, so it's dropped entirely
+        assert_eq!(truncate_with_ellipsis("ＡＢＣ", 5, "…"), "ＡＢ…");
+        assert_eq!(truncate_with_ellipsis("ＡＢＣ", 4, "…"), "Ａ…");
+
+        let combining = "e\u{0301}e\u{0301}e\u{0301}";
+        assert_eq!(truncate_with_ellipsis(combining, 2, "…"), "e\u{0301}…");
+
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let input = format!("{family}{family}");
+        assert_eq!(truncate_with_ellipsis(&input, 3, "…"), format!("{family}…"));
+        assert_eq!(truncate_with_ellipsis(&input, 2, "…"), "…");
+    }
+
     #[test]
     fn t_substr_with_width_stfl() {
         assert_eq!(substr_with_width_stfl("a", 1), "a");
@@ -3,20 +3,29 @@ use section_testing::{enable_sections, section};
 use std::{env, fs, path};
 use tempfile::TempDir;
 
-fn assert_paths_are_inside_dirs(config_dir: &path::Path, data_dir: &path::Path) {
+fn assert_paths_are_inside_dirs(
+    config_dir: &path::Path,
+    data_dir: &path::Path,
+    state_dir: &path::Path,
+) {
     let paths = ConfigPaths::new();
     assert!(paths.initialized());
     assert_eq!(paths.config_file(), config_dir.join("config"));
     assert_eq!(paths.url_file(), config_dir.join("urls"));
 
     assert_eq!(paths.cache_file(), data_dir.join("cache.db"));
-    assert_eq!(paths.lock_file(), data_dir.join("cache.db.lock"));
     assert_eq!(paths.queue_file(), data_dir.join("queue"));
-    assert_eq!(paths.search_history_file(), data_dir.join("history.search"));
+
+    assert_eq!(paths.lock_file(), state_dir.join("cache.db.lock"));
+    assert_eq!(
+        paths.search_history_file(),
+        state_dir.join("history.search")
+    );
     assert_eq!(
         paths.cmdline_history_file(),
-        data_dir.join("history.cmdline")
+        state_dir.join("history.cmdline")
     );
+    assert_eq!(paths.log_dir(), Some(state_dir));
 }
 
 enable_sections! {
@@ -28,20 +37,25 @@ fn t_configpaths_returns_paths_to_newsboat_xdg_dirs_if_they_exist_and_dotdir_doe
     fs::create_dir_all(&config_dir).ok();
     let data_dir = tmp.path().join(".local").join("share").join("newsboat");
     fs::create_dir_all(&data_dir).ok();
+    let state_dir = tmp.path().join(".local").join("state").join("newsboat");
 
     unsafe { env::set_var("HOME", tmp.path()) };
 
+    // Without a runtime directory, the lock goes into the state directory
+    unsafe { env::remove_var("XDG_STATE_HOME") };
+    unsafe { env::remove_var("XDG_RUNTIME_DIR") };
+
     if section!("XDG_CONFIG_HOME is set") {
         unsafe { env::set_var("XDG_CONFIG_HOME", tmp.path().join(".config")) };
 
         if section!("XDG_DATA_HOME is set") {
             unsafe { env::set_var("XDG_DATA_HOME", tmp.path().join(".local").join("share")) };
-            assert_paths_are_inside_dirs(&config_dir, &data_dir);
+            assert_paths_are_inside_dirs(&config_dir, &data_dir, &state_dir);
         }
 
         if section!("XDG_DATA_HOME is not set") {
             unsafe { env::remove_var("XDG_DATA_HOME") };
-            assert_paths_are_inside_dirs(&config_dir, &data_dir);
+            assert_paths_are_inside_dirs(&config_dir, &data_dir, &state_dir);
         }
     }
 
@@ -50,12 +64,12 @@ fn t_configpaths_returns_paths_to_newsboat_xdg_dirs_if_they_exist_and_dotdir_doe
 
         if section!("XDG_DATA_HOME is set") {
             unsafe { env::set_var("XDG_DATA_HOME", tmp.path().join(".local").join("share")) };
-            assert_paths_are_inside_dirs(&config_dir, &data_dir);
+            assert_paths_are_inside_dirs(&config_dir, &data_dir, &state_dir);
         }
 
         if section!("XDG_DATA_HOME is not set") {
             unsafe { env::remove_var("XDG_DATA_HOME") };
-            assert_paths_are_inside_dirs(&config_dir, &data_dir);
+            assert_paths_are_inside_dirs(&config_dir, &data_dir, &state_dir);
         }
     }
 }
@@ -0,0 +1,64 @@
+use libnewsboat::{cliargsparser::CliArgsParser, configpaths::ConfigPaths};
+use section_testing::{enable_sections, section};
+use std::env;
+use tempfile::TempDir;
+
+mod configpaths_helpers;
+
+enable_sections! {
+#[test]
+fn t_configpaths_process_args_resolves_files_under_profile_dirs() {
+    let tmp = TempDir::new().unwrap();
+
+    unsafe { env::set_var("HOME", tmp.path()) };
+
+    // ConfigPaths rely on these variables, so let's sanitize them to ensure
+    // that the tests aren't affected
+    unsafe { env::remove_var("XDG_CONFIG_HOME") };
+    unsafe { env::remove_var("XDG_DATA_HOME") };
+    unsafe { env::remove_var("XDG_STATE_HOME") };
+    unsafe { env::remove_var("XDG_RUNTIME_DIR") };
+
+    if section!("Newsboat dotdir exists") {
+        configpaths_helpers::mock_newsboat_dotdir(&tmp);
+    }
+
+    if section!("Newsboat XDG dirs exist") {
+        configpaths_helpers::mock_newsboat_xdg_dirs(&tmp);
+    }
+
+    let config_dir = tmp.path().join(".config/newsboat/profiles/work");
+    let data_dir = tmp.path().join(".local/share/newsboat/profiles/work");
+    let state_dir = tmp.path().join(".local/state/newsboat/profiles/work");
+
+    let parser = CliArgsParser::new(vec![
+        "newsboat".into(),
+        "--profile".into(),
+        "work".into(),
+    ]);
+
+    let mut paths = ConfigPaths::new();
+    assert!(paths.initialized());
+    paths.process_args(&parser);
+
+    assert_eq!(paths.profile(), Some("work"));
+    assert!(!paths.profile_exists());
+
+    assert_eq!(paths.url_file(), config_dir.join("urls"));
+    assert_eq!(paths.config_file(), config_dir.join("config"));
+    assert_eq!(paths.cache_file(), data_dir.join("cache.db"));
+    assert_eq!(paths.queue_file(), data_dir.join("queue"));
+    assert_eq!(paths.lock_file(), state_dir.join("cache.db.lock"));
+    assert_eq!(paths.search_history_file(), state_dir.join("history.search"));
+    assert_eq!(
+        paths.cmdline_history_file(),
+        state_dir.join("history.cmdline")
+    );
+
+    assert!(paths.create_dirs());
+    assert!(config_dir.is_dir());
+    assert!(data_dir.is_dir());
+    assert!(state_dir.is_dir());
+    assert!(paths.profile_exists());
+}
+}
@@ -0,0 +1,34 @@
+use libnewsboat::{cliargsparser::CliArgsParser, configpaths::ConfigPaths};
+use std::env;
+use tempfile::TempDir;
+
+#[test]
+fn t_configpaths_process_args_prefers_explicit_paths_to_profile_ones() {
+    let tmp = TempDir::new().unwrap();
+
+    unsafe { env::set_var("HOME", tmp.path()) };
+    unsafe { env::set_var("XDG_CONFIG_HOME", tmp.path().join("config")) };
+    unsafe { env::set_var("XDG_DATA_HOME", tmp.path().join("data")) };
+
+    let url_file = tmp.path().join("my urls");
+    let parser = CliArgsParser::new(vec![
+        "newsboat".into(),
+        "-u".into(),
+        url_file.clone().into(),
+        "--profile=work".into(),
+    ]);
+
+    let mut paths = ConfigPaths::new();
+    assert!(paths.initialized());
+    paths.process_args(&parser);
+
+    assert_eq!(paths.url_file(), url_file);
+    assert_eq!(
+        paths.config_file(),
+        tmp.path().join("config/newsboat/profiles/work/config")
+    );
+    assert_eq!(
+        paths.cache_file(),
+        tmp.path().join("data/newsboat/profiles/work/cache.db")
+    );
+}
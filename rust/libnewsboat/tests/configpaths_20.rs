@@ -0,0 +1,29 @@
+use libnewsboat::{cliargsparser::CliArgsParser, configpaths::ConfigPaths};
+use std::env;
+use tempfile::TempDir;
+
+mod configpaths_helpers;
+
+#[test]
+fn t_configpaths_try_migrate_from_newsbeuter_does_not_migrate_into_a_profile() {
+    let tmp = TempDir::new().unwrap();
+
+    unsafe { env::set_var("HOME", tmp.path()) };
+
+    // ConfigPaths rely on these variables, so let's sanitize them to ensure
+    // that the tests aren't affected
+    unsafe { env::remove_var("XDG_CONFIG_HOME") };
+    unsafe { env::remove_var("XDG_DATA_HOME") };
+
+    configpaths_helpers::mock_newsbeuter_dotdir(&tmp);
+
+    let parser = CliArgsParser::new(vec!["newsboat".into(), "--profile=work".into()]);
+
+    let mut paths = ConfigPaths::new();
+    assert!(paths.initialized());
+    paths.process_args(&parser);
+
+    assert!(!paths.try_migrate_from_newsbeuter());
+    assert!(!tmp.path().join(".newsboat").exists());
+    assert!(!tmp.path().join(".config/newsboat/profiles/work").exists());
+}
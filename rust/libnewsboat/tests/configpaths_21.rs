@@ -0,0 +1,33 @@
+use libnewsboat::configpaths::ConfigPaths;
+use std::env;
+use std::fs;
+use tempfile::TempDir;
+
+mod configpaths_helpers;
+
+#[test]
+fn t_configpaths_list_profiles_returns_names_of_profile_config_dirs() {
+    let tmp = TempDir::new().unwrap();
+
+    unsafe { env::set_var("HOME", tmp.path()) };
+
+    // ConfigPaths rely on these variables, so let's sanitize them to ensure
+    // that the tests aren't affected
+    unsafe { env::remove_var("XDG_CONFIG_HOME") };
+    unsafe { env::remove_var("XDG_DATA_HOME") };
+
+    let paths = ConfigPaths::new();
+    assert!(paths.initialized());
+    assert!(paths.list_profiles().is_empty());
+
+    let profiles_dir = tmp.path().join(".config/newsboat/profiles");
+    for name in ["work", "personal", ".hidden"] {
+        fs::create_dir_all(profiles_dir.join(name)).unwrap();
+    }
+    assert!(configpaths_helpers::create_file(
+        &profiles_dir.join("not-a-profile"),
+        ""
+    ));
+
+    assert_eq!(paths.list_profiles(), vec!["personal", "work"]);
+}
@@ -0,0 +1,63 @@
+use libnewsboat::{cliargsparser::CliArgsParser, configpaths::ConfigPaths};
+use std::env;
+use std::fs;
+use tempfile::TempDir;
+
+mod configpaths_helpers;
+
+#[test]
+fn t_configpaths_lists_config_and_url_fragments_in_lexical_order() {
+    let tmp = TempDir::new().unwrap();
+
+    unsafe { env::set_var("HOME", tmp.path()) };
+
+    // ConfigPaths rely on these variables, so let's sanitize them to ensure
+    // that the tests aren't affected
+    unsafe { env::remove_var("XDG_CONFIG_HOME") };
+    unsafe { env::remove_var("XDG_DATA_HOME") };
+
+    let dir = tmp.path().join("shared");
+    let config_file = dir.join("config");
+    let url_file = dir.join("urls");
+
+    let args = CliArgsParser::new(vec![
+        "newsboat".into(),
+        "-C".into(),
+        config_file.clone().into(),
+        "-u".into(),
+        url_file.clone().into(),
+    ]);
+    let mut paths = ConfigPaths::new();
+    assert!(paths.initialized());
+    paths.process_args(&args);
+
+    // Directories don't exist yet
+    assert_eq!(paths.config_files(), vec![config_file.clone()]);
+    assert_eq!(paths.url_files(), vec![url_file.clone()]);
+
+    let config_d = dir.join("config.d");
+    fs::create_dir_all(&config_d).unwrap();
+    for name in ["20-keys.conf", "10-base.conf", "notes.txt", ".hidden.conf"] {
+        assert!(configpaths_helpers::create_file(&config_d.join(name), ""));
+    }
+    fs::create_dir_all(config_d.join("subdir.conf")).unwrap();
+
+    let urls_d = dir.join("urls.d");
+    fs::create_dir_all(&urls_d).unwrap();
+    for name in ["work", "news", ".news.swp"] {
+        assert!(configpaths_helpers::create_file(&urls_d.join(name), ""));
+    }
+
+    assert_eq!(
+        paths.config_files(),
+        vec![
+            config_file,
+            config_d.join("10-base.conf"),
+            config_d.join("20-keys.conf"),
+        ]
+    );
+    assert_eq!(
+        paths.url_files(),
+        vec![url_file, urls_d.join("news"), urls_d.join("work")]
+    );
+}
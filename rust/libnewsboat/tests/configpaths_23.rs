@@ -0,0 +1,55 @@
+use libnewsboat::{cliargsparser::CliArgsParser, configpaths::ConfigPaths, migrations};
+use std::env;
+use std::fs;
+use tempfile::TempDir;
+
+mod configpaths_helpers;
+
+#[test]
+fn t_configpaths_try_migrate_records_version_only_after_moving_files() {
+    let tmp = TempDir::new().unwrap();
+
+    unsafe { env::set_var("HOME", tmp.path()) };
+
+    // ConfigPaths rely on these variables, so let's sanitize them to ensure
+    // that the tests aren't affected
+    unsafe { env::remove_var("XDG_CONFIG_HOME") };
+    unsafe { env::remove_var("XDG_DATA_HOME") };
+    unsafe { env::remove_var("XDG_STATE_HOME") };
+
+    let config_dir = tmp.path().join(".config").join("newsboat");
+    fs::create_dir_all(&config_dir).unwrap();
+    let data_dir = tmp.path().join(".local").join("share").join("newsboat");
+    fs::create_dir_all(&data_dir).unwrap();
+    let state_dir = tmp.path().join(".local").join("state").join("newsboat");
+    let version_file = data_dir.join(migrations::VERSION_FILENAME);
+
+    let try_migrate = |args: Vec<&str>| {
+        let parser = CliArgsParser::new(args.into_iter().map(Into::into).collect());
+        let mut paths = ConfigPaths::new();
+        assert!(paths.initialized());
+        paths.process_args(&parser);
+        paths.try_migrate().unwrap()
+    };
+
+    // There are no files to move yet, so the version isn't recorded
+    assert!(!try_migrate(vec!["newsboat"]));
+    assert!(!version_file.exists());
+
+    fs::write(data_dir.join("history.search"), "query").unwrap();
+
+    let urls = tmp.path().join("urls");
+    assert!(!try_migrate(vec!["newsboat", "-u", urls.to_str().unwrap()]));
+    assert!(!version_file.exists());
+    assert!(data_dir.join("history.search").exists());
+
+    assert!(try_migrate(vec!["newsboat"]));
+    assert_eq!(
+        fs::read_to_string(&version_file).unwrap().trim(),
+        migrations::current_version().to_string()
+    );
+    assert_eq!(
+        fs::read_to_string(state_dir.join("history.search")).unwrap(),
+        "query"
+    );
+}
@@ -0,0 +1,71 @@
+use libnewsboat::configpaths::ConfigPaths;
+use std::env;
+use std::fs::{self, DirBuilder};
+use std::os::unix::fs::DirBuilderExt;
+use tempfile::TempDir;
+
+mod configpaths_helpers;
+
+#[test]
+fn t_configpaths_puts_lock_into_xdg_runtime_dir_if_it_is_set() {
+    let tmp = TempDir::new().unwrap();
+
+    unsafe { env::set_var("HOME", tmp.path()) };
+
+    // ConfigPaths rely on these variables, so let's sanitize them to ensure
+    // that the tests aren't affected
+    unsafe { env::remove_var("XDG_CONFIG_HOME") };
+    unsafe { env::remove_var("XDG_DATA_HOME") };
+
+    configpaths_helpers::mock_newsboat_xdg_dirs(&tmp);
+
+    let state_home = tmp.path().join("state");
+    unsafe { env::set_var("XDG_STATE_HOME", &state_home) };
+
+    // The runtime directory has to be private to the user
+    let runtime_dir = tmp.path().join("runtime");
+    DirBuilder::new().mode(0o700).create(&runtime_dir).unwrap();
+    unsafe { env::set_var("XDG_RUNTIME_DIR", &runtime_dir) };
+
+    let paths = ConfigPaths::new();
+    assert!(paths.initialized());
+
+    assert_eq!(
+        paths.lock_file(),
+        runtime_dir.join("newsboat").join("cache.db.lock")
+    );
+    assert_eq!(paths.lock_dir(), runtime_dir.join("newsboat"));
+    // Older versions kept the lock next to the cache
+    assert_eq!(
+        paths.legacy_lock_file(),
+        Some(
+            tmp.path()
+                .join(".local")
+                .join("share")
+                .join("newsboat")
+                .join("cache.db.lock")
+                .as_path()
+        )
+    );
+    assert_eq!(
+        paths.search_history_file(),
+        state_home.join("newsboat").join("history.search")
+    );
+    assert_eq!(
+        paths.cmdline_history_file(),
+        state_home.join("newsboat").join("history.cmdline")
+    );
+
+    assert!(paths.create_dirs());
+    assert!(runtime_dir.join("newsboat").is_dir());
+    assert!(state_home.join("newsboat").is_dir());
+
+    // Dotdir users keep everything in one place
+    fs::remove_dir_all(tmp.path().join(".config")).unwrap();
+    let dotdir = tmp.path().join(".newsboat");
+    let paths = ConfigPaths::new();
+    assert_eq!(paths.lock_file(), dotdir.join("cache.db.lock"));
+    assert_eq!(paths.legacy_lock_file(), None);
+    assert_eq!(paths.search_history_file(), dotdir.join("history.search"));
+    assert_eq!(paths.log_dir(), None);
+}
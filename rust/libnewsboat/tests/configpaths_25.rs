@@ -0,0 +1,63 @@
+use libnewsboat::configpaths::ConfigPaths;
+use section_testing::{enable_sections, section};
+use std::env;
+use tempfile::TempDir;
+
+mod configpaths_helpers;
+
+enable_sections! {
+#[test]
+fn t_configpaths_puts_all_files_into_newsboat_home_if_it_is_set() {
+    let tmp = TempDir::new().unwrap();
+
+    unsafe { env::set_var("HOME", tmp.path()) };
+
+    // ConfigPaths rely on these variables, so let's sanitize them to ensure
+    // that the tests aren't affected
+    unsafe { env::remove_var("XDG_CONFIG_HOME") };
+    unsafe { env::remove_var("XDG_DATA_HOME") };
+    unsafe { env::remove_var("XDG_STATE_HOME") };
+    unsafe { env::remove_var("XDG_RUNTIME_DIR") };
+
+    // Existing directories are ignored in portable mode
+    if section!("Newsbeuter dotdir exists") {
+        configpaths_helpers::mock_newsbeuter_dotdir(&tmp);
+    }
+
+    if section!("Newsboat dotdir exists") {
+        configpaths_helpers::mock_newsboat_dotdir(&tmp);
+    }
+
+    if section!("Newsboat XDG dirs exist") {
+        configpaths_helpers::mock_newsboat_xdg_dirs(&tmp);
+    }
+
+    let portable_dir = tmp.path().join("usb-stick").join("newsboat");
+    unsafe { env::set_var("NEWSBOAT_HOME", &portable_dir) };
+
+    let mut paths = ConfigPaths::new();
+    assert!(paths.initialized());
+    assert_eq!(paths.portable_dir(), Some(portable_dir.as_path()));
+
+    assert_eq!(paths.url_file(), portable_dir.join("urls"));
+    assert_eq!(paths.config_file(), portable_dir.join("config"));
+    assert_eq!(paths.cache_file(), portable_dir.join("cache.db"));
+    assert_eq!(paths.queue_file(), portable_dir.join("queue"));
+    assert_eq!(paths.lock_file(), portable_dir.join("cache.db.lock"));
+    assert_eq!(
+        paths.search_history_file(),
+        portable_dir.join("history.search")
+    );
+    assert_eq!(
+        paths.cmdline_history_file(),
+        portable_dir.join("history.cmdline")
+    );
+    assert_eq!(paths.log_dir(), Some(portable_dir.as_path()));
+
+    assert!(!paths.try_migrate_from_newsbeuter());
+    assert!(!portable_dir.join("urls").exists());
+
+    assert!(paths.create_dirs());
+    assert!(portable_dir.is_dir());
+}
+}
@@ -0,0 +1,15 @@
+use libnewsboat::configpaths::ConfigPaths;
+use std::env;
+use tempfile::TempDir;
+
+#[test]
+fn t_configpaths_works_without_home_directory_in_portable_mode() {
+    let tmp = TempDir::new().unwrap();
+
+    unsafe { env::remove_var("HOME") };
+    unsafe { env::set_var("NEWSBOAT_HOME", tmp.path()) };
+
+    let paths = ConfigPaths::new();
+    assert!(paths.initialized());
+    assert_eq!(paths.config_file(), tmp.path().join("config"));
+}
@@ -0,0 +1,41 @@
+use libnewsboat::{cliargsparser::CliArgsParser, configpaths::ConfigPaths};
+use std::env;
+use tempfile::TempDir;
+
+#[test]
+fn t_configpaths_puts_profiles_into_newsboat_home_in_portable_mode() {
+    let tmp = TempDir::new().unwrap();
+
+    unsafe { env::set_var("HOME", tmp.path()) };
+
+    // ConfigPaths rely on these variables, so let's sanitize them to ensure
+    // that the tests aren't affected
+    unsafe { env::remove_var("XDG_CONFIG_HOME") };
+    unsafe { env::remove_var("XDG_DATA_HOME") };
+    unsafe { env::remove_var("XDG_STATE_HOME") };
+    unsafe { env::remove_var("XDG_RUNTIME_DIR") };
+
+    let portable_dir = tmp.path().join("portable");
+    unsafe { env::set_var("NEWSBOAT_HOME", &portable_dir) };
+
+    let profile_dir = portable_dir.join("profiles").join("work");
+
+    let parser = CliArgsParser::new(vec!["newsboat".into(), "--profile".into(), "work".into()]);
+
+    let mut paths = ConfigPaths::new();
+    paths.process_args(&parser);
+
+    assert!(!paths.profile_exists());
+    assert_eq!(paths.url_file(), profile_dir.join("urls"));
+    assert_eq!(paths.config_file(), profile_dir.join("config"));
+    assert_eq!(paths.cache_file(), profile_dir.join("cache.db"));
+    assert_eq!(paths.lock_file(), profile_dir.join("cache.db.lock"));
+    assert_eq!(
+        paths.search_history_file(),
+        profile_dir.join("history.search")
+    );
+
+    assert!(paths.create_dirs());
+    assert!(paths.profile_exists());
+    assert_eq!(paths.list_profiles(), vec!["work".to_owned()]);
+}
@@ -0,0 +1,118 @@
+use libnewsboat::controlsocket::{self, Command, ControlSocket};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+/// Answers `count` commands sent to the socket at `path`, echoing them back.
+fn spawn_echo_server(path: &Path, count: usize) -> thread::JoinHandle<()> {
+    let mut socket = ControlSocket::bind(path).unwrap();
+    thread::spawn(move || {
+        let mut answered = 0;
+        while answered < count {
+            if let Some(command) = socket.next_command(Duration::from_millis(100)) {
+                socket.reply(Ok(command.to_string()));
+                answered += 1;
+            }
+        }
+    })
+}
+
+#[test]
+fn t_send_command_returns_the_reply_of_the_running_instance() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("cache.db.sock");
+    let server = spawn_echo_server(&path, 2);
+
+    assert_eq!(
+        controlsocket::send_command(&path, &Command::Reload(None)),
+        Ok("reload".to_owned())
+    );
+    assert_eq!(
+        controlsocket::send_command(&path, &Command::Open("guid".to_owned())),
+        Ok("open guid".to_owned())
+    );
+
+    server.join().unwrap();
+}
+
+#[test]
+fn t_several_commands_can_be_sent_over_one_connection() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("cache.db.sock");
+    let server = spawn_echo_server(&path, 2);
+
+    let mut stream = UnixStream::connect(&path).unwrap();
+    stream
+        .write_all(b"unread-count\nbogus\nmark-read https://example.com/feed.xml")
+        .unwrap();
+    stream.shutdown(std::net::Shutdown::Write).unwrap();
+    let lines: Vec<String> = BufReader::new(stream)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect();
+
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "OK unread-count");
+    assert!(lines[1].starts_with("ERR "));
+    assert_eq!(lines[2], "OK mark-read https://example.com/feed.xml");
+
+    server.join().unwrap();
+}
+
+#[test]
+fn t_next_command_returns_none_if_nothing_arrives_before_the_timeout() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("cache.db.sock");
+    let mut socket = ControlSocket::bind(&path).unwrap();
+
+    assert_eq!(socket.next_command(Duration::from_millis(10)), None);
+}
+
+#[test]
+fn t_bind_replaces_a_stale_socket_but_not_other_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("cache.db.sock");
+
+    let stale = std::os::unix::net::UnixListener::bind(&path).unwrap();
+    drop(stale);
+    assert!(path.exists());
+    assert!(ControlSocket::bind(&path).is_ok());
+
+    let regular_file = tmp.path().join("notes.txt");
+    fs::write(&regular_file, "important").unwrap();
+    assert!(ControlSocket::bind(&regular_file).is_err());
+    assert_eq!(fs::read_to_string(&regular_file).unwrap(), "important");
+}
+
+#[test]
+fn t_socket_is_only_accessible_by_its_owner() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("cache.db.sock");
+
+    let _socket = ControlSocket::bind(&path).unwrap();
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o077, 0, "mode is {:o}", mode);
+}
+
+#[test]
+fn t_socket_is_removed_when_dropped() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("cache.db.sock");
+
+    let socket = ControlSocket::bind(&path).unwrap();
+    assert!(path.exists());
+    drop(socket);
+    assert!(!path.exists());
+}
+
+#[test]
+fn t_send_command_fails_if_no_instance_is_running() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("cache.db.sock");
+
+    assert!(controlsocket::send_command(&path, &Command::Reload(None)).is_err());
+}
@@ -1,9 +1,12 @@
-use libnewsboat::fslock::FsLock;
+use libnewsboat::fslock::{self, FsLock, LockMode, LockOwner, OwnerStatus};
 use std::env;
+use std::fs;
 use std::io::{Error, ErrorKind};
 use std::io::{Read, Write};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 use tempfile::NamedTempFile;
 
 fn get_exe_path(exe: &str) -> Result<PathBuf, Error> {
@@ -16,6 +19,38 @@ fn get_exe_path(exe: &str) -> Result<PathBuf, Error> {
     Err(Error::new(ErrorKind::NotFound, exe))
 }
 
+/// Starts a process that holds a lock on `lock_location` until `release_lock` is called.
+fn spawn_lock_process(lock_location: &Path) -> Child {
+    let cmd = match env::var("CARGO_BIN_EXE_lock-process") {
+        Ok(dir) => dir,
+        Err(_) => get_exe_path("lock-process")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string(),
+    };
+
+    let mut child = Command::new(cmd)
+        .arg(lock_location)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // wait for locked signal
+    let stdout = child.stdout.as_mut().unwrap();
+    stdout.read_exact(&mut [0]).unwrap();
+
+    child
+}
+
+fn release_lock(mut child: Child) {
+    // notify child to exit and drop lock
+    let stdin = child.stdin.as_mut().unwrap();
+    stdin.write_all(b"\n").unwrap();
+    child.wait().unwrap();
+}
+
 #[test]
 fn t_returns_an_error_if_invalid_lock_location() {
     let tmp = tempfile::tempdir().unwrap();
@@ -46,30 +81,12 @@ fn t_returns_an_error_lock_file_without_write_access() {
 fn t_fails_if_lock_was_already_created() {
     let lock_location = NamedTempFile::new().unwrap();
 
-    let cmd = match env::var("CARGO_BIN_EXE_lock-process") {
-        Ok(dir) => dir,
-        Err(_) => get_exe_path("lock-process")
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_string(),
-    };
-
-    let mut child = Command::new(cmd)
-        .arg(lock_location.path())
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()
-        .unwrap();
+    let child = spawn_lock_process(lock_location.path());
 
     let mut lock = FsLock::default();
     let mut pid = 0;
     let cid = child.id() as i32;
 
-    // wait for locked signal
-    let stdout = child.stdout.as_mut().unwrap();
-    stdout.read_exact(&mut [0]).unwrap();
-
     let result = lock.try_lock(lock_location.as_ref(), &mut pid);
     assert!(result.is_err());
     if let Err(e) = result {
@@ -77,10 +94,7 @@ fn t_fails_if_lock_was_already_created() {
     }
     assert_eq!(pid, cid, "pid should be process holding the lock");
 
-    // notify child to exit and drop lock
-    let stdin = child.stdin.as_mut().unwrap();
-    stdin.write_all(b"\n").unwrap();
-    child.wait().unwrap();
+    release_lock(child);
 }
 
 #[test]
@@ -103,3 +117,277 @@ fn t_succeeds_if_lock_file_location_is_valid_and_not_locked_by_different_process
     assert!(!lock_location.path().exists());
     assert!(new_lock_location.path().exists());
 }
+
+fn owner_on(hostname: &str) -> LockOwner {
+    LockOwner {
+        pid: 42,
+        hostname: Some(hostname.to_owned()),
+        boot_id: Some("boot-1".to_owned()),
+        start_time: Some(1000),
+    }
+}
+
+#[test]
+fn t_lock_owner_survives_a_round_trip_through_lock_file_contents() {
+    let owner = owner_on("example");
+    assert_eq!(
+        owner.serialize(),
+        "42\nhost=example\nboot_id=boot-1\nstart_time=1000\n"
+    );
+    assert_eq!(LockOwner::parse(&owner.serialize()), owner);
+
+    let current = LockOwner::current();
+    assert_eq!(current.pid, std::process::id() as i32);
+    assert_eq!(LockOwner::parse(&current.serialize()), current);
+}
+
+#[test]
+fn t_lock_owner_parses_lock_files_that_only_contain_pid() {
+    assert_eq!(
+        LockOwner::parse("1234"),
+        LockOwner {
+            pid: 1234,
+            ..LockOwner::default()
+        }
+    );
+    assert_eq!(LockOwner::parse("").pid, 0);
+}
+
+#[test]
+fn t_lock_owner_status_depends_on_host_boot_and_process() {
+    let current = owner_on("example");
+    let running = |_| Some(Some(1000));
+
+    assert_eq!(
+        owner_on("example").status_with(&current, running),
+        OwnerStatus::Running
+    );
+    assert_eq!(
+        owner_on("elsewhere").status_with(&current, running),
+        OwnerStatus::OtherHost
+    );
+    assert_eq!(
+        LockOwner::parse("42").status_with(&current, running),
+        OwnerStatus::Unknown
+    );
+
+    // The host rebooted since
+    let mut owner = owner_on("example");
+    owner.boot_id = Some("boot-0".to_owned());
+    assert_eq!(owner.status_with(&current, running), OwnerStatus::Dead);
+
+    // The process exited
+    assert_eq!(
+        owner_on("example").status_with(&current, |_| None),
+        OwnerStatus::Dead
+    );
+
+    // The PID now belongs to another process
+    assert_eq!(
+        owner_on("example").status_with(&current, |_| Some(Some(2000))),
+        OwnerStatus::Dead
+    );
+
+    // Start time is unknown, so we assume it's the same process
+    assert_eq!(
+        owner_on("example").status_with(&current, |_| Some(None)),
+        OwnerStatus::Running
+    );
+}
+
+#[test]
+fn t_does_not_take_over_held_lock_whose_owner_ran_on_this_host_before_a_reboot() {
+    let lock_location = NamedTempFile::new().unwrap();
+    let child = spawn_lock_process(lock_location.path());
+
+    // Pretend that the lock survived a reboot
+    let mut owner = LockOwner::parse(&fs::read_to_string(lock_location.path()).unwrap());
+    owner.boot_id = Some("a-boot-long-ago".to_owned());
+    fs::write(lock_location.path(), owner.serialize()).unwrap();
+
+    let mut lock = FsLock::default();
+    let mut pid = -1;
+    let message = lock.try_lock(lock_location.as_ref(), &mut pid).unwrap_err();
+    assert_eq!(pid, 0, "the recorded owner isn't running");
+    assert!(message.contains("--force-unlock"));
+
+    // The lock file is left alone
+    assert_eq!(
+        LockOwner::parse(&fs::read_to_string(lock_location.path()).unwrap()),
+        owner
+    );
+
+    release_lock(child);
+}
+
+#[test]
+fn t_refuses_to_take_over_lock_held_on_another_host() {
+    let lock_location = NamedTempFile::new().unwrap();
+    let child = spawn_lock_process(lock_location.path());
+
+    fs::write(
+        lock_location.path(),
+        owner_on("elsewhere.example").serialize(),
+    )
+    .unwrap();
+
+    let mut lock = FsLock::default();
+    let mut pid = -1;
+    let result = lock.try_lock(lock_location.as_ref(), &mut pid);
+    assert_eq!(pid, 0, "PID from another host isn't meaningful here");
+    let message = result.unwrap_err();
+    assert!(message.contains("elsewhere.example"));
+    assert!(message.contains("--force-unlock"));
+
+    assert!(fslock::force_unlock(lock_location.path()).unwrap());
+    assert!(!lock_location.path().exists());
+    assert!(lock.try_lock(lock_location.as_ref(), &mut pid).is_ok());
+
+    release_lock(child);
+}
+
+#[test]
+fn t_force_unlock_keeps_lock_held_by_running_process_on_this_host() {
+    let lock_location = NamedTempFile::new().unwrap();
+    let child = spawn_lock_process(lock_location.path());
+
+    assert!(fslock::force_unlock(lock_location.path()).is_err());
+    assert!(lock_location.path().exists());
+
+    release_lock(child);
+
+    // Nobody holds the lock anymore, so there is nothing to remove
+    assert!(!fslock::force_unlock(lock_location.path()).unwrap());
+
+    let tmp = tempfile::tempdir().unwrap();
+    assert!(!fslock::force_unlock(&tmp.path().join("missing.lock")).unwrap());
+}
+
+#[test]
+fn t_lock_holder_returns_owner_of_held_lock_without_creating_the_file() {
+    let lock_location = NamedTempFile::new().unwrap();
+    let child = spawn_lock_process(lock_location.path());
+    let cid = child.id() as i32;
+
+    assert_eq!(
+        fslock::lock_holder(lock_location.path()).map(|owner| owner.pid),
+        Some(cid)
+    );
+
+    release_lock(child);
+    assert_eq!(fslock::lock_holder(lock_location.path()), None);
+
+    let tmp = tempfile::tempdir().unwrap();
+    let missing = tmp.path().join("missing.lock");
+    assert_eq!(fslock::lock_holder(&missing), None);
+    assert!(!missing.exists());
+}
+
+#[test]
+fn t_try_lock_or_go_unlocked_succeeds_without_lock_if_held_by_process_on_this_host() {
+    let lock_location = NamedTempFile::new().unwrap();
+    let child = spawn_lock_process(lock_location.path());
+    let cid = child.id() as i32;
+
+    let mut lock = FsLock::default();
+    let mut pid = 0;
+    assert_eq!(
+        lock.try_lock_or_go_unlocked(lock_location.as_ref(), &mut pid),
+        Ok(LockMode::Unlocked)
+    );
+    assert_eq!(pid, cid);
+
+    // Going on without a lock doesn't change the lock file
+    assert_eq!(
+        LockOwner::parse(&fs::read_to_string(lock_location.path()).unwrap()).pid,
+        cid
+    );
+
+    release_lock(child);
+
+    assert_eq!(
+        lock.try_lock_or_go_unlocked(lock_location.as_ref(), &mut pid),
+        Ok(LockMode::Exclusive)
+    );
+}
+
+#[test]
+fn t_try_lock_or_go_unlocked_fails_if_lock_is_held_on_another_host() {
+    let lock_location = NamedTempFile::new().unwrap();
+    let child = spawn_lock_process(lock_location.path());
+
+    fs::write(
+        lock_location.path(),
+        owner_on("elsewhere.example").serialize(),
+    )
+    .unwrap();
+
+    let mut lock = FsLock::default();
+    let mut pid = -1;
+    assert!(
+        lock.try_lock_or_go_unlocked(lock_location.as_ref(), &mut pid)
+            .is_err()
+    );
+    assert_eq!(pid, 0);
+
+    release_lock(child);
+}
+
+#[test]
+fn t_lock_with_timeout_gives_up_if_lock_is_not_released_in_time() {
+    let lock_location = NamedTempFile::new().unwrap();
+    let child = spawn_lock_process(lock_location.path());
+    let cid = child.id() as i32;
+
+    let mut lock = FsLock::default();
+    let mut pid = 0;
+    let started = Instant::now();
+    assert!(
+        lock.lock_with_timeout(lock_location.as_ref(), &mut pid, Duration::from_millis(200))
+            .is_err()
+    );
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert_eq!(pid, cid);
+
+    release_lock(child);
+}
+
+#[test]
+fn t_lock_with_timeout_locks_once_the_lock_is_released() {
+    let lock_location = NamedTempFile::new().unwrap();
+    let child = spawn_lock_process(lock_location.path());
+
+    let releaser = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(300));
+        release_lock(child);
+    });
+
+    let mut lock = FsLock::default();
+    let mut pid = 0;
+    assert_eq!(
+        lock.lock_with_timeout(lock_location.as_ref(), &mut pid, Duration::from_secs(30)),
+        Ok(())
+    );
+    assert_eq!(
+        LockOwner::parse(&fs::read_to_string(lock_location.path()).unwrap()).pid,
+        std::process::id() as i32
+    );
+
+    releaser.join().unwrap();
+}
+
+#[test]
+fn t_lock_with_timeout_does_not_wait_if_lock_file_cant_be_opened() {
+    let tmp = tempfile::tempdir().unwrap();
+    let lock_location = tmp.path().join("does-not-exist").join("lockfile");
+
+    let mut lock = FsLock::default();
+    let mut pid = 0;
+    let started = Instant::now();
+    assert!(
+        lock.lock_with_timeout(&lock_location, &mut pid, Duration::from_secs(30))
+            .is_err()
+    );
+    assert!(started.elapsed() < Duration::from_secs(30));
+    assert_eq!(pid, 0);
+}
@@ -164,6 +164,23 @@ static int vectorofstring_callback(void* vp, int argc, char** argv,
 	return 0;
 }
 
+static int articlestate_callback(void* vp, int argc, char** argv,
+	char** /* azColName */)
+{
+	auto articles =
+		static_cast<std::vector<readstate::bridged::ArticleState>*>(vp);
+	assert(argc == 6);
+	readstate::bridged::ArticleState article;
+	article.guid = argv[0];
+	article.feed_url = argv[1];
+	article.unread = (std::string("1") == argv[2]);
+	article.read_changed = std::stoll(argv[3]);
+	article.flags = argv[4] ? argv[4] : "";
+	article.flags_changed = std::stoll(argv[5]);
+	articles->push_back(std::move(article));
+	return 0;
+}
+
 static int rssitem_callback(void* myfeed, int argc, char** argv,
 	char** /* azColName */)
 {
@@ -256,10 +273,15 @@ static int guid_callback(void* myguids, int argc, char** argv,
 	return 0;
 }
 
-Cache::Cache(const Filepath& cachefile, ConfigContainer& c)
+Cache::Cache(const Filepath& cachefile, ConfigContainer& c, CacheMode mode)
 	: cfg(c)
+	, mode(mode)
 {
-	const int error = sqlite3_open(cachefile.to_locale_string().c_str(), &db);
+	const int flags = mode == CacheMode::ReadOnly
+		? SQLITE_OPEN_READONLY
+		: SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE;
+	const int error = sqlite3_open_v2(cachefile.to_locale_string().c_str(), &db,
+			flags, nullptr);
 	if (error != SQLITE_OK) {
 		LOG(Level::ERROR,
 			"couldn't sqlite3_open(%s): error = %d",
@@ -268,10 +290,25 @@ Cache::Cache(const Filepath& cachefile, ConfigContainer& c)
 		throw DbException(db);
 	}
 
-	populate_tables();
+	if (mode == CacheMode::DryRun) {
+		LOG(Level::INFO, "Cache::Cache: dry run, changes won't be saved");
+		run_sql("BEGIN TRANSACTION;");
+	}
+
+	if (mode == CacheMode::ReadOnly) {
+		// The instance that writes to the cache has already brought it up to
+		// date and cleaned it up, so all we need is to check that we
+		// understand the schema.
+		LOG(Level::INFO, "Cache::Cache: read-only, changes won't be saved");
+		check_schema_is_current();
+	} else {
+		populate_tables();
+	}
 	set_pragmas();
 
-	clean_old_articles();
+	if (mode != CacheMode::ReadOnly) {
+		clean_old_articles();
+	}
 
 	// we need to manually lock all DB operations because SQLite has no
 	// explicit support for multithreading.
@@ -296,6 +333,10 @@ void Cache::set_pragmas()
 	// then we disable case-sensitive matching for the LIKE operator in
 	// SQLite, for search operations
 	run_sql("PRAGMA case_sensitive_like=OFF;");
+
+	// another instance might be reading or writing the file at the same
+	// time, so wait for it instead of failing right away
+	run_sql("PRAGMA busy_timeout = 5000;");
 }
 
 static const schema_patches schemaPatches{
@@ -396,6 +437,25 @@ static const schema_patches schemaPatches{
 		}
 	},
 
+	{	{2, 45},
+		{
+			"ALTER TABLE rss_item ADD COLUMN read_changed INTEGER NOT NULL DEFAULT 0;",
+			"ALTER TABLE rss_item ADD COLUMN flags_changed INTEGER NOT NULL DEFAULT 0;",
+			// Remember when the read state or flags last changed, unless the
+			// update sets the time itself (like importing the read state does).
+			"CREATE TRIGGER rss_item_read_changed AFTER UPDATE OF unread ON rss_item "
+			"WHEN OLD.unread != NEW.unread AND OLD.read_changed = NEW.read_changed "
+			"BEGIN "
+			"UPDATE rss_item SET read_changed = strftime('%s', 'now') WHERE id = NEW.id; "
+			"END;",
+			"CREATE TRIGGER rss_item_flags_changed AFTER UPDATE OF flags ON rss_item "
+			"WHEN OLD.flags IS NOT NEW.flags AND OLD.flags_changed = NEW.flags_changed "
+			"BEGIN "
+			"UPDATE rss_item SET flags_changed = strftime('%s', 'now') WHERE id = NEW.id; "
+			"END;",
+		}
+	},
+
 	// Note: schema changes should use the version number of the release that introduced them.
 };
 
@@ -445,6 +505,34 @@ void Cache::populate_tables()
 	}
 }
 
+void Cache::check_schema_is_current()
+{
+	std::lock_guard<std::recursive_mutex> lock(mtx);
+	const SchemaVersion version = get_schema_version();
+	const SchemaVersion latest = schemaPatches.crbegin()->first;
+	if (version < latest || version > latest) {
+		const std::string msg = strprintf::fmt(
+				"Database schema version %u.%u doesn't match the expected "
+				"%u.%u, and the database can't be upgraded because it's "
+				"opened read-only",
+				version.major,
+				version.minor,
+				latest.major,
+				latest.minor);
+		LOG(Level::ERROR, msg);
+		throw DbException(msg);
+	}
+}
+
+bool Cache::drops_changes(const std::string& caller) const
+{
+	if (mode != CacheMode::ReadOnly) {
+		return false;
+	}
+	LOG(Level::DEBUG, "%s: cache is read-only, dropping changes", caller);
+	return true;
+}
+
 void Cache::fetch_lastmodified(const std::string& feedurl,
 	time_t& t,
 	std::string& etag)
@@ -470,6 +558,9 @@ void Cache::update_lastmodified(const std::string& feedurl,
 	time_t t,
 	const std::string& etag)
 {
+	if (drops_changes("Cache::update_lastmodified")) {
+		return;
+	}
 	if (t == 0 && etag.length() == 0) {
 		LOG(Level::INFO,
 			"Cache::update_lastmodified: both time and etag are "
@@ -497,6 +588,9 @@ void Cache::update_lastmodified(const std::string& feedurl,
 
 void Cache::mark_item_deleted(const std::string& guid, bool b)
 {
+	if (drops_changes("Cache::mark_item_deleted")) {
+		return;
+	}
 	std::lock_guard<std::recursive_mutex> lock(mtx);
 	std::string query = prepare_query(
 			"UPDATE rss_item SET deleted = %u WHERE guid = '%q'",
@@ -509,6 +603,9 @@ void Cache::mark_item_deleted(const std::string& guid, bool b)
 void Cache::externalize_rssfeed(RssFeed& feed,
 	bool reset_unread)
 {
+	if (drops_changes("Cache::externalize_rssfeed")) {
+		return;
+	}
 	ScopeMeasure m1("Cache::externalize_feed");
 	if (feed.is_query_feed()) {
 		return;
@@ -671,6 +768,7 @@ std::shared_ptr<RssFeed> Cache::internalize_rssfeed(std::string rssurl,
 			++j) {
 			if (feed->items()[j]->flags().length() == 0) {
 				delete_item_unlocked(*feed->items()[j]);
+				excess_articles++;
 			} else {
 				flagged_items.push_back(feed->items()[j]);
 			}
@@ -779,6 +877,9 @@ std::unordered_set<std::string> Cache::search_in_items(
 
 void Cache::delete_item_unlocked(const RssItem& item)
 {
+	if (drops_changes("Cache::delete_item_unlocked")) {
+		return;
+	}
 	const std::string query = prepare_query(
 			"DELETE FROM rss_item WHERE guid = '%q';", item.guid());
 	run_sql(query);
@@ -786,16 +887,16 @@ void Cache::delete_item_unlocked(const RssItem& item)
 
 void Cache::do_vacuum()
 {
+	if (drops_changes("Cache::do_vacuum")) {
+		return;
+	}
 	std::lock_guard<std::recursive_mutex> lock(mtx);
 	run_sql("VACUUM;");
 }
 
-std::vector<std::string> Cache::cleanup_cache(std::vector<std::shared_ptr<RssFeed>> feeds,
-	bool always_clean)
+std::string Cache::feed_url_list(
+	const std::vector<std::shared_ptr<RssFeed>>& feeds)
 {
-	std::lock_guard<std::recursive_mutex> lock(mtx);
-
-	std::vector<std::string> unreachable_feeds{};
 	std::string list = "(";
 
 	for (const auto& feed : feeds) {
@@ -805,6 +906,64 @@ std::vector<std::string> Cache::cleanup_cache(std::vector<std::shared_ptr<RssFee
 	}
 	list.append("'')");
 
+	return list;
+}
+
+unsigned int Cache::run_count_query(const std::string& query)
+{
+	CbHandler count_cbh;
+	run_sql(query, count_callback, &count_cbh);
+	return count_cbh.count() > 0 ? count_cbh.count() : 0;
+}
+
+CleanupSummary Cache::summarize_cleanup(
+	const std::vector<std::shared_ptr<RssFeed>>& feeds)
+{
+	std::lock_guard<std::recursive_mutex> lock(mtx);
+
+	const std::string list = feed_url_list(feeds);
+
+	CleanupSummary summary{0, 0, 0};
+	summary.feeds = run_count_query(
+			"SELECT count(*) FROM rss_feed WHERE rssurl NOT IN " + list + ";");
+	summary.articles = run_count_query(
+			"SELECT count(*) FROM rss_item WHERE feedurl NOT IN " + list + ";");
+	if (cfg.get_configvalue_as_bool("delete-read-articles-on-quit")) {
+		summary.read_articles = run_count_query(
+				"SELECT count(*) FROM rss_item "
+				"WHERE unread = 0 AND deleted = 0 AND feedurl IN " + list + ";");
+	}
+
+	return summary;
+}
+
+std::uint64_t Cache::reclaimable_space()
+{
+	std::lock_guard<std::recursive_mutex> lock(mtx);
+
+	const std::uint64_t page_size = run_count_query("PRAGMA page_size;");
+	const std::uint64_t free_pages = run_count_query("PRAGMA freelist_count;");
+	return page_size * free_pages;
+}
+
+unsigned int Cache::expired_articles_count() const
+{
+	return expired_articles;
+}
+
+unsigned int Cache::excess_articles_count() const
+{
+	return excess_articles;
+}
+
+std::vector<std::string> Cache::cleanup_cache(std::vector<std::shared_ptr<RssFeed>> feeds,
+	bool always_clean)
+{
+	std::lock_guard<std::recursive_mutex> lock(mtx);
+
+	std::vector<std::string> unreachable_feeds{};
+	const std::string list = feed_url_list(feeds);
+
 	/*
 	 * cache cleanup means that all entries in both the RssFeed and
 	 * RssItem tables that are associated with an RSS feed URL that is not
@@ -817,7 +976,9 @@ std::vector<std::string> Cache::cleanup_cache(std::vector<std::shared_ptr<RssFee
 	 * The behaviour whether the cleanup is done or not is configurable via
 	 * the configuration file.
 	 */
-	if (always_clean || cfg.get_configvalue_as_bool("cleanup-on-quit")) {
+	const bool clean = always_clean
+		|| cfg.get_configvalue_as_bool("cleanup-on-quit");
+	if (clean && !drops_changes("Cache::cleanup_cache")) {
 		LOG(Level::DEBUG, "Cache::cleanup_cache: cleaning up cache...");
 
 		std::string cleanup_rss_feeds_statement(
@@ -974,6 +1135,9 @@ void Cache::update_rssitem_unlocked(RssItem& item,
 
 void Cache::mark_all_read(RssFeed& feed)
 {
+	if (drops_changes("Cache::mark_all_read")) {
+		return;
+	}
 	std::lock_guard<std::recursive_mutex> lock(mtx);
 	std::lock_guard<std::mutex> itemlock(feed.item_mutex);
 	std::string query =
@@ -992,6 +1156,9 @@ void Cache::mark_all_read(RssFeed& feed)
  */
 void Cache::mark_all_read(const std::string& feedurl)
 {
+	if (drops_changes("Cache::mark_all_read")) {
+		return;
+	}
 	std::lock_guard<std::recursive_mutex> lock(mtx);
 
 	std::string query;
@@ -1014,6 +1181,9 @@ void Cache::mark_all_read(const std::string& feedurl)
 void Cache::update_rssitem_unread_and_enqueued(RssItem& item,
 	const std::string& /* feedurl */)
 {
+	if (drops_changes("Cache::update_rssitem_unread_and_enqueued")) {
+		return;
+	}
 	std::lock_guard<std::recursive_mutex> lock(mtx);
 
 	const auto query = prepare_query(
@@ -1060,6 +1230,9 @@ std::string Cache::prepare_query(const std::string& format, const T& argument,
 
 void Cache::update_rssitem_flags(RssItem* item)
 {
+	if (drops_changes("Cache::update_rssitem_flags")) {
+		return;
+	}
 	std::lock_guard<std::recursive_mutex> lock(mtx);
 
 	const std::string update = prepare_query(
@@ -1072,6 +1245,9 @@ void Cache::update_rssitem_flags(RssItem* item)
 
 void Cache::remove_old_deleted_items(RssFeed* feed)
 {
+	if (drops_changes("Cache::remove_old_deleted_items")) {
+		return;
+	}
 	ScopeMeasure m1("Cache::remove_old_deleted_items");
 
 	std::lock_guard<std::recursive_mutex> cache_lock(mtx);
@@ -1106,6 +1282,9 @@ void Cache::remove_old_deleted_items(RssFeed* feed)
 
 void Cache::mark_items_read_by_guid(const std::vector<std::string>& guids)
 {
+	if (drops_changes("Cache::mark_items_read_by_guid")) {
+		return;
+	}
 	ScopeMeasure m1("Cache::mark_items_read_by_guid");
 	std::lock_guard<std::recursive_mutex> lock(mtx);
 	std::string guidset("(");
@@ -1133,6 +1312,43 @@ std::vector<std::string> Cache::get_read_item_guids()
 	return guids;
 }
 
+std::vector<readstate::bridged::ArticleState> Cache::get_article_states()
+{
+	std::vector<readstate::bridged::ArticleState> articles;
+	const std::string query =
+		"SELECT guid, feedurl, unread, read_changed, flags, flags_changed "
+		"FROM rss_item WHERE deleted = 0;";
+
+	std::lock_guard<std::recursive_mutex> lock(mtx);
+	run_sql(query, articlestate_callback, &articles);
+
+	return articles;
+}
+
+void Cache::update_article_states(
+	const rust::Vec<readstate::bridged::ArticleState>& articles)
+{
+	if (drops_changes("Cache::update_article_states")) {
+		return;
+	}
+	ScopeMeasure m1("Cache::update_article_states");
+	std::lock_guard<std::recursive_mutex> lock(mtx);
+
+	for (const auto& article : articles) {
+		const std::string update = prepare_query(
+				"UPDATE rss_item SET unread = %d, read_changed = %" PRId64 ", "
+				"flags = '%q', flags_changed = %" PRId64 " "
+				"WHERE guid = '%q' AND feedurl = '%q';",
+				article.unread ? 1 : 0,
+				article.read_changed,
+				std::string(article.flags),
+				article.flags_changed,
+				std::string(article.guid),
+				std::string(article.feed_url));
+		run_sql(update);
+	}
+}
+
 void Cache::clean_old_articles()
 {
 	std::lock_guard<std::recursive_mutex> lock(mtx);
@@ -1165,6 +1381,7 @@ void Cache::clean_old_articles()
 			// always safe.
 			static_cast<int64_t>(old_date));
 		run_sql(query);
+		expired_articles += sqlite3_changes(db);
 	} else {
 		LOG(Level::DEBUG,
 			"Cache::clean_old_articles: days == 0, not cleaning up "
@@ -1249,6 +1466,9 @@ SchemaVersion Cache::get_schema_version()
 void Cache::close_database()
 {
 	if (db != nullptr) {
+		if (mode == CacheMode::DryRun) {
+			run_sql_nothrow("ROLLBACK;");
+		}
 		sqlite3_close(db);
 		db = nullptr;
 	}
@@ -48,6 +48,35 @@ bool CliArgsParser::do_cleanup() const
 	return newsboat::cliargsparser::bridged::do_cleanup(*rs_object);
 }
 
+bool CliArgsParser::do_migrate() const
+{
+	return newsboat::cliargsparser::bridged::do_migrate(*rs_object);
+}
+
+bool CliArgsParser::dry_run() const
+{
+	return newsboat::cliargsparser::bridged::dry_run(*rs_object);
+}
+
+bool CliArgsParser::force_unlock() const
+{
+	return newsboat::cliargsparser::bridged::force_unlock(*rs_object);
+}
+
+std::optional<std::uint64_t> CliArgsParser::wait_lock() const
+{
+	std::uint64_t seconds = 0;
+	if (newsboat::cliargsparser::bridged::wait_lock(*rs_object, seconds)) {
+		return seconds;
+	}
+	return std::nullopt;
+}
+
+bool CliArgsParser::can_run_read_only() const
+{
+	return newsboat::cliargsparser::bridged::can_run_read_only(*rs_object);
+}
+
 Filepath CliArgsParser::importfile() const
 {
 	auto output = filepath::bridged::create_empty();
@@ -117,6 +146,57 @@ bool CliArgsParser::refresh_on_start() const
 	return newsboat::cliargsparser::bridged::refresh_on_start(*rs_object);
 }
 
+bool CliArgsParser::lint_keymap() const
+{
+	return newsboat::cliargsparser::bridged::lint_keymap(*rs_object);
+}
+
+std::optional<std::string> CliArgsParser::export_keymap() const
+{
+	rust::String format;
+	if (newsboat::cliargsparser::bridged::export_keymap(*rs_object, format)) {
+		return std::string(format);
+	}
+	return std::nullopt;
+}
+
+bool CliArgsParser::create_profile() const
+{
+	return newsboat::cliargsparser::bridged::create_profile(*rs_object);
+}
+
+bool CliArgsParser::list_profiles() const
+{
+	return newsboat::cliargsparser::bridged::list_profiles(*rs_object);
+}
+
+std::optional<std::string> CliArgsParser::completions() const
+{
+	rust::String script;
+	if (newsboat::cliargsparser::bridged::completions(*rs_object, script)) {
+		return std::string(script);
+	}
+	return std::nullopt;
+}
+
+std::optional<std::string> CliArgsParser::remote_command() const
+{
+	rust::String command;
+	if (newsboat::cliargsparser::bridged::remote_command(*rs_object, command)) {
+		return std::string(command);
+	}
+	return std::nullopt;
+}
+
+std::optional<cliargsparser::bridged::Subcommand> CliArgsParser::subcommand() const
+{
+	cliargsparser::bridged::Subcommand subcommand;
+	if (newsboat::cliargsparser::bridged::subcommand(*rs_object, subcommand)) {
+		return subcommand;
+	}
+	return std::nullopt;
+}
+
 std::optional<Filepath> CliArgsParser::url_file() const
 {
 	auto path = filepath::bridged::create_empty();
@@ -193,6 +273,18 @@ const
 	return cmds;
 }
 
+std::vector<std::pair<std::string, std::string>> CliArgsParser::config_overrides()
+const
+{
+	std::vector<std::pair<std::string, std::string>> result;
+	for (const auto& assignment : newsboat::cliargsparser::bridged::config_overrides(
+			*rs_object)) {
+		result.emplace_back(std::string(assignment.name),
+			std::string(assignment.value));
+	}
+	return result;
+}
+
 std::optional<Filepath> CliArgsParser::log_file() const
 {
 	auto path = filepath::bridged::create_empty();
@@ -211,6 +303,11 @@ std::optional<Level> CliArgsParser::log_level() const
 	return std::nullopt;
 }
 
+std::string CliArgsParser::options_usage()
+{
+	return std::string(newsboat::cliargsparser::bridged::options_usage());
+}
+
 const cliargsparser::bridged::CliArgsParser& CliArgsParser::get_rust_ref() const
 {
 	return *rs_object;
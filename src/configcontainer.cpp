@@ -15,6 +15,8 @@
 #include "strprintf.h"
 #include "utils.h"
 
+#include "libnewsboat-ffi/src/configoverrides.rs.h" // IWYU pragma: keep
+
 namespace newsboat {
 
 const std::string ConfigContainer::PARTIAL_FILE_SUFFIX = "part";
@@ -28,8 +30,8 @@ ConfigContainer::ConfigContainer()
 		ConfigData("date-asc", ConfigDataType::STR)},
 	{
 		"articlelist-format",
-		ConfigData("%4i %f %D %6L  %?T?|%-17T|  &?%t",
-			ConfigDataType::STR)},
+		ConfigData::format_string("%4i %f %D %6L  %?T?|%-17T|  &?%t",
+			"aDfndFitTLe")},
 	{"auto-reload", ConfigData("no", ConfigDataType::BOOL)},
 	{
 		"bookmark-autopilot",
@@ -58,6 +60,7 @@ ConfigContainer::ConfigContainer()
 	{"confirm-mark-feed-read", ConfigData("yes", ConfigDataType::BOOL)},
 	{"confirm-exit", ConfigData("no", ConfigDataType::BOOL)},
 	{"cookie-cache", ConfigData("", ConfigDataType::PATH)},
+	{"count-prefix", ConfigData("no", ConfigDataType::BOOL)},
 	{"datetime-format", ConfigData("%b %d", ConfigDataType::STR)},
 	{
 		"delete-read-articles-on-quit",
@@ -68,8 +71,8 @@ ConfigContainer::ConfigContainer()
 		ConfigData("yes", ConfigDataType::BOOL)},
 	{
 		"download-filename-format",
-		ConfigData("%?u?%u&%Y-%b-%d-%H%M%S.unknown?",
-			ConfigDataType::STR)},
+		ConfigData::format_string("%?u?%u&%Y-%b-%d-%H%M%S.unknown?",
+			"unNhteFmbdHMSyY")},
 	{
 		"download-full-page",
 		ConfigData("false", ConfigDataType::BOOL)},
@@ -114,7 +117,7 @@ ConfigContainer::ConfigContainer()
 		ConfigData("", ConfigDataType::STR)},
 	{
 		"feedlist-format",
-		ConfigData("%4i %n %11u %t", ConfigDataType::STR)},
+		ConfigData::format_string("%4i %n %11u %t", "dilLnStTuUc")},
 	{"goto-first-unread", ConfigData("true", ConfigDataType::BOOL)},
 	{"goto-next-feed", ConfigData("yes", ConfigDataType::BOOL)},
 	{"history-limit", ConfigData("100", ConfigDataType::INT)},
@@ -160,6 +163,7 @@ ConfigContainer::ConfigContainer()
 	{"max-download-speed", ConfigData("0", ConfigDataType::INT)},
 	{"max-downloads", ConfigData("1", ConfigDataType::INT)},
 	{"max-items", ConfigData("0", ConfigDataType::INT)},
+	{"mouse-support", ConfigData("no", ConfigDataType::BOOL)},
 	{"newsblur-login", ConfigData("", ConfigDataType::STR)},
 	{"newsblur-min-items", ConfigData("20", ConfigDataType::INT)},
 	{"newsblur-password", ConfigData("", ConfigDataType::STR)},
@@ -173,9 +177,9 @@ ConfigContainer::ConfigContainer()
 	{"notify-beep", ConfigData("no", ConfigDataType::BOOL)},
 	{
 		"notify-format",
-		ConfigData(_("Newsboat: finished reload, %f unread "
+		ConfigData::format_string(_("Newsboat: finished reload, %f unread "
 				"feeds (%n unread articles total)"),
-			ConfigDataType::STR)},
+			"nfdD")},
 	{"notify-program", ConfigData("", ConfigDataType::PATH)},
 	{"notify-screen", ConfigData("no", ConfigDataType::BOOL)},
 	{"notify-xterm", ConfigData("no", ConfigDataType::BOOL)},
@@ -202,7 +206,8 @@ ConfigContainer::ConfigContainer()
 		ConfigData("no", ConfigDataType::BOOL)},
 	{
 		"podlist-format",
-		ConfigData( _("%4i [%6dMB/%6tMB] [%5p %%] [%12K] %-20S %u -> %F"), ConfigDataType::STR)},
+		ConfigData::format_string( _("%4i [%6dMB/%6tMB] [%5p %%] [%12K] %-20S %u -> %F"),
+			"idtpkKSuFb")},
 	{
 		"prepopulate-query-feeds",
 		ConfigData("false", ConfigDataType::BOOL)},
@@ -244,7 +249,7 @@ ConfigContainer::ConfigContainer()
 			true)},
 	{
 		"selecttag-format",
-		ConfigData("%4i  %T (%u)", ConfigDataType::STR)},
+		ConfigData::format_string("%4i  %T (%u)", "iTfnu")},
 	{"show-keymap-hint", ConfigData("yes", ConfigDataType::BOOL)},
 	{"show-title-bar", ConfigData("yes", ConfigDataType::BOOL)},
 	{"show-read-articles", ConfigData("yes", ConfigDataType::BOOL)},
@@ -314,45 +319,43 @@ ConfigContainer::ConfigContainer()
 	/* title formats: */
 	{
 		"articlelist-title-format",
-		ConfigData(_("%N %V - Articles in feed '%T' (%u unread, %t total)%?F? matching filter '%F'&? - %U"),
-			ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - Articles in feed '%T' (%u unread, %t total)%?F? matching filter '%F'&? - %U"),
+			"NVTUutF")},
 	{
 		"dialogs-title-format",
-		ConfigData(_("%N %V - Dialogs"), ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - Dialogs"), "NV")},
 	{
 		"feedlist-title-format",
-		ConfigData(_("%N %V - %?F?Feeds&Your feeds? (%u unread, %t total)%?F? matching filter '%F'&?%?T? - tag '%T'&?"),
-			ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - %?F?Feeds&Your feeds? (%u unread, %t total)%?F? matching filter '%F'&?%?T? - tag '%T'&?"),
+			"NVTtuUF")},
 	{
 		"filebrowser-title-format",
-		ConfigData(_("%N %V - %?O?Open File&Save File? - %f"),
-			ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - %?O?Open File&Save File? - %f"),
+			"NVfO")},
 	{
 		"dirbrowser-title-format",
-		ConfigData(_("%N %V - %?O?Open Directory&Save File? - %f"),
-			ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - %?O?Open Directory&Save File? - %f"),
+			"NVfO")},
 	{
 		"help-title-format",
-		ConfigData(_("%N %V - Help"), ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - Help"), "NV")},
 	{
 		"itemview-title-format",
-		ConfigData(_("%N %V - Article '%T' (%u unread, %t total)"),
-			ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - Article '%T' (%u unread, %t total)"),
+			"NVTFut")},
 	{
 		"searchresult-title-format",
-		ConfigData(_("%N %V - Search results for '%s' (%u unread, %t total)%?F? matching filter '%F'&?"),
-			ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - Search results for '%s' (%u unread, %t total)%?F? matching filter '%F'&?"),
+			"NVsTUutF")},
 	{
 		"selectfilter-title-format",
-		ConfigData(_("%N %V - Select Filter"),
-			ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - Select Filter"), "NV")},
 	{
 		"selecttag-title-format",
-		ConfigData(_("%N %V - Select Tag"),
-			ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - Select Tag"), "NV")},
 	{
 		"urlview-title-format",
-		ConfigData(_("%N %V - URLs"), ConfigDataType::STR)},
+		ConfigData::format_string(_("%N %V - URLs"), "NV")},
 	{
 		"wrap-scroll",
 		ConfigData("no", ConfigDataType::BOOL)},
@@ -412,13 +415,25 @@ void ConfigContainer::handle_action(std::string_view action,
 	break;
 
 	case ConfigDataType::STR:
-	case ConfigDataType::PATH:
-		if (cfgdata.multi_option()) {
-			cfgdata.set_value(utils::join(params, " "));
-		} else {
-			cfgdata.set_value(params[0]);
+	case ConfigDataType::PATH: {
+		const auto result = cfgdata.multi_option()
+			? cfgdata.set_value(utils::join(params, " "))
+			: cfgdata.set_value(params[0]);
+		if (!result) {
+			throw ConfigHandlerException(result.error());
+		}
+
+		const auto errors = cfgdata.format_errors();
+		if (!errors.empty()) {
+			const auto msg = strprintf::fmt(
+					_("Warning: invalid format string in `%s': %s"),
+					action,
+					utils::join(errors, "; "));
+			LOG(Level::USERERROR, msg);
+			std::cerr << msg << std::endl;
 		}
-		break;
+	}
+	break;
 	}
 }
 
@@ -434,6 +449,18 @@ std::string ConfigContainer::get_configvalue(const std::string& key) const
 	return {};
 }
 
+std::vector<std::string> ConfigContainer::get_format_errors(
+	const std::string& key) const
+{
+	std::lock_guard<std::recursive_mutex> guard(config_data_mtx);
+	auto it = config_data.find(key);
+	if (it != config_data.cend()) {
+		return it->second.format_errors();
+	}
+
+	return {};
+}
+
 int ConfigContainer::get_configvalue_as_int(const std::string& key) const
 {
 	std::lock_guard<std::recursive_mutex> guard(config_data_mtx);
@@ -648,4 +675,58 @@ ArticleSortStrategy ConfigContainer::get_article_sort_strategy() const
 	return ss;
 }
 
+namespace {
+
+rust::Vec<configoverrides::bridged::ConfigOverride> collect_overrides(
+	const std::vector<std::string>& settings,
+	const std::vector<std::pair<std::string, std::string>>& assignments)
+{
+	std::vector<rust::Str> names;
+	for (const auto& setting : settings) {
+		names.push_back(rust::Str(setting.data(), setting.size()));
+	}
+	std::vector<configoverrides::bridged::Assignment> rs_assignments;
+	for (const auto& assignment : assignments) {
+		rs_assignments.push_back({assignment.first, assignment.second});
+	}
+	return configoverrides::bridged::collect_overrides(
+			rust::Slice<const rust::Str>(names.data(), names.size()),
+			rust::Slice<const configoverrides::bridged::Assignment>(
+				rs_assignments.data(), rs_assignments.size()));
+}
+
+} // namespace
+
+nonstd::expected<void, std::string> ConfigContainer::apply_overrides(
+	const std::vector<std::pair<std::string, std::string>>& assignments)
+{
+	const auto overrides = collect_overrides(get_suggestions(""), assignments);
+	for (const auto& config_override : overrides) {
+		const auto description = std::string(config_override.description);
+		const auto result = set_configvalue(std::string(config_override.name),
+				std::string(config_override.value));
+		if (!result) {
+			return nonstd::make_unexpected(strprintf::fmt(
+						_("couldn't apply %s: %s"),
+						description,
+						result.error()));
+		}
+		LOG(Level::INFO,
+			"ConfigContainer::apply_overrides: %s",
+			description);
+	}
+	return {};
+}
+
+std::vector<std::string> ConfigContainer::describe_overrides(
+	const std::vector<std::pair<std::string, std::string>>& assignments) const
+{
+	std::vector<std::string> result;
+	for (const auto& config_override : collect_overrides(get_suggestions(""),
+			assignments)) {
+		result.push_back(std::string(config_override.description));
+	}
+	return result;
+}
+
 } // namespace newsboat
@@ -5,6 +5,7 @@
 #include <vector>
 
 #include "config.h"
+#include "fmtstrformatter.h"
 #include "strprintf.h"
 
 bool is_bool(const std::string& s)
@@ -41,6 +42,22 @@ ConfigData::ConfigData(const std::string& v,
 {
 }
 
+ConfigData ConfigData::format_string(const std::string& v,
+	const std::string& format_keys)
+{
+	ConfigData result(v, ConfigDataType::STR);
+	result.format_keys_ = format_keys;
+	return result;
+}
+
+std::vector<std::string> ConfigData::format_errors() const
+{
+	if (format_keys_.empty()) {
+		return {};
+	}
+	return FmtStrFormatter::validate(value_, format_keys_);
+}
+
 nonstd::expected<void, std::string> ConfigData::set_value(
 	std::string new_value)
 {
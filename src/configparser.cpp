@@ -1,6 +1,8 @@
 #include "configparser.h"
 
 #include <algorithm>
+#include <cinttypes>
+#include <glob.h>
 #include <pwd.h>
 #include <sys/types.h>
 
@@ -36,8 +38,35 @@ void ConfigParser::handle_action(std::string_view action,
 		const Filepath path = Filepath::from_locale_string(params[0]);
 		const Filepath tilde_expanded = utils::resolve_tilde(path);
 		const Filepath current_fpath = included_files.back();
-		if (!this->parse_file(utils::resolve_relative(current_fpath, tilde_expanded))) {
-			throw ConfigHandlerException(ActionHandlerStatus::FILENOTFOUND);
+		const Filepath resolved = utils::resolve_relative(current_fpath, tilde_expanded);
+
+		const std::string pattern = resolved.to_locale_string();
+		if (pattern.find_first_of("*?[") == std::string::npos) {
+			if (!this->parse_file(resolved)) {
+				throw ConfigHandlerException(ActionHandlerStatus::FILENOTFOUND);
+			}
+			return;
+		}
+
+		// A pattern that matches nothing is not an error: it's a common
+		// way to include an optional directory of files
+		std::vector<Filepath> matches;
+		glob_t exp;
+		if (glob(pattern.c_str(), 0, nullptr, &exp) == 0) {
+			for (std::size_t i = 0; i < exp.gl_pathc; ++i) {
+				matches.push_back(Filepath::from_locale_string(exp.gl_pathv[i]));
+			}
+		}
+		globfree(&exp);
+
+		LOG(Level::DEBUG,
+			"ConfigParser::handle_action: pattern %s matched %" PRIu64 " files",
+			pattern,
+			static_cast<std::uint64_t>(matches.size()));
+		for (const auto& match : matches) {
+			if (!this->parse_file(match)) {
+				throw ConfigHandlerException(ActionHandlerStatus::FILENOTFOUND);
+			}
 		}
 	} else {
 		throw ConfigHandlerException(ActionHandlerStatus::INVALID_COMMAND);
@@ -27,11 +27,69 @@ bool ConfigPaths::try_migrate_from_newsbeuter()
 	return newsboat::configpaths::bridged::try_migrate_from_newsbeuter(*rs_object);
 }
 
+nonstd::expected<std::vector<std::string>, std::string>
+ConfigPaths::migration_report() const
+{
+	rust::Vec<rust::String> report;
+	rust::String error_message;
+	if (!newsboat::configpaths::bridged::migration_report(*rs_object, report,
+			error_message)) {
+		return nonstd::make_unexpected(std::string(error_message));
+	}
+
+	std::vector<std::string> result;
+	for (const auto& line : report) {
+		result.push_back(std::string(line));
+	}
+	return result;
+}
+
+nonstd::expected<void, std::string> ConfigPaths::migrate() const
+{
+	rust::String error_message;
+	if (!newsboat::configpaths::bridged::migrate(*rs_object, error_message)) {
+		return nonstd::make_unexpected(std::string(error_message));
+	}
+	return {};
+}
+
+nonstd::expected<void, std::string> ConfigPaths::try_migrate() const
+{
+	rust::String error_message;
+	if (!newsboat::configpaths::bridged::try_migrate(*rs_object, error_message)) {
+		return nonstd::make_unexpected(std::string(error_message));
+	}
+	return {};
+}
+
 bool ConfigPaths::create_dirs() const
 {
 	return newsboat::configpaths::bridged::create_dirs(*rs_object);
 }
 
+std::optional<std::string> ConfigPaths::profile() const
+{
+	rust::String name;
+	if (newsboat::configpaths::bridged::profile(*rs_object, name)) {
+		return std::string(name);
+	}
+	return std::nullopt;
+}
+
+bool ConfigPaths::profile_exists() const
+{
+	return newsboat::configpaths::bridged::profile_exists(*rs_object);
+}
+
+std::vector<std::string> ConfigPaths::list_profiles() const
+{
+	std::vector<std::string> result;
+	for (const auto& name : newsboat::configpaths::bridged::list_profiles(*rs_object)) {
+		result.push_back(std::string(name));
+	}
+	return result;
+}
+
 void ConfigPaths::set_cache_file(const Filepath& new_cachefile)
 {
 	newsboat::configpaths::bridged::set_cache_file(*rs_object, new_cachefile);
@@ -65,6 +123,22 @@ Filepath ConfigPaths::lock_file() const
 	return path;
 }
 
+std::optional<Filepath> ConfigPaths::legacy_lock_file() const
+{
+	auto path = filepath::bridged::create_empty();
+	if (!newsboat::configpaths::bridged::legacy_lock_file(*rs_object, *path)) {
+		return std::nullopt;
+	}
+	return Filepath(std::move(path));
+}
+
+Filepath ConfigPaths::lock_dir() const
+{
+	auto path = filepath::bridged::create_empty();
+	newsboat::configpaths::bridged::lock_dir(*rs_object, *path);
+	return path;
+}
+
 Filepath ConfigPaths::queue_file() const
 {
 	auto path = filepath::bridged::create_empty();
@@ -86,4 +160,48 @@ Filepath ConfigPaths::cmdline_history_file() const
 	return path;
 }
 
+std::optional<Filepath> ConfigPaths::log_dir() const
+{
+	auto path = filepath::bridged::create_empty();
+	if (!newsboat::configpaths::bridged::log_dir(*rs_object, *path)) {
+		return std::nullopt;
+	}
+	return Filepath(std::move(path));
+}
+
+std::optional<Filepath> ConfigPaths::portable_dir() const
+{
+	auto path = filepath::bridged::create_empty();
+	if (!newsboat::configpaths::bridged::portable_dir(*rs_object, *path)) {
+		return std::nullopt;
+	}
+	return Filepath(std::move(path));
+}
+
+std::vector<Filepath> ConfigPaths::config_files() const
+{
+	std::vector<Filepath> result;
+	for (std::size_t i = 0; ; ++i) {
+		auto path = filepath::bridged::create_empty();
+		if (!newsboat::configpaths::bridged::config_files(*rs_object, i, *path)) {
+			break;
+		}
+		result.push_back(std::move(path));
+	}
+	return result;
+}
+
+std::vector<Filepath> ConfigPaths::url_files() const
+{
+	std::vector<Filepath> result;
+	for (std::size_t i = 0; ; ++i) {
+		auto path = filepath::bridged::create_empty();
+		if (!newsboat::configpaths::bridged::url_files(*rs_object, i, *path)) {
+			break;
+		}
+		result.push_back(std::move(path));
+	}
+	return result;
+}
+
 } // namespace newsboat
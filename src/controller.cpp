@@ -17,6 +17,7 @@
 #include <memory>
 #include <pwd.h>
 #include <signal.h>
+#include <sstream>
 #include <sys/stat.h>
 #include <sys/time.h>
 #include <sys/types.h>
@@ -41,6 +42,7 @@
 #include "inoreaderapi.h"
 #include "inoreaderurlreader.h"
 #include "itemrenderer.h"
+#include "libnewsboat-ffi/src/scriptoutput.rs.h"
 #include "logger.h"
 #include "minifluxapi.h"
 #include "minifluxurlreader.h"
@@ -79,6 +81,7 @@ void ignore_signal(int sig)
 Controller::Controller(ConfigPaths& configpaths)
 	: v(0)
 	, refresh_on_start(false)
+	, read_only(false)
 	, configpaths(configpaths)
 	, queueManager(&cfg, configpaths.queue_file())
 {
@@ -107,7 +110,12 @@ int Controller::run(const CliArgsParser& args)
 		const std::string date_time_string = utils::mt_strf_localtime("%Y-%m-%d_%H.%M.%S",
 				std::time(nullptr));
 		const std::string filename = "newsboat_" + date_time_string + ".log";
-		const auto filepath = Filepath::from_locale_string(filename);
+		auto filepath = Filepath::from_locale_string(filename);
+		if (const auto log_dir = configpaths.log_dir()) {
+			if (utils::mkdir_parents(log_dir.value(), 0700) == 0) {
+				filepath = log_dir.value().join(filepath);
+			}
+		}
 		logger::set_logfile(filepath);
 	}
 
@@ -119,6 +127,37 @@ int Controller::run(const CliArgsParser& args)
 		return args.return_code().value();
 	}
 
+	if (args.list_profiles()) {
+		for (const auto& name : configpaths.list_profiles()) {
+			std::cout << name << std::endl;
+		}
+		return EXIT_SUCCESS;
+	}
+
+	if (const auto profile = configpaths.profile()) {
+		if (args.create_profile()) {
+			if (!configpaths.create_dirs()) {
+				return EXIT_FAILURE;
+			}
+			std::cout << strprintf::fmt(
+					_("Created profile `%s'. Put its feeds into `%s' "
+						"and its settings into `%s'."),
+					profile.value(),
+					configpaths.url_file(),
+					configpaths.config_file())
+				<< std::endl;
+			return EXIT_SUCCESS;
+		} else if (!configpaths.profile_exists()) {
+			std::cerr << strprintf::fmt(
+					_("Error: profile `%s' doesn't exist. Use "
+						"--create-profile=%s to create it."),
+					profile.value(),
+					profile.value())
+				<< std::endl;
+			return EXIT_FAILURE;
+		}
+	}
+
 	const auto migrated = configpaths.try_migrate_from_newsbeuter();
 	if (migrated) {
 		std::cerr << "\nPlease check the results and press Enter to "
@@ -132,7 +171,7 @@ int Controller::run(const CliArgsParser& args)
 
 	if (args.do_import()) {
 		LOG(Level::INFO, "Importing OPML file from %s", args.importfile());
-		return import_opml(args.importfile(), configpaths.url_file());
+		return import_opml(args.importfile(), configpaths.url_files());
 	}
 
 	LOG(Level::INFO, "nl_langinfo(CODESET): %s", nl_langinfo(CODESET));
@@ -171,7 +210,9 @@ int Controller::run(const CliArgsParser& args)
 
 	try {
 		cfgparser.parse_file(Filepath::from_locale_string("/etc/" PACKAGE "/config"));
-		cfgparser.parse_file(configpaths.config_file());
+		for (const auto& config_file : configpaths.config_files()) {
+			cfgparser.parse_file(config_file);
+		}
 	} catch (const ConfigException& ex) {
 		LOG(Level::ERROR,
 			"an exception occurred while parsing the configuration "
@@ -181,6 +222,28 @@ int Controller::run(const CliArgsParser& args)
 		return EXIT_FAILURE;
 	}
 
+	const auto overrides_applied = cfg.apply_overrides(args.config_overrides());
+	if (!overrides_applied) {
+		std::cerr << _("Error: ") << overrides_applied.error() << std::endl;
+		return EXIT_FAILURE;
+	}
+
+	const auto keymap_issues = keys.lint_bindings();
+	for (const auto& issue : keymap_issues) {
+		LOG(Level::WARN, "Controller::run: %s", issue);
+	}
+	if (args.lint_keymap()) {
+		for (const auto& issue : keymap_issues) {
+			std::cout << issue << std::endl;
+		}
+		return keymap_issues.empty() ? EXIT_SUCCESS : EXIT_FAILURE;
+	}
+
+	if (const auto format = args.export_keymap()) {
+		std::cout << keys.render_reference(format.value());
+		return EXIT_SUCCESS;
+	}
+
 	update_config();
 
 	if (!args.silent()) {
@@ -193,9 +256,62 @@ int Controller::run(const CliArgsParser& args)
 		configpaths.set_cache_file(cachefilepath);
 	}
 
+	if (const auto command = args.remote_command()) {
+		const auto socket = ControlSocket::socket_path(configpaths.lock_file());
+		const auto reply = ControlSocket::send(socket, command.value());
+		if (!reply) {
+			std::cerr << _("Error: ") << reply.error() << std::endl;
+			return EXIT_FAILURE;
+		}
+		if (!reply.value().empty()) {
+			std::cout << reply.value() << std::endl;
+		}
+		return EXIT_SUCCESS;
+	}
+
+	if (args.force_unlock()) {
+		const auto removed = FsLock::force_unlock(configpaths.lock_file());
+		if (!removed) {
+			std::cerr << _("Error: ") << removed.error() << std::endl;
+			return EXIT_FAILURE;
+		}
+		if (removed.value() && !args.silent()) {
+			std::cout << strprintf::fmt(_("Removed stale lock file `%s'."),
+					configpaths.lock_file())
+				<< std::endl;
+		}
+	}
+
+	// Older versions keep the lock next to the cache, so they don't notice
+	// ours. We don't take their lock (that would bring the lock file back
+	// into the data directory), but we don't run alongside them either.
+	if (const auto legacy_lock_file = configpaths.legacy_lock_file()) {
+		if (const auto holder = FsLock::lock_holder(legacy_lock_file.value())) {
+			std::cerr << strprintf::fmt(
+					_("Error: an older version of %s is already running "
+						"(PID: %s)"),
+					PROGRAM_NAME,
+					std::to_string(holder.value()))
+				<< std::endl;
+			return EXIT_FAILURE;
+		}
+	}
+
 	pid_t pid;
 	std::string error;
-	if (!fslock.try_lock(configpaths.lock_file(), pid, error)) {
+	bool locked = false;
+	if (const auto wait_lock = args.wait_lock()) {
+		locked = fslock.lock_with_timeout(configpaths.lock_file(), pid,
+				std::chrono::seconds(wait_lock.value()), error);
+	} else if (args.can_run_read_only()) {
+		// Without the lock, we only read the cache, and rely on SQLite's
+		// locking to not see half-written changes of the running instance
+		locked = fslock.try_lock_or_go_unlocked(configpaths.lock_file(), pid,
+				read_only, error);
+	} else {
+		locked = fslock.try_lock(configpaths.lock_file(), pid, error);
+	}
+	if (!locked) {
 		if (pid != 0) {
 			std::cerr << strprintf::fmt(
 					_("Error: an instance of %s is "
@@ -209,12 +325,49 @@ int Controller::run(const CliArgsParser& args)
 		return EXIT_FAILURE;
 	}
 
+	if (read_only) {
+		LOG(Level::INFO,
+			"Controller::run: an instance with PID %d holds the lock, running "
+			"read-only",
+			pid);
+		if (!args.silent()) {
+			std::cout << strprintf::fmt(
+					_("Another instance of %s is running (PID: %s), so "
+						"this one is read-only."),
+					PROGRAM_NAME,
+					std::to_string(pid))
+				<< std::endl;
+		}
+		refresh_on_start = false;
+	}
+
+	if (args.do_migrate()) {
+		return run_migrations(args.dry_run());
+	}
+
+	if (!args.dry_run() && !read_only) {
+		const auto migrated = configpaths.try_migrate();
+		if (!migrated) {
+			std::cerr << strprintf::fmt(
+					_("Error: migrating Newsboat's files failed: %s"),
+					migrated.error())
+				<< std::endl;
+			return EXIT_FAILURE;
+		}
+	}
+
 	if (!args.silent()) {
 		std::cout << _("Opening cache...");
 		std::cout.flush();
 	}
 	try {
-		rsscache = std::make_unique<Cache>(configpaths.cache_file(), cfg);
+		CacheMode mode = CacheMode::ReadWrite;
+		if (read_only) {
+			mode = CacheMode::ReadOnly;
+		} else if (args.dry_run()) {
+			mode = CacheMode::DryRun;
+		}
+		rsscache = std::make_unique<Cache>(configpaths.cache_file(), cfg, mode);
 	} catch (const DbException& e) {
 		std::cerr << strprintf::fmt(
 				_("Error: opening the cache file `%s' "
@@ -241,7 +394,9 @@ int Controller::run(const CliArgsParser& args)
 
 	std::string type = cfg.get_configvalue("urls-source");
 	if (type == "local") {
-		urlcfg = std::make_unique<FileUrlReader>(configpaths.url_file());
+		const auto url_files = configpaths.url_files();
+		urlcfg = std::make_unique<FileUrlReader>(url_files.front(),
+				std::vector<Filepath>(url_files.begin() + 1, url_files.end()));
 	} else if (type == "opml") {
 		urlcfg = std::make_unique<OpmlUrlReader>(cfg, configpaths.url_file());
 	} else if (type == "oldreader") {
@@ -440,6 +595,13 @@ int Controller::run(const CliArgsParser& args)
 	if (args.do_vacuum()) {
 		std::cout << _("Opening cache...");
 		std::cout << _("done.") << std::endl;
+		if (args.dry_run()) {
+			report_cache_limits();
+			const std::uint64_t space = rsscache->reclaimable_space();
+			std::cout << strprintf::fmt(_("Vacuuming would reclaim at least %" PRIu64
+						" bytes."), space) << std::endl;
+			return EXIT_SUCCESS;
+		}
 		std::cout << _("Cleaning up cache thoroughly...");
 		std::cout.flush();
 		rsscache->do_vacuum();
@@ -486,6 +648,23 @@ int Controller::run(const CliArgsParser& args)
 		std::cout << _("done.") << std::endl;
 	}
 
+	if (args.do_cleanup() && args.dry_run()) {
+		const auto summary = rsscache->summarize_cleanup(
+				feedcontainer.get_all_feeds());
+		report_cache_limits();
+		std::cout << strprintf::fmt(_("Cleaning up would delete %u feeds that "
+					"are no longer in the urls file, with %u articles."),
+				summary.feeds,
+				summary.articles) << std::endl;
+		if (cfg.get_configvalue_as_bool("delete-read-articles-on-quit")) {
+			std::cout << strprintf::fmt(_("Cleaning up would delete %u read "
+						"articles (because delete-read-articles-on-quit is "
+						"enabled)."),
+					summary.read_articles) << std::endl;
+		}
+		return EXIT_SUCCESS;
+	}
+
 	if (args.do_cleanup()) {
 		std::cout << _("Cleaning up cache...");
 		std::cout.flush();
@@ -511,6 +690,10 @@ int Controller::run(const CliArgsParser& args)
 
 	feedcontainer.sort_feeds(cfg.get_feed_sort_strategy());
 
+	if (args.subcommand().has_value()) {
+		return run_subcommand(args);
+	}
+
 	if (args.do_export()) {
 		export_opml(args.export_as_opml2());
 		return EXIT_SUCCESS;
@@ -522,7 +705,14 @@ int Controller::run(const CliArgsParser& args)
 			args.readinfo_import_file().value());
 		std::cout << _("Importing list of read articles...");
 		std::cout.flush();
-		import_read_information(args.readinfo_import_file().value());
+		const auto result = import_read_information(
+				args.readinfo_import_file().value());
+		if (!result) {
+			std::cout << std::endl;
+			std::cerr << strprintf::fmt(_("Error: failed to import read information: %s"),
+					result.error()) << std::endl;
+			return EXIT_FAILURE;
+		}
 		std::cout << _("done.") << std::endl;
 		return EXIT_SUCCESS;
 	}
@@ -533,7 +723,7 @@ int Controller::run(const CliArgsParser& args)
 			args.readinfo_export_file().value());
 		std::cout << _("Exporting list of read articles...");
 		std::cout.flush();
-		export_read_information(args.readinfo_export_file().value());
+		export_read_information(args.readinfo_export_file().value(), args);
 		std::cout << _("done.") << std::endl;
 		return EXIT_SUCCESS;
 	}
@@ -546,13 +736,16 @@ int Controller::run(const CliArgsParser& args)
 
 	const auto cmds_to_execute = args.cmds_to_execute();
 	if (cmds_to_execute.size() >= 1) {
-		execute_commands(cmds_to_execute);
+		execute_commands(cmds_to_execute, args.dry_run());
+		if (args.dry_run()) {
+			report_cache_limits();
+		}
 		return EXIT_SUCCESS;
 	}
 
 	// if the user wants to refresh on startup via configuration file, then
 	// do so, but only if -r hasn't been supplied.
-	if (!refresh_on_start &&
+	if (!refresh_on_start && !read_only &&
 		cfg.get_configvalue_as_bool("refresh-on-startup")) {
 		refresh_on_start = true;
 	}
@@ -560,8 +753,28 @@ int Controller::run(const CliArgsParser& args)
 	FormAction::load_histories(
 		configpaths.search_history_file(), configpaths.cmdline_history_file());
 
+	// The instance that holds the lock is the one that other programs control
+	if (!read_only) {
+		const auto socket = ControlSocket::socket_path(configpaths.lock_file());
+		const auto started = control_socket.start(socket,
+		[this](const RemoteCommand& command) {
+			return handle_remote_command(command);
+		});
+		if (started) {
+			v->enable_remote_requests(control_socket);
+		} else {
+			LOG(Level::ERROR, "Controller::run: %s", started.error());
+		}
+	}
+
 	// run the View
 	int ret = v->run();
+	control_socket.stop();
+
+	// The instance that holds the lock owns the history files and the cache
+	if (read_only) {
+		return ret;
+	}
 
 	unsigned int history_limit =
 		cfg.get_configvalue_as_int("history-limit");
@@ -628,7 +841,7 @@ void Controller::mark_all_read(const std::string& feedurl)
 	}
 
 	if (feedurl.empty()) { // Mark all feeds as read
-		if (api) {
+		if (api && !read_only) {
 			for (const auto& feed : feedcontainer.get_all_feeds()) {
 				api->mark_all_read(feed->rssurl());
 			}
@@ -640,7 +853,7 @@ void Controller::mark_all_read(const std::string& feedurl)
 			return;
 		}
 
-		if (api) {
+		if (api && !read_only) {
 			api->mark_all_read(feed->rssurl());
 		}
 
@@ -650,7 +863,7 @@ void Controller::mark_all_read(const std::string& feedurl)
 
 void Controller::mark_article_read(const std::string& guid, bool read)
 {
-	if (api) {
+	if (api && !read_only) {
 		api->mark_article_read(guid, read);
 	}
 }
@@ -664,7 +877,7 @@ void Controller::mark_all_read(unsigned int pos)
 	}
 
 	if (feed->is_query_feed()) {
-		if (api) {
+		if (api && !read_only) {
 			std::vector<std::string> item_guids;
 			for (const auto& item : feed->items()) {
 				if (item->unread()) {
@@ -676,7 +889,7 @@ void Controller::mark_all_read(unsigned int pos)
 		rsscache->mark_all_read(*feed);
 	} else {
 		rsscache->mark_all_read(feed->rssurl());
-		if (api) {
+		if (api && !read_only) {
 			api->mark_all_read(feed->rssurl());
 		}
 	}
@@ -690,7 +903,7 @@ void Controller::mark_all_read(unsigned int pos)
 void Controller::mark_all_read(const std::vector<std::string>& item_guids)
 {
 	ScopeMeasure m("Controller::mark_all_read");
-	if (api) {
+	if (api && !read_only) {
 		api->mark_articles_read(item_guids);
 	}
 }
@@ -745,9 +958,13 @@ void Controller::replace_feed(RssFeed& oldfeed, RssFeed& newfeed, unsigned int p
 }
 
 int Controller::import_opml(const Filepath& opmlFile,
-	const Filepath& urlFile)
+	const std::vector<Filepath>& urlFiles)
 {
-	FileUrlReader urlReader(urlFile);
+	// Fragments are loaded so that feeds which are already in them aren't
+	// added to the urls file once more
+	const Filepath& urlFile = urlFiles.front();
+	FileUrlReader urlReader(urlFile,
+		std::vector<Filepath>(urlFiles.begin() + 1, urlFiles.end()));
 	const auto error_message = urlReader.reload(); // Load existing URLs
 	if (error_message.has_value()) {
 		std::cerr << strprintf::fmt(
@@ -773,6 +990,35 @@ int Controller::import_opml(const Filepath& opmlFile,
 	}
 }
 
+int Controller::run_migrations(bool dry_run)
+{
+	const auto report = configpaths.migration_report();
+	if (!report) {
+		std::cerr << strprintf::fmt(
+				_("Error: migrating Newsboat's files failed: %s"),
+				report.error())
+			<< std::endl;
+		return EXIT_FAILURE;
+	}
+	for (const auto& line : report.value()) {
+		std::cout << line << std::endl;
+	}
+
+	if (dry_run) {
+		return EXIT_SUCCESS;
+	}
+
+	const auto migrated = configpaths.migrate();
+	if (!migrated) {
+		std::cerr << strprintf::fmt(
+				_("Error: migrating Newsboat's files failed: %s"),
+				migrated.error())
+			<< std::endl;
+		return EXIT_FAILURE;
+	}
+	return EXIT_SUCCESS;
+}
+
 void Controller::export_opml(bool version2)
 {
 	xmlDocPtr root = opml::generate(feedcontainer, version2);
@@ -902,7 +1148,176 @@ Filepath Controller::get_urls_file()
 	return configpaths.url_file();
 }
 
-int Controller::execute_commands(const std::vector<std::string>& cmds)
+Filepath Controller::get_config_file()
+{
+	return configpaths.config_file();
+}
+
+nonstd::expected<std::string, std::string> Controller::handle_remote_command(
+	const RemoteCommand& command)
+{
+	using controlsocket::bridged::CommandKind;
+
+	if (command.kind == CommandKind::Open) {
+		const std::string& guid = command.argument.value();
+		for (const auto& feed : feedcontainer.get_all_feeds()) {
+			if (!feed->is_query_feed() && feed->has_item(guid)) {
+				v->open_article(feed, guid);
+				return "";
+			}
+		}
+		return nonstd::make_unexpected(strprintf::fmt(
+					_("no article with GUID `%s'"), guid));
+	}
+
+	// Other commands take an optional feed URL
+	std::optional<unsigned int> feed_pos;
+	std::shared_ptr<RssFeed> feed;
+	if (command.argument.has_value()) {
+		const auto feeds = feedcontainer.get_all_feeds();
+		for (unsigned int i = 0; i < feeds.size(); ++i) {
+			if (feeds[i]->rssurl() == command.argument.value()) {
+				feed_pos = i;
+				feed = feeds[i];
+				break;
+			}
+		}
+		if (!feed) {
+			return nonstd::make_unexpected(strprintf::fmt(
+						_("no feed with URL `%s'"), command.argument.value()));
+		}
+	}
+
+	switch (command.kind) {
+	case CommandKind::Reload:
+		if (feed_pos.has_value()) {
+			reloader->start_reload_all_thread({feed_pos.value()});
+		} else {
+			reloader->start_reload_all_thread();
+		}
+		return "";
+	case CommandKind::MarkRead:
+		mark_all_read(feed ? feed->rssurl() : "");
+		v->force_redraw();
+		return "";
+	case CommandKind::UnreadCount:
+		return std::to_string(feed
+				? feed->unread_item_count()
+				: feedcontainer.unread_item_count());
+	default:
+		return nonstd::make_unexpected(std::string(_("unsupported command")));
+	}
+}
+
+int Controller::run_subcommand(const CliArgsParser& args)
+{
+	using cliargsparser::bridged::SubcommandKind;
+
+	const auto subcommand = args.subcommand().value();
+
+	std::vector<std::shared_ptr<RssFeed>> feeds;
+	for (const auto& feed : feedcontainer.get_all_feeds()) {
+		// Query feeds only contain items from other feeds
+		if (!feed->is_query_feed()) {
+			feeds.push_back(feed);
+		}
+	}
+
+	switch (subcommand.kind) {
+	case SubcommandKind::ListFeeds: {
+		std::vector<scriptoutput::bridged::FeedRecord> records;
+		for (const auto& feed : feeds) {
+			scriptoutput::bridged::FeedRecord record;
+			record.url = feed->rssurl();
+			record.title = feed->title();
+			record.unread_count = feed->unread_item_count();
+			record.total_count = feed->total_item_count();
+			records.push_back(std::move(record));
+		}
+		std::cout << std::string(scriptoutput::bridged::format_feeds(
+					rust::Slice<const scriptoutput::bridged::FeedRecord>(records.data(), records.size()),
+					subcommand.format));
+		return EXIT_SUCCESS;
+	}
+
+	case SubcommandKind::ListItems: {
+		const std::string feedurl(subcommand.feed);
+		if (subcommand.has_feed && !feedcontainer.get_feed_by_url(feedurl)) {
+			std::cerr << strprintf::fmt(_("Error: no feed with URL `%s'"), feedurl) << std::endl;
+			return EXIT_FAILURE;
+		}
+
+		std::vector<scriptoutput::bridged::ItemRecord> records;
+		for (const auto& feed : feeds) {
+			if (subcommand.has_feed && feed->rssurl() != feedurl) {
+				continue;
+			}
+			std::lock_guard<std::mutex> lock(feed->item_mutex);
+			for (const auto& item : feed->items()) {
+				if (subcommand.unread_only && !item->unread()) {
+					continue;
+				}
+				scriptoutput::bridged::ItemRecord record;
+				record.guid = item->guid();
+				record.feed_url = item->feedurl();
+				record.title = item->title();
+				record.link = item->link();
+				record.author = item->author();
+				record.pub_date = item->pubDate_timestamp();
+				record.unread = item->unread();
+				records.push_back(std::move(record));
+			}
+		}
+		std::cout << std::string(scriptoutput::bridged::format_items(
+					rust::Slice<const scriptoutput::bridged::ItemRecord>(records.data(), records.size()),
+					subcommand.format));
+		return EXIT_SUCCESS;
+	}
+
+	case SubcommandKind::MarkItemsRead: {
+		std::unordered_set<std::string> known_guids;
+		for (const auto& feed : feeds) {
+			std::lock_guard<std::mutex> lock(feed->item_mutex);
+			for (const auto& item : feed->items()) {
+				known_guids.insert(item->guid());
+			}
+		}
+
+		std::vector<std::string> guids;
+		std::vector<std::string> unknown_guids;
+		std::vector<scriptoutput::bridged::MarkReadRecord> records;
+		for (const auto& guid : subcommand.guids) {
+			guids.emplace_back(guid);
+			scriptoutput::bridged::MarkReadRecord record;
+			record.guid = guid;
+			record.found = known_guids.count(guids.back()) > 0;
+			if (!record.found) {
+				unknown_guids.push_back(guids.back());
+			}
+			records.push_back(std::move(record));
+		}
+		rsscache->mark_items_read_by_guid(guids);
+
+		std::cout << std::string(scriptoutput::bridged::format_mark_read(
+					rust::Slice<const scriptoutput::bridged::MarkReadRecord>(records.data(), records.size()),
+					subcommand.format));
+
+		if (!unknown_guids.empty()) {
+			std::cout << std::flush;
+			for (const auto& guid : unknown_guids) {
+				std::cerr << strprintf::fmt(_("Error: no item with GUID `%s'"), guid) << std::endl;
+			}
+			return EXIT_FAILURE;
+		}
+		return EXIT_SUCCESS;
+	}
+	}
+
+	return EXIT_FAILURE;
+}
+
+int Controller::execute_commands(const std::vector<std::string>& cmds,
+	bool dry_run)
 {
 	if (v->formaction_stack_size() > 0) {
 		v->pop_current_formaction();
@@ -911,7 +1326,9 @@ int Controller::execute_commands(const std::vector<std::string>& cmds)
 		LOG(Level::DEBUG,
 			"Controller::execute_commands: executing `%s'",
 			cmd);
-		if (cmd == "reload") {
+		if (cmd == "reload" && dry_run) {
+			report_reload_plan();
+		} else if (cmd == "reload") {
 			reloader->reload_all(true);
 		} else if (cmd == "print-unread") {
 			std::cout << strprintf::fmt(_("%u unread articles"),
@@ -929,6 +1346,35 @@ int Controller::execute_commands(const std::vector<std::string>& cmds)
 	return EXIT_SUCCESS;
 }
 
+void Controller::report_reload_plan()
+{
+	std::vector<std::string> urls;
+	for (const auto& feed : feedcontainer.get_all_feeds()) {
+		// Query feeds are filled from the cache, not fetched
+		if (!feed->is_query_feed()) {
+			urls.push_back(feed->rssurl());
+		}
+	}
+
+	// Workaround for missing overload of strprintf::fmt for size_type on macOS.
+	std::uint64_t num_feeds = urls.size();
+	std::cout << strprintf::fmt(_("Reloading would fetch %" PRIu64 " feeds:"),
+			num_feeds) << std::endl;
+	for (const auto& url : urls) {
+		std::cout << "  " << utils::censor_url(url) << std::endl;
+	}
+}
+
+void Controller::report_cache_limits()
+{
+	std::cout << strprintf::fmt(_("%u articles older than keep-articles-days "
+				"would be deleted."),
+			rsscache->expired_articles_count()) << std::endl;
+	std::cout << strprintf::fmt(_("%u articles exceeding max-items would be "
+				"deleted."),
+			rsscache->excess_articles_count()) << std::endl;
+}
+
 Filepath Controller::write_temporary_item(RssItem& item)
 {
 	Filepath filename_template;
@@ -971,32 +1417,46 @@ void Controller::write_item(RssItem& item, std::ostream& ostr)
 	ostr << item_renderer::to_plain_text(cfg, item) << std::endl;
 }
 
-void Controller::import_read_information(const Filepath& readinfofile)
+nonstd::expected<void, std::string> Controller::import_read_information(
+	const Filepath& readinfofile)
 {
-	std::vector<std::string> guids;
-
 	std::ifstream f(readinfofile.to_locale_string());
-	std::string line;
-	getline(f, line);
 	if (!f.is_open()) {
-		return;
+		return {};
 	}
-	while (f.is_open() && !f.eof()) {
-		guids.push_back(line);
-		getline(f, line);
+	std::stringstream input;
+	input << f.rdbuf();
+
+	const auto local = rsscache->get_article_states();
+	rust::Vec<readstate::bridged::ArticleState> changed;
+	rust::String error_message;
+	const bool merged = readstate::bridged::merge_read_state(
+			rust::Slice<const readstate::bridged::ArticleState>(local.data(), local.size()),
+			input.str(),
+			std::time(nullptr),
+			changed,
+			error_message);
+	if (!merged) {
+		return nonstd::make_unexpected(std::string(error_message));
 	}
-	rsscache->mark_items_read_by_guid(guids);
+
+	LOG(Level::INFO,
+		"Controller::import_read_information: state of %" PRIu64 " articles changed",
+		static_cast<uint64_t>(changed.size()));
+	rsscache->update_article_states(changed);
+	return {};
 }
 
-void Controller::export_read_information(const Filepath& readinfofile)
+void Controller::export_read_information(const Filepath& readinfofile,
+	const CliArgsParser& args)
 {
-	std::vector<std::string> guids = rsscache->get_read_item_guids();
+	const auto articles = rsscache->get_article_states();
 
 	std::fstream f(readinfofile.to_locale_string(), std::fstream::out);
 	if (f.is_open()) {
-		for (const auto& guid : guids) {
-			f << guid << std::endl;
-		}
+		f << std::string(readstate::bridged::export_read_state(
+					rust::Slice<const readstate::bridged::ArticleState>(articles.data(), articles.size()),
+					args.get_rust_ref()));
 	}
 }
 
@@ -1048,7 +1508,7 @@ void Controller::dump_config(const Filepath& filename) const
 
 void Controller::update_flags(std::shared_ptr<RssItem> item)
 {
-	if (api) {
+	if (api && !read_only) {
 		api->update_article_flags(
 			item->oldflags(), item->flags(), item->guid());
 	}
@@ -0,0 +1,168 @@
+#include "controlsocket.h"
+
+#include <cerrno>
+#include <chrono>
+#include <cstring>
+#include <fcntl.h>
+#include <unistd.h>
+
+#include "config.h"
+#include "logger.h"
+#include "strprintf.h"
+
+namespace newsboat {
+
+namespace {
+
+/// How often the thread checks if it should stop.
+const std::uint64_t POLL_INTERVAL_MS = 200;
+
+} // namespace
+
+ControlSocket::ControlSocket()
+	: rs_object(controlsocket::bridged::create())
+	, stopping(false)
+	, wakeup_pipe{-1, -1}
+{
+}
+
+ControlSocket::~ControlSocket()
+{
+	stop();
+}
+
+nonstd::expected<void, std::string> ControlSocket::start(const Filepath& path,
+	Handler handler)
+{
+	if (::pipe(wakeup_pipe) != 0) {
+		return nonstd::make_unexpected(strprintf::fmt(
+					_("Failed to create control socket '%s': %s"),
+					path,
+					std::strerror(errno)));
+	}
+	for (const int fd : wakeup_pipe) {
+		::fcntl(fd, F_SETFL, O_NONBLOCK);
+		::fcntl(fd, F_SETFD, FD_CLOEXEC);
+	}
+
+	rust::String error_message;
+	if (!controlsocket::bridged::bind(*rs_object, path, error_message)) {
+		stop();
+		return nonstd::make_unexpected(std::string(error_message));
+	}
+
+	this->handler = std::move(handler);
+	stopping = false;
+	thread = std::thread(&ControlSocket::run, this);
+	return {};
+}
+
+void ControlSocket::stop()
+{
+	if (thread.joinable()) {
+		stopping = true;
+		thread.join();
+	}
+	// Dropping the Rust object closes the socket and removes its file.
+	rs_object = controlsocket::bridged::create();
+
+	requests.clear();
+	for (int& fd : wakeup_pipe) {
+		if (fd != -1) {
+			::close(fd);
+			fd = -1;
+		}
+	}
+}
+
+int ControlSocket::wakeup_fd() const
+{
+	return wakeup_pipe[0];
+}
+
+void ControlSocket::handle_requests()
+{
+	if (wakeup_pipe[0] == -1) {
+		return;
+	}
+
+	// Drain the pipe first: a command queued after that writes to it again,
+	// so the owner wakes up for it even if it isn't handled below.
+	char buffer[64];
+	while (::read(wakeup_pipe[0], buffer, sizeof(buffer)) > 0) {
+	}
+
+	std::deque<Request> pending;
+	{
+		std::lock_guard<std::mutex> lock(requests_mtx);
+		std::swap(pending, requests);
+	}
+	for (auto& request : pending) {
+		request.reply.set_value(handler(request.command));
+	}
+}
+
+void ControlSocket::run()
+{
+	while (!stopping) {
+		controlsocket::bridged::Command command;
+		if (!controlsocket::bridged::next_command(*rs_object, POLL_INTERVAL_MS,
+				command)) {
+			continue;
+		}
+
+		Request request{RemoteCommand{command.kind, std::nullopt}, {}};
+		if (command.has_argument) {
+			request.command.argument = std::string(command.argument);
+		}
+		auto future_reply = request.reply.get_future();
+		{
+			std::lock_guard<std::mutex> lock(requests_mtx);
+			requests.push_back(std::move(request));
+		}
+		const char wakeup = 0;
+		if (::write(wakeup_pipe[1], &wakeup, 1) == -1 && errno != EAGAIN) {
+			LOG(Level::ERROR, "ControlSocket::run: couldn't wake up the owner: %s",
+				std::strerror(errno));
+		}
+
+		// The owner might quit without handling the command, so keep checking
+		// if we should stop, too.
+		const auto interval = std::chrono::milliseconds(POLL_INTERVAL_MS);
+		while (future_reply.wait_for(interval) != std::future_status::ready) {
+			if (stopping) {
+				controlsocket::bridged::reply(*rs_object, false,
+					_("Newsboat is shutting down"));
+				return;
+			}
+		}
+
+		const auto result = future_reply.get();
+		if (result.has_value()) {
+			controlsocket::bridged::reply(*rs_object, true, result.value());
+		} else {
+			LOG(Level::INFO, "ControlSocket::run: command failed: %s",
+				result.error());
+			controlsocket::bridged::reply(*rs_object, false, result.error());
+		}
+	}
+}
+
+Filepath ControlSocket::socket_path(const Filepath& lock_file)
+{
+	auto output = filepath::bridged::create_empty();
+	controlsocket::bridged::socket_path(lock_file, *output);
+	return output;
+}
+
+nonstd::expected<std::string, std::string> ControlSocket::send(
+	const Filepath& path, const std::string& command)
+{
+	rust::String reply;
+	if (!controlsocket::bridged::send_command(path, command, reply)) {
+		return nonstd::make_unexpected(std::string(reply));
+	}
+	return std::string(reply);
+}
+
+} // namespace newsboat
@@ -1126,6 +1126,22 @@ std::string FeedListFormAction::title()
 			static_cast<unsigned int>(visible_feeds.size()));
 }
 
+std::map<std::string, std::string> FeedListFormAction::get_variables()
+{
+	const unsigned int pos = list.get_position();
+	if (pos >= visible_feeds.size()) {
+		return {};
+	}
+	const auto& feed = visible_feeds[pos].first;
+	return {
+		{"title", feed->title()},
+		{"link", feed->link()},
+		{"url", feed->rssurl()},
+		{"unread", std::to_string(feed->unread_item_count())},
+		{"total", std::to_string(feed->total_item_count())},
+	};
+}
+
 void FeedListFormAction::apply_filter(const std::string& filtertext)
 {
 	if (filtertext.empty()) {
@@ -15,8 +15,10 @@
 
 namespace newsboat {
 
-FileUrlReader::FileUrlReader(const Filepath& file)
+FileUrlReader::FileUrlReader(const Filepath& file,
+	const std::vector<Filepath>& fragments)
 	: filename(file)
+	, fragments(fragments)
 {
 }
 
@@ -37,7 +39,24 @@ std::optional<utils::ReadTextFileError> FileUrlReader::reload()
 	urls.clear();
 	tags.clear();
 
-	auto result = utils::read_text_file(filename);
+	const auto main_error = load_file(filename);
+	if (main_error.has_value()) {
+		return main_error;
+	}
+	for (const auto& fragment : fragments) {
+		const auto fragment_error = load_file(fragment);
+		if (fragment_error.has_value()) {
+			return fragment_error;
+		}
+	}
+
+	return {};
+}
+
+std::optional<utils::ReadTextFileError> FileUrlReader::load_file(
+	const Filepath& file)
+{
+	auto result = utils::read_text_file(file);
 	if (!result) {
 		return result.error();
 	}
@@ -63,15 +82,15 @@ std::optional<utils::ReadTextFileError> FileUrlReader::reload()
 			return u.first == url;
 		});
 		if (it == urls.end()) {
-			urls.push_back({url, FeedOrigin{FileOrigin{line_number}}});
+			urls.push_back({url, FeedOrigin{FileOrigin{line_number, file}}});
 			tokens.erase(tokens.begin());
 			if (!tokens.empty()) {
 				tags[url] = tokens;
 			}
 		} else {
 			std::string warn_msg = strprintf::fmt(
-					_("Warning: Duplicate URL found: %s. Merging tags."),
-					url);
+					_("Warning: Duplicate URL found in %s line %u: %s. Merging tags."),
+					file, line_number, url);
 
 			LOG(Level::USERERROR, warn_msg.c_str());
 			std::cerr << warn_msg << std::endl;
@@ -103,6 +122,9 @@ std::optional<std::string> FileUrlReader::write_config()
 
 	std::size_t line_number = 0;
 	for (auto& [url, origin] : urls) {
+		if (is_from_fragment(origin)) {
+			continue;
+		}
 		line_number++;
 		f << utils::quote_if_necessary(url);
 		if (tags[url].size() > 0) {
@@ -114,10 +136,15 @@ std::optional<std::string> FileUrlReader::write_config()
 
 		// Update origin as writing to urls file might remove comments and empty lines,
 		// resulting in URLs ending up at different line numbers
-		origin = FeedOrigin{FileOrigin{line_number}};
+		origin = FeedOrigin{FileOrigin{line_number, filename}};
 	}
 
 	return {};
 }
 
+bool FileUrlReader::is_from_fragment(const FeedOrigin& origin) const
+{
+	return origin.file_origin.has_value() && origin.file_origin->file != filename;
+}
+
 }
@@ -12,6 +12,11 @@ void FmtStrFormatter::register_fmt(char f, const std::string& value)
 	fmtstrformatter::bridged::register_fmt(*rs_object, f, value);
 }
 
+void FmtStrFormatter::set_ellipsis(const std::string& ellipsis)
+{
+	fmtstrformatter::bridged::set_ellipsis(*rs_object, ellipsis);
+}
+
 std::string FmtStrFormatter::do_format(const std::string& fmt,
 	unsigned int width)
 {
@@ -19,4 +24,36 @@ std::string FmtStrFormatter::do_format(const std::string& fmt,
 	return std::string(formatted);
 }
 
+std::vector<std::string> FmtStrFormatter::do_format_rows(
+	std::vector<FmtStrFormatter> rows,
+	const std::string& fmt,
+	unsigned int width)
+{
+	auto rs_rows = fmtstrformatter::bridged::create_rows();
+	for (auto& row : rows) {
+		fmtstrformatter::bridged::push_row(*rs_rows, std::move(row.rs_object));
+	}
+
+	const auto rs_formatted = fmtstrformatter::bridged::do_format_rows(*rs_rows,
+			fmt, width);
+
+	std::vector<std::string> formatted;
+	for (const auto& line : rs_formatted) {
+		formatted.push_back(std::string(line));
+	}
+	return formatted;
+}
+
+std::vector<std::string> FmtStrFormatter::validate(const std::string& fmt,
+	const std::string& valid_keys)
+{
+	const auto rs_errors = fmtstrformatter::bridged::validate(fmt, valid_keys);
+
+	std::vector<std::string> errors;
+	for (const auto& error : rs_errors) {
+		errors.push_back(std::string(error));
+	}
+	return errors;
+}
+
 } // namespace newsboat
@@ -6,6 +6,7 @@
 
 #include "config.h"
 #include "configexception.h"
+#include "confighandlerexception.h"
 #include "controller.h"
 #include "fmtstrformatter.h"
 #include "logger.h"
@@ -20,6 +21,33 @@ namespace newsboat {
 History FormAction::searchhistory;
 History FormAction::cmdlinehistory;
 
+namespace {
+
+bool modifies_cache(Operation op)
+{
+	switch (op) {
+	case OP_RELOAD:
+	case OP_RELOADALL:
+	case OP_MARKFEEDREAD:
+	case OP_MARKALLFEEDSREAD:
+	case OP_MARKALLABOVEASREAD:
+	case OP_OPENBROWSER_AND_MARK:
+	case OP_OPENALLUNREADINBROWSER_AND_MARK:
+	case OP_TOGGLEITEMREAD:
+	case OP_ENQUEUE:
+	case OP_EDITFLAGS:
+	case OP_DELETE:
+	case OP_DELETE_ALL:
+	case OP_PURGE_DELETED:
+	case OP_EDIT_URLS:
+		return true;
+	default:
+		return false;
+	}
+}
+
+} // namespace
+
 FormAction::FormAction(View& vv, std::string formstr, ConfigContainer* cfg)
 	: v(vv)
 	, cfg(cfg)
@@ -52,6 +80,7 @@ FormAction::FormAction(View& vv, std::string formstr, ConfigContainer* cfg)
 	valid_cmds.push_back("quit");
 	valid_cmds.push_back("source");
 	valid_cmds.push_back("dumpconfig");
+	valid_cmds.push_back("savemacro");
 	valid_cmds.push_back("exec");
 }
 
@@ -77,13 +106,14 @@ void FormAction::edit_urls(const std::vector<std::string>& args,
 		break;
 	case 1: {
 		std::size_t line_number = 0;
+		Filepath file = v.get_ctrl().get_urls_file();
 		if (feed) {
 			const auto file_origin = feed->get_origin().file_origin;
 			if (file_origin.has_value()) {
 				line_number = file_origin->line_number;
+				file = file_origin->file;
 			}
 		}
-		Filepath file = v.get_ctrl().get_urls_file();
 		std::string quoted_file_arg = strprintf::fmt(R"("%s")",
 				utils::replace_all(file.to_locale_string(), "\"", "\\\""));
 
@@ -103,6 +133,22 @@ void FormAction::edit_urls(const std::vector<std::string>& args,
 	}
 }
 
+std::map<std::string, std::string> FormAction::get_variables()
+{
+	return {};
+}
+
+std::map<std::string, std::string> FormAction::item_variables(const RssItem& item)
+{
+	return {
+		{"title", item.title()},
+		{"link", item.link()},
+		{"author", item.author()},
+		{"flags", item.flags()},
+		{"unread", item.unread() ? "1" : "0"},
+	};
+}
+
 std::string FormAction::get_value(const std::string& name)
 {
 	return f.get(name);
@@ -145,6 +191,12 @@ bool FormAction::process_op(Operation op,
 	const std::vector<std::string>& args,
 	BindingType bindingType)
 {
+	// Operations that replay macros are left out, but the operations they
+	// run end up in the recording one by one
+	if (op != OP_RECORDMACRO && op != OP_REPLAYMACRO && op != OP_MACROPREFIX) {
+		v.get_macro_recorder().record({op, args});
+	}
+
 	switch (op) {
 	case OP_REDRAW:
 		LOG(Level::DEBUG, "FormAction::process_op: redrawing screen");
@@ -192,6 +244,10 @@ bool FormAction::process_op(Operation op,
 			break;
 		}
 		break;
+	case OP_RECORDMACRO:
+		return handle_record_macro(args);
+	case OP_REPLAYMACRO:
+		return handle_replay_macro(args);
 	case OP_VIEWDIALOGS:
 		v.view_dialogs();
 		break;
@@ -202,6 +258,12 @@ bool FormAction::process_op(Operation op,
 		v.goto_prev_dialog();
 		break;
 	default:
+		if (v.get_ctrl().is_read_only() && modifies_cache(op)) {
+			v.get_statusline().show_error(strprintf::fmt(
+					_("Operation \"%s\" is not available in read-only mode"),
+					KeyMap::get_op_name(op)));
+			return false;
+		}
 		return this->process_operation(op, args, bindingType);
 	}
 	return true;
@@ -360,6 +422,95 @@ void FormAction::handle_dumpconfig(const std::vector<std::string>& args)
 	}
 }
 
+void FormAction::handle_savemacro(const std::vector<std::string>& args)
+{
+	if (args.size() < 2 || args.size() > 3 || args[0].size() != 1) {
+		v.get_statusline().show_error(
+			_("usage: savemacro <register> <key> [<description>]"));
+		return;
+	}
+
+	const char reg = args[0][0];
+	const std::string& key = args[1];
+	const std::string description = args.size() == 3 ? args[2] : "";
+	const MacroRecorder& recorder = v.get_macro_recorder();
+	const auto command = recorder.macro_command(reg, key, description);
+	if (!command) {
+		v.get_statusline().show_error(command.error());
+		return;
+	}
+
+	const auto config_file = v.get_ctrl().get_config_file();
+	const auto saved = recorder.save_macro(reg, key, description, config_file);
+	if (!saved) {
+		v.get_statusline().show_error(saved.error());
+		return;
+	}
+
+	// Make the macro available without restarting
+	try {
+		const std::string prefix = "macro ";
+		v.get_keymap()->handle_action("macro", command.value().substr(prefix.size()));
+	} catch (const ConfigHandlerException& e) {
+		v.get_statusline().show_error(e.what());
+		return;
+	}
+	v.get_statusline().show_message(strprintf::fmt(_("Saved macro to %s"),
+			config_file.display()));
+}
+
+bool FormAction::handle_record_macro(const std::vector<std::string>& args)
+{
+	MacroRecorder& recorder = v.get_macro_recorder();
+	if (recorder.is_recording()) {
+		const auto reg = recorder.stop_recording();
+		if (!reg) {
+			v.get_statusline().show_error(reg.error());
+			return false;
+		}
+		v.get_statusline().show_message(strprintf::fmt(
+				_("Recorded macro into register `%s'"), std::string(1, reg.value())));
+		return true;
+	}
+
+	if (args.empty()) {
+		v.read_register(OP_RECORDMACRO, "record-");
+		return true;
+	}
+	if (args.size() != 1 || args[0].size() != 1) {
+		v.get_statusline().show_error(_("usage: record-macro [<register>]"));
+		return false;
+	}
+
+	const auto started = recorder.start_recording(args[0][0]);
+	if (!started) {
+		v.get_statusline().show_error(started.error());
+		return false;
+	}
+	v.get_statusline().show_message(strprintf::fmt(
+			_("Recording into register `%s'"), args[0]));
+	return true;
+}
+
+bool FormAction::handle_replay_macro(const std::vector<std::string>& args)
+{
+	if (args.empty()) {
+		v.read_register(OP_REPLAYMACRO, "replay-");
+		return true;
+	}
+	if (args.size() != 1 || args[0].size() != 1) {
+		v.get_statusline().show_error(_("usage: replay-macro [<register>]"));
+		return false;
+	}
+
+	const auto cmds = v.get_macro_recorder().replay(args[0][0], *v.get_keymap());
+	if (!cmds) {
+		v.get_statusline().show_error(cmds.error());
+		return false;
+	}
+	return v.run_commands(cmds.value(), BindingType::Macro);
+}
+
 void FormAction::handle_exec(const std::vector<std::string>& args)
 {
 	if (args.size() != 1) {
@@ -390,6 +541,9 @@ void FormAction::handle_parsed_command(const Command& command)
 	case CommandType::DUMPCONFIG:
 		handle_dumpconfig(command.args);
 		break;
+	case CommandType::SAVEMACRO:
+		handle_savemacro(command.args);
+		break;
 	case CommandType::EXEC:
 		handle_exec(command.args);
 		break;
@@ -698,7 +852,11 @@ void FormAction::finished_qna(QnaFinishAction op)
 
 void FormAction::set_title(const std::string& title)
 {
-	head_line.set_text(title);
+	if (v.get_ctrl().is_read_only()) {
+		head_line.set_text(_("[read-only] ") + title);
+	} else {
+		head_line.set_text(title);
+	}
 }
 
 void FormAction::start_bookmark_qna(const std::string& default_title,
@@ -777,6 +935,8 @@ Command FormAction::parse_command(const std::string& input,
 			return Command(CommandType::SOURCE, std::move(tokens));
 		} else if (cmd_name == "dumpconfig") {
 			return Command(CommandType::DUMPCONFIG, std::move(tokens));
+		} else if (cmd_name == "savemacro") {
+			return Command(CommandType::SAVEMACRO, std::move(tokens));
 		} else if (cmd_name == "exec") {
 			return Command(CommandType::EXEC, std::move(tokens));
 		} else if (cmd_name == "tag") {
@@ -26,4 +26,54 @@ bool FsLock::try_lock(const Filepath& new_lock_filepath, pid_t& pid,
 	return result;
 }
 
+bool FsLock::lock_with_timeout(const Filepath& new_lock_filepath, pid_t& pid,
+	std::chrono::seconds timeout, std::string& error_message)
+{
+	std::int64_t p;
+	rust::String message;
+	const bool result = newsboat::fslock::bridged::lock_with_timeout(*rs_object,
+			new_lock_filepath, p, static_cast<std::uint64_t>(timeout.count()), message);
+
+	// We use `libc::pid_t` on the rust side so we can guarantee this will fit
+	pid = static_cast<std::int64_t>(p);
+	error_message = std::string(message);
+
+	return result;
+}
+
+bool FsLock::try_lock_or_go_unlocked(const Filepath& new_lock_filepath, pid_t& pid,
+	bool& unlocked, std::string& error_message)
+{
+	std::int64_t p;
+	rust::String message;
+	const bool result = newsboat::fslock::bridged::try_lock_or_go_unlocked(*rs_object,
+			new_lock_filepath, p, unlocked, message);
+
+	// We use `libc::pid_t` on the rust side so we can guarantee this will fit
+	pid = static_cast<std::int64_t>(p);
+	error_message = std::string(message);
+
+	return result;
+}
+
+std::optional<pid_t> FsLock::lock_holder(const Filepath& lock_file)
+{
+	std::int64_t pid = 0;
+	if (!newsboat::fslock::bridged::lock_holder(lock_file, pid)) {
+		return std::nullopt;
+	}
+	return static_cast<pid_t>(pid);
+}
+
+nonstd::expected<bool, std::string> FsLock::force_unlock(
+	const Filepath& lock_file)
+{
+	bool removed = false;
+	rust::String message;
+	if (!newsboat::fslock::bridged::force_unlock(lock_file, removed, message)) {
+		return nonstd::make_unexpected(std::string(message));
+	}
+	return removed;
+}
+
 } // namespace newsboat
@@ -1581,6 +1581,15 @@ std::string ItemListFormAction::title()
 	}
 }
 
+std::map<std::string, std::string> ItemListFormAction::get_variables()
+{
+	const unsigned int pos = list.get_position();
+	if (pos >= visible_items.size()) {
+		return {};
+	}
+	return item_variables(*visible_items[pos].first);
+}
+
 void ItemListFormAction::handle_op_saveall()
 {
 	LOG(Level::INFO,
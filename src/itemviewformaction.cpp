@@ -737,6 +737,11 @@ std::string ItemViewFormAction::title()
 	return strprintf::fmt(_("Article - %s"), utils::utf8_to_locale(title));
 }
 
+std::map<std::string, std::string> ItemViewFormAction::get_variables()
+{
+	return item_variables(*item);
+}
+
 void ItemViewFormAction::set_highlightphrase(const std::string& text)
 {
 	highlight_text(text);
@@ -3,7 +3,6 @@
 #include <cctype>
 #include <tuple>
 
-#include "libnewsboat-ffi/src/keycombination.rs.h"
 #include "strprintf.h"
 
 namespace newsboat {
@@ -141,4 +140,33 @@ bool KeyCombination::has_alt() const
 	return alt == AltState::Alt;
 }
 
+KeyDecoder::KeyDecoder()
+	: rs_object(keycombination::bridged::create_decoder())
+{
+}
+
+std::vector<KeyCombination> KeyDecoder::push_event(const std::string& event)
+{
+	std::vector<KeyCombination> key_combinations;
+	for (const auto& key_combination_rs : keycombination::bridged::push_event(*rs_object,
+			event)) {
+		key_combinations.push_back(convert(key_combination_rs));
+	}
+	return key_combinations;
+}
+
+std::vector<KeyCombination> KeyDecoder::flush()
+{
+	std::vector<KeyCombination> key_combinations;
+	for (const auto& key_combination_rs : keycombination::bridged::flush(*rs_object)) {
+		key_combinations.push_back(convert(key_combination_rs));
+	}
+	return key_combinations;
+}
+
+bool KeyDecoder::has_pending() const
+{
+	return keycombination::bridged::has_pending(*rs_object);
+}
+
 } // namespace newsboat
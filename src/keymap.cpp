@@ -457,6 +457,20 @@ static const std::vector<OpDesc> opdescs = {
 		translatable("Call a macro"),
 		KM_NEWSBOAT
 	},
+	{
+		OP_RECORDMACRO,
+		"record-macro",
+		KeyCombination("m", ShiftState::Shift),
+		translatable("Start or stop recording a macro"),
+		KM_NEWSBOAT
+	},
+	{
+		OP_REPLAYMACRO,
+		"replay-macro",
+		KeyCombination("@"),
+		translatable("Replay a recorded macro"),
+		KM_NEWSBOAT
+	},
 	{
 		OP_DELETE,
 		"delete-article",
@@ -750,6 +764,13 @@ static const std::vector<OpDesc> opdescs = {
 		"",
 		KM_INTERNAL
 	},
+	{
+		OP_RUNPROGRAM,
+		"run-program",
+		{},
+		"",
+		KM_INTERNAL
+	},
 };
 
 static const std::map<Dialog, std::uint32_t> contexts = {
@@ -1189,6 +1210,7 @@ void KeyMap::handle_action(std::string_view action, std::string_view params)
 		if (parsing_failed) {
 			throw ConfigHandlerException(strprintf::fmt(_("failed to parse binding")));
 		}
+		bind_commands.emplace_back(params);
 		std::vector<Dialog> bind_contexts;
 		if (binding.contexts.size() == 1 && binding.contexts[0] == "everywhere") {
 			bind_contexts.clear();
@@ -1282,7 +1304,7 @@ ParsedOperations KeyMap::parse_operation_sequence(std::string_view line,
 {
 	rust::String description;
 	bool parsing_failed = false;
-	const auto operations = keymap::bridged::tokenize_operation_sequence(
+	const auto operations = keymap::bridged::parse_operation_sequence(
 			rust::Str(line.data(), line.size()),
 			description,
 			allow_description,
@@ -1300,6 +1322,106 @@ ParsedOperations KeyMap::parse_operation_sequence(std::string_view line,
 	return result;
 }
 
+std::vector<std::string> KeyMap::lint_bindings() const
+{
+	std::vector<rust::Str> bindings;
+	for (const auto& command : bind_commands) {
+		bindings.emplace_back(command);
+	}
+
+	std::vector<std::string> context_names;
+	for (const auto& context : contexts) {
+		context_names.push_back(dialog_name(context.first));
+	}
+	std::vector<rust::Str> rs_contexts;
+	for (const auto& name : context_names) {
+		rs_contexts.emplace_back(name);
+	}
+
+	std::vector<rust::Str> operations;
+	for (const auto& opdesc : opdescs) {
+		operations.emplace_back(opdesc.opstr);
+	}
+
+	const auto issues = keymap::bridged::lint_bindings(
+			rust::Slice<const rust::Str>(bindings.data(), bindings.size()),
+			rust::Slice<const rust::Str>(rs_contexts.data(), rs_contexts.size()),
+			rust::Slice<const rust::Str>(operations.data(), operations.size()));
+
+	std::vector<std::string> result;
+	for (const auto& issue : issues) {
+		result.push_back(strprintf::fmt("bind %s: %s", bind_commands[issue.binding],
+				std::string(issue.message)));
+	}
+	return result;
+}
+
+std::string KeyMap::render_reference(const std::string& format) const
+{
+	std::vector<keymap::bridged::Binding> defaults;
+	const auto add_default = [&](const KeyCombination& key,
+	const std::vector<std::string>& context_names, const OpDesc& opdesc) {
+		keymap::bridged::Binding binding;
+		binding.key_sequence = key.to_bind_string();
+		for (const auto& name : context_names) {
+			binding.contexts.push_back(name);
+		}
+		keymap::bridged::Operation operation;
+		operation.tokens.push_back(opdesc.opstr);
+		binding.operations.push_back(std::move(operation));
+		binding.description = _(opdesc.help_text.c_str());
+		defaults.push_back(std::move(binding));
+	};
+
+	// Same as what the constructor does, except that internal operations
+	// are left out: they can't be invoked by the user
+	for (const auto& opdesc : opdescs) {
+		if (!opdesc.default_key.has_value() || (opdesc.flags & KM_INTERNAL)) {
+			continue;
+		}
+		std::vector<std::string> context_names;
+		for (const auto& context : contexts) {
+			if (opdesc.flags & (context.second | KM_SYSKEYS)) {
+				context_names.push_back(dialog_name(context.first));
+			}
+		}
+		if (!context_names.empty()) {
+			add_default(opdesc.default_key.value(), context_names, opdesc);
+		}
+	}
+	const std::vector<std::string> paged_contexts = {
+		dialog_name(Dialog::Help),
+		dialog_name(Dialog::Article)
+	};
+	for (const auto& opdesc : opdescs) {
+		if (opdesc.op == OP_SK_PGUP) {
+			add_default(KeyCombination("b"), paged_contexts, opdesc);
+		} else if (opdesc.op == OP_SK_PGDOWN) {
+			add_default(KeyCombination("SPACE"), paged_contexts, opdesc);
+		}
+	}
+
+	std::vector<rust::Str> bindings;
+	for (const auto& command : bind_commands) {
+		bindings.emplace_back(command);
+	}
+
+	std::vector<std::string> context_names;
+	for (const auto& context : contexts) {
+		context_names.push_back(dialog_name(context.first));
+	}
+	std::vector<rust::Str> rs_contexts;
+	for (const auto& name : context_names) {
+		rs_contexts.emplace_back(name);
+	}
+
+	return std::string(keymap::bridged::render_keymap_reference(
+				rust::Slice<const keymap::bridged::Binding>(defaults.data(), defaults.size()),
+				rust::Slice<const rust::Str>(bindings.data(), bindings.size()),
+				rust::Slice<const rust::Str>(rs_contexts.data(), rs_contexts.size()),
+				format));
+}
+
 std::vector<MacroCmd> KeyMap::get_startup_operation_sequence()
 {
 	return startup_operations_sequence;
@@ -1324,6 +1446,29 @@ std::vector<KeyCombination> KeyMap::get_keys(Operation op, Dialog context)
 	return keys;
 }
 
+std::vector<MacroCmd> KeyMap::apply_count(const std::vector<MacroCmd>& cmds,
+	unsigned int count)
+{
+	std::vector<keymap::bridged::Operation> operations;
+	for (const auto& cmd : cmds) {
+		keymap::bridged::Operation operation;
+		operation.tokens.push_back(get_op_name(cmd.op));
+		for (const auto& arg : cmd.args) {
+			operation.tokens.push_back(arg);
+		}
+		operations.push_back(std::move(operation));
+	}
+
+	return convert_operations(keymap::bridged::operations_with_count(
+				rust::Slice<const keymap::bridged::Operation>(operations.data(), operations.size()),
+				count));
+}
+
+unsigned int KeyMap::max_count()
+{
+	return keymap::bridged::max_count();
+}
+
 std::vector<MacroCmd> KeyMap::get_macro(const KeyCombination& key_combination)
 {
 	if (macros_.count(key_combination) >= 1) {
@@ -0,0 +1,82 @@
+#include "macrorecorder.h"
+
+namespace newsboat {
+
+MacroRecorder::MacroRecorder()
+	: rs_object(macrorecorder::bridged::create())
+{
+}
+
+nonstd::expected<void, std::string> MacroRecorder::start_recording(char reg)
+{
+	rust::String error_message;
+	if (!macrorecorder::bridged::start_recording(*rs_object, reg, error_message)) {
+		return nonstd::make_unexpected(std::string(error_message));
+	}
+	return {};
+}
+
+nonstd::expected<char, std::string> MacroRecorder::stop_recording()
+{
+	std::uint8_t reg = 0;
+	rust::String error_message;
+	if (!macrorecorder::bridged::stop_recording(*rs_object, reg, error_message)) {
+		return nonstd::make_unexpected(std::string(error_message));
+	}
+	return static_cast<char>(reg);
+}
+
+bool MacroRecorder::is_recording() const
+{
+	return macrorecorder::bridged::is_recording(*rs_object);
+}
+
+void MacroRecorder::record(const MacroCmd& cmd)
+{
+	std::vector<rust::String> operation;
+	operation.emplace_back(KeyMap::get_op_name(cmd.op));
+	for (const auto& arg : cmd.args) {
+		operation.emplace_back(arg);
+	}
+	macrorecorder::bridged::record(*rs_object,
+		rust::Slice<const rust::String>(operation.data(), operation.size()));
+}
+
+nonstd::expected<std::vector<MacroCmd>, std::string> MacroRecorder::replay(
+	char reg, KeyMap& keymap) const
+{
+	rust::String operations;
+	rust::String error_message;
+	if (!macrorecorder::bridged::replay(*rs_object, reg, operations, error_message)) {
+		return nonstd::make_unexpected(std::string(error_message));
+	}
+	const bool allow_description = false;
+	return keymap.parse_operation_sequence(std::string(operations), "replay-macro",
+			allow_description).operations;
+}
+
+nonstd::expected<std::string, std::string> MacroRecorder::macro_command(char reg,
+	const std::string& key, const std::string& description) const
+{
+	rust::String command;
+	rust::String error_message;
+	if (!macrorecorder::bridged::macro_command(*rs_object, reg, key, description,
+			command, error_message)) {
+		return nonstd::make_unexpected(std::string(error_message));
+	}
+	return std::string(command);
+}
+
+nonstd::expected<void, std::string> MacroRecorder::save_macro(char reg,
+	const std::string& key, const std::string& description,
+	const Filepath& path) const
+{
+	rust::String error_message;
+	if (!macrorecorder::bridged::save_macro(*rs_object, reg, key, description, path,
+			error_message)) {
+		return nonstd::make_unexpected(std::string(error_message));
+	}
+	return {};
+}
+
+} // namespace newsboat
@@ -17,6 +17,7 @@
 #include "configcontainer.h"
 #include "configexception.h"
 #include "configparser.h"
+#include "configpaths.h"
 #include "logger.h"
 #include "nullconfigactionhandler.h"
 #include "pbview.h"
@@ -100,10 +101,25 @@ bool PbController::setup_dirs_xdg(const newsboat::Filepath& home)
 	config_file = config_dir.join(config_file);
 
 	/* in data */
-	const Filepath LOCK_SUFFIX = ".lock"_path;
-	lock_file = xdg_data_dir.join(LOCK_SUFFIX);
 	queue_file = xdg_data_dir.join(queue_file);
 
+	/* The lock goes wherever Newsboat puts its own, so that it isn't synced
+	 * between machines along with the data. */
+	const Filepath lock_dir = ConfigPaths().lock_dir();
+	ret = utils::mkdir_parents(lock_dir, 0700);
+	if (ret == -1) {
+		LOG(Level::CRITICAL,
+			"Couldn't create `%s'",
+			lock_dir);
+		::exit(EXIT_FAILURE);
+	}
+
+	const Filepath LOCK_SUFFIX = ".lock"_path;
+	lock_file = lock_dir.join(LOCK_SUFFIX);
+	if (lock_dir != xdg_data_dir) {
+		legacy_lock_file = xdg_data_dir.join(LOCK_SUFFIX);
+	}
+
 	return true;
 }
 
@@ -114,6 +130,27 @@ PbController::PbController()
 	, lock_file("pb-lock.pid"_path)
 	, keys(KM_PODBOAT)
 {
+	// In portable mode, all files are in one directory, just like Newsboat's
+	const auto portable_dir = ConfigPaths().portable_dir();
+	if (portable_dir.has_value()) {
+		config_dir = portable_dir.value();
+		if (utils::mkdir_parents(config_dir, 0700) == -1) {
+			std::cerr << strprintf::fmt(
+					_("Fatal error: couldn't create "
+						"configuration directory `%s': (%i) %s"),
+					config_dir,
+					errno,
+					std::strerror(errno))
+				<< std::endl;
+			::exit(EXIT_FAILURE);
+		}
+
+		config_file = config_dir.join(config_file);
+		queue_file = config_dir.join(queue_file);
+		lock_file = config_dir.join(lock_file);
+		return;
+	}
+
 	char* cfgdir;
 	if (!(cfgdir = ::getenv("HOME"))) {
 		struct passwd* spw = ::getpwuid(::getuid());
@@ -166,6 +203,7 @@ void PbController::initialize(int argc, char* argv[])
 		{"config-file", required_argument, 0, 'C'},
 		{"queue-file", required_argument, 0, 'q'},
 		{"lock-file", required_argument, 0, 256},
+		{"force-unlock", no_argument, 0, 257},
 		{"log-file", required_argument, 0, 'd'},
 		{"log-level", required_argument, 0, 'l'},
 		{"help", no_argument, 0, 'h'},
@@ -191,6 +229,10 @@ void PbController::initialize(int argc, char* argv[])
 			break;
 		case 256:
 			lock_file = Filepath::from_locale_string(optarg);
+			legacy_lock_file.reset();
+			break;
+		case 257:
+			force_unlock = true;
 			break;
 		case 'a':
 			automatic_dl = true;
@@ -235,6 +277,31 @@ void PbController::initialize(int argc, char* argv[])
 			_("Starting %s %s..."), "Podboat", utils::program_version())
 		<< std::endl;
 
+	if (force_unlock) {
+		const auto removed = FsLock::force_unlock(lock_file);
+		if (!removed) {
+			std::cout << _("Error: ") << removed.error() << std::endl;
+			exit(EXIT_FAILURE);
+		}
+		if (removed.value()) {
+			std::cout << strprintf::fmt(_("Removed stale lock file `%s'."),
+					lock_file)
+				<< std::endl;
+		}
+	}
+
+	if (legacy_lock_file.has_value()) {
+		if (const auto holder = FsLock::lock_holder(legacy_lock_file.value())) {
+			std::cout << strprintf::fmt(
+					_("Error: an older version of %s is already running "
+						"(PID: %s)"),
+					"Podboat",
+					std::to_string(holder.value()))
+				<< std::endl;
+			exit(EXIT_FAILURE);
+		}
+	}
+
 	fslock = std::make_unique<FsLock>();
 	pid_t pid;
 	std::string error_message;
@@ -350,6 +417,12 @@ void PbController::print_usage(const char* argv0)
 			_s("<file>"),
 			_s("use <file> as lock file")
 		},
+		{
+			'-',
+			"force-unlock",
+			"",
+			_s("remove a lock left behind by an instance that isn't running anymore")
+		},
 		{'a', "autodownload", "", _s("start download on startup")},
 		{
 			'l',
@@ -0,0 +1,56 @@
+#include "programrunner.h"
+
+#include <vector>
+
+#include "config.h"
+#include "strprintf.h"
+
+namespace newsboat {
+
+ProgramRunner::ProgramRunner(const std::string& program)
+	: rs_object(keymap::bridged::create_program_runner(program, parsing_failed))
+{
+}
+
+nonstd::expected<std::optional<MacroCmd>, std::string> ProgramRunner::next_operation(
+	const std::map<std::string, std::string>& variables)
+{
+	if (parsing_failed) {
+		return nonstd::make_unexpected(
+				strprintf::fmt(_("failed to parse operation sequence for %s"),
+					KeyMap::get_op_name(OP_RUNPROGRAM)));
+	}
+
+	std::vector<rust::Str> names;
+	std::vector<rust::Str> values;
+	for (const auto& variable : variables) {
+		names.emplace_back(variable.first);
+		values.emplace_back(variable.second);
+	}
+
+	keymap::bridged::Operation operation;
+	rust::String error_message;
+	if (!keymap::bridged::next_program_operation(*rs_object,
+			rust::Slice<const rust::Str>(names.data(), names.size()),
+			rust::Slice<const rust::Str>(values.data(), values.size()),
+			operation, error_message)) {
+		if (!error_message.empty()) {
+			return nonstd::make_unexpected(std::string(error_message));
+		}
+		return std::nullopt;
+	}
+
+	const auto name = std::string(operation.tokens[0]);
+	MacroCmd cmd;
+	cmd.op = KeyMap::get_opcode(name);
+	if (cmd.op == OP_NIL) {
+		return nonstd::make_unexpected(
+				strprintf::fmt(_("`%s' is not a valid operation"), name));
+	}
+	for (std::size_t i = 1; i < operation.tokens.size(); ++i) {
+		cmd.args.emplace_back(operation.tokens[i]);
+	}
+	return cmd;
+}
+
+} // namespace newsboat
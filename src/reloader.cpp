@@ -31,12 +31,20 @@ Reloader::Reloader(Controller& c, Cache& cc, ConfigContainer& cfg)
 
 void Reloader::spawn_reloadthread()
 {
+	if (ctrl.is_read_only()) {
+		LOG(Level::INFO, "Reloader: read-only, so not reloading automatically");
+		return;
+	}
 	std::thread t{ReloadThread(ctrl, cfg)};
 	t.detach();
 }
 
 void Reloader::start_reload_all_thread(const std::vector<unsigned int>& indexes)
 {
+	if (ctrl.is_read_only()) {
+		LOG(Level::INFO, "Reloader: read-only, so not reloading");
+		return;
+	}
 	LOG(Level::INFO, "starting reload all thread");
 	std::thread t([=]() {
 		LOG(Level::DEBUG,
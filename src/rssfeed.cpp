@@ -143,6 +143,12 @@ std::shared_ptr<RssItem> RssFeed::get_item_by_guid(const std::string& guid)
 	return get_item_by_guid_unlocked(guid);
 }
 
+bool RssFeed::has_item(const std::string& guid)
+{
+	std::lock_guard<std::mutex> lock(item_mutex);
+	return items_guid_map.find(guid) != items_guid_map.end();
+}
+
 std::shared_ptr<RssItem> RssFeed::get_item_by_guid_unlocked(
 	const std::string& guid)
 {
@@ -6,6 +6,7 @@
 
 #include "cache.h"
 #include "dbexception.h"
+#include "logger.h"
 #include "rssfeed.h"
 #include "scopemeasure.h"
 #include "strprintf.h"
@@ -99,6 +100,15 @@ void RssItem::set_unread_nowrite_notify(bool u, bool notify)
 
 void RssItem::set_unread(bool u)
 {
+	if (ch && ch->is_read_only()) {
+		// The change can't be stored, so the item keeps showing what's in
+		// the cache instead of a state that will be lost on exit
+		LOG(Level::DEBUG,
+			"RssItem::set_unread: cache is read-only, keeping the item %s",
+			unread_ ? "unread" : "read");
+		return;
+	}
+
 	if (unread_ != u) {
 		bool old_u = unread_;
 		unread_ = u;
@@ -240,6 +250,11 @@ std::optional<std::string> RssItem::attribute_value(const std::string&
 
 void RssItem::update_flags()
 {
+	if (ch && ch->is_read_only()) {
+		// Same as in `set_unread`: don't show flags that won't be stored
+		flags_ = oldflags_;
+		return;
+	}
 	if (ch) {
 		ch->update_rssitem_flags(this);
 	}
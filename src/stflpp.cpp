@@ -1,6 +1,7 @@
 #include "stflpp.h"
 
 #include <cerrno>
+#include <cstdio>
 #include <langinfo.h>
 #include <mutex>
 
@@ -101,11 +102,26 @@ void Stfl::Form::modify(const std::string& name,
 	stfl_ipool_flush(ipool);
 }
 
+static bool mouse_reporting = false;
+
 void Stfl::reset()
 {
+	set_mouse_reporting(false);
 	stfl_reset();
 }
 
+void Stfl::set_mouse_reporting(bool enabled)
+{
+	if (enabled == mouse_reporting) {
+		return;
+	}
+	// Mode 1000 reports presses, releases and the wheel; mode 1006 makes the
+	// terminal use SGR encoding for these reports
+	std::fputs(enabled ? "\033[?1000h\033[?1006h" : "\033[?1006l\033[?1000l", stdout);
+	std::fflush(stdout);
+	mouse_reporting = enabled;
+}
+
 static std::mutex quote_mtx;
 
 std::string Stfl::quote(const std::string& text)
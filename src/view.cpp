@@ -1,5 +1,6 @@
 #include "view.h"
 
+#include <algorithm>
 #include <assert.h>
 #include <cstring>
 #include <dirent.h>
@@ -9,6 +10,7 @@
 #include <limits.h>
 #include <ncurses.h>
 #include <optional>
+#include <poll.h>
 #include <pwd.h>
 #include <string.h>
 #include <sys/param.h>
@@ -23,6 +25,7 @@ extern "C" {
 #include "colormanager.h"
 #include "controller.h"
 #include "configcontainer.h"
+#include "controlsocket.h"
 #include "dbexception.h"
 #include "dialogs.h"
 #include "dialogsformaction.h"
@@ -44,6 +47,7 @@ extern "C" {
 #include "keymap.h"
 #include "logger.h"
 #include "matcherexception.h"
+#include "programrunner.h"
 #include "rssfeed.h"
 #include "selectformaction.h"
 #include "selecttag.h"
@@ -55,6 +59,29 @@ extern "C" {
 
 namespace {
 bool ctrl_c_hit = false;
+
+/// How long to wait (in milliseconds) for the rest of an escape sequence
+/// before taking its beginning as separate keys
+const int ESCAPE_SEQUENCE_TIMEOUT = 25;
+
+/// Returns the digit that `key` adds to the numeric prefix `count` (zero
+/// meaning that no prefix has been typed yet), or std::nullopt if `key` isn't
+/// part of the prefix. A prefix can't start with `0`.
+std::optional<unsigned int> count_digit(const newsboat::KeyCombination& key,
+	unsigned int count)
+{
+	if (key.has_shift() || key.has_control() || key.has_alt()) {
+		return std::nullopt;
+	}
+	const std::string& name = key.get_key();
+	if (name.size() != 1 || name[0] < '0' || name[0] > '9') {
+		return std::nullopt;
+	}
+	if (name[0] == '0' && count == 0) {
+		return std::nullopt;
+	}
+	return name[0] - '0';
+}
 }
 
 namespace newsboat {
@@ -71,6 +98,8 @@ View::View(Controller& c)
 	, rsscache(nullptr)
 	, filters(ctrl.get_filtercontainer())
 	, colorman(ctrl.get_colormanager())
+	, control_socket(nullptr)
+	, register_op(OP_NIL)
 {
 	if (getenv("ESCDELAY") == nullptr) {
 		set_escdelay(25);
@@ -130,6 +159,12 @@ bool View::run_commands(const std::vector<MacroCmd>& commands, BindingType bindi
 		if (formaction_stack_size() == 0) {
 			return true;
 		}
+		if (command.op == OP_RUNPROGRAM) {
+			if (!run_program(command.args, binding_type)) {
+				return false;
+			}
+			continue;
+		}
 		std::shared_ptr<FormAction> fa = get_current_formaction();
 		fa->prepare();
 		fa->draw_form();
@@ -141,9 +176,41 @@ bool View::run_commands(const std::vector<MacroCmd>& commands, BindingType bindi
 	return true;
 }
 
+bool View::run_program(const std::vector<std::string>& args, BindingType binding_type)
+{
+	if (args.size() != 1) {
+		status_line.show_error(_("usage: run-program <operations>"));
+		return false;
+	}
+
+	ProgramRunner runner(args[0]);
+	while (formaction_stack_size() > 0) {
+		const auto cmd = runner.next_operation(get_current_formaction()->get_variables());
+		if (!cmd) {
+			status_line.show_error(cmd.error());
+			return false;
+		}
+		if (!cmd.value().has_value()) {
+			break;
+		}
+		if (!run_commands({cmd.value().value()}, binding_type)) {
+			return false;
+		}
+	}
+	return true;
+}
+
+void View::read_register(Operation op, const std::string& prompt)
+{
+	register_op = op;
+	status_line.show_message(prompt);
+}
+
 int View::run()
 {
 	bool have_macroprefix = false;
+	// Numeric prefix typed before the current key sequence, or zero if none
+	unsigned int count = 0;
 
 	feedlist_form = std::make_shared<FeedListFormAction>(
 			*this, feedlist_str, rsscache, filters, cfg, rxman);
@@ -176,7 +243,7 @@ int View::run()
 		fa->prepare();
 
 		// we then receive the event and ignore timeouts.
-		const std::string event = fa->draw_form_wait_for_event(INT_MAX);
+		const std::string event = wait_for_event(fa);
 
 		if (ctrl_c_hit) {
 			ctrl_c_hit = false;
@@ -191,7 +258,7 @@ int View::run()
 			}
 		}
 
-		if (event.empty() || event == "TIMEOUT") {
+		if (event.empty() || (event == "TIMEOUT" && !key_decoder.has_pending())) {
 			continue;
 		}
 
@@ -200,38 +267,89 @@ int View::run()
 			continue;
 		}
 
-		if (handle_qna_event(event, fa)) {
+		if (event != "TIMEOUT" && handle_qna_event(event, fa)) {
 			continue;
 		}
 
 		LOG(Level::DEBUG, "View::run: event = %s", event);
 
-		const auto key_combination = KeyCombination::from_bindkey(event);
-		if (have_macroprefix) {
-			have_macroprefix = false;
-			status_line.show_message("");
-			LOG(Level::DEBUG,
-				"View::run: running macro `%s'",
-				event);
-			run_commands(keys->get_macro(key_combination), BindingType::Macro);
-		} else {
-			if (key_combination == KeyCombination("ESC") && !key_sequence.empty()) {
-				key_sequence.clear();
-			} else {
-				key_sequence.push_back(key_combination);
+		for (const auto& key_combination : decode_event(event)) {
+			if (formaction_stack_size() == 0) {
+				break;
 			}
-			auto binding_state = MultiKeyBindingState::NotFound;
-			BindingType type = BindingType::Bind;
-			auto cmds = keys->get_operation(key_sequence, fa->id(), binding_state, type);
-
-			if (binding_state != MultiKeyBindingState::MoreInputNeeded) {
-				key_sequence.clear();
-
-				run_commands(cmds, type);
-
-				if (cmds.size() >= 1 && cmds.back().op == OP_MACROPREFIX) {
-					have_macroprefix = true;
-					status_line.show_message("macro-");
+			// Earlier keys from the same event might have switched dialogs
+			fa = get_current_formaction();
+
+			const auto digit = count_digit(key_combination, count);
+			if (have_macroprefix) {
+				have_macroprefix = false;
+				status_line.show_message("");
+				LOG(Level::DEBUG,
+					"View::run: running macro `%s'",
+					key_combination.to_bindkey_string());
+				auto cmds = keys->get_macro(key_combination);
+				if (count > 0) {
+					cmds = KeyMap::apply_count(cmds, count);
+					count = 0;
+				}
+				run_commands(cmds, BindingType::Macro);
+			} else if (register_op != OP_NIL) {
+				const Operation op = register_op;
+				register_op = OP_NIL;
+				status_line.show_message("");
+				if (!(key_combination == KeyCombination("ESC"))) {
+					std::vector<MacroCmd> cmds = {{op, {key_combination.to_bindkey_string()}}};
+					if (count > 0) {
+						cmds = KeyMap::apply_count(cmds, count);
+					}
+					run_commands(cmds, BindingType::Bind);
+				}
+				count = 0;
+			} else if (digit.has_value() && key_sequence.empty()
+				&& cfg->get_configvalue_as_bool("count-prefix")) {
+				count = std::min(count * 10 + digit.value(), KeyMap::max_count());
+				status_line.show_message(std::to_string(count));
+			} else {
+				if (key_combination == KeyCombination("ESC")
+					&& (!key_sequence.empty() || count > 0)) {
+					key_sequence.clear();
+					count = 0;
+					status_line.show_message("");
+				} else {
+					key_sequence.push_back(key_combination);
+				}
+				auto binding_state = MultiKeyBindingState::NotFound;
+				BindingType type = BindingType::Bind;
+				auto cmds = keys->get_operation(key_sequence, fa->id(), binding_state, type);
+
+				if (binding_state != MultiKeyBindingState::MoreInputNeeded) {
+					key_sequence.clear();
+
+					const bool is_macroprefix = cmds.size() >= 1 && cmds.back().op == OP_MACROPREFIX;
+					// The count applies to the macro or register selected by the
+					// next key press, if these operations end up waiting for it
+					const bool might_read_register = cmds.size() >= 1
+						&& (cmds.back().op == OP_RECORDMACRO || cmds.back().op == OP_REPLAYMACRO)
+						&& cmds.back().args.empty();
+					if (count > 0 && !is_macroprefix && !might_read_register) {
+						// `bind-key` bindings don't take arguments, but the count
+						// might have become one
+						cmds = KeyMap::apply_count(cmds, count);
+						if (type == BindingType::BindKey) {
+							type = BindingType::Bind;
+						}
+						count = 0;
+						status_line.show_message("");
+					}
+
+					run_commands(cmds, type);
+
+					if (is_macroprefix) {
+						have_macroprefix = true;
+						status_line.show_message(count > 0 ? std::to_string(count) + " macro-" : "macro-");
+					} else if (register_op == OP_NIL) {
+						count = 0;
+					}
 				}
 			}
 		}
@@ -266,9 +384,11 @@ std::string View::run_modal(std::shared_ptr<FormAction> f,
 
 		fa->prepare();
 
-		const std::string event = fa->draw_form_wait_for_event(INT_MAX);
+		update_mouse_reporting();
+		const std::string event = fa->draw_form_wait_for_event(
+				key_decoder.has_pending() ? ESCAPE_SEQUENCE_TIMEOUT : INT_MAX);
 		LOG(Level::DEBUG, "View::run: event = %s", event);
-		if (event.empty() || event == "TIMEOUT") {
+		if (event.empty() || (event == "TIMEOUT" && !key_decoder.has_pending())) {
 			continue;
 		}
 
@@ -277,50 +397,62 @@ std::string View::run_modal(std::shared_ptr<FormAction> f,
 			continue;
 		}
 
-		if (handle_qna_event(event, fa)) {
+		if (event != "TIMEOUT" && handle_qna_event(event, fa)) {
 			continue;
 		}
 
-		const auto key_combination = KeyCombination::from_bindkey(event);
-		if (key_combination == KeyCombination("ESC") && !key_sequence.empty()) {
-			key_sequence.clear();
-		} else {
-			key_sequence.push_back(key_combination);
-		}
-
-		auto binding_state = MultiKeyBindingState::NotFound;
-		BindingType type = BindingType::Bind;
-		auto cmds = keys->get_operation(key_sequence, fa->id(), binding_state, type);
-
-		if (binding_state != MultiKeyBindingState::MoreInputNeeded) {
-			key_sequence.clear();
-
-			for (auto command : cmds) {
-				if (formaction_stack_size() == 0) {
-					break;
-				}
+		for (const auto& key_combination : decode_event(event)) {
+			if (formaction_stack.size() <= stacksize) {
+				break;
+			}
+			fa = get_current_formaction();
 
-				if (OP_REDRAW == command.op) {
-					Stfl::reset();
-					continue;
-				}
+			if (key_combination == KeyCombination("ESC") && !key_sequence.empty()) {
+				key_sequence.clear();
+			} else {
+				key_sequence.push_back(key_combination);
+			}
 
-				if (ignoredOperations.count(command.op)) {
-					status_line.show_message(_("Operation ignored in modal dialog"));
-					break;
-				}
+			auto binding_state = MultiKeyBindingState::NotFound;
+			BindingType type = BindingType::Bind;
+			auto cmds = keys->get_operation(key_sequence, fa->id(), binding_state, type);
 
-				std::shared_ptr<FormAction> fa = get_current_formaction();
-				fa->prepare();
-				fa->draw_form();
-				if (!fa->process_op(command.op, command.args, type)) {
-					// Operation failed, don't run further commands
-					break;
-				}
+			if (binding_state != MultiKeyBindingState::MoreInputNeeded) {
+				key_sequence.clear();
 
-				if (formaction_stack.size() <= stacksize) {
-					// Stop running further commands if the current modal FormAction gets closed
-					break;
+				for (auto command : cmds) {
+					if (formaction_stack_size() == 0) {
+						break;
+					}
+
+					if (OP_REDRAW == command.op) {
+						Stfl::reset();
+						continue;
+					}
+
+					if (ignoredOperations.count(command.op)) {
+						status_line.show_message(_("Operation ignored in modal dialog"));
+						break;
+					}
+
+					if (command.op == OP_RUNPROGRAM) {
+						if (!run_program(command.args, type)) {
+							break;
+						}
+					} else {
+						std::shared_ptr<FormAction> fa = get_current_formaction();
+						fa->prepare();
+						fa->draw_form();
+						if (!fa->process_op(command.op, command.args, type)) {
+							// Operation failed, don't run further commands
+							break;
+						}
+					}
+
+					if (formaction_stack.size() <= stacksize) {
+						// Stop running further commands if the current modal FormAction gets closed
+						break;
+					}
 				}
 			}
 		}
@@ -1027,6 +1159,69 @@ bool View::try_prepare_query_feed(std::shared_ptr<RssFeed> feed)
 	}
 }
 
+void View::enable_remote_requests(ControlSocket& socket)
+{
+	control_socket = &socket;
+}
+
+void View::open_article(std::shared_ptr<RssFeed> feed, const std::string& guid)
+{
+	LOG(Level::INFO, "View::open_article: opening article %s", guid);
+	// Other dialogs stay open, and can be returned to.
+	current_formaction = 0;
+	const auto itemlist = push_itemlist(feed);
+	if (itemlist != nullptr) {
+		push_itemview(feed, guid);
+	}
+}
+
+std::vector<KeyCombination> View::decode_event(const std::string& event)
+{
+	if (event == "TIMEOUT") {
+		// The rest of the escape sequence didn't arrive
+		return key_decoder.flush();
+	}
+	return key_decoder.push_event(event);
+}
+
+void View::update_mouse_reporting()
+{
+	Stfl::set_mouse_reporting(cfg->get_configvalue_as_bool("mouse-support")
+		&& !is_inside_qna);
+}
+
+std::string View::wait_for_event(std::shared_ptr<FormAction> fa)
+{
+	update_mouse_reporting();
+
+	if (key_decoder.has_pending()) {
+		return fa->draw_form_wait_for_event(ESCAPE_SEQUENCE_TIMEOUT);
+	}
+
+	if (control_socket == nullptr) {
+		return fa->draw_form_wait_for_event(INT_MAX);
+	}
+
+	// Draw the form and take the input that's already there. ncurses might
+	// have buffered some of it, so we can't wait on stdin before this.
+	const std::string event = fa->draw_form_wait_for_event(1);
+	if (event != "TIMEOUT") {
+		return event;
+	}
+
+	// Sleep until the user presses a key or a command arrives. Signals like
+	// SIGWINCH and SIGINT interrupt the wait, too.
+	pollfd fds[] = {
+		{STDIN_FILENO, POLLIN, 0},
+		{control_socket->wakeup_fd(), POLLIN, 0},
+	};
+	::poll(fds, 2, -1);
+	if (fds[1].revents & POLLIN) {
+		control_socket->handle_requests();
+	}
+	return "";
+}
+
 void View::force_redraw()
 {
 	std::shared_ptr<FormAction> fa = get_current_formaction();
@@ -1,5 +1,7 @@
 #include "cache.h"
 
+#include <algorithm>
+#include <ctime>
 #include <memory>
 #include <sstream>
 #include <thread>
@@ -8,6 +10,7 @@
 #include "3rd-party/catch.hpp"
 #include "configcontainer.h"
 #include "curlhandle.h"
+#include "dbexception.h"
 #include "feedretriever.h"
 #include "rssfeed.h"
 #include "rssignores.h"
@@ -607,6 +610,84 @@ TEST_CASE("mark_items_read_by_guid marks items with given GUIDs as unread ",
 	}
 }
 
+TEST_CASE("update_article_states stores read state, flags and the times "
+	"they changed",
+	"[Cache]")
+{
+	test_helpers::TempFile dbfile;
+	ConfigContainer cfg;
+	auto rsscache = std::make_unique<Cache>(dbfile.get_path(), cfg);
+	auto feedurl = "file://data/rss.xml";
+	CurlHandle easyHandle;
+	FeedRetriever feed_retriever(cfg, *rsscache, easyHandle);
+	RssParser parser(feedurl, *rsscache, cfg, nullptr);
+	auto feed = parser.parse(feed_retriever.retrieve(feedurl));
+	rsscache->externalize_rssfeed(*feed, false);
+
+	const auto guid = feed->items()[1]->guid();
+	auto states = rsscache->get_article_states();
+	REQUIRE(states.size() == 8);
+	for (const auto& state : states) {
+		REQUIRE(state.unread);
+		REQUIRE(std::string(state.feed_url) == feedurl);
+		REQUIRE(state.read_changed == 0);
+		REQUIRE(state.flags_changed == 0);
+	}
+
+	rust::Vec<readstate::bridged::ArticleState> changed;
+	readstate::bridged::ArticleState state;
+	state.guid = guid;
+	state.feed_url = feedurl;
+	state.unread = false;
+	state.read_changed = 1000;
+	state.flags = "ab";
+	state.flags_changed = 2000;
+	changed.push_back(state);
+	rsscache->update_article_states(changed);
+
+	rsscache = std::make_unique<Cache>(dbfile.get_path(), cfg);
+	states = rsscache->get_article_states();
+	const auto it = std::find_if(states.cbegin(), states.cend(),
+	[&guid](const readstate::bridged::ArticleState& s) {
+		return std::string(s.guid) == guid;
+	});
+	REQUIRE(it != states.cend());
+	REQUIRE_FALSE(it->unread);
+	REQUIRE(it->read_changed == 1000);
+	REQUIRE(std::string(it->flags) == "ab");
+	REQUIRE(it->flags_changed == 2000);
+}
+
+TEST_CASE("Changing read state or flags records when it happened", "[Cache]")
+{
+	ConfigContainer cfg;
+	auto rsscache = Cache::in_memory(cfg);
+	auto feedurl = "file://data/rss.xml";
+	CurlHandle easyHandle;
+	FeedRetriever feed_retriever(cfg, *rsscache, easyHandle);
+	RssParser parser(feedurl, *rsscache, cfg, nullptr);
+	auto feed = parser.parse(feed_retriever.retrieve(feedurl));
+	rsscache->externalize_rssfeed(*feed, false);
+
+	const auto before = std::time(nullptr);
+	auto item = feed->items()[0];
+	rsscache->mark_items_read_by_guid({item->guid()});
+	item->set_flags("x");
+	rsscache->update_rssitem_flags(item.get());
+
+	for (const auto& state : rsscache->get_article_states()) {
+		if (std::string(state.guid) == item->guid()) {
+			REQUIRE_FALSE(state.unread);
+			REQUIRE(state.read_changed >= before);
+			REQUIRE(std::string(state.flags) == "x");
+			REQUIRE(state.flags_changed >= before);
+		} else {
+			REQUIRE(state.read_changed == 0);
+			REQUIRE(state.flags_changed == 0);
+		}
+	}
+}
+
 TEST_CASE(
 	"remove_old_deleted_items removes deleted items that belong to the given "
 	"feed, but aren't mentioned in the given RssFeed object",
@@ -938,6 +1019,96 @@ TEST_CASE(
 	}
 }
 
+TEST_CASE("Cache opened in dry-run mode counts deleted articles, but doesn't "
+	"save any changes",
+	"[Cache]")
+{
+	test_helpers::TempFile dbfile;
+	auto cfg = std::make_unique<ConfigContainer>();
+	auto rsscache = std::make_unique<Cache>(dbfile.get_path(), *cfg);
+
+	const auto feedurl = "file://data/rss.xml";
+	CurlHandle easyHandle;
+	FeedRetriever feed_retriever(*cfg, *rsscache, easyHandle);
+	RssParser parser(feedurl, *rsscache, *cfg, nullptr);
+	auto feed = parser.parse(feed_retriever.retrieve(feedurl));
+	REQUIRE(feed->total_item_count() == 8);
+	rsscache->externalize_rssfeed(*feed, false);
+
+	SECTION("keep-articles-days") {
+		cfg = std::make_unique<ConfigContainer>();
+		// The items in the test feed date back to 2006
+		cfg->set_configvalue("keep-articles-days", "42");
+		rsscache = std::make_unique<Cache>(dbfile.get_path(), *cfg,
+				CacheMode::DryRun);
+		REQUIRE(rsscache->expired_articles_count() == 8);
+		REQUIRE(rsscache->excess_articles_count() == 0);
+	}
+
+	SECTION("max-items") {
+		cfg = std::make_unique<ConfigContainer>();
+		cfg->set_configvalue("max-items", "3");
+		rsscache = std::make_unique<Cache>(dbfile.get_path(), *cfg,
+				CacheMode::DryRun);
+		feed = rsscache->internalize_rssfeed(feedurl, nullptr);
+		REQUIRE(feed->total_item_count() == 3);
+		REQUIRE(rsscache->expired_articles_count() == 0);
+		REQUIRE(rsscache->excess_articles_count() == 5);
+	}
+
+	cfg = std::make_unique<ConfigContainer>();
+	rsscache = std::make_unique<Cache>(dbfile.get_path(), *cfg);
+	feed = rsscache->internalize_rssfeed(feedurl, nullptr);
+	REQUIRE(feed->total_item_count() == 8);
+}
+
+TEST_CASE("summarize_cleanup counts what cleanup_cache would delete",
+	"[Cache]")
+{
+	ConfigContainer cfg;
+	auto rsscache = Cache::in_memory(cfg);
+	CurlHandle easyHandle;
+	FeedRetriever feed_retriever(cfg, *rsscache, easyHandle);
+
+	std::vector<std::shared_ptr<RssFeed>> feeds;
+	for (const auto& feedurl : {
+			"file://data/rss.xml", "file://data/atom10_1.xml"
+		}) {
+		RssParser parser(feedurl, *rsscache, cfg, nullptr);
+		feeds.push_back(parser.parse(feed_retriever.retrieve(feedurl)));
+		rsscache->externalize_rssfeed(*feeds.back(), false);
+	}
+	REQUIRE(feeds[0]->total_item_count() == 8);
+	rsscache->mark_items_read_by_guid({feeds[0]->items()[0]->guid()});
+
+	// Only the first feed remains in the urls file
+	feeds.pop_back();
+
+	SECTION("read articles are kept by default") {
+		const auto summary = rsscache->summarize_cleanup(feeds);
+		REQUIRE(summary.feeds == 1);
+		REQUIRE(summary.articles == 3);
+		REQUIRE(summary.read_articles == 0);
+	}
+
+	SECTION("read articles are counted if delete-read-articles-on-quit is enabled") {
+		cfg.set_configvalue("delete-read-articles-on-quit", "yes");
+		const auto summary = rsscache->summarize_cleanup(feeds);
+		REQUIRE(summary.feeds == 1);
+		REQUIRE(summary.articles == 3);
+		REQUIRE(summary.read_articles == 1);
+	}
+}
+
+TEST_CASE("reclaimable_space is zero for a freshly created cache", "[Cache]")
+{
+	test_helpers::TempFile dbfile;
+	ConfigContainer cfg;
+	Cache rsscache(dbfile.get_path(), cfg);
+
+	REQUIRE(rsscache.reclaimable_space() == 0);
+}
+
 TEST_CASE(
 	"internalize_rssfeed returns feed without items but specified RSS URL",
 	"[Cache]")
@@ -1208,3 +1379,103 @@ TEST_CASE("Ignoring articles in search", "[Cache]")
 	REQUIRE(search_items.size() == 0 );
 	REQUIRE(no_ignore_items.size() == 1);
 }
+
+TEST_CASE("Cache opened in read-only mode reads the file, but drops changes",
+	"[Cache]")
+{
+	test_helpers::TempFile dbfile;
+	ConfigContainer cfg;
+	auto rsscache = std::make_unique<Cache>(dbfile.get_path(), cfg);
+
+	const auto feedurl = "file://data/rss.xml";
+	CurlHandle easyHandle;
+	FeedRetriever feed_retriever(cfg, *rsscache, easyHandle);
+	RssParser parser(feedurl, *rsscache, cfg, nullptr);
+	auto feed = parser.parse(feed_retriever.retrieve(feedurl));
+	REQUIRE(feed->total_item_count() == 8);
+	rsscache->externalize_rssfeed(*feed, false);
+	REQUIRE(rsscache->get_read_item_guids().empty());
+
+	// The writer keeps the cache open while the reader uses it
+	Cache reader(dbfile.get_path(), cfg, CacheMode::ReadOnly);
+	feed = reader.internalize_rssfeed(feedurl, nullptr);
+	REQUIRE(feed->total_item_count() == 8);
+
+	reader.mark_all_read(feedurl);
+	reader.update_rssitem_unread_and_enqueued(*feed->items()[0], feedurl);
+	REQUIRE(reader.get_read_item_guids().empty());
+	REQUIRE(rsscache->get_read_item_guids().empty());
+
+	// The writer can still modify the cache
+	rsscache->mark_all_read(feedurl);
+	REQUIRE(reader.get_read_item_guids().size() == 8);
+}
+
+TEST_CASE("Cache can't be opened read-only if the file doesn't exist",
+	"[Cache]")
+{
+	test_helpers::TempFile dbfile;
+	ConfigContainer cfg;
+
+	REQUIRE_THROWS_AS(Cache(dbfile.get_path(), cfg, CacheMode::ReadOnly),
+		DbException);
+}
+
+TEST_CASE("Cache opened in read-only mode doesn't throw on methods that "
+	"modify the cache",
+	"[Cache]")
+{
+	test_helpers::TempFile dbfile;
+	ConfigContainer cfg;
+	auto rsscache = std::make_unique<Cache>(dbfile.get_path(), cfg);
+
+	const auto feedurl = "file://data/rss.xml";
+	CurlHandle easyHandle;
+	FeedRetriever feed_retriever(cfg, *rsscache, easyHandle);
+	RssParser parser(feedurl, *rsscache, cfg, nullptr);
+	auto feed = parser.parse(feed_retriever.retrieve(feedurl));
+	rsscache->externalize_rssfeed(*feed, false);
+
+	Cache reader(dbfile.get_path(), cfg, CacheMode::ReadOnly);
+	feed = reader.internalize_rssfeed(feedurl, nullptr);
+	auto item = feed->items()[0];
+	item->set_flags("abc");
+
+	REQUIRE_NOTHROW(reader.externalize_rssfeed(*feed, true));
+	REQUIRE_NOTHROW(reader.update_rssitem_flags(item.get()));
+	REQUIRE_NOTHROW(reader.update_lastmodified(feedurl, 42, "etag"));
+	REQUIRE_NOTHROW(reader.mark_item_deleted(item->guid(), true));
+	REQUIRE_NOTHROW(reader.mark_items_read_by_guid({item->guid()}));
+	REQUIRE_NOTHROW(reader.remove_old_deleted_items(feed.get()));
+	REQUIRE_NOTHROW(reader.do_vacuum());
+	REQUIRE_NOTHROW(reader.cleanup_cache({}, true));
+
+	// None of the changes made it into the file
+	REQUIRE(rsscache->get_read_item_guids().empty());
+	feed = rsscache->internalize_rssfeed(feedurl, nullptr);
+	REQUIRE(feed->total_item_count() == 8);
+	REQUIRE(feed->items()[0]->flags().empty());
+}
+
+TEST_CASE("Cache can't be opened read-only if its schema is outdated",
+	"[Cache]")
+{
+	test_helpers::TempFile dbfile;
+	ConfigContainer cfg;
+	{
+		Cache rsscache(dbfile.get_path(), cfg);
+	}
+
+	sqlite3* db = nullptr;
+	REQUIRE(sqlite3_open(dbfile.get_path().to_locale_string().c_str(), &db)
+		== SQLITE_OK);
+	const int rc = sqlite3_exec(db,
+			"UPDATE metadata SET db_schema_version_major = 2, "
+			"db_schema_version_minor = 10;",
+			nullptr, nullptr, nullptr);
+	sqlite3_close(db);
+	REQUIRE(rc == SQLITE_OK);
+
+	REQUIRE_THROWS_AS(Cache(dbfile.get_path(), cfg, CacheMode::ReadOnly),
+		DbException);
+}
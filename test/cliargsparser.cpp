@@ -525,6 +525,255 @@ TEST_CASE("Sets `do_cleanup` if --cleanup is provided", "[CliArgsParser]")
 	}
 }
 
+TEST_CASE("Sets `dry_run` if --dry-run is provided along with -x, -X or "
+	"--cleanup",
+	"[CliArgsParser]")
+{
+	auto check = [](test_helpers::Opts opts) {
+		CliArgsParser args(opts.argc(), opts.argv());
+
+		REQUIRE(args.dry_run());
+		REQUIRE_FALSE(args.return_code().has_value());
+	};
+
+	SECTION("-x reload") {
+		check({"newsboat", "--dry-run", "-x", "reload"});
+	}
+
+	SECTION("-X") {
+		check({"newsboat", "-X", "--dry-run"});
+	}
+
+	SECTION("--cleanup") {
+		check({"newsboat", "--cleanup", "--dry-run"});
+	}
+
+	SECTION("--migrate") {
+		check({"newsboat", "--migrate", "--dry-run"});
+	}
+}
+
+TEST_CASE("Sets `do_migrate` and `silent` if --migrate is provided",
+	"[CliArgsParser]")
+{
+	const test_helpers::Opts opts{"newsboat", "--migrate"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE(args.do_migrate());
+	REQUIRE(args.silent());
+	REQUIRE_FALSE(args.dry_run());
+	REQUIRE_FALSE(args.return_code().has_value());
+}
+
+TEST_CASE("Sets `force_unlock` if --force-unlock is provided",
+	"[CliArgsParser]")
+{
+	const test_helpers::Opts opts{"newsboat", "--force-unlock"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE(args.force_unlock());
+	REQUIRE_FALSE(args.return_code().has_value());
+}
+
+TEST_CASE("Sets `wait_lock` if --wait-lock is provided", "[CliArgsParser]")
+{
+	const test_helpers::Opts opts{"newsboat", "--wait-lock=120"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE(args.wait_lock() == 120u);
+	REQUIRE_FALSE(args.return_code().has_value());
+}
+
+TEST_CASE("Sets `remote_command` and `silent` if --remote is provided",
+	"[CliArgsParser]")
+{
+	const test_helpers::Opts opts{"newsboat", "--remote", "unread-count"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE(args.remote_command() == "unread-count");
+	REQUIRE(args.silent());
+	REQUIRE_FALSE(args.return_code().has_value());
+}
+
+TEST_CASE("Sets `lint_keymap` and `silent` if --lint-keymap is provided",
+	"[CliArgsParser]")
+{
+	test_helpers::Opts opts = {"newsboat", "--lint-keymap"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE(args.lint_keymap());
+	REQUIRE(args.silent());
+}
+
+TEST_CASE("Sets `export_keymap` and `silent` if --export-keymap is provided",
+	"[CliArgsParser]")
+{
+	auto check = [](test_helpers::Opts opts, const std::string& expected) {
+		CliArgsParser args(opts.argc(), opts.argv());
+
+		REQUIRE(args.export_keymap() == expected);
+		REQUIRE(args.silent());
+	};
+
+	SECTION("markdown") {
+		check({"newsboat", "--export-keymap=markdown"}, "markdown");
+	}
+
+	SECTION("man is an alias for roff") {
+		check({"newsboat", "--export-keymap=man"}, "roff");
+	}
+
+	SECTION("json") {
+		check({"newsboat", "--export-keymap", "json"}, "json");
+	}
+}
+
+TEST_CASE("Sets `display_msg` and `return_code` if --export-keymap is given "
+	"an unknown format",
+	"[CliArgsParser]")
+{
+	test_helpers::Opts opts = {"newsboat", "--export-keymap=html"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE_FALSE(args.display_msg().empty());
+	REQUIRE(args.return_code() == EXIT_FAILURE);
+	REQUIRE_FALSE(args.export_keymap().has_value());
+}
+
+TEST_CASE("Sets `create_profile` if --create-profile is provided",
+	"[CliArgsParser]")
+{
+	test_helpers::Opts opts = {"newsboat", "--create-profile=work"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE(args.create_profile());
+	REQUIRE_FALSE(args.using_nonstandard_configs());
+	REQUIRE_FALSE(args.return_code().has_value());
+}
+
+TEST_CASE("Sets `list_profiles` if --list-profiles is provided",
+	"[CliArgsParser]")
+{
+	test_helpers::Opts opts = {"newsboat", "--list-profiles"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE(args.list_profiles());
+	REQUIRE_FALSE(args.create_profile());
+}
+
+TEST_CASE("Sets `display_msg` and `return_code` if --profile is given "
+	"an invalid name",
+	"[CliArgsParser]")
+{
+	test_helpers::Opts opts = {"newsboat", "--profile=../work"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE_FALSE(args.display_msg().empty());
+	REQUIRE(args.return_code() == EXIT_FAILURE);
+}
+
+TEST_CASE("Sets `completions` to a script for the shell if --completions is "
+	"provided",
+	"[CliArgsParser]")
+{
+	auto check = [](test_helpers::Opts opts, const std::string& expected) {
+		CliArgsParser args(opts.argc(), opts.argv());
+
+		const auto script = args.completions();
+		REQUIRE(script.has_value());
+		REQUIRE(script.value().find(expected) != std::string::npos);
+	};
+
+	SECTION("bash") {
+		check({"newsboat", "--completions=bash"},
+			"complete -o filenames -F _newsboat newsboat");
+	}
+
+	SECTION("zsh") {
+		check({"newsboat", "--completions", "zsh"}, "#compdef newsboat");
+	}
+
+	SECTION("fish") {
+		check({"newsboat", "--completions=fish"}, "complete -c newsboat");
+	}
+}
+
+TEST_CASE("options_usage() lists options that the parser accepts",
+	"[CliArgsParser]")
+{
+	const auto usage = CliArgsParser::options_usage();
+
+	REQUIRE(usage.find("-u, --url-file=<file>") != std::string::npos);
+	REQUIRE(usage.find("--completions=<bash|zsh|fish>") != std::string::npos);
+	REQUIRE(usage.find("--remote=<command>") != std::string::npos);
+
+	// Subcommand options are described along with subcommands
+	REQUIRE(usage.find("--unread") == std::string::npos);
+}
+
+TEST_CASE("Sets `display_msg` and `return_code` if --completions is given "
+	"an unsupported shell",
+	"[CliArgsParser]")
+{
+	test_helpers::Opts opts = {"newsboat", "--completions=tcsh"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE_FALSE(args.display_msg().empty());
+	REQUIRE(args.return_code() == EXIT_FAILURE);
+	REQUIRE_FALSE(args.completions().has_value());
+}
+
+TEST_CASE("Sets `subcommand` and `silent` if a subcommand is provided",
+	"[CliArgsParser]")
+{
+	using cliargsparser::bridged::SubcommandKind;
+
+	SECTION("feeds list") {
+		test_helpers::Opts opts = {"newsboat", "feeds", "list", "--format=json"};
+		CliArgsParser args(opts.argc(), opts.argv());
+
+		const auto subcommand = args.subcommand();
+		REQUIRE(subcommand.has_value());
+		REQUIRE(subcommand->kind == SubcommandKind::ListFeeds);
+		REQUIRE(std::string(subcommand->format) == "json");
+		REQUIRE(args.silent());
+	}
+
+	SECTION("items list") {
+		test_helpers::Opts opts = {"newsboat", "items", "list", "--feed", "https://example.com", "--unread"};
+		CliArgsParser args(opts.argc(), opts.argv());
+
+		const auto subcommand = args.subcommand();
+		REQUIRE(subcommand.has_value());
+		REQUIRE(subcommand->kind == SubcommandKind::ListItems);
+		REQUIRE(std::string(subcommand->format) == "tsv");
+		REQUIRE(subcommand->has_feed);
+		REQUIRE(std::string(subcommand->feed) == "https://example.com");
+		REQUIRE(subcommand->unread_only);
+	}
+
+	SECTION("items mark-read") {
+		test_helpers::Opts opts = {"newsboat", "items", "mark-read", "a", "b"};
+		CliArgsParser args(opts.argc(), opts.argv());
+
+		const auto subcommand = args.subcommand();
+		REQUIRE(subcommand.has_value());
+		REQUIRE(subcommand->kind == SubcommandKind::MarkItemsRead);
+		REQUIRE(subcommand->guids.size() == 2);
+		REQUIRE(std::string(subcommand->guids[0]) == "a");
+		REQUIRE(std::string(subcommand->guids[1]) == "b");
+	}
+}
+
+TEST_CASE("`subcommand` is not set if no subcommand is provided",
+	"[CliArgsParser]")
+{
+	test_helpers::Opts opts = {"newsboat", "-r"};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	REQUIRE_FALSE(args.subcommand().has_value());
+}
+
 TEST_CASE("Increases `show_version` with each -v/-V/--version provided",
 	"[CliArgsParser]")
 {
@@ -630,6 +879,22 @@ TEST_CASE("Inserts commands to cmds_to_execute if -x/--execute is provided",
 	}
 }
 
+TEST_CASE("Collects (name, value) pairs from -o options in order",
+	"[CliArgsParser]")
+{
+	test_helpers::Opts opts = {
+		"newsboat", "-o", "browser=w3m", "-o", "max-items=10", "-o", "browser=lynx"
+	};
+	CliArgsParser args(opts.argc(), opts.argv());
+
+	const std::vector<std::pair<std::string, std::string>> expected = {
+		{"browser", "w3m"},
+		{"max-items", "10"},
+		{"browser", "lynx"},
+	};
+	REQUIRE(args.config_overrides() == expected);
+}
+
 TEST_CASE("Requests silent mode if -q/--quiet is provided", "[CliArgsParser]")
 {
 	auto check = [](test_helpers::Opts opts) {
@@ -7,6 +7,7 @@
 #include "confighandlerexception.h"
 #include "configparser.h"
 #include "keymap.h"
+#include "test_helpers/envvar.h"
 
 using namespace newsboat;
 
@@ -101,6 +102,31 @@ TEST_CASE("Throws if command argument has invalid type", "[ConfigContainer]")
 	}
 }
 
+TEST_CASE("Invalid format strings are stored despite the warning",
+	"[ConfigContainer]")
+{
+	ConfigContainer cfg;
+
+	REQUIRE_NOTHROW(cfg.handle_action("articlelist-format", {"%4i %X %t"}));
+	REQUIRE(cfg.get_configvalue("articlelist-format") == "%4i %X %t");
+	REQUIRE(cfg.get_format_errors("articlelist-format").size() == 1);
+
+	REQUIRE_NOTHROW(cfg.handle_action("feedlist-title-format", {"%?T?tag %T"}));
+	REQUIRE(cfg.get_configvalue("feedlist-title-format") == "%?T?tag %T");
+}
+
+TEST_CASE("Default values of format string settings are valid",
+	"[ConfigContainer]")
+{
+	ConfigContainer cfg;
+
+	for (const auto& setting : cfg.get_suggestions("")) {
+		const auto value = cfg.get_configvalue(setting);
+		INFO("setting: " << setting << ", value: " << value);
+		REQUIRE(cfg.get_format_errors(setting).empty());
+	}
+}
+
 TEST_CASE("Throws if there are too few arguments", "[ConfigContainer]")
 {
 	ConfigContainer cfg;
@@ -738,3 +764,47 @@ TEST_CASE("get_article_sort_strategy() returns ascending direction "
 		REQUIRE(cfg.get_article_sort_strategy().sd == SortDirection::ASC);
 	}
 }
+
+TEST_CASE("apply_overrides() applies environment variables, then -o options",
+	"[ConfigContainer]")
+{
+	test_helpers::EnvVar browser("NEWSBOAT_BROWSER");
+	browser.set("lynx");
+	test_helpers::EnvVar show_read_feeds("NEWSBOAT_SHOW_READ_FEEDS");
+	show_read_feeds.set("no");
+
+	const std::vector<std::pair<std::string, std::string>> assignments = {
+		{"browser", "w3m"},
+		{"max-items", "10"},
+	};
+
+	ConfigContainer cfg;
+	REQUIRE(cfg.apply_overrides(assignments));
+
+	REQUIRE(cfg.get_configvalue("browser") == "w3m");
+	REQUIRE(cfg.get_configvalue("show-read-feeds") == "no");
+	REQUIRE(cfg.get_configvalue_as_int("max-items") == 10);
+
+	const std::vector<std::string> expected = {
+		"browser = lynx (from NEWSBOAT_BROWSER)",
+		"show-read-feeds = no (from NEWSBOAT_SHOW_READ_FEEDS)",
+		"browser = w3m (from -o)",
+		"max-items = 10 (from -o)",
+	};
+	REQUIRE(cfg.describe_overrides(assignments) == expected);
+}
+
+TEST_CASE("apply_overrides() returns an error if an override names an unknown "
+	"setting or has an invalid value",
+	"[ConfigContainer]")
+{
+	ConfigContainer cfg;
+
+	SECTION("Unknown setting") {
+		REQUIRE_FALSE(cfg.apply_overrides({{"no-such-setting", "1"}}));
+	}
+
+	SECTION("Invalid value") {
+		REQUIRE_FALSE(cfg.apply_overrides({{"max-items", "many"}}));
+	}
+}
@@ -74,6 +74,33 @@ TEST_CASE("set_value() returns error if new value for a \"number\" setting "
 	REQUIRE_FALSE(setting.set_value("123 minutes"));
 }
 
+TEST_CASE("format_errors() describes problems with the value of a format "
+	"string setting, which is still stored",
+	"[ConfigData]")
+{
+	auto setting = ConfigData::format_string("%a", "ab");
+
+	REQUIRE(setting.set_value("%b %-*a %?a?(%a)&none?"));
+	REQUIRE(setting.format_errors().empty());
+
+	for (const std::string value : {
+			"%c", "%?a?unterminated", "%99999999999999999999a"
+		}) {
+		INFO("value: " << value);
+		REQUIRE(setting.set_value(value));
+		REQUIRE(setting.value() == value);
+		REQUIRE_FALSE(setting.format_errors().empty());
+	}
+}
+
+TEST_CASE("format_errors() is empty for settings that aren't format strings",
+	"[ConfigData]")
+{
+	ConfigData setting("%c", ConfigDataType::STR);
+
+	REQUIRE(setting.format_errors().empty());
+}
+
 TEST_CASE("set_value() returns error if new value for an \"enum\" setting "
 	"does not belong to the enum",
 	"[ConfigData]")
@@ -10,7 +10,9 @@
 #include "keycombination.h"
 #include "keymap.h"
 #include "test_helpers/envvar.h"
+#include "test_helpers/tempdir.h"
 #include "test_helpers/tempfile.h"
+#include "utils.h"
 
 using namespace newsboat;
 
@@ -322,3 +324,44 @@ TEST_CASE("`include` directive includes other config files", "[ConfigParser]")
 		REQUIRE(line_count == 1); // only 1 line from date command
 	}
 }
+
+TEST_CASE("`include` expands glob patterns", "[ConfigParser]")
+{
+	test_helpers::TempDir tmp;
+	const auto fragments = tmp.get_path().join("conf.d"_path);
+	REQUIRE(utils::mkdir_parents(fragments, 0700) == 0);
+
+	const auto write = [](const Filepath& path, const std::string& content) {
+		std::ofstream out(path.to_locale_string());
+		out << content << std::endl;
+	};
+	write(fragments.join("20-second.conf"_path), "command-name second");
+	write(fragments.join("10-first.conf"_path), "command-name first");
+	write(fragments.join("30-ignored.txt"_path), "command-name ignored");
+
+	ConfigParser cfgparser;
+	ConfigHandlerHistoryDummy handler;
+	cfgparser.register_handler("command-name", handler);
+
+	const auto config = tmp.get_path().join("config"_path);
+
+	SECTION("matching files are included in lexical order") {
+		write(config, "include conf.d/*.conf");
+		REQUIRE_NOTHROW(cfgparser.parse_file(config));
+
+		REQUIRE(handler.history.size() == 2);
+		REQUIRE(handler.history[0].second == std::vector<std::string>({"first"}));
+		REQUIRE(handler.history[1].second == std::vector<std::string>({"second"}));
+	}
+
+	SECTION("a pattern that matches nothing is not an error") {
+		write(config, "include conf.d/*.missing");
+		REQUIRE_NOTHROW(cfgparser.parse_file(config));
+		REQUIRE(handler.history.empty());
+	}
+
+	SECTION("a missing file without wildcards is still an error") {
+		write(config, "include conf.d/missing.conf");
+		REQUIRE_THROWS_AS(cfgparser.parse_file(config), ConfigException);
+	}
+}
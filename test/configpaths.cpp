@@ -40,6 +40,8 @@ TEST_CASE("ConfigPaths returns paths to Newsboat dotdir if no Newsboat dirs "
 	REQUIRE(paths.queue_file() == newsboat_dir.join("queue"_path));
 	REQUIRE(paths.search_history_file() == newsboat_dir.join("history.search"_path));
 	REQUIRE(paths.cmdline_history_file() == newsboat_dir.join("history.cmdline"_path));
+	REQUIRE_FALSE(paths.log_dir().has_value());
+	REQUIRE_FALSE(paths.legacy_lock_file().has_value());
 }
 
 TEST_CASE("ConfigPaths returns paths to Newsboat XDG dirs if they exist and "
@@ -61,6 +63,13 @@ TEST_CASE("ConfigPaths returns paths to Newsboat XDG dirs if they exist and "
 	xdg_config.unset();
 	test_helpers::EnvVar xdg_data("XDG_DATA_HOME");
 	xdg_data.unset();
+	test_helpers::EnvVar xdg_state("XDG_STATE_HOME");
+	xdg_state.unset();
+	// Without a runtime directory, the lock goes into the state directory
+	test_helpers::EnvVar xdg_runtime("XDG_RUNTIME_DIR");
+	xdg_runtime.unset();
+
+	const auto state_dir = tmp.get_path().join(".local/state/newsboat"_path);
 
 	const auto check = [&]() {
 		ConfigPaths paths;
@@ -68,10 +77,15 @@ TEST_CASE("ConfigPaths returns paths to Newsboat XDG dirs if they exist and "
 		REQUIRE(paths.config_file() == config_dir.join("config"_path));
 		REQUIRE(paths.url_file() == config_dir.join("urls"_path));
 		REQUIRE(paths.cache_file() == data_dir.join("cache.db"_path));
-		REQUIRE(paths.lock_file() == data_dir.join("cache.db.lock"_path));
 		REQUIRE(paths.queue_file() == data_dir.join("queue"_path));
-		REQUIRE(paths.search_history_file() == data_dir.join("history.search"_path));
-		REQUIRE(paths.cmdline_history_file() == data_dir.join("history.cmdline"_path));
+		REQUIRE(paths.lock_file() == state_dir.join("cache.db.lock"_path));
+		REQUIRE(paths.lock_dir() == state_dir);
+		// Older versions kept the lock next to the cache
+		REQUIRE(paths.legacy_lock_file().value()
+			== data_dir.join("cache.db.lock"_path));
+		REQUIRE(paths.search_history_file() == state_dir.join("history.search"_path));
+		REQUIRE(paths.cmdline_history_file() == state_dir.join("history.cmdline"_path));
+		REQUIRE(paths.log_dir().value() == state_dir);
 	};
 
 	SECTION("XDG_CONFIG_HOME is set") {
@@ -143,6 +157,53 @@ TEST_CASE("ConfigPaths::process_args replaces paths with the ones supplied by "
 	REQUIRE(paths.config_file() == config_file);
 }
 
+TEST_CASE("ConfigPaths::process_args resolves files under the profile's "
+	"directories if --profile is given",
+	"[ConfigPaths]")
+{
+	test_helpers::TempDir tmp;
+
+	test_helpers::EnvVar home("HOME");
+	home.set(tmp.get_path().to_locale_string());
+	INFO("Temporary directory (used as HOME): " << tmp.get_path());
+
+	// ConfigPaths rely on these variables, so let's sanitize them to ensure
+	// that the tests aren't affected
+	test_helpers::EnvVar xdg_config("XDG_CONFIG_HOME");
+	xdg_config.unset();
+	test_helpers::EnvVar xdg_data("XDG_DATA_HOME");
+	xdg_data.unset();
+	test_helpers::EnvVar xdg_state("XDG_STATE_HOME");
+	xdg_state.unset();
+	test_helpers::EnvVar xdg_runtime("XDG_RUNTIME_DIR");
+	xdg_runtime.unset();
+
+	const auto config_dir =
+		tmp.get_path().join(".config/newsboat/profiles/work"_path);
+	const auto data_dir =
+		tmp.get_path().join(".local/share/newsboat/profiles/work"_path);
+	const auto state_dir =
+		tmp.get_path().join(".local/state/newsboat/profiles/work"_path);
+
+	test_helpers::Opts opts({"newsboat", "--profile=work"});
+	CliArgsParser parser(opts.argc(), opts.argv());
+	ConfigPaths paths;
+	REQUIRE(paths.initialized());
+	paths.process_args(parser);
+
+	REQUIRE(paths.profile() == "work");
+	REQUIRE_FALSE(paths.profile_exists());
+	REQUIRE(paths.url_file() == config_dir.join("urls"_path));
+	REQUIRE(paths.config_file() == config_dir.join("config"_path));
+	REQUIRE(paths.cache_file() == data_dir.join("cache.db"_path));
+	REQUIRE(paths.lock_file() == state_dir.join("cache.db.lock"_path));
+	REQUIRE(paths.search_history_file() == state_dir.join("history.search"_path));
+
+	REQUIRE(paths.create_dirs());
+	REQUIRE(paths.profile_exists());
+	REQUIRE(paths.list_profiles() == std::vector<std::string>({"work"}));
+}
+
 TEST_CASE("ConfigPaths::set_cache_file changes paths to cache and lock files",
 	"[ConfigPaths]")
 {
@@ -1376,3 +1437,138 @@ TEST_CASE("create_dirs() returns false if XDG config dir exists but data dir "
 		verify_create_dirs_returns_false(tmp);
 	}
 }
+
+TEST_CASE("config_files() and url_files() list the fragments after the main "
+	"files",
+	"[ConfigPaths]")
+{
+	test_helpers::TempDir tmp;
+	const auto newsboat_dir = tmp.get_path().join(".newsboat"_path);
+	REQUIRE(0 == utils::mkdir_parents(newsboat_dir.join("config.d"_path), 0700));
+	REQUIRE(0 == utils::mkdir_parents(newsboat_dir.join("urls.d"_path), 0700));
+
+	test_helpers::EnvVar home("HOME");
+	home.set(tmp.get_path().to_locale_string());
+
+	// ConfigPaths rely on these variables, so let's sanitize them to ensure
+	// that the tests aren't affected
+	test_helpers::EnvVar xdg_config("XDG_CONFIG_HOME");
+	xdg_config.unset();
+	test_helpers::EnvVar xdg_data("XDG_DATA_HOME");
+	xdg_data.unset();
+
+	for (const auto& name : {
+			"config.d/b.conf", "config.d/a.conf", "config.d/README",
+			"urls.d/work", "urls.d/news"
+		}) {
+		std::ofstream(newsboat_dir.join(Filepath::from_locale_string(name)).to_locale_string());
+	}
+
+	ConfigPaths paths;
+	REQUIRE(paths.initialized());
+
+	const std::vector<Filepath> expected_config_files = {
+		newsboat_dir.join("config"_path),
+		newsboat_dir.join("config.d/a.conf"_path),
+		newsboat_dir.join("config.d/b.conf"_path),
+	};
+	REQUIRE(paths.config_files() == expected_config_files);
+
+	const std::vector<Filepath> expected_url_files = {
+		newsboat_dir.join("urls"_path),
+		newsboat_dir.join("urls.d/news"_path),
+		newsboat_dir.join("urls.d/work"_path),
+	};
+	REQUIRE(paths.url_files() == expected_url_files);
+}
+
+TEST_CASE("migrate() records the version of Newsboat's files once it moved "
+	"some, and migration_report() describes pending steps",
+	"[ConfigPaths]")
+{
+	test_helpers::TempDir tmp;
+	const auto config_dir = tmp.get_path().join(".config/newsboat"_path);
+	REQUIRE(0 == utils::mkdir_parents(config_dir, 0700));
+	const auto data_dir = tmp.get_path().join(".local/share/newsboat"_path);
+	REQUIRE(0 == utils::mkdir_parents(data_dir, 0700));
+	const auto state_dir = tmp.get_path().join(".local/state/newsboat"_path);
+
+	test_helpers::EnvVar home("HOME");
+	home.set(tmp.get_path().to_locale_string());
+
+	// ConfigPaths rely on these variables, so let's sanitize them to ensure
+	// that the tests aren't affected
+	test_helpers::EnvVar xdg_config("XDG_CONFIG_HOME");
+	xdg_config.unset();
+	test_helpers::EnvVar xdg_data("XDG_DATA_HOME");
+	xdg_data.unset();
+	test_helpers::EnvVar xdg_state("XDG_STATE_HOME");
+	xdg_state.unset();
+
+	ConfigPaths paths;
+	REQUIRE(paths.initialized());
+
+	const auto version_file = data_dir.join("data-version"_path);
+
+	SECTION("nothing is recorded if there are no files to move") {
+		REQUIRE(paths.migrate().has_value());
+		REQUIRE_FALSE(0 == access(version_file.to_locale_string().c_str(), F_OK));
+	}
+
+	SECTION("history files are moved into the state directory") {
+		test_helpers::copy_file("data/test-urls.txt"_path,
+			data_dir.join("history.search"_path));
+
+		const auto report = paths.migration_report();
+		REQUIRE(report.has_value());
+		REQUIRE_FALSE(report.value().empty());
+		REQUIRE(report.value()[0].find("from version 0") != std::string::npos);
+		REQUIRE_FALSE(0 == access(version_file.to_locale_string().c_str(), F_OK));
+
+		REQUIRE(paths.migrate().has_value());
+		REQUIRE(0 == access(version_file.to_locale_string().c_str(), F_OK));
+		REQUIRE(0 == access(state_dir.join("history.search"_path).to_locale_string().c_str(),
+				F_OK));
+
+		const auto up_to_date = paths.migration_report();
+		REQUIRE(up_to_date.has_value());
+		REQUIRE(up_to_date.value().size() == 1);
+		REQUIRE(up_to_date.value()[0].find("up to date") != std::string::npos);
+	}
+
+	SECTION("invalid version file is reported as an error") {
+		test_helpers::copy_file("data/test-urls.txt"_path, version_file);
+		REQUIRE_FALSE(paths.migration_report().has_value());
+		REQUIRE_FALSE(paths.migrate().has_value());
+	}
+}
+
+TEST_CASE("ConfigPaths puts all files into NEWSBOAT_HOME if it's set",
+	"[ConfigPaths]")
+{
+	test_helpers::TempDir tmp;
+	const auto newsboat_dir = tmp.get_path().join(".newsboat"_path);
+	REQUIRE(0 == utils::mkdir_parents(newsboat_dir, 0700));
+
+	test_helpers::EnvVar home("HOME");
+	home.set(tmp.get_path().to_locale_string());
+
+	const auto portable_dir = tmp.get_path().join("portable"_path);
+	test_helpers::EnvVar newsboat_home("NEWSBOAT_HOME");
+	newsboat_home.set(portable_dir.to_locale_string());
+
+	ConfigPaths paths;
+	REQUIRE(paths.initialized());
+	REQUIRE(paths.portable_dir().value() == portable_dir);
+	REQUIRE(paths.url_file() == portable_dir.join("urls"_path));
+	REQUIRE(paths.cache_file() == portable_dir.join("cache.db"_path));
+	REQUIRE(paths.lock_file() == portable_dir.join("cache.db.lock"_path));
+	REQUIRE(paths.config_file() == portable_dir.join("config"_path));
+	REQUIRE(paths.queue_file() == portable_dir.join("queue"_path));
+	REQUIRE(paths.search_history_file() == portable_dir.join("history.search"_path));
+	REQUIRE(paths.cmdline_history_file() == portable_dir.join("history.cmdline"_path));
+	REQUIRE(paths.log_dir().value() == portable_dir);
+
+	REQUIRE(paths.create_dirs());
+	REQUIRE(0 == access(portable_dir.to_locale_string().c_str(), R_OK | X_OK));
+}
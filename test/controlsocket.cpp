@@ -0,0 +1,133 @@
+#include "controlsocket.h"
+
+#include <chrono>
+#include <future>
+#include <poll.h>
+#include <thread>
+#include <unistd.h>
+
+#include "3rd-party/catch.hpp"
+#include "test_helpers/tempdir.h"
+
+using namespace newsboat;
+using controlsocket::bridged::CommandKind;
+
+namespace {
+
+/// Sends `command` from another thread, and handles it on this one, like the
+/// main loop does.
+ControlSocket::Reply send_and_handle(ControlSocket& socket,
+	const Filepath& path, const std::string& command)
+{
+	auto reply = std::async(std::launch::async, [&]() {
+		return ControlSocket::send(path, command);
+	});
+	while (reply.wait_for(std::chrono::milliseconds(0))
+		!= std::future_status::ready) {
+		pollfd wakeup{socket.wakeup_fd(), POLLIN, 0};
+		::poll(&wakeup, 1, 10);
+		socket.handle_requests();
+	}
+	return reply.get();
+}
+
+} // namespace
+
+TEST_CASE("socket_path() puts the socket next to the lock file",
+	"[ControlSocket]")
+{
+	REQUIRE(ControlSocket::socket_path("/tmp/newsboat/cache.db.lock"_path)
+		== "/tmp/newsboat/cache.db.sock"_path);
+}
+
+TEST_CASE("handle_requests() passes commands to the handler and send() "
+	"returns its reply",
+	"[ControlSocket]")
+{
+	test_helpers::TempDir tmp;
+	const auto path = tmp.get_path().join("cache.db.sock"_path);
+
+	ControlSocket socket;
+	const auto started = socket.start(path, [](const RemoteCommand& command) {
+		if (command.kind == CommandKind::UnreadCount && !command.argument.has_value()) {
+			return nonstd::expected<std::string, std::string>("42");
+		}
+		return nonstd::expected<std::string, std::string>(
+			nonstd::make_unexpected(std::string("unexpected command")));
+	});
+	REQUIRE(started.has_value());
+
+	SECTION("successful command") {
+		const auto reply = send_and_handle(socket, path, "unread-count");
+		REQUIRE(reply.has_value());
+		REQUIRE(reply.value() == "42");
+	}
+
+	SECTION("failed command") {
+		const auto reply = send_and_handle(socket, path, "reload");
+		REQUIRE_FALSE(reply.has_value());
+		REQUIRE(reply.error() == "unexpected command");
+	}
+
+	SECTION("malformed command") {
+		const auto reply = send_and_handle(socket, path, "launch-rockets");
+		REQUIRE_FALSE(reply.has_value());
+	}
+}
+
+TEST_CASE("Commands wait for handle_requests(), which calls the handler on "
+	"its own thread",
+	"[ControlSocket]")
+{
+	test_helpers::TempDir tmp;
+	const auto path = tmp.get_path().join("cache.db.sock"_path);
+
+	std::thread::id handler_thread;
+	ControlSocket socket;
+	REQUIRE(socket.start(path, [&](const RemoteCommand&) {
+		handler_thread = std::this_thread::get_id();
+		return ControlSocket::Reply("");
+	}).has_value());
+
+	// Nothing is waiting, so this returns right away
+	socket.handle_requests();
+	REQUIRE(handler_thread == std::thread::id());
+
+	auto reply = std::async(std::launch::async, [&]() {
+		return ControlSocket::send(path, "reload");
+	});
+
+	// The socket wakes us up once the command arrives...
+	pollfd wakeup{socket.wakeup_fd(), POLLIN, 0};
+	REQUIRE(::poll(&wakeup, 1, 5000) == 1);
+	// ...but the client doesn't get a reply until we handle it
+	REQUIRE(reply.wait_for(std::chrono::milliseconds(100))
+		== std::future_status::timeout);
+
+	socket.handle_requests();
+	REQUIRE(handler_thread == std::this_thread::get_id());
+	REQUIRE(reply.get().has_value());
+}
+
+TEST_CASE("send() fails if nobody listens on the socket", "[ControlSocket]")
+{
+	test_helpers::TempDir tmp;
+	const auto path = tmp.get_path().join("cache.db.sock"_path);
+
+	REQUIRE_FALSE(ControlSocket::send(path, "reload").has_value());
+}
+
+TEST_CASE("stop() removes the socket", "[ControlSocket]")
+{
+	test_helpers::TempDir tmp;
+	const auto path = tmp.get_path().join("cache.db.sock"_path);
+
+	ControlSocket socket;
+	REQUIRE(socket.start(path, [](const RemoteCommand&) {
+		return ControlSocket::Reply("");
+	}).has_value());
+	REQUIRE(::access(path.to_locale_string().c_str(), F_OK) == 0);
+
+	socket.stop();
+	REQUIRE(::access(path.to_locale_string().c_str(), F_OK) != 0);
+}
@@ -290,4 +290,45 @@ TEST_CASE("FileUrlReader handles duplicate URLs by merging tags",
 	REQUIRE(u.get_tags("http://anotherfeed.com/")[1] == "tag2");
 	REQUIRE(u.get_tags("http://anotherfeed.com/")[2] == "tag3");
 	REQUIRE(u.get_tags("http://anotherfeed.com/")[3] == "tag4");
-}
\ No newline at end of file
+}
+
+TEST_CASE("URL reader reads fragments after the urls file, but doesn't write "
+	"them back",
+	"[FileUrlReader]")
+{
+	test_helpers::TempFile urlsFile;
+	test_helpers::TempFile fragment;
+
+	{
+		std::ofstream f(urlsFile.get_path().to_locale_string());
+		f << "https://example.com/main.xml" << std::endl;
+	}
+	{
+		std::ofstream f(fragment.get_path().to_locale_string());
+		f << "# a comment" << std::endl;
+		f << "https://example.com/fragment.xml \"work\"" << std::endl;
+		f << "https://example.com/main.xml \"merged\"" << std::endl;
+	}
+
+	FileUrlReader u(urlsFile.get_path(), {fragment.get_path()});
+	REQUIRE_FALSE(u.reload().has_value());
+
+	const auto urls = u.get_urls();
+	REQUIRE(urls.size() == 2);
+	REQUIRE(urls[0].first == "https://example.com/main.xml");
+	REQUIRE(urls[0].second.file_origin->file == urlsFile.get_path());
+	REQUIRE(urls[0].second.file_origin->line_number == 1);
+	REQUIRE(urls[1].first == "https://example.com/fragment.xml");
+	REQUIRE(urls[1].second.file_origin->file == fragment.get_path());
+	REQUIRE(urls[1].second.file_origin->line_number == 2);
+	REQUIRE(u.get_tags("https://example.com/main.xml") == std::vector<std::string>({"merged"}));
+
+	u.add_url("https://example.com/new.xml", {});
+	REQUIRE_FALSE(u.write_config().has_value());
+
+	const auto content = test_helpers::file_contents(urlsFile.get_path());
+	REQUIRE(content.size() >= 2);
+	REQUIRE(content[0] == R"(https://example.com/main.xml "merged")");
+	REQUIRE(content[1] == "https://example.com/new.xml");
+	REQUIRE(test_helpers::file_contents(fragment.get_path()).size() >= 3);
+}
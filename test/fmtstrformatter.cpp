@@ -406,3 +406,45 @@ TEST_CASE("Center Text formatting",
 	REQUIRE(fmt.do_format("%=3T", 0) == "wha");
 	REQUIRE(fmt.do_format("%=0T", 20) == "      whatever      ");
 }
+
+TEST_CASE("do_format_rows() pads \"auto width\" specifiers to the widest "
+	"value among all rows",
+	"[FmtStrFormatter]")
+{
+	const auto make_row = [](const std::string& title, const std::string& count) {
+		FmtStrFormatter fmt;
+		fmt.register_fmt('t', title);
+		fmt.register_fmt('n', count);
+		return fmt;
+	};
+
+	SECTION("%-*x pads on the right, %*x pads on the left") {
+		std::vector<FmtStrFormatter> rows;
+		rows.push_back(make_row("Short", "7"));
+		rows.push_back(make_row("A longer title", "123"));
+
+		const auto formatted = FmtStrFormatter::do_format_rows(std::move(rows),
+				"%-*t|%*n|");
+		REQUIRE(formatted == std::vector<std::string>({
+			"Short         |  7|",
+			"A longer title|123|",
+		}));
+	}
+
+	SECTION("Limit after the asterisk caps the column width") {
+		std::vector<FmtStrFormatter> rows;
+		rows.push_back(make_row("Short", "7"));
+		rows.push_back(make_row("A longer title", "123"));
+
+		const auto formatted = FmtStrFormatter::do_format_rows(std::move(rows),
+				"%-*8t|");
+		REQUIRE(formatted == std::vector<std::string>({
+			"Short   |",
+			"A longer|",
+		}));
+	}
+
+	SECTION("Empty input produces no rows") {
+		REQUIRE(FmtStrFormatter::do_format_rows({}, "%-*t").empty());
+	}
+}
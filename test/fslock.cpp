@@ -132,6 +132,51 @@ TEST_CASE("try_lock() fails if lock was already created", "[FsLock]")
 	}
 }
 
+TEST_CASE("lock_holder() returns the PID of the process that holds the lock",
+	"[FsLock]")
+{
+	const test_helpers::TempFile lock_location;
+	REQUIRE_FALSE(FsLock::lock_holder(lock_location.get_path()).has_value());
+
+	{
+		LockProcess lock_process(lock_location.get_path());
+		REQUIRE(FsLock::lock_holder(lock_location.get_path())
+			== lock_process.get_child_pid());
+	}
+
+	REQUIRE_FALSE(FsLock::lock_holder(lock_location.get_path()).has_value());
+}
+
+TEST_CASE("lock_with_timeout() gives up if the lock isn't released in time",
+	"[FsLock]")
+{
+	const test_helpers::TempFile lock_location;
+	LockProcess lock_process(lock_location.get_path());
+
+	FsLock lock;
+	pid_t pid = 0;
+	std::string error_message;
+	REQUIRE_FALSE(lock.lock_with_timeout(lock_location.get_path(), pid,
+			std::chrono::seconds(1), error_message));
+	REQUIRE(pid == lock_process.get_child_pid());
+	REQUIRE(error_message.length() > 0);
+}
+
+TEST_CASE("lock_with_timeout() doesn't wait if the lock file can't be opened",
+	"[FsLock]")
+{
+	const test_helpers::TempDir test_directory;
+	const auto lock_location =
+		test_directory.get_path().join("does-not-exist/lockfile"_path);
+
+	FsLock lock;
+	pid_t pid = -1;
+	std::string error_message;
+	REQUIRE_FALSE(lock.lock_with_timeout(lock_location, pid,
+			std::chrono::seconds(3600), error_message));
+	REQUIRE(pid == 0);
+}
+
 TEST_CASE("try_lock() succeeds if lock file location is valid and not locked by a different process",
 	"[FsLock]")
 {
@@ -1,6 +1,7 @@
 #include "itemlistformaction.h"
 
 #include <fstream>
+#include <iterator>
 #include <tuple>
 #include <unistd.h>
 
@@ -71,6 +72,98 @@ TEST_CASE("OP_OPEN displays article using an external pager",
 		test_description);
 }
 
+TEST_CASE("OP_OPEN leaves the article unread, in memory and in the cache, "
+	"if the cache is read-only",
+	"[ItemListFormAction]")
+{
+	ConfigPaths paths;
+	Controller c(paths);
+	newsboat::View v(c);
+	test_helpers::TempFile dbfile;
+	test_helpers::TempFile pagerfile;
+
+	ConfigContainer cfg;
+	FilterContainer filters;
+	RegexManager rxman;
+	cfg.set_configvalue("pager", "cat %f > " + pagerfile.get_path().to_locale_string());
+
+	const std::string feedurl = "http://example.com/feed.xml";
+	const std::string guid = "http://example.com/article";
+	{
+		Cache writer(dbfile.get_path(), cfg);
+		auto feed = std::make_shared<RssFeed>(&writer, feedurl);
+		auto item = std::make_shared<RssItem>(&writer);
+		item->set_guid(guid);
+		item->set_feedurl(feedurl);
+		item->set_title("Article Title");
+		item->set_unread_nowrite(true);
+		feed->add_item(item);
+		writer.externalize_rssfeed(*feed, false);
+	}
+
+	Cache reader(dbfile.get_path(), cfg, CacheMode::ReadOnly);
+	auto feed = reader.internalize_rssfeed(feedurl, nullptr);
+	REQUIRE(feed->total_item_count() == 1);
+	const auto item = feed->items()[0];
+	REQUIRE(item->unread());
+
+	v.set_config_container(&cfg);
+	c.set_view(&v);
+
+	ItemListFormAction itemlist(v, itemlist_str, &reader, filters, &cfg, rxman);
+	itemlist.set_feed(feed);
+
+	const std::vector<std::string> args;
+	REQUIRE_NOTHROW(itemlist.process_op(OP_OPEN, args));
+
+	// The article was shown...
+	std::ifstream pager_output(pagerfile.get_path().to_locale_string());
+	const std::string shown((std::istreambuf_iterator<char>(pager_output)),
+		std::istreambuf_iterator<char>());
+	REQUIRE(shown.find("Article Title") != std::string::npos);
+	// ...but its state is still the one that's stored
+	REQUIRE(item->unread());
+	REQUIRE(feed->unread_item_count() == 1);
+	REQUIRE(reader.get_read_item_guids().empty());
+
+	Cache writer(dbfile.get_path(), cfg);
+	REQUIRE(writer.get_read_item_guids().empty());
+}
+
+TEST_CASE("Operations are recorded between two OP_RECORDMACRO",
+	"[ItemListFormAction]")
+{
+	ConfigPaths paths;
+	Controller c(paths);
+	newsboat::View v(c);
+	ConfigContainer cfg;
+	auto rsscache = Cache::in_memory(cfg);
+	FilterContainer filters;
+	RegexManager rxman;
+	KeyMap keys(KM_NEWSBOAT);
+
+	v.set_config_container(&cfg);
+	v.set_keymap(&keys);
+	c.set_view(&v);
+
+	ItemListFormAction itemlist(v, itemlist_str, rsscache.get(), filters, &cfg, rxman);
+
+	REQUIRE(itemlist.process_op(OP_SET, {"browser", "lynx"}, BindingType::Bind));
+	REQUIRE(itemlist.process_op(OP_RECORDMACRO, {"q"}));
+	REQUIRE(v.get_macro_recorder().is_recording());
+	REQUIRE(itemlist.process_op(OP_SET, {"browser", "w3m"}, BindingType::Bind));
+	REQUIRE(itemlist.process_op(OP_REPLAYMACRO, {"x"}) == false);
+	REQUIRE(itemlist.process_op(OP_RECORDMACRO, {}));
+	REQUIRE_FALSE(v.get_macro_recorder().is_recording());
+	REQUIRE(cfg.get_configvalue("browser") == "w3m");
+
+	const auto cmds = v.get_macro_recorder().replay('q', keys);
+	REQUIRE(cmds);
+	REQUIRE(cmds->size() == 1);
+	REQUIRE(cmds->at(0).op == OP_SET);
+	REQUIRE(cmds->at(0).args == std::vector<std::string>({"browser", "w3m"}));
+}
+
 TEST_CASE("OP_PURGE_DELETED purges previously deleted items",
 	"[ItemListFormAction]")
 {
@@ -234,3 +234,41 @@ TEST_CASE("from_bind parses sequence of key combinations", "[KeyCombination]")
 		}
 	}
 }
+
+TEST_CASE("KeyDecoder puts escape sequences reported byte by byte back together",
+	"[KeyDecoder]")
+{
+	KeyDecoder decoder;
+
+	SECTION("Ordinary events are passed through") {
+		REQUIRE(decoder.push_event("J") == std::vector<KeyCombination>({
+			KeyCombination("j", ShiftState::Shift),
+		}));
+		REQUIRE(decoder.push_event("UP") == std::vector<KeyCombination>({
+			KeyCombination("UP"),
+		}));
+		REQUIRE_FALSE(decoder.has_pending());
+	}
+
+	SECTION("Escape sequences") {
+		std::vector<KeyCombination> keys;
+		for (const std::string event : {
+					"ESC", "[", "2", "7", ";", "5", ";", "1", "3", "~"
+				}) {
+			const auto decoded = decoder.push_event(event);
+			keys.insert(keys.end(), decoded.begin(), decoded.end());
+		}
+		REQUIRE(keys == std::vector<KeyCombination>({
+			KeyCombination("ENTER", ShiftState::NoShift, ControlState::Control),
+		}));
+	}
+
+	SECTION("A lone ESC is returned when flushed") {
+		REQUIRE(decoder.push_event("ESC").empty());
+		REQUIRE(decoder.has_pending());
+		REQUIRE(decoder.flush() == std::vector<KeyCombination>({
+			KeyCombination("ESC"),
+		}));
+		REQUIRE_FALSE(decoder.has_pending());
+	}
+}
@@ -746,6 +746,70 @@ TEST_CASE("Semicolons in operation's arguments don't break parsing of a macro",
 	REQUIRE(macro[1].args == std::vector<std::string>());
 }
 
+TEST_CASE("Macros and bindings with conditions or variables run as a program",
+	"[KeyMap]")
+{
+	KeyMap k(KM_NEWSBOAT);
+
+	k.handle_action("macro",
+		R"(x if unread then open else next-unread end -- "Open or skip")");
+	const auto macro = k.get_macro(KeyCombination("x"));
+	REQUIRE(macro.size() == 1);
+	REQUIRE(macro[0].op == OP_RUNPROGRAM);
+	REQUIRE(macro[0].args == std::vector<std::string>({"if unread then open else next-unread end"}));
+
+	k.handle_action("bind", R"(o articlelist open-in-browser "%{link}")");
+	MultiKeyBindingState state;
+	BindingType type;
+	const auto cmds = k.get_operation({KeyCombination("o")}, Dialog::ArticleList, state,
+			type);
+	REQUIRE(state == MultiKeyBindingState::Found);
+	REQUIRE(cmds.size() == 1);
+	REQUIRE(cmds[0].op == OP_RUNPROGRAM);
+	REQUIRE(cmds[0].args == std::vector<std::string>({R"(open-in-browser "%{link}")"}));
+
+	REQUIRE_THROWS_AS(k.handle_action("macro", "y if then open"), ConfigHandlerException);
+}
+
+TEST_CASE("apply_count() repeats commands or passes the count as an argument",
+	"[KeyMap]")
+{
+	SECTION("Commands are repeated") {
+		const std::vector<MacroCmd> cmds = {
+			{OP_NEXT, {}},
+			{OP_SET, {"browser", "lynx"}},
+		};
+
+		const auto result = KeyMap::apply_count(cmds, 2);
+
+		REQUIRE(result.size() == 4);
+		for (std::size_t i = 0; i < result.size(); ++i) {
+			REQUIRE(result[i].op == cmds[i % 2].op);
+			REQUIRE(result[i].args == cmds[i % 2].args);
+		}
+	}
+
+	SECTION("goto-url and goto-title get the count as their argument") {
+		for (const auto op : {
+				OP_GOTO_URL, OP_GOTO_TITLE
+			}) {
+			const auto result = KeyMap::apply_count({{op, {}}}, 12);
+
+			REQUIRE(result.size() == 1);
+			REQUIRE(result[0].op == op);
+			REQUIRE(result[0].args == std::vector<std::string>({"12"}));
+		}
+	}
+
+	SECTION("goto-url with an argument is repeated") {
+		const auto result = KeyMap::apply_count({{OP_GOTO_URL, {"1"}}}, 2);
+
+		REQUIRE(result.size() == 2);
+		REQUIRE(result[0].args == std::vector<std::string>({"1"}));
+		REQUIRE(result[1].args == std::vector<std::string>({"1"}));
+	}
+}
+
 TEST_CASE("prepare_keymap_hint() returns a string describing keys to which given operations are bound",
 	"[KeyMap]")
 {
@@ -0,0 +1,81 @@
+#include "macrorecorder.h"
+
+#include <fstream>
+
+#include "3rd-party/catch.hpp"
+#include "test_helpers/tempfile.h"
+#include "utils.h"
+
+using namespace newsboat;
+
+TEST_CASE("Recorded operations can be replayed", "[MacroRecorder]")
+{
+	KeyMap keymap(KM_NEWSBOAT);
+	MacroRecorder recorder;
+	REQUIRE_FALSE(recorder.is_recording());
+
+	recorder.record({OP_QUIT, {}});
+	REQUIRE(recorder.start_recording('a'));
+	REQUIRE(recorder.is_recording());
+	recorder.record({OP_NEXT, {}});
+	recorder.record({OP_SET, {"browser", "lynx \"%u\"; echo"}});
+	const auto reg = recorder.stop_recording();
+	REQUIRE(reg);
+	REQUIRE(reg.value() == 'a');
+	REQUIRE_FALSE(recorder.is_recording());
+	recorder.record({OP_QUIT, {}});
+
+	const auto cmds = recorder.replay('a', keymap);
+	REQUIRE(cmds);
+	REQUIRE(cmds->size() == 2);
+	REQUIRE(cmds->at(0).op == OP_NEXT);
+	REQUIRE(cmds->at(0).args.empty());
+	REQUIRE(cmds->at(1).op == OP_SET);
+	REQUIRE(cmds->at(1).args == std::vector<std::string>({"browser", "lynx \"%u\"; echo"}));
+}
+
+TEST_CASE("MacroRecorder reports errors", "[MacroRecorder]")
+{
+	KeyMap keymap(KM_NEWSBOAT);
+	MacroRecorder recorder;
+
+	REQUIRE_FALSE(recorder.start_recording('@'));
+	REQUIRE_FALSE(recorder.stop_recording());
+	REQUIRE_FALSE(recorder.replay('b', keymap));
+	REQUIRE_FALSE(recorder.macro_command('b', "x", ""));
+
+	REQUIRE(recorder.start_recording('a'));
+	const auto result = recorder.start_recording('b');
+	REQUIRE_FALSE(result);
+	REQUIRE(result.error() == "Already recording into register `a'");
+}
+
+TEST_CASE("save_macro() appends a macro command to the config file",
+	"[MacroRecorder]")
+{
+	test_helpers::TempFile config;
+	{
+		std::ofstream f(config.get_path().to_locale_string());
+		f << "bind-key j down";
+	}
+
+	MacroRecorder recorder;
+	REQUIRE(recorder.start_recording('1'));
+	recorder.record({OP_TOGGLEITEMREAD, {}});
+	recorder.record({OP_NEXT, {}});
+	REQUIRE(recorder.stop_recording());
+
+	const auto command = recorder.macro_command('1', "x", "mark and go on");
+	REQUIRE(command);
+	REQUIRE(command.value() ==
+		R"(macro x toggle-article-read ; next -- "mark and go on")");
+
+	REQUIRE(recorder.save_macro('1', "x", "mark and go on", config.get_path()));
+
+	const auto lines = utils::read_text_file(config.get_path());
+	REQUIRE(lines);
+	REQUIRE(lines.value() == std::vector<std::string>({
+		"bind-key j down",
+		command.value(),
+	}));
+}
@@ -0,0 +1,49 @@
+#include "programrunner.h"
+
+#include "3rd-party/catch.hpp"
+
+using namespace newsboat;
+
+TEST_CASE("next_operation() evaluates conditions with the current variables",
+	"[ProgramRunner]")
+{
+	ProgramRunner runner(R"(next-unread ; if unread > 0 then open-in-browser "%{link}")");
+
+	auto cmd = runner.next_operation({});
+	REQUIRE(cmd);
+	REQUIRE(cmd->has_value());
+	REQUIRE(cmd->value().op == OP_NEXTUNREAD);
+	REQUIRE(cmd->value().args.empty());
+
+	cmd = runner.next_operation({{"unread", "1"}, {"link", "https://example.com"}});
+	REQUIRE(cmd);
+	REQUIRE(cmd->has_value());
+	REQUIRE(cmd->value().op == OP_OPENINBROWSER);
+	REQUIRE(cmd->value().args == std::vector<std::string>({"https://example.com"}));
+
+	cmd = runner.next_operation({});
+	REQUIRE(cmd);
+	REQUIRE_FALSE(cmd->has_value());
+}
+
+TEST_CASE("next_operation() reports errors", "[ProgramRunner]")
+{
+	SECTION("Undefined variable") {
+		ProgramRunner runner("if unread then open");
+		const auto cmd = runner.next_operation({});
+		REQUIRE_FALSE(cmd);
+		REQUIRE(cmd.error() == "Undefined variable `unread'");
+	}
+
+	SECTION("Unknown operation") {
+		ProgramRunner runner("if 1 then no-such-operation");
+		const auto cmd = runner.next_operation({});
+		REQUIRE_FALSE(cmd);
+		REQUIRE(cmd.error() == "`no-such-operation' is not a valid operation");
+	}
+
+	SECTION("Program that can't be parsed") {
+		ProgramRunner runner("if then");
+		REQUIRE_FALSE(runner.next_operation({}));
+	}
+}
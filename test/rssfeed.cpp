@@ -241,6 +241,20 @@ TEST_CASE("RssFeed::unread_item_count() returns number of unread articles",
 	REQUIRE(f.unread_item_count() == 0);
 }
 
+TEST_CASE("RssFeed::has_item() returns true if feed has an article with given GUID",
+	"[RssFeed]")
+{
+	ConfigContainer cfg;
+	auto rsscache = Cache::in_memory(cfg);
+	RssFeed f(rsscache.get(), "");
+	const auto item = std::make_shared<RssItem>(rsscache.get());
+	item->set_guid("present");
+	f.add_item(item);
+
+	REQUIRE(f.has_item("present"));
+	REQUIRE_FALSE(f.has_item("absent"));
+}
+
 TEST_CASE("RssFeed::matches_tag() returns true if article has a specified tag",
 	"[RssFeed]")
 {